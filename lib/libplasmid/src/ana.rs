@@ -0,0 +1,31 @@
+pub mod assembly;
+pub mod composition;
+pub mod design;
+pub mod digest;
+pub mod effect;
+pub mod features;
+pub mod index;
+pub mod ligation;
+pub mod mapping;
+pub mod motif;
+pub mod pcr;
+pub mod pileup;
+pub mod primer;
+pub mod thermo;
+pub mod variants;
+
+pub use self::assembly::*;
+pub use self::composition::*;
+pub use self::design::*;
+pub use self::digest::*;
+pub use self::effect::*;
+pub use self::features::*;
+pub use self::index::*;
+pub use self::ligation::*;
+pub use self::mapping::*;
+pub use self::motif::*;
+pub use self::pcr::*;
+pub use self::pileup::*;
+pub use self::primer::*;
+pub use self::thermo::*;
+pub use self::variants::*;
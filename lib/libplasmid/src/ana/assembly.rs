@@ -0,0 +1,515 @@
+use std::collections::HashMap;
+
+use crate::{
+    seq::{reverse_complement, Annotation, DnaSequence, Topology},
+    traits::ToLetter,
+    uni::{IupacNucleotide, RestrictionEnzyme},
+};
+
+use super::{digest, FragmentEnd, Overhang};
+
+/// Maximum length of a terminal homology considered by Gibson assembly
+pub const GIBSON_MAX_OVERLAP: usize = 200;
+
+/// A part as placed in an assembled product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembledPart {
+    /// Index of the part in the input
+    pub part: usize,
+    /// Whether the part was inserted as its reverse complement
+    pub reverse: bool,
+    /// First product position of the part
+    pub start: usize,
+    /// Product position after the last base of the part, may exceed
+    /// the product length if the part spans the origin of a circular product
+    pub end: usize,
+}
+
+/// A problem encountered during an assembly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssemblyIssue {
+    /// More than two fragment ends share an overhang, so ligation is ambiguous
+    DuplicateOverhang(Vec<IupacNucleotide>),
+    /// A fragment end has no compatible partner
+    UnmatchedOverhang(Vec<IupacNucleotide>),
+    /// A part overlaps the growing product as well as another part
+    AmbiguousOverlap { part: usize },
+    /// A part could not be joined to the product
+    UnusedPart(usize),
+    /// The ends of the product could not be joined
+    NotCircular,
+}
+
+impl std::fmt::Display for AssemblyIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let letters =
+            |seq: &[IupacNucleotide]| seq.iter().map(|n| n.to_letter()).collect::<String>();
+        match self {
+            AssemblyIssue::DuplicateOverhang(seq) => {
+                write!(f, "Duplicate overhang: {}", letters(seq))
+            }
+            AssemblyIssue::UnmatchedOverhang(seq) => {
+                write!(f, "Unmatched overhang: {}", letters(seq))
+            }
+            AssemblyIssue::AmbiguousOverlap { part } => {
+                write!(
+                    f,
+                    "Part {} competes with another part for an overlap",
+                    part + 1
+                )
+            }
+            AssemblyIssue::UnusedPart(part) => {
+                write!(f, "Part {} could not be joined to the product", part + 1)
+            }
+            AssemblyIssue::NotCircular => write!(f, "The product could not be circularized"),
+        }
+    }
+}
+
+/// Product of an assembly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assembly {
+    /// Top strand of the product, 5' to 3'
+    pub sequence: Vec<IupacNucleotide>,
+    pub topology: Topology,
+    /// Parts in the order of the product
+    pub parts: Vec<AssembledPart>,
+    /// Overhangs or overlaps joining the parts
+    pub junctions: Vec<Annotation>,
+    pub issues: Vec<AssemblyIssue>,
+}
+
+impl Assembly {
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+
+    /// Whether all parts were joined into a single circular product.
+    pub fn is_complete(&self) -> bool {
+        self.topology.is_circular() && self.issues.is_empty()
+    }
+}
+
+/// A fragment released from a part, with the 5' overhangs of both of its ends.
+struct Piece {
+    part: usize,
+    sequence: Vec<IupacNucleotide>,
+    /// Top strand 5' overhang of the left end
+    left: Vec<IupacNucleotide>,
+    /// Bottom strand 5' overhang of the right end
+    right: Vec<IupacNucleotide>,
+}
+
+impl Piece {
+    /// Sequence and (left, right) overhangs of the piece in either orientation.
+    fn oriented(
+        &self,
+        reverse: bool,
+    ) -> (Vec<IupacNucleotide>, &[IupacNucleotide], &[IupacNucleotide]) {
+        match reverse {
+            false => (self.sequence.clone(), &self.left, &self.right),
+            true => {
+                let mut sequence = self.right.clone();
+                sequence.extend(reverse_complement(&self.sequence[self.left.len()..]));
+                (sequence, &self.right, &self.left)
+            }
+        }
+    }
+}
+
+/// Simulate a Golden Gate assembly: digest every part with a Type IIS enzyme
+/// and ligate the released fragments by their matching 5' overhangs.
+///
+/// Every part has to release exactly one fragment with cut ends on both sides
+/// that is free of further sites, i.e. the insert of a donor or the backbone of
+/// the destination vector. Parts are taken as linear or circular by their topology.
+/// Assembly starts with the first part, the other parts are inserted in the
+/// orientation their overhangs require.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::{prelude::*, uni::find_restriction_enzyme};
+///
+/// let insert = DnaSequence::from_str("GGTCTCAAATGCCCCCCCCTTGCAGAGACC").unwrap();
+/// let mut vector = DnaSequence::from_str("GGTCTCATTGCTTTTTTTTTTAATGAGAGACC").unwrap();
+/// vector.set_topology(Topology::Circular);
+/// let bsai = find_restriction_enzyme("BsaI").unwrap();
+/// let assembly = golden_gate(&[vector, insert], bsai).unwrap();
+/// assert!(assembly.is_complete());
+/// assert_eq!(assembly.junctions.len(), 2);
+/// ```
+pub fn golden_gate(parts: &[DnaSequence], enzyme: &RestrictionEnzyme) -> anyhow::Result<Assembly> {
+    if parts.is_empty() {
+        bail!("No parts to assemble");
+    }
+    if enzyme.overhang().is_none_or(|overhang| overhang <= 0) {
+        bail!(
+            "Golden Gate assembly requires an enzyme leaving 5' overhangs, {} does not",
+            enzyme.name
+        );
+    }
+
+    // Release one fragment per part
+    let mut pieces = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        let candidates = digest(part.as_nucleotides(), part.topology(), &[enzyme])
+            .into_iter()
+            .filter(|fragment| enzyme.find_sites(&fragment.sequence, false).is_empty())
+            .filter_map(|fragment| match (fragment.left, fragment.right) {
+                (
+                    FragmentEnd::Cut {
+                        overhang: Overhang::FivePrime(left),
+                        ..
+                    },
+                    FragmentEnd::Cut {
+                        overhang: Overhang::FivePrime(right),
+                        ..
+                    },
+                ) => Some(Piece {
+                    part: i,
+                    sequence: fragment.sequence,
+                    left,
+                    right,
+                }),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        match candidates.len() {
+            0 => bail!(
+                "Part {} releases no fragment with {} overhangs on both ends",
+                i + 1,
+                enzyme.name
+            ),
+            1 => pieces.extend(candidates),
+            n => bail!(
+                "Part {} releases {} fragments free of {} sites",
+                i + 1,
+                n,
+                enzyme.name
+            ),
+        }
+    }
+
+    // Every overhang has to pair with exactly one other end
+    let mut issues = Vec::new();
+    let canonical = |seq: &[IupacNucleotide]| {
+        let rc = reverse_complement(seq);
+        let letters =
+            |seq: &[IupacNucleotide]| seq.iter().map(|n| n.to_letter()).collect::<String>();
+        if letters(&rc) < letters(seq) {
+            rc
+        } else {
+            seq.to_vec()
+        }
+    };
+    let mut counts = HashMap::<Vec<IupacNucleotide>, usize>::new();
+    let mut order = Vec::new();
+    for piece in pieces.iter() {
+        for overhang in [&piece.left, &piece.right] {
+            let key = canonical(overhang);
+            if !counts.contains_key(&key) {
+                order.push(key.clone());
+            }
+            *counts.entry(key).or_default() += 1;
+        }
+    }
+    for key in order {
+        match counts[&key] {
+            1 => issues.push(AssemblyIssue::UnmatchedOverhang(key)),
+            2 => {}
+            _ => issues.push(AssemblyIssue::DuplicateOverhang(key)),
+        }
+    }
+
+    // Ligate pieces to the right end of the product
+    let (mut sequence, first_left, mut right) = {
+        let (sequence, left, right) = pieces[0].oriented(false);
+        (sequence, left.to_vec(), right.to_vec())
+    };
+    let mut used = vec![false; pieces.len()];
+    used[0] = true;
+    let mut assembled = vec![AssembledPart {
+        part: pieces[0].part,
+        reverse: false,
+        start: 0,
+        end: sequence.len(),
+    }];
+    let mut junctions = Vec::new();
+    let junction = |start: usize, overhang: &[IupacNucleotide], n: usize| {
+        let text = overhang.iter().map(|b| b.to_letter()).collect::<String>();
+        Annotation::new(
+            start,
+            start + overhang.len() - 1,
+            None,
+            format!("Junction {}: {}", n, text),
+        )
+    };
+    loop {
+        let needed = reverse_complement(&right);
+        let next = (0..pieces.len())
+            .filter(|i| !used[*i])
+            .flat_map(|i| [(i, false), (i, true)])
+            .find(|(i, reverse)| pieces[*i].oriented(*reverse).1 == needed.as_slice());
+        let Some((i, reverse)) = next else {
+            break;
+        };
+        let (piece_sequence, _, piece_right) = pieces[i].oriented(reverse);
+        junctions.push(junction(sequence.len(), &needed, junctions.len() + 1));
+        assembled.push(AssembledPart {
+            part: pieces[i].part,
+            reverse,
+            start: sequence.len(),
+            end: sequence.len() + piece_sequence.len(),
+        });
+        sequence.extend(piece_sequence);
+        right = piece_right.to_vec();
+        used[i] = true;
+    }
+
+    let topology = if reverse_complement(&right) == first_left {
+        junctions.push(junction(0, &first_left, junctions.len() + 1));
+        Topology::Circular
+    } else {
+        issues.push(AssemblyIssue::NotCircular);
+        Topology::Linear
+    };
+    issues.extend(
+        used.iter()
+            .enumerate()
+            .filter(|(_, used)| !**used)
+            .map(|(i, _)| AssemblyIssue::UnusedPart(pieces[i].part)),
+    );
+
+    Ok(Assembly {
+        sequence,
+        topology,
+        parts: assembled,
+        junctions,
+        issues,
+    })
+}
+
+/// Length of the longest suffix of `a` that is a prefix of `b`, of at least `min` bases.
+fn terminal_overlap<T: PartialEq>(a: &[T], b: &[T], min: usize) -> Option<usize> {
+    let max = a.len().min(b.len()).min(GIBSON_MAX_OVERLAP);
+    (min.max(1)..=max)
+        .rev()
+        .find(|k| a[a.len() - k..] == b[..*k])
+}
+
+/// Simulate a Gibson/HiFi assembly: join linear parts whose ends share
+/// a homologous overlap of at least `min_overlap` bases.
+///
+/// Assembly starts with the first part and repeatedly joins the part, in either
+/// orientation, with the longest overlap to the end of the product. The product
+/// is circularized if its ends overlap as well.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// let a = DnaSequence::from_str("ACGTACGTTTGGCCAATTGG").unwrap();
+/// let b = DnaSequence::from_str("GCCAATTGGCATCATCATCATACGTACGT").unwrap();
+/// let assembly = gibson(&[a, b], 8).unwrap();
+/// assert!(assembly.is_complete());
+/// assert_eq!(assembly.len(), 32);
+/// ```
+pub fn gibson(parts: &[DnaSequence], min_overlap: usize) -> anyhow::Result<Assembly> {
+    if parts.is_empty() {
+        bail!("No parts to assemble");
+    }
+    let oriented = |i: usize, reverse: bool| -> Vec<IupacNucleotide> {
+        let seq = parts[i].iupac_iter().collect::<Vec<_>>();
+        match reverse {
+            false => seq,
+            true => reverse_complement(&seq),
+        }
+    };
+
+    let mut sequence = oriented(0, false);
+    let mut used = vec![false; parts.len()];
+    used[0] = true;
+    let mut assembled = vec![AssembledPart {
+        part: 0,
+        reverse: false,
+        start: 0,
+        end: sequence.len(),
+    }];
+    let mut junctions = Vec::new();
+    let mut issues = Vec::new();
+    let junction = |start: usize, k: usize, n: usize| {
+        Annotation::new(
+            start,
+            start + k - 1,
+            None,
+            format!("Junction {}: {} bp overlap", n, k),
+        )
+    };
+    loop {
+        let mut candidates = (0..parts.len())
+            .filter(|i| !used[*i])
+            .flat_map(|i| [(i, false), (i, true)])
+            .filter_map(|(i, reverse)| {
+                let part = oriented(i, reverse);
+                terminal_overlap(&sequence, &part, min_overlap).map(|k| (i, reverse, k, part))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, _, k, _)| std::cmp::Reverse(*k));
+        let Some((i, reverse, k, part)) = candidates.first().cloned() else {
+            break;
+        };
+        if candidates.iter().any(|(other, ..)| *other != i) {
+            issues.push(AssemblyIssue::AmbiguousOverlap { part: i });
+        }
+        let start = sequence.len() - k;
+        junctions.push(junction(start, k, junctions.len() + 1));
+        assembled.push(AssembledPart {
+            part: i,
+            reverse,
+            start,
+            end: start + part.len(),
+        });
+        sequence.extend_from_slice(&part[k..]);
+        used[i] = true;
+    }
+
+    // Circularize if the end of the product overlaps its start
+    let first = oriented(0, false);
+    let closing = match assembled.len() > 1 {
+        true => terminal_overlap(&sequence[first.len()..], &first, min_overlap),
+        false => None,
+    };
+    let topology = match closing {
+        Some(k) => {
+            sequence.truncate(sequence.len() - k);
+            junctions.push(junction(0, k, junctions.len() + 1));
+            Topology::Circular
+        }
+        None => {
+            issues.push(AssemblyIssue::NotCircular);
+            Topology::Linear
+        }
+    };
+    issues.extend(
+        used.iter()
+            .enumerate()
+            .filter(|(_, used)| !**used)
+            .map(|(i, _)| AssemblyIssue::UnusedPart(i)),
+    );
+
+    Ok(Assembly {
+        sequence,
+        topology,
+        parts: assembled,
+        junctions,
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gibson, golden_gate, AssemblyIssue};
+    use crate::{
+        seq::{DnaSequence, Topology},
+        test_utils::{circular, letters, reverse_complement_letters},
+        uni::find_restriction_enzyme,
+    };
+
+    // Backbone releasing TTGC ... AATG, inserts AATG-A-GCTT and GCTT-B-TTGC
+    const VECTOR: &str = "GGTCTCATTGCTTTTTTTTTTAATGAGAGACC";
+    const INSERT_A: &str = "GGTCTCAAATGCCCCCCCCGCTTAGAGACC";
+    const INSERT_B: &str = "GGTCTCAGCTTGGGGGGGGTTGCAGAGACC";
+
+    #[test]
+    fn test_golden_gate_three_parts() {
+        let bsai = find_restriction_enzyme("BsaI").unwrap();
+        let parts = [
+            circular(VECTOR),
+            DnaSequence::from_str(INSERT_A).unwrap(),
+            DnaSequence::from_str(INSERT_B).unwrap(),
+        ];
+        let assembly = golden_gate(&parts, bsai).unwrap();
+        assert!(assembly.is_complete(), "{:?}", assembly.issues);
+        assert_eq!(
+            letters(&assembly.sequence),
+            "TTGCTTTTTTTTTTAATGCCCCCCCCGCTTGGGGGGGG"
+        );
+        let order = assembly.parts.iter().map(|p| p.part).collect::<Vec<_>>();
+        assert_eq!(order, [0, 1, 2]);
+        assert_eq!(
+            (assembly.junctions[0].start, assembly.junctions[0].end),
+            (14, 17)
+        );
+    }
+
+    #[test]
+    fn test_golden_gate_reverse_insert() {
+        let bsai = find_restriction_enzyme("BsaI").unwrap();
+        let parts = [
+            circular(VECTOR),
+            DnaSequence::from_str(reverse_complement_letters(INSERT_A)).unwrap(),
+        ];
+        let assembly = golden_gate(&parts, bsai).unwrap();
+        assert_eq!(assembly.parts[1].part, 1);
+        assert!(assembly.parts[1].reverse);
+        assert!(assembly.issues.contains(&AssemblyIssue::NotCircular));
+    }
+
+    #[test]
+    fn test_golden_gate_duplicate_overhang() {
+        let bsai = find_restriction_enzyme("BsaI").unwrap();
+        let parts = [
+            circular(VECTOR),
+            DnaSequence::from_str(INSERT_A).unwrap(),
+            DnaSequence::from_str(INSERT_A).unwrap(),
+        ];
+        let assembly = golden_gate(&parts, bsai).unwrap();
+        assert!(!assembly.is_complete());
+        assert!(assembly
+            .issues
+            .iter()
+            .any(|issue| matches!(issue, AssemblyIssue::DuplicateOverhang(_))));
+    }
+
+    #[test]
+    fn test_golden_gate_invalid_parts() {
+        let bsai = find_restriction_enzyme("BsaI").unwrap();
+        let ecori = find_restriction_enzyme("EcoRI").unwrap();
+        let plain = DnaSequence::from_str("AAAAAAAAAA").unwrap();
+        assert!(golden_gate(&[plain], bsai).is_err());
+        assert!(golden_gate(&[], bsai).is_err());
+        let pst = find_restriction_enzyme("PstI").unwrap();
+        assert!(golden_gate(&[circular(VECTOR)], pst).is_err());
+        assert!(golden_gate(&[circular(VECTOR)], ecori).is_err());
+    }
+
+    #[test]
+    fn test_gibson_missing_overlap() {
+        let a = DnaSequence::from_str("ACGTACGTTTGGCCAATTGG").unwrap();
+        let b = DnaSequence::from_str("TTTTTTTTTTTTTTTTTTTT").unwrap();
+        let assembly = gibson(&[a, b], 8).unwrap();
+        assert_eq!(assembly.topology, Topology::Linear);
+        assert!(assembly.issues.contains(&AssemblyIssue::UnusedPart(1)));
+        assert!(assembly.issues.contains(&AssemblyIssue::NotCircular));
+    }
+
+    #[test]
+    fn test_gibson_reverse_part() {
+        let a = DnaSequence::from_str("ACGTACGTTTGGCCAATTGG").unwrap();
+        let b_rc =
+            DnaSequence::from_str(reverse_complement_letters("GCCAATTGGCATCATCATCATACGTACGT"))
+                .unwrap();
+        let assembly = gibson(&[a, b_rc], 8).unwrap();
+        assert!(assembly.is_complete());
+        assert!(assembly.parts[1].reverse);
+        assert_eq!(
+            letters(&assembly.sequence),
+            "ACGTACGTTTGGCCAATTGGCATCATCATCAT"
+        );
+        assert_eq!(assembly.junctions.len(), 2);
+    }
+}
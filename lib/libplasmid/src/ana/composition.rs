@@ -0,0 +1,357 @@
+use crate::{seq::reverse_complement, traits::*, uni::IupacNucleotide};
+
+/// Molecular weight of a nucleotide monophosphate in a DNA chain (g/mol).
+fn nucleotide_weight(n: &IupacNucleotide) -> f64 {
+    use IupacNucleotide::*;
+    match n {
+        A => 313.21,
+        C => 289.18,
+        G => 329.21,
+        T => 304.2,
+        _ => 0.0,
+    }
+}
+
+/// Extinction coefficient at 260 nm of a single nucleotide (M⁻¹cm⁻¹).
+fn nucleotide_extinction(n: &IupacNucleotide) -> f64 {
+    use IupacNucleotide::*;
+    match n {
+        A => 15400.0,
+        C => 7400.0,
+        G => 11500.0,
+        T => 8700.0,
+        _ => 0.0,
+    }
+}
+
+/// Nearest-neighbor extinction coefficient at 260 nm of a dinucleotide (M⁻¹cm⁻¹).
+fn dinucleotide_extinction(a: &IupacNucleotide, b: &IupacNucleotide) -> f64 {
+    use IupacNucleotide::*;
+    match (a, b) {
+        (A, A) => 27400.0,
+        (A, C) => 21200.0,
+        (A, G) => 25000.0,
+        (A, T) => 22800.0,
+        (C, A) => 21200.0,
+        (C, C) => 14600.0,
+        (C, G) => 18000.0,
+        (C, T) => 15200.0,
+        (G, A) => 25200.0,
+        (G, C) => 17600.0,
+        (G, G) => 21600.0,
+        (G, T) => 20000.0,
+        (T, A) => 23400.0,
+        (T, C) => 16200.0,
+        (T, G) => 19000.0,
+        (T, T) => 16800.0,
+        _ => 0.0,
+    }
+}
+
+/// Average a per-base property over all bases an IUPAC code stands for.
+fn average_over<F>(n: &IupacNucleotide, f: F) -> f64
+where
+    F: Fn(&IupacNucleotide) -> f64,
+{
+    let bases = n.bases();
+    if bases.is_empty() {
+        return 0.0;
+    }
+    bases.iter().map(f).sum::<f64>() / bases.len() as f64
+}
+
+/// Average a dinucleotide property over all base pairs two IUPAC codes stand for.
+fn average_over_pair<F>(a: &IupacNucleotide, b: &IupacNucleotide, f: F) -> f64
+where
+    F: Fn(&IupacNucleotide, &IupacNucleotide) -> f64,
+{
+    let (a, b) = (a.bases(), b.bases());
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let sum = a
+        .iter()
+        .flat_map(|x| b.iter().map(move |y| (x, y)))
+        .map(|(x, y)| f(x, y))
+        .sum::<f64>();
+    sum / (a.len() * b.len()) as f64
+}
+
+/// Single-stranded molecular weight of an oligonucleotide without 5' phosphate.
+fn single_strand_weight(seq: &[IupacNucleotide]) -> f64 {
+    if seq.is_empty() {
+        return 0.0;
+    }
+    seq.iter()
+        .map(|n| average_over(n, nucleotide_weight))
+        .sum::<f64>()
+        - 61.96
+}
+
+/// Single-stranded extinction coefficient using the nearest-neighbor model.
+fn single_strand_extinction(seq: &[IupacNucleotide]) -> f64 {
+    match seq.len() {
+        0 => 0.0,
+        1 => average_over(&seq[0], nucleotide_extinction),
+        len => {
+            let pairs = seq
+                .windows(2)
+                .map(|w| average_over_pair(&w[0], &w[1], dinucleotide_extinction))
+                .sum::<f64>();
+            let inner = seq[1..len - 1]
+                .iter()
+                .map(|n| average_over(n, nucleotide_extinction))
+                .sum::<f64>();
+            pairs - inner
+        }
+    }
+}
+
+/// Count guanine, cytosine and strong (G/C) nucleotides.
+fn gc_count(seq: &[IupacNucleotide]) -> usize {
+    use IupacNucleotide::*;
+    seq.iter().filter(|n| [G, C, S].contains(n)).count()
+}
+
+/// Count adenine, thymine and weak (A/T) nucleotides.
+fn at_count(seq: &[IupacNucleotide]) -> usize {
+    use IupacNucleotide::*;
+    seq.iter().filter(|n| [A, T, W].contains(n)).count()
+}
+
+/// Compute (G - C) / (G + C), or `0` if neither occurs.
+fn gc_skew(seq: &[IupacNucleotide]) -> f32 {
+    let g = seq.iter().filter(|&&n| n == IupacNucleotide::G).count() as f32;
+    let c = seq.iter().filter(|&&n| n == IupacNucleotide::C).count() as f32;
+    if g + c == 0_f32 {
+        0_f32
+    } else {
+        (g - c) / (g + c)
+    }
+}
+
+fn ratio(count: usize, len: usize) -> f32 {
+    if len == 0 {
+        0_f32
+    } else {
+        count as f32 / len as f32
+    }
+}
+
+/// Composition statistics of a nucleotide sequence.
+///
+/// Ambiguous IUPAC codes are counted as they are. Strong (`S`) and weak (`W`)
+/// codes count towards GC and AT content respectively, while weights and
+/// extinction coefficients of ambiguous codes are averaged over their bases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceStats {
+    /// Number of nucleotides in the sequence
+    pub length: usize,
+    /// Occurrences of every IUPAC code found in the sequence
+    pub counts: Vec<(IupacNucleotide, usize)>,
+    /// Fraction of G, C and S nucleotides
+    pub gc_content: f32,
+    /// Fraction of A, T and W nucleotides
+    pub at_content: f32,
+    /// (G - C) / (G + C)
+    pub gc_skew: f32,
+    /// Observed CpG dinucleotides over the number expected from C and G content
+    pub cpg_observed_expected: f32,
+    /// Molecular weight of the single strand (g/mol)
+    pub molecular_weight_ss: f64,
+    /// Molecular weight of the double strand (g/mol)
+    pub molecular_weight_ds: f64,
+    /// Extinction coefficient at 260 nm of the single strand (M⁻¹cm⁻¹)
+    pub extinction_coefficient_ss: f64,
+    /// Extinction coefficient at 260 nm of the double strand (M⁻¹cm⁻¹)
+    pub extinction_coefficient_ds: f64,
+}
+
+impl SequenceStats {
+    /// Compute composition statistics of a nucleotide sequence.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::{*, IupacNucleotide::*};
+    ///
+    /// let stats = SequenceStats::compute(&[A, C, G, C, G, N]);
+    /// assert_eq!(stats.length, 6);
+    /// assert_eq!(stats.counts, [(A, 1), (C, 2), (G, 2), (N, 1)]);
+    /// assert!((stats.gc_content - 4_f32 / 6_f32).abs() <= f32::EPSILON);
+    /// ```
+    pub fn compute<T>(sequence: &[T]) -> Self
+    where
+        T: ToIupac,
+    {
+        let seq = sequence.iter().map(|n| n.to_iupac()).collect::<Vec<_>>();
+        let length = seq.len();
+
+        let counts = IupacNucleotide::all_as_str()
+            .chars()
+            .filter_map(|c| IupacNucleotide::try_from_letter(c).ok())
+            .map(|code| (code, seq.iter().filter(|&&n| n == code).count()))
+            .filter(|(_, count)| *count > 0)
+            .collect::<Vec<_>>();
+
+        let count_of = |code: IupacNucleotide| {
+            counts
+                .iter()
+                .find(|(n, _)| *n == code)
+                .map(|(_, count)| *count)
+                .unwrap_or(0)
+        };
+
+        let cpg_observed_expected = {
+            use IupacNucleotide::*;
+            let cpg = seq.windows(2).filter(|w| w == &[C, G]).count();
+            let expected = count_of(C) * count_of(G);
+            if expected == 0 {
+                0_f32
+            } else {
+                (cpg * length) as f32 / expected as f32
+            }
+        };
+
+        let complement = reverse_complement(&seq);
+        let gc = gc_count(&seq);
+        let at = at_count(&seq);
+
+        let molecular_weight_ss = single_strand_weight(&seq);
+        let molecular_weight_ds = molecular_weight_ss + single_strand_weight(&complement);
+
+        let extinction_coefficient_ss = single_strand_extinction(&seq);
+        let extinction_coefficient_ds = (extinction_coefficient_ss
+            + single_strand_extinction(&complement)
+            - 3200.0 * at as f64
+            - 2000.0 * gc as f64)
+            .max(0.0);
+
+        Self {
+            length,
+            counts,
+            gc_content: ratio(gc, length),
+            at_content: ratio(at, length),
+            gc_skew: gc_skew(&seq),
+            cpg_observed_expected,
+            molecular_weight_ss,
+            molecular_weight_ds,
+            extinction_coefficient_ss,
+            extinction_coefficient_ds,
+        }
+    }
+}
+
+/// GC content and GC skew over a sliding window, suitable for plotting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcWindows {
+    /// Size of each window in nucleotides
+    pub window: usize,
+    /// Distance between the starts of two consecutive windows
+    pub step: usize,
+    /// Start position of every window
+    pub positions: Vec<usize>,
+    /// GC content of every window
+    pub gc_content: Vec<f32>,
+    /// GC skew of every window
+    pub gc_skew: Vec<f32>,
+}
+
+impl GcWindows {
+    /// Compute GC content and GC skew for every full window of the sequence.
+    ///
+    /// A zero `window` or `step` yields no windows.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::{*, IupacNucleotide::*};
+    ///
+    /// let windows = GcWindows::compute(&[G, G, A, A, C, C], 2, 2);
+    /// assert_eq!(windows.positions, [0, 2, 4]);
+    /// assert_eq!(windows.gc_content, [1.0, 0.0, 1.0]);
+    /// assert_eq!(windows.gc_skew, [1.0, 0.0, -1.0]);
+    /// ```
+    pub fn compute<T>(sequence: &[T], window: usize, step: usize) -> Self
+    where
+        T: ToIupac,
+    {
+        let seq = sequence.iter().map(|n| n.to_iupac()).collect::<Vec<_>>();
+        let mut windows = Self {
+            window,
+            step,
+            positions: Vec::new(),
+            gc_content: Vec::new(),
+            gc_skew: Vec::new(),
+        };
+        if window == 0 || step == 0 || window > seq.len() {
+            return windows;
+        }
+        for start in (0..=seq.len() - window).step_by(step) {
+            let slice = &seq[start..start + window];
+            windows.positions.push(start);
+            windows.gc_content.push(ratio(gc_count(slice), window));
+            windows.gc_skew.push(gc_skew(slice));
+        }
+        windows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GcWindows, SequenceStats};
+    use crate::uni::IupacNucleotide::*;
+
+    #[test]
+    fn test_sequence_stats_empty() {
+        let stats = SequenceStats::compute::<crate::uni::IupacNucleotide>(&[]);
+        assert_eq!(stats.length, 0);
+        assert!(stats.counts.is_empty());
+        assert_eq!(stats.gc_content, 0.0);
+        assert_eq!(stats.molecular_weight_ss, 0.0);
+        assert_eq!(stats.extinction_coefficient_ss, 0.0);
+    }
+
+    #[test]
+    fn test_sequence_stats_cpg_and_skew() {
+        let stats = SequenceStats::compute(&[C, G, C, G, G, A]);
+        // 2 CpG * 6 nt / (2 C * 3 G)
+        assert!((stats.cpg_observed_expected - 2.0).abs() <= f32::EPSILON);
+        assert!((stats.gc_skew - 0.2).abs() <= f32::EPSILON);
+    }
+
+    #[test]
+    fn test_sequence_stats_molecular_weight() {
+        let stats = SequenceStats::compute(&[A, C, G, T]);
+        let ss = 313.21 + 289.18 + 329.21 + 304.2 - 61.96;
+        assert!((stats.molecular_weight_ss - ss).abs() < 1e-6);
+        // ACGT is its own reverse complement
+        assert!((stats.molecular_weight_ds - 2.0 * ss).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sequence_stats_extinction_coefficient() {
+        let stats = SequenceStats::compute(&[A, C, G]);
+        // AC + CG - C
+        assert_eq!(stats.extinction_coefficient_ss, 21200.0 + 18000.0 - 7400.0);
+
+        let ambiguous = SequenceStats::compute(&[S]);
+        assert_eq!(
+            ambiguous.extinction_coefficient_ss,
+            (7400.0 + 11500.0) / 2.0
+        );
+    }
+
+    #[test]
+    fn test_gc_windows_short_sequence() {
+        let windows = GcWindows::compute(&[G, C], 10, 1);
+        assert!(windows.positions.is_empty());
+        assert!(windows.gc_content.is_empty());
+    }
+
+    #[test]
+    fn test_gc_windows_overlapping() {
+        let windows = GcWindows::compute(&[A, G, G, C], 2, 1);
+        assert_eq!(windows.positions, [0, 1, 2]);
+        assert_eq!(windows.gc_content, [0.5, 1.0, 1.0]);
+        assert_eq!(windows.gc_skew, [1.0, 1.0, 0.0]);
+    }
+}
@@ -0,0 +1,401 @@
+use std::ops::{Range, RangeInclusive};
+
+use crate::{
+    dna::DnaNucleotide,
+    err::PlasmidError,
+    seq::{reverse_complement, Annotation, DnaSequence, FeatureType, Strand},
+};
+
+use super::{primer::PrimerProperties, thermo::*};
+
+/// Number of best scoring candidates per strand that are paired up.
+const CANDIDATES_PER_STRAND: usize = 100;
+
+/// Number of 3' terminal bases that have to match for a primer to bind off-target.
+const OFF_TARGET_ANCHOR: usize = 5;
+
+/// Maximum number of mismatches of an off-target binding site.
+const OFF_TARGET_MISMATCHES: usize = 3;
+
+/// Constraints for designing a primer pair around a target region.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrimerConstraints {
+    /// Allowed size of the amplified product, including both primers
+    pub product_size: RangeInclusive<usize>,
+    /// Allowed primer length
+    pub length: RangeInclusive<usize>,
+    /// Allowed nearest-neighbor melting temperature (°C)
+    pub tm: RangeInclusive<f64>,
+    /// Preferred melting temperature (°C)
+    pub optimal_tm: f64,
+    /// Allowed fraction of G and C bases
+    pub gc_content: RangeInclusive<f32>,
+    /// Maximum melting temperature difference of a pair (°C)
+    pub max_tm_difference: f64,
+    /// Number of ranked pairs to return
+    pub max_pairs: usize,
+    pub conditions: TmConditions,
+}
+
+impl Default for PrimerConstraints {
+    fn default() -> Self {
+        Self {
+            product_size: 100..=1000,
+            length: 18..=25,
+            tm: 52_f64..=65_f64,
+            optimal_tm: 60_f64,
+            gc_content: 0.4..=0.6,
+            max_tm_difference: 5_f64,
+            max_pairs: 5,
+            conditions: TmConditions::default(),
+        }
+    }
+}
+
+/// A primer binding to the template, 5' to 3'.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrimerCandidate {
+    /// First template position covered by the primer
+    pub start: usize,
+    /// Template position after the last base covered by the primer
+    pub end: usize,
+    /// Whether the primer anneals to the bottom strand and reads along the top strand
+    pub forward: bool,
+    pub sequence: Vec<DnaNucleotide>,
+    pub properties: PrimerProperties,
+    /// Number of additional binding sites on either strand of the template
+    pub off_target: usize,
+    /// Penalty of the primer on its own, lower is better
+    pub penalty: f64,
+}
+
+impl PrimerCandidate {
+    fn new(
+        start: usize,
+        end: usize,
+        forward: bool,
+        sequence: Vec<DnaNucleotide>,
+        constraints: &PrimerConstraints,
+    ) -> Option<Self> {
+        let properties = PrimerProperties::compute(&sequence, &constraints.conditions);
+        if !constraints.tm.contains(&properties.tm)
+            || !constraints.gc_content.contains(&properties.gc_content)
+        {
+            return None;
+        }
+        let penalty = (properties.tm - constraints.optimal_tm).abs()
+            + if (1..=3).contains(&properties.gc_clamp) {
+                0_f64
+            } else {
+                1_f64
+            }
+            + (-properties.hairpin_dg - 2_f64).max(0_f64)
+            + (-properties.self_three_prime_dg - 5_f64).max(0_f64)
+            + (-properties.self_dimer_dg - 8_f64).max(0_f64) / 2_f64;
+        Some(Self {
+            start,
+            end,
+            forward,
+            sequence,
+            properties,
+            off_target: 0,
+            penalty,
+        })
+    }
+}
+
+/// A ranked forward/reverse primer pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesignedPrimerPair {
+    pub forward: PrimerCandidate,
+    pub reverse: PrimerCandidate,
+    pub product_size: usize,
+    /// Absolute difference of the melting temperatures (°C)
+    pub tm_difference: f64,
+    /// Most stable heterodimer (kcal/mol)
+    pub dimer_dg: f64,
+    /// Most stable heterodimer with a paired 3' end of either primer (kcal/mol)
+    pub three_prime_dg: f64,
+    /// Penalty of the pair, lower is better
+    pub penalty: f64,
+}
+
+impl DesignedPrimerPair {
+    /// Annotations of both primers, labelled with the rank of the pair.
+    /// Annotation ends are inclusive.
+    pub fn annotations(&self, rank: usize) -> [Annotation; 2] {
+        [
+            Annotation::new(
+                self.forward.start,
+                self.forward.end - 1,
+                None,
+                format!("Primer {} F", rank),
+            )
+            .with_kind(FeatureType::PrimerBind)
+            .with_strand(Strand::Forward),
+            Annotation::new(
+                self.reverse.start,
+                self.reverse.end - 1,
+                None,
+                format!("Primer {} R", rank),
+            )
+            .with_kind(FeatureType::PrimerBind)
+            .with_strand(Strand::Reverse),
+        ]
+    }
+}
+
+/// Whether `primer` binds to `site` with a perfectly matching 3' end.
+fn binds(site: &[DnaNucleotide], primer: &[DnaNucleotide]) -> bool {
+    let anchor = OFF_TARGET_ANCHOR.min(primer.len());
+    site[site.len() - anchor..] == primer[primer.len() - anchor..]
+        && site
+            .iter()
+            .zip(primer)
+            .filter(|(a, b)| a != b)
+            .take(OFF_TARGET_MISMATCHES + 1)
+            .count()
+            <= OFF_TARGET_MISMATCHES
+}
+
+/// Count binding sites of a primer on both strands of the template.
+fn binding_sites(
+    template: &[DnaNucleotide],
+    template_rc: &[DnaNucleotide],
+    primer: &[DnaNucleotide],
+) -> usize {
+    [template, template_rc]
+        .iter()
+        .flat_map(|strand| strand.windows(primer.len()))
+        .filter(|site| binds(site, primer))
+        .count()
+}
+
+/// Keep the best candidates and count their off-target binding sites.
+fn shortlist(
+    mut candidates: Vec<PrimerCandidate>,
+    template: &[DnaNucleotide],
+    template_rc: &[DnaNucleotide],
+) -> Vec<PrimerCandidate> {
+    candidates.sort_by(|a, b| a.penalty.total_cmp(&b.penalty));
+    candidates.truncate(CANDIDATES_PER_STRAND);
+    for candidate in candidates.iter_mut() {
+        candidate.off_target =
+            binding_sites(template, template_rc, &candidate.sequence).saturating_sub(1);
+    }
+    candidates
+}
+
+/// Design primer pairs that amplify the `target` region of a template.
+///
+/// Forward primers are placed upstream and reverse primers downstream of the
+/// target. Pairs are ranked by the melting temperature deviation of both
+/// primers, their difference, self-/heterodimer and hairpin stability and the
+/// number of off-target binding sites in the template.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// let template = DnaSequence::from_str(concat!(
+///     "TTGACAGCTAGCTCAGTCCTAGGTATAATGCTAGCGAATTCATTAAAGAGGAGAAAGGTACC",
+///     "ATGCGTAAAGGAGAAGAACTTTTCACTGGAGTTGTCCCAATTCTTGTTGAATTAGATGGTG",
+///     "ATGTTAATGGGCACAAATTTTCTGTCAGTGGAGAGGGTGAAGGTGATGCAACATACGGAAA",
+///     "ACTTACCCTTAAATTTATTTGCACTACTGGAAAACTACCTGTTCCATGGCCAACACTTGTC",
+/// )).unwrap();
+/// let constraints = PrimerConstraints {
+///     product_size: 80..=240,
+///     tm: 50.0..=65.0,
+///     gc_content: 0.3..=0.7,
+///     max_tm_difference: 10.0,
+///     ..PrimerConstraints::default()
+/// };
+/// let pairs = design_primers(template.as_nucleotides(), 100..140, &constraints).unwrap();
+/// let best = pairs.first().unwrap();
+/// assert!(best.forward.end <= 100 && best.reverse.start >= 140);
+/// ```
+pub fn design_primers(
+    template: &[DnaNucleotide],
+    target: Range<usize>,
+    constraints: &PrimerConstraints,
+) -> anyhow::Result<Vec<DesignedPrimerPair>> {
+    if target.start >= target.end || target.end > template.len() {
+        bail!(PlasmidError::InvalidRegion {
+            start: target.start,
+            end: target.end,
+            len: template.len(),
+        });
+    }
+    let template_rc = reverse_complement(template);
+    let max_product = *constraints.product_size.end();
+
+    // Forward primers end before the target, reverse primers start after it
+    let mut forward = Vec::new();
+    let mut reverse = Vec::new();
+    for len in constraints.length.clone() {
+        for start in target.start.saturating_sub(max_product)..=target.start.saturating_sub(len) {
+            if start + len > target.start {
+                break;
+            }
+            let seq = template[start..start + len].to_vec();
+            forward.extend(PrimerCandidate::new(
+                start,
+                start + len,
+                true,
+                seq,
+                constraints,
+            ));
+        }
+        for start in target.end..(target.start + max_product).min(template.len()) {
+            if start + len > template.len() {
+                break;
+            }
+            let seq = reverse_complement(&template[start..start + len]);
+            reverse.extend(PrimerCandidate::new(
+                start,
+                start + len,
+                false,
+                seq,
+                constraints,
+            ));
+        }
+    }
+    let forward = shortlist(forward, template, &template_rc);
+    let reverse = shortlist(reverse, template, &template_rc);
+
+    let mut pairs = Vec::new();
+    for fwd in forward.iter() {
+        for rev in reverse.iter() {
+            let product_size = rev.end - fwd.start;
+            let tm_difference = (fwd.properties.tm - rev.properties.tm).abs();
+            if !constraints.product_size.contains(&product_size)
+                || tm_difference > constraints.max_tm_difference
+            {
+                continue;
+            }
+            let dimer_dg = dimer_dg(&fwd.sequence, &rev.sequence);
+            let three_prime_dg = three_prime_dimer_dg(&fwd.sequence, &rev.sequence)
+                .min(three_prime_dimer_dg(&rev.sequence, &fwd.sequence));
+            let penalty = fwd.penalty
+                + rev.penalty
+                + tm_difference
+                + (-three_prime_dg - 5_f64).max(0_f64)
+                + (-dimer_dg - 8_f64).max(0_f64) / 2_f64
+                + 5_f64 * (fwd.off_target + rev.off_target) as f64;
+            pairs.push(DesignedPrimerPair {
+                forward: fwd.clone(),
+                reverse: rev.clone(),
+                product_size,
+                tm_difference,
+                dimer_dg,
+                three_prime_dg,
+                penalty,
+            });
+        }
+    }
+    pairs.sort_by(|a, b| a.penalty.total_cmp(&b.penalty));
+    pairs.truncate(constraints.max_pairs);
+    Ok(pairs)
+}
+
+impl DnaSequence {
+    /// Design primer pairs that amplify the `target` region of the sequence.
+    pub fn design_primers(
+        &self,
+        target: Range<usize>,
+        constraints: &PrimerConstraints,
+    ) -> anyhow::Result<Vec<DesignedPrimerPair>> {
+        design_primers(self.as_nucleotides(), target, constraints)
+    }
+
+    /// Design primer pairs for the `target` region and annotate them on the sequence.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("ATGC").unwrap();
+    /// assert!(seq.annotate_primer_pairs(2..8, &PrimerConstraints::default()).is_err());
+    /// ```
+    pub fn annotate_primer_pairs(
+        &mut self,
+        target: Range<usize>,
+        constraints: &PrimerConstraints,
+    ) -> anyhow::Result<Vec<DesignedPrimerPair>> {
+        let pairs = self.design_primers(target, constraints)?;
+        for (rank, pair) in pairs.iter().enumerate() {
+            self.as_mut_annotations().extend(pair.annotations(rank + 1));
+        }
+        Ok(pairs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{binding_sites, design_primers, PrimerConstraints};
+    use crate::seq::{reverse_complement, DnaSequence};
+
+    const TEMPLATE: &str = concat!(
+        "TTGACAGCTAGCTCAGTCCTAGGTATAATGCTAGCGAATTCATTAAAGAGGAGAAAGGTACC",
+        "ATGCGTAAAGGAGAAGAACTTTTCACTGGAGTTGTCCCAATTCTTGTTGAATTAGATGGTG",
+        "ATGTTAATGGGCACAAATTTTCTGTCAGTGGAGAGGGTGAAGGTGATGCAACATACGGAAA",
+        "ACTTACCCTTAAATTTATTTGCACTACTGGAAAACTACCTGTTCCATGGCCAACACTTGTC",
+    );
+
+    fn constraints() -> PrimerConstraints {
+        PrimerConstraints {
+            product_size: 80..=240,
+            tm: 50_f64..=65_f64,
+            gc_content: 0.3..=0.7,
+            max_tm_difference: 10_f64,
+            ..PrimerConstraints::default()
+        }
+    }
+
+    #[test]
+    fn test_design_primers_ranked() {
+        let template = DnaSequence::from_str(TEMPLATE).unwrap();
+        let pairs = design_primers(template.as_nucleotides(), 100..140, &constraints()).unwrap();
+        assert!(!pairs.is_empty());
+        assert!(pairs.len() <= 5);
+        assert!(pairs.windows(2).all(|p| p[0].penalty <= p[1].penalty));
+        for pair in pairs.iter() {
+            assert!(constraints().product_size.contains(&pair.product_size));
+            let fwd = &template.as_nucleotides()[pair.forward.start..pair.forward.end];
+            assert_eq!(fwd, pair.forward.sequence);
+            let rev = &template.as_nucleotides()[pair.reverse.start..pair.reverse.end];
+            assert_eq!(reverse_complement(rev), pair.reverse.sequence);
+        }
+    }
+
+    #[test]
+    fn test_design_primers_invalid_target() {
+        let template = DnaSequence::from_str(TEMPLATE).unwrap();
+        let (start, end) = (40, 20);
+        assert!(design_primers(template.as_nucleotides(), start..end, &constraints()).is_err());
+        assert!(design_primers(template.as_nucleotides(), 200..400, &constraints()).is_err());
+    }
+
+    #[test]
+    fn test_annotate_primer_pairs() {
+        let mut template = DnaSequence::from_str(TEMPLATE).unwrap();
+        let pairs = template
+            .annotate_primer_pairs(100..140, &constraints())
+            .unwrap();
+        assert_eq!(template.as_annotations().len(), pairs.len() * 2);
+        let ann = template.as_annotations().first().unwrap();
+        assert_eq!(ann.text, "Primer 1 F");
+        assert_eq!(ann.end, pairs[0].forward.end - 1);
+    }
+
+    #[test]
+    fn test_binding_sites_repeat() {
+        let template = DnaSequence::from_str("ACGTACGGATCCAAAACGTACGGATCC").unwrap();
+        let template = template.as_nucleotides();
+        let primer = &template[0..12];
+        assert_eq!(
+            binding_sites(template, &reverse_complement(template), primer),
+            2
+        );
+    }
+}
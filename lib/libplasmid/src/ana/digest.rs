@@ -0,0 +1,438 @@
+use crate::{
+    seq::{reverse_complement, DnaSequence, Topology},
+    traits::ToIupac,
+    uni::{find_restriction_enzyme, IupacNucleotide, RestrictionEnzyme},
+};
+
+/// Single-stranded extension at the end of a fragment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Overhang {
+    Blunt,
+    /// Protruding 5' end, 5' to 3'
+    FivePrime(Vec<IupacNucleotide>),
+    /// Protruding 3' end, 5' to 3'
+    ThreePrime(Vec<IupacNucleotide>),
+}
+
+impl Overhang {
+    pub fn len(&self) -> usize {
+        match self {
+            Overhang::Blunt => 0,
+            Overhang::FivePrime(seq) | Overhang::ThreePrime(seq) => seq.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// End of a digested fragment.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FragmentEnd {
+    /// Original end of a linear template
+    Terminus,
+    /// End created by a restriction enzyme
+    Cut { enzyme: String, overhang: Overhang },
+}
+
+/// A fragment of a restriction digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    /// Template position of the first top strand base
+    pub start: usize,
+    /// Template position after the last top strand base, below `start`
+    /// if the fragment spans the origin of a circular template
+    pub end: usize,
+    pub left: FragmentEnd,
+    pub right: FragmentEnd,
+    /// Top strand sequence, 5' to 3'
+    pub sequence: Vec<IupacNucleotide>,
+    /// Whether the fragment is an uncut circular molecule
+    pub circular: bool,
+}
+
+impl Fragment {
+    /// Length of the top strand.
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+
+    /// The fragment turned around, with its bottom strand as top strand
+    /// and its ends swapped. Template positions are kept.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::{prelude::*, traits::ToLetter, uni::find_restriction_enzyme};
+    ///
+    /// let seq = DnaSequence::from_str("AAGAATTCAAAAGGATCCAA").unwrap();
+    /// let enzymes = [
+    ///     find_restriction_enzyme("EcoRI").unwrap(),
+    ///     find_restriction_enzyme("BamHI").unwrap(),
+    /// ];
+    /// let fragment = digest(seq.as_nucleotides(), Topology::Linear, &enzymes).remove(1);
+    /// let letters = |f: &Fragment| f.sequence.iter().map(|n| n.to_letter()).collect::<String>();
+    /// assert_eq!(letters(&fragment), "AATTCAAAAG");
+    /// assert_eq!(letters(&fragment.reverse_complement()), "GATCCTTTTG");
+    /// ```
+    pub fn reverse_complement(&self) -> Fragment {
+        // Bottom strand in top strand orientation
+        let mut bottom = match &self.left {
+            FragmentEnd::Cut {
+                overhang: Overhang::FivePrime(seq),
+                ..
+            } => self.sequence[seq.len().min(self.len())..].to_vec(),
+            FragmentEnd::Cut {
+                overhang: Overhang::ThreePrime(seq),
+                ..
+            } => {
+                let mut bottom = reverse_complement(seq);
+                bottom.extend_from_slice(&self.sequence);
+                bottom
+            }
+            _ => self.sequence.clone(),
+        };
+        match &self.right {
+            FragmentEnd::Cut {
+                overhang: Overhang::FivePrime(seq),
+                ..
+            } => bottom.extend(reverse_complement(seq)),
+            FragmentEnd::Cut {
+                overhang: Overhang::ThreePrime(seq),
+                ..
+            } => bottom.truncate(bottom.len().saturating_sub(seq.len())),
+            _ => {}
+        }
+        Fragment {
+            start: self.start,
+            end: self.end,
+            left: self.right.clone(),
+            right: self.left.clone(),
+            sequence: reverse_complement(&bottom),
+            circular: self.circular,
+        }
+    }
+}
+
+/// A double-strand break, in template coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cut<'a> {
+    enzyme: &'a str,
+    top: usize,
+    /// Bottom strand cut relative to the top strand cut
+    offset: isize,
+}
+
+/// Bases between `from` and `to`, wrapping around the origin of circular templates.
+fn slice(sequence: &[IupacNucleotide], from: isize, to: isize) -> Vec<IupacNucleotide> {
+    let len = sequence.len() as isize;
+    (from..to)
+        .map(|i| sequence[i.rem_euclid(len) as usize])
+        .collect()
+}
+
+/// Find all double-strand breaks of an enzyme in the sequence.
+/// Nicking enzymes leave the molecule intact and yield no cuts.
+fn find_cuts<'a>(
+    sequence: &[IupacNucleotide],
+    topology: Topology,
+    enzyme: &'a RestrictionEnzyme,
+) -> Vec<Cut<'a>> {
+    let len = sequence.len() as isize;
+    let mut cuts = Vec::new();
+    for site in enzyme.find_sites(sequence, topology.is_circular()) {
+        let (top, bottom) = match (site.top_cut, site.bottom_cut) {
+            (Some(top), Some(bottom)) => (top, bottom),
+            _ => continue,
+        };
+        let cut = match topology {
+            Topology::Linear => {
+                let inside = |pos: isize| pos > 0 && pos < len;
+                if !inside(top) || !inside(bottom) {
+                    continue;
+                }
+                top as usize
+            }
+            Topology::Circular => top.rem_euclid(len) as usize,
+        };
+        cuts.push(Cut {
+            enzyme: &enzyme.name,
+            top: cut,
+            offset: bottom - top,
+        });
+    }
+    cuts
+}
+
+/// Ends created on the left and right side of a cut.
+fn cut_ends(sequence: &[IupacNucleotide], cut: &Cut) -> (FragmentEnd, FragmentEnd) {
+    let top = cut.top as isize;
+    let bottom = top + cut.offset;
+    let (left, right) = match cut.offset {
+        0 => (Overhang::Blunt, Overhang::Blunt),
+        // Bottom strand protrudes on the left, top strand on the right fragment
+        offset if offset > 0 => (
+            Overhang::FivePrime(reverse_complement(&slice(sequence, top, bottom))),
+            Overhang::FivePrime(slice(sequence, top, bottom)),
+        ),
+        // Top strand protrudes on the left, bottom strand on the right fragment
+        _ => (
+            Overhang::ThreePrime(slice(sequence, bottom, top)),
+            Overhang::ThreePrime(reverse_complement(&slice(sequence, bottom, top))),
+        ),
+    };
+    let end = |overhang| FragmentEnd::Cut {
+        enzyme: cut.enzyme.to_string(),
+        overhang,
+    };
+    (end(left), end(right))
+}
+
+/// Digest a sequence with a set of restriction enzymes.
+///
+/// Fragments are returned in the order of their position on the template.
+/// A circular template without any cut site yields a single uncut circular fragment.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::{prelude::*, uni::find_restriction_enzyme};
+///
+/// let seq = DnaSequence::from_str("AAGAATTCAAAAGGATCCAA").unwrap();
+/// let enzymes = [
+///     find_restriction_enzyme("EcoRI").unwrap(),
+///     find_restriction_enzyme("BamHI").unwrap(),
+/// ];
+/// let fragments = digest(seq.as_nucleotides(), Topology::Linear, &enzymes);
+/// let sizes = fragments.iter().map(|f| f.len()).collect::<Vec<_>>();
+/// assert_eq!(sizes, [3, 10, 7]);
+/// ```
+pub fn digest<T>(
+    sequence: &[T],
+    topology: Topology,
+    enzymes: &[&RestrictionEnzyme],
+) -> Vec<Fragment>
+where
+    T: ToIupac,
+{
+    let sequence = sequence.iter().map(|n| n.to_iupac()).collect::<Vec<_>>();
+    let len = sequence.len();
+
+    let mut cuts = enzymes
+        .iter()
+        .flat_map(|enzyme| find_cuts(&sequence, topology, enzyme))
+        .collect::<Vec<_>>();
+    cuts.sort_by_key(|cut| (cut.top, cut.offset));
+    cuts.dedup_by_key(|cut| (cut.top, cut.offset));
+
+    if cuts.is_empty() {
+        return vec![Fragment {
+            start: 0,
+            end: len,
+            left: FragmentEnd::Terminus,
+            right: FragmentEnd::Terminus,
+            sequence,
+            circular: topology.is_circular(),
+        }];
+    }
+
+    let ends = cuts
+        .iter()
+        .map(|cut| cut_ends(&sequence, cut))
+        .collect::<Vec<_>>();
+    let fragment = |start: usize, end: usize, left: FragmentEnd, right: FragmentEnd| {
+        let stop = if end <= start && topology.is_circular() {
+            end + len
+        } else {
+            end
+        };
+        Fragment {
+            start,
+            end,
+            left,
+            right,
+            sequence: slice(&sequence, start as isize, stop as isize),
+            circular: false,
+        }
+    };
+
+    let mut fragments = Vec::new();
+    if topology == Topology::Linear {
+        fragments.push(fragment(
+            0,
+            cuts[0].top,
+            FragmentEnd::Terminus,
+            ends[0].0.clone(),
+        ));
+    }
+    for i in 0..cuts.len() - 1 {
+        fragments.push(fragment(
+            cuts[i].top,
+            cuts[i + 1].top,
+            ends[i].1.clone(),
+            ends[i + 1].0.clone(),
+        ));
+    }
+    let last = cuts.len() - 1;
+    match topology {
+        Topology::Linear => fragments.push(fragment(
+            cuts[last].top,
+            len,
+            ends[last].1.clone(),
+            FragmentEnd::Terminus,
+        )),
+        Topology::Circular => fragments.push(fragment(
+            cuts[last].top,
+            cuts[0].top,
+            ends[last].1.clone(),
+            ends[0].0.clone(),
+        )),
+    }
+    fragments
+}
+
+impl DnaSequence {
+    /// Digest the sequence with restriction enzymes given by name.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let seq = DnaSequence::from_str("AAGAATTCAAAAGGATCCAA").unwrap();
+    /// let fragments = seq.digest(Topology::Circular, &["EcoRI", "BamHI"]).unwrap();
+    /// assert_eq!(fragments.len(), 2);
+    /// assert!(seq.digest(Topology::Circular, &["FooI"]).is_err());
+    /// ```
+    pub fn digest<S>(&self, topology: Topology, enzymes: &[S]) -> anyhow::Result<Vec<Fragment>>
+    where
+        S: AsRef<str>,
+    {
+        let enzymes = enzymes
+            .iter()
+            .map(|name| match find_restriction_enzyme(name.as_ref()) {
+                Some(enzyme) => Ok(enzyme),
+                None => Err(anyhow!("Unknown restriction enzyme: {}", name.as_ref())),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(digest(self.as_nucleotides(), topology, &enzymes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{digest, FragmentEnd, Overhang};
+    use crate::{
+        seq::{DnaSequence, Topology},
+        test_utils::iupac,
+        uni::find_restriction_enzyme,
+    };
+
+    fn overhang(end: &FragmentEnd) -> &Overhang {
+        match end {
+            FragmentEnd::Cut { overhang, .. } => overhang,
+            FragmentEnd::Terminus => panic!("expected a cut end"),
+        }
+    }
+
+    #[test]
+    fn test_digest_linear_five_prime_overhang() {
+        let seq = DnaSequence::from_str("AAGAATTCAA").unwrap();
+        let ecori = find_restriction_enzyme("EcoRI").unwrap();
+        let fragments = digest(seq.as_nucleotides(), Topology::Linear, &[ecori]);
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[0].sequence, iupac("AAG"));
+        assert_eq!(fragments[0].left, FragmentEnd::Terminus);
+        assert_eq!(
+            overhang(&fragments[0].right),
+            &Overhang::FivePrime(iupac("AATT"))
+        );
+        assert_eq!(fragments[1].sequence, iupac("AATTCAA"));
+        assert_eq!(
+            overhang(&fragments[1].left),
+            &Overhang::FivePrime(iupac("AATT"))
+        );
+    }
+
+    #[test]
+    fn test_digest_three_prime_overhang_and_blunt() {
+        let seq = DnaSequence::from_str("AACTGCAGAACCCGGGAA").unwrap();
+        let pst = find_restriction_enzyme("PstI").unwrap();
+        let sma = find_restriction_enzyme("SmaI").unwrap();
+        let fragments = digest(seq.as_nucleotides(), Topology::Linear, &[pst, sma]);
+        assert_eq!(fragments.len(), 3);
+        assert_eq!(
+            overhang(&fragments[0].right),
+            &Overhang::ThreePrime(iupac("TGCA"))
+        );
+        assert_eq!(overhang(&fragments[1].right), &Overhang::Blunt);
+        assert_eq!(fragments[2].sequence, iupac("GGGAA"));
+    }
+
+    #[test]
+    fn test_digest_circular_wraps_origin() {
+        // EcoRI site spans the origin: GAA|TTC
+        let seq = DnaSequence::from_str("TTCAAAAGGATCCAAAAGAA").unwrap();
+        let ecori = find_restriction_enzyme("EcoRI").unwrap();
+        let bamhi = find_restriction_enzyme("BamHI").unwrap();
+        let fragments = digest(seq.as_nucleotides(), Topology::Circular, &[ecori, bamhi]);
+        assert_eq!(fragments.len(), 2);
+        let total = fragments.iter().map(|f| f.len()).sum::<usize>();
+        assert_eq!(total, 20);
+        let wrapped = fragments.iter().find(|f| f.end < f.start).unwrap();
+        assert_eq!(wrapped.sequence, iupac("AATTCAAAAG"));
+    }
+
+    #[test]
+    fn test_digest_type_iis_both_orientations() {
+        // BsaI sites facing each other excise the insert with distinct overhangs
+        let seq = DnaSequence::from_str("AAGGTCTCAAATGCCCCCCCCGCTTAGAGACCAA").unwrap();
+        let bsai = find_restriction_enzyme("BsaI").unwrap();
+        let fragments = digest(seq.as_nucleotides(), Topology::Linear, &[bsai]);
+        assert_eq!(fragments.len(), 3);
+        assert_eq!(fragments[1].sequence, iupac("AATGCCCCCCCC"));
+        assert_eq!(
+            overhang(&fragments[1].left),
+            &Overhang::FivePrime(iupac("AATG"))
+        );
+        assert_eq!(
+            overhang(&fragments[1].right),
+            &Overhang::FivePrime(iupac("AAGC"))
+        );
+    }
+
+    #[test]
+    fn test_fragment_reverse_complement_three_prime() {
+        // PstI: CTGCA^G, 3' overhangs TGCA
+        let seq = DnaSequence::from_str("AACTGCAGTTTTCTGCAGAA").unwrap();
+        let psti = find_restriction_enzyme("PstI").unwrap();
+        let fragments = digest(seq.as_nucleotides(), Topology::Linear, &[psti]);
+        let fragment = &fragments[1];
+        assert_eq!(fragment.sequence, iupac("GTTTTCTGCA"));
+        let reversed = fragment.reverse_complement();
+        assert_eq!(reversed.sequence, iupac("GAAAACTGCA"));
+        assert_eq!(reversed.left, fragment.right);
+        assert_eq!(reversed.reverse_complement().sequence, fragment.sequence);
+    }
+
+    #[test]
+    fn test_digest_nicking_enzyme() {
+        let seq = DnaSequence::from_str("AAAACACGAGAAAA").unwrap();
+        let nb = find_restriction_enzyme("NbBssSI").unwrap();
+        let fragments = digest(seq.as_nucleotides(), Topology::Linear, &[nb]);
+        assert_eq!(fragments.len(), 1);
+    }
+
+    #[test]
+    fn test_digest_uncut() {
+        let seq = DnaSequence::from_str("AAAAAAAA").unwrap();
+        let ecori = find_restriction_enzyme("EcoRI").unwrap();
+        let fragments = digest(seq.as_nucleotides(), Topology::Circular, &[ecori]);
+        assert_eq!(fragments.len(), 1);
+        assert!(fragments[0].circular);
+        let fragments = digest(seq.as_nucleotides(), Topology::Circular, &[]);
+        assert_eq!(fragments[0].len(), 8);
+    }
+}
@@ -0,0 +1,593 @@
+use std::fmt;
+
+use anyhow::bail;
+use serde::Serialize;
+
+use super::features::translate;
+use crate::{
+    eaa::Eaa,
+    seq::{reverse_complement, Annotation, FeatureType, Strand},
+    traits::*,
+    uni::IupacNucleotide,
+};
+
+/// A change of a sequence with VCF-style alleles, e.g. `C` to `CAT` for an insertion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SequenceVariant {
+    /// 0-based position of the first base of the reference allele
+    pub position: usize,
+    pub reference: String,
+    pub alternate: String,
+}
+
+impl SequenceVariant {
+    pub fn new<R: AsRef<str>, A: AsRef<str>>(position: usize, reference: R, alternate: A) -> Self {
+        SequenceVariant {
+            position,
+            reference: reference.as_ref().to_ascii_uppercase(),
+            alternate: alternate.as_ref().to_ascii_uppercase(),
+        }
+    }
+}
+
+/// Consequence of a variant for the protein encoded by a CDS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariantEffect {
+    /// Codons change, the protein does not
+    Synonymous,
+    /// A single amino acid is replaced, or as many as are removed
+    Missense,
+    /// A premature stop codon is introduced
+    Nonsense,
+    /// The reading frame downstream of the variant shifts
+    Frameshift,
+    InframeInsertion,
+    InframeDeletion,
+    /// The start codon no longer codes for Met
+    StartLost,
+    /// The stop codon no longer codes for a stop, extending the protein
+    StopLost,
+    /// The variant only partly overlaps the CDS, e.g. a splice junction
+    Complex,
+}
+
+impl fmt::Display for VariantEffect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            VariantEffect::Synonymous => "synonymous",
+            VariantEffect::Missense => "missense",
+            VariantEffect::Nonsense => "nonsense",
+            VariantEffect::Frameshift => "frameshift",
+            VariantEffect::InframeInsertion => "inframe_insertion",
+            VariantEffect::InframeDeletion => "inframe_deletion",
+            VariantEffect::StartLost => "start_lost",
+            VariantEffect::StopLost => "stop_lost",
+            VariantEffect::Complex => "complex",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Effect of a variant on one CDS, in HGVS notation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CodingEffect {
+    /// Label of the CDS annotation
+    pub feature: String,
+    pub effect: VariantEffect,
+    /// Coding DNA change, e.g. `c.35G>A`
+    pub hgvs_c: String,
+    /// Protein change, e.g. `p.Gly12Asp`
+    pub hgvs_p: String,
+    /// Codons affected by the variant, in the reading frame of the CDS
+    pub reference_codons: String,
+    pub alternate_codons: String,
+    /// Translation of the affected codons
+    pub reference_amino_acids: String,
+    pub alternate_amino_acids: String,
+}
+
+impl CodingEffect {
+    fn complex(feature: &str) -> Self {
+        CodingEffect {
+            feature: feature.to_string(),
+            effect: VariantEffect::Complex,
+            hgvs_c: "c.?".to_string(),
+            hgvs_p: "p.?".to_string(),
+            reference_codons: String::new(),
+            alternate_codons: String::new(),
+            reference_amino_acids: String::new(),
+            alternate_amino_acids: String::new(),
+        }
+    }
+}
+
+fn parse_allele(allele: &str) -> anyhow::Result<Vec<IupacNucleotide>> {
+    allele
+        .trim()
+        .chars()
+        .filter(|c| *c != '-' && *c != '.')
+        .map(IupacNucleotide::try_from_letter)
+        .collect()
+}
+
+fn to_text(seq: &[IupacNucleotide]) -> String {
+    seq.iter().map(|n| n.to_letter()).collect()
+}
+
+fn translate_all(seq: &[IupacNucleotide]) -> String {
+    seq.chunks_exact(3).map(translate).collect()
+}
+
+/// Three letter code of a one letter amino acid, as used in HGVS.
+fn three_letter(aa: char) -> &'static str {
+    match aa {
+        '*' => "Ter",
+        aa => Eaa::try_from_letter(aa)
+            .map(|eaa| eaa.to_three_letter())
+            .unwrap_or("Xaa"),
+    }
+}
+
+/// Positions of a CDS in the sequence, in the order of its codons.
+fn cds_positions(annotation: &Annotation, seq_len: usize) -> Vec<usize> {
+    let mut positions = Vec::new();
+    for (start, end) in annotation.spans() {
+        if end < start {
+            positions.extend(start..seq_len);
+            positions.extend(0..=end);
+        } else {
+            positions.extend(start..=end.min(seq_len.saturating_sub(1)));
+        }
+    }
+    if annotation.strand == Strand::Reverse {
+        positions.reverse();
+    }
+    positions
+}
+
+/// HGVS coding DNA notation of replacing `cds[start..end]` by `inserted`.
+fn hgvs_c(
+    cds: &[IupacNucleotide],
+    start: usize,
+    end: usize,
+    inserted: &[IupacNucleotide],
+) -> String {
+    let range = |from: usize, to: usize| match to - from {
+        1 => format!("{}", from + 1),
+        _ => format!("{}_{}", from + 1, to),
+    };
+    match (end - start, inserted.len()) {
+        (1, 1) => format!(
+            "c.{}{}>{}",
+            start + 1,
+            cds[start].to_letter(),
+            inserted[0].to_letter()
+        ),
+        (_, 0) => format!("c.{}del", range(start, end)),
+        (0, len) if start >= len && cds[start - len..start] == *inserted => {
+            format!("c.{}dup", range(start - len, start))
+        }
+        (0, _) => format!("c.{}_{}ins{}", start, start + 1, to_text(inserted)),
+        _ => format!("c.{}delins{}", range(start, end), to_text(inserted)),
+    }
+}
+
+/// Classify the change of `reference` to `alternate` protein, both ending at their first stop.
+fn protein_effect(
+    reference: &[char],
+    alternate: &[char],
+    frameshift: bool,
+) -> (VariantEffect, String) {
+    let Some(first) =
+        (0..reference.len().max(alternate.len())).find(|i| reference.get(*i) != alternate.get(*i))
+    else {
+        return (VariantEffect::Synonymous, String::new());
+    };
+    let (Some(&ref_aa), Some(&alt_aa)) = (reference.get(first), alternate.get(first)) else {
+        return match frameshift {
+            true => (VariantEffect::Frameshift, "p.?".to_string()),
+            false => (VariantEffect::Complex, "p.?".to_string()),
+        };
+    };
+    let at = |aa: char, i: usize| format!("{}{}", three_letter(aa), i + 1);
+    // Codons to the next stop of the alternate protein, counting the changed one as 1
+    let to_stop = || match alternate[first..].iter().position(|aa| *aa == '*') {
+        Some(stop) => format!("Ter{}", stop + 1),
+        None => "Ter?".to_string(),
+    };
+
+    if first == 0 && ref_aa == 'M' {
+        return (VariantEffect::StartLost, "p.Met1?".to_string());
+    }
+    if ref_aa == '*' {
+        let stop = match alternate[first + 1..].iter().position(|aa| *aa == '*') {
+            Some(stop) => format!("Ter{}", stop + 1),
+            None => "Ter?".to_string(),
+        };
+        return (
+            VariantEffect::StopLost,
+            format!("p.{}{}ext{}", at(ref_aa, first), three_letter(alt_aa), stop),
+        );
+    }
+    if alt_aa == '*' {
+        let effect = match frameshift {
+            true => VariantEffect::Frameshift,
+            false => VariantEffect::Nonsense,
+        };
+        return (effect, format!("p.{}Ter", at(ref_aa, first)));
+    }
+    if frameshift {
+        return (
+            VariantEffect::Frameshift,
+            format!(
+                "p.{}{}fs{}",
+                at(ref_aa, first),
+                three_letter(alt_aa),
+                to_stop()
+            ),
+        );
+    }
+
+    // In frame, the proteins share a prefix up to `first` and a suffix after the change
+    let common = reference[first..]
+        .iter()
+        .rev()
+        .zip(alternate[first..].iter().rev())
+        .take_while(|(r, a)| r == a)
+        .count();
+    let removed = &reference[first..reference.len() - common];
+    let added = &alternate[first..alternate.len() - common];
+    let codes = |aas: &[char]| aas.iter().map(|aa| three_letter(*aa)).collect::<String>();
+    let span = |aas: &[char], from: usize| match aas.len() {
+        1 => at(aas[0], from),
+        len => format!("{}_{}", at(aas[0], from), at(aas[len - 1], from + len - 1)),
+    };
+    match (removed.len(), added.len()) {
+        (1, 1) => (
+            VariantEffect::Missense,
+            format!("p.{}{}", at(ref_aa, first), three_letter(alt_aa)),
+        ),
+        (_, 0) => (
+            VariantEffect::InframeDeletion,
+            format!("p.{}del", span(removed, first)),
+        ),
+        (0, _) if first == 0 => (VariantEffect::InframeInsertion, "p.?".to_string()),
+        (0, len) if first >= len && reference[first - len..first] == *added => (
+            VariantEffect::InframeInsertion,
+            format!("p.{}dup", span(added, first - len)),
+        ),
+        (0, _) => (
+            VariantEffect::InframeInsertion,
+            format!(
+                "p.{}_{}ins{}",
+                at(reference[first - 1], first - 1),
+                at(ref_aa, first),
+                codes(added)
+            ),
+        ),
+        (removed_len, added_len) => {
+            let effect = match added_len.cmp(&removed_len) {
+                std::cmp::Ordering::Greater => VariantEffect::InframeInsertion,
+                std::cmp::Ordering::Less => VariantEffect::InframeDeletion,
+                std::cmp::Ordering::Equal => VariantEffect::Missense,
+            };
+            (
+                effect,
+                format!("p.{}delins{}", span(removed, first), codes(added)),
+            )
+        }
+    }
+}
+
+/// Effect of a variant on a single CDS, `None` if it does not touch the coding bases.
+fn cds_effect(
+    seq: &[IupacNucleotide],
+    circular: bool,
+    annotation: &Annotation,
+    start: usize,
+    deleted: usize,
+    inserted: &[IupacNucleotide],
+) -> Option<CodingEffect> {
+    let len = seq.len();
+    let positions = cds_positions(annotation, len);
+    let reverse = annotation.strand == Strand::Reverse;
+    let index = |position: usize| positions.iter().position(|p| *p == position);
+    let mut cds = positions.iter().map(|p| seq[*p]).collect::<Vec<_>>();
+    let mut inserted = inserted.to_vec();
+    if reverse {
+        cds.iter_mut().for_each(|n| *n = n.complement());
+        inserted = reverse_complement(&inserted);
+    }
+
+    // Bounds of the change in CDS coordinates
+    let (mut from, mut to) = if deleted == 0 {
+        // An insertion must fall between two consecutive coding bases
+        let before = match (start, circular) {
+            (0, true) => len - 1,
+            (0, false) => return None,
+            (start, _) => start - 1,
+        };
+        let (before, after) = (index(before)?, index(start % len)?);
+        match reverse {
+            false if after == before + 1 => (after, after),
+            true if before == after + 1 => (before, before),
+            _ => return None,
+        }
+    } else {
+        let indices = (start..start + deleted)
+            .map(|p| index(p % len))
+            .collect::<Vec<_>>();
+        if indices.iter().all(|i| i.is_none()) {
+            return None;
+        }
+        let indices = indices.into_iter().collect::<Option<Vec<_>>>();
+        let Some(indices) = indices else {
+            return Some(CodingEffect::complex(&annotation.text));
+        };
+        let (min, max) = (*indices.iter().min()?, *indices.iter().max()?);
+        if max - min + 1 != indices.len() {
+            return Some(CodingEffect::complex(&annotation.text));
+        }
+        (min, max + 1)
+    };
+
+    // HGVS places indels at their most 3' position in the CDS
+    if inserted.is_empty() {
+        while to < cds.len() && cds[from] == cds[to] {
+            from += 1;
+            to += 1;
+        }
+    } else if from == to {
+        while from < cds.len() && inserted[0] == cds[from] {
+            inserted.rotate_left(1);
+            from += 1;
+            to += 1;
+        }
+    }
+
+    let alternate = [&cds[..from], &inserted[..], &cds[to..]].concat();
+    let protein = |seq: &[IupacNucleotide]| {
+        let mut protein = translate_all(seq).chars().collect::<Vec<_>>();
+        if let Some(stop) = protein.iter().position(|aa| *aa == '*') {
+            protein.truncate(stop + 1);
+        }
+        protein
+    };
+    let frameshift = inserted.len().abs_diff(to - from) % 3 != 0;
+    let (effect, mut hgvs_p) = protein_effect(&protein(&cds), &protein(&alternate), frameshift);
+
+    // Codons of the change, up to the first shifted codon for frameshifts
+    let codon = from / 3;
+    let (ref_end, alt_end) = match frameshift {
+        true => (3 * codon + 3, 3 * codon + 3),
+        false => {
+            let last = match to > from {
+                true => (to - 1) / 3,
+                false => codon,
+            };
+            (3 * last + 3, 3 * last + 3 + inserted.len() - (to - from))
+        }
+    };
+    let reference_codons = &cds[(3 * codon).min(cds.len())..ref_end.min(cds.len())];
+    let alternate_codons =
+        &alternate[(3 * codon).min(alternate.len())..alt_end.min(alternate.len())];
+    if effect == VariantEffect::Synonymous {
+        let aa = translate_all(reference_codons)
+            .chars()
+            .next()
+            .unwrap_or('X');
+        hgvs_p = format!("p.{}{}=", three_letter(aa), codon + 1);
+    }
+
+    Some(CodingEffect {
+        feature: annotation.text.to_owned(),
+        effect,
+        hgvs_c: hgvs_c(&cds, from, to, &inserted),
+        hgvs_p,
+        reference_codons: to_text(reference_codons),
+        alternate_codons: to_text(alternate_codons),
+        reference_amino_acids: translate_all(reference_codons),
+        alternate_amino_acids: translate_all(alternate_codons),
+    })
+}
+
+/// Effects of a variant on every CDS among `annotations` that it changes.
+///
+/// Coding changes are given in HGVS `c.` notation, shifted to their most 3'
+/// position, and protein changes in `p.` notation with three letter codes.
+/// CDS on the reverse strand, spanning the origin or made of joined
+/// segments are followed in the direction they are translated.
+pub fn variant_effects(
+    seq: &[IupacNucleotide],
+    circular: bool,
+    annotations: &[Annotation],
+    variant: &SequenceVariant,
+) -> anyhow::Result<Vec<CodingEffect>> {
+    let reference = parse_allele(&variant.reference)?;
+    let alternate = parse_allele(&variant.alternate)?;
+    let len = seq.len();
+    if variant.position >= len || (!circular && variant.position + reference.len() > len) {
+        bail!(
+            "variant at {} lies outside the sequence",
+            variant.position + 1
+        );
+    }
+    let found = (0..reference.len())
+        .map(|i| seq[(variant.position + i) % len])
+        .collect::<Vec<_>>();
+    if found != reference {
+        bail!(
+            "reference allele {} does not match {} at {}",
+            variant.reference,
+            to_text(&found),
+            variant.position + 1
+        );
+    }
+
+    // Drop the bases the alleles share, e.g. the anchor base of VCF indels
+    let prefix = reference
+        .iter()
+        .zip(alternate.iter())
+        .take_while(|(r, a)| r == a)
+        .count();
+    let suffix = reference[prefix..]
+        .iter()
+        .rev()
+        .zip(alternate[prefix..].iter().rev())
+        .take_while(|(r, a)| r == a)
+        .count();
+    let deleted = reference.len() - prefix - suffix;
+    let inserted = &alternate[prefix..alternate.len() - suffix];
+    if deleted == 0 && inserted.is_empty() {
+        return Ok(vec![]);
+    }
+
+    Ok(annotations
+        .iter()
+        .filter(|annotation| annotation.kind == FeatureType::Cds)
+        .filter_map(|annotation| {
+            cds_effect(
+                seq,
+                circular,
+                annotation,
+                (variant.position + prefix) % len,
+                deleted,
+                inserted,
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{variant_effects, CodingEffect, SequenceVariant, VariantEffect};
+    use crate::{
+        seq::{Annotation, FeatureType, Strand},
+        test_utils::iupac,
+    };
+
+    // Met Lys Gly Leu Gln Ter, flanked by non-coding bases
+    const GENE: &str = "GGATGAAAGGCCTGCAGTAACC";
+
+    fn cds(start: usize, end: usize, strand: Strand) -> Annotation {
+        let mut annotation = Annotation::new(start, end, None, "gene");
+        annotation.kind = FeatureType::Cds;
+        annotation.strand = strand;
+        annotation
+    }
+
+    fn effect(
+        seq: &str,
+        annotation: Annotation,
+        position: usize,
+        r: &str,
+        a: &str,
+    ) -> CodingEffect {
+        let variant = SequenceVariant::new(position, r, a);
+        let mut effects = variant_effects(&iupac(seq), false, &[annotation], &variant).unwrap();
+        assert_eq!(effects.len(), 1);
+        effects.remove(0)
+    }
+
+    fn forward(position: usize, r: &str, a: &str) -> CodingEffect {
+        effect(GENE, cds(2, 19, Strand::Forward), position, r, a)
+    }
+
+    #[test]
+    fn test_substitution_effects() {
+        let e = forward(10, "C", "T");
+        assert_eq!(e.effect, VariantEffect::Synonymous);
+        assert_eq!(
+            (e.hgvs_c.as_str(), e.hgvs_p.as_str()),
+            ("c.9C>T", "p.Gly3=")
+        );
+        assert_eq!(
+            (e.reference_codons.as_str(), e.alternate_codons.as_str()),
+            ("GGC", "GGT")
+        );
+
+        let e = forward(9, "G", "A");
+        assert_eq!(e.effect, VariantEffect::Missense);
+        assert_eq!(
+            (e.hgvs_c.as_str(), e.hgvs_p.as_str()),
+            ("c.8G>A", "p.Gly3Asp")
+        );
+        assert_eq!(
+            (
+                e.reference_amino_acids.as_str(),
+                e.alternate_amino_acids.as_str()
+            ),
+            ("G", "D")
+        );
+
+        let e = forward(14, "C", "T");
+        assert_eq!(e.effect, VariantEffect::Nonsense);
+        assert_eq!(e.hgvs_p, "p.Gln5Ter");
+
+        let e = forward(3, "T", "C");
+        assert_eq!(e.effect, VariantEffect::StartLost);
+        assert_eq!(e.hgvs_p, "p.Met1?");
+
+        let e = forward(17, "T", "C");
+        assert_eq!(e.effect, VariantEffect::StopLost);
+        assert_eq!(e.hgvs_p, "p.Ter6GlnextTer?");
+    }
+
+    #[test]
+    fn test_indel_effects() {
+        // Deleting one of the two A of AAA is shifted to the 3' end of the run
+        let e = forward(4, "GA", "G");
+        assert_eq!(e.effect, VariantEffect::Frameshift);
+        assert_eq!(e.hgvs_c, "c.6del");
+        assert_eq!(e.hgvs_p, "p.Gly3AlafsTer?");
+
+        let e = forward(7, "AGGC", "A");
+        assert_eq!(e.effect, VariantEffect::InframeDeletion);
+        assert_eq!(
+            (e.hgvs_c.as_str(), e.hgvs_p.as_str()),
+            ("c.7_9del", "p.Gly3del")
+        );
+
+        let e = forward(7, "A", "ATTG");
+        assert_eq!(e.effect, VariantEffect::InframeInsertion);
+        assert_eq!(e.hgvs_c, "c.6_7insTTG");
+        assert_eq!(e.hgvs_p, "p.Lys2_Gly3insLeu");
+
+        let e = forward(10, "C", "CGGC");
+        assert_eq!(e.hgvs_c, "c.7_9dup");
+        assert_eq!(e.hgvs_p, "p.Gly3dup");
+    }
+
+    #[test]
+    fn test_reverse_strand_effect() {
+        let rc = GENE
+            .chars()
+            .rev()
+            .map(|c| match c {
+                'A' => 'T',
+                'T' => 'A',
+                'G' => 'C',
+                _ => 'G',
+            })
+            .collect::<String>();
+        // c.8G>A of the forward gene
+        let e = effect(&rc, cds(2, 19, Strand::Reverse), 12, "C", "T");
+        assert_eq!(e.effect, VariantEffect::Missense);
+        assert_eq!(
+            (e.hgvs_c.as_str(), e.hgvs_p.as_str()),
+            ("c.8G>A", "p.Gly3Asp")
+        );
+    }
+
+    #[test]
+    fn test_noncoding_and_mismatched_variants() {
+        let variant = SequenceVariant::new(0, "G", "T");
+        let annotations = [cds(2, 19, Strand::Forward)];
+        assert!(variant_effects(&iupac(GENE), false, &annotations, &variant)
+            .unwrap()
+            .is_empty());
+        let variant = SequenceVariant::new(0, "A", "T");
+        assert!(variant_effects(&iupac(GENE), false, &annotations, &variant).is_err());
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{
+    dna::{DnaCodon, DnaNucleotide},
+    seq::{reverse_complement, Annotation, Strand},
+    traits::*,
+    uni::{CommonFeature, FeatureSignature, IupacNucleotide},
+};
+
+/// Default minimum fraction of matching bases or residues of a detected feature
+pub const DEFAULT_FEATURE_IDENTITY: f64 = 0.9;
+
+/// A match of a feature, on the strand it was found on.
+struct FeatureHit {
+    /// Start on the searched strand
+    start: usize,
+    identity: f64,
+}
+
+/// One letter code of a codon, `X` if it contains ambiguous bases.
+pub(crate) fn translate(codon: &[IupacNucleotide]) -> char {
+    let bases = codon
+        .iter()
+        .map(|n| DnaNucleotide::try_from_letter(n.to_letter()))
+        .collect::<anyhow::Result<Vec<_>>>();
+    match bases {
+        Ok(bases) => DnaCodon::from_triplet_arr([bases[0], bases[1], bases[2]])
+            .translate()
+            .to_letter(),
+        Err(_) => 'X',
+    }
+}
+
+/// Slide `signature` over `target` and keep all windows starting before `starts`
+/// with at most `max_mismatches`.
+fn scan<T, F>(
+    target: &[T],
+    signature: &[T],
+    starts: usize,
+    max_mismatches: usize,
+    matches: F,
+) -> Vec<FeatureHit>
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let m = signature.len();
+    (0..starts.min((target.len() + 1).saturating_sub(m)))
+        .filter_map(|start| {
+            let mut mismatches = 0;
+            for (s, t) in signature.iter().zip(&target[start..start + m]) {
+                if !matches(s, t) {
+                    mismatches += 1;
+                    if mismatches > max_mismatches {
+                        return None;
+                    }
+                }
+            }
+            Some(FeatureHit {
+                start,
+                identity: 1_f64 - mismatches as f64 / m as f64,
+            })
+        })
+        .collect()
+}
+
+/// Find all matches of a feature on one strand, with starts in strand coordinates.
+fn find_on_strand(
+    strand: &[IupacNucleotide],
+    circular: bool,
+    feature: &CommonFeature,
+    min_identity: f64,
+) -> Vec<FeatureHit> {
+    let len = strand.len();
+    let m = feature.len();
+    if m == 0 || m > len {
+        return Vec::new();
+    }
+    // Continue past the end of circular sequences to find features spanning the origin
+    let target = match circular {
+        true => [strand, &strand[..m - 1]].concat(),
+        false => strand.to_vec(),
+    };
+    let max_mismatches = |n: usize| ((1_f64 - min_identity) * n as f64 + 1e-9).floor() as usize;
+    match &feature.signature {
+        FeatureSignature::Nucleotide(signature) => {
+            scan(&target, signature, len, max_mismatches(m), |s, t| {
+                s.matches(t)
+            })
+        }
+        FeatureSignature::Protein(signature) => {
+            let signature = signature.chars().collect::<Vec<_>>();
+            (0..3)
+                .flat_map(|frame| {
+                    let residues = target[frame.min(target.len())..]
+                        .chunks_exact(3)
+                        .map(translate)
+                        .collect::<Vec<_>>();
+                    // Codons starting within the sequence
+                    let starts = (len + 2 - frame) / 3;
+                    scan(
+                        &residues,
+                        &signature,
+                        starts,
+                        max_mismatches(signature.len()),
+                        |s, t| *s == 'X' || s == t,
+                    )
+                    .into_iter()
+                    .map(move |hit| FeatureHit {
+                        start: frame + hit.start * 3,
+                        identity: hit.identity,
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+/// Find features of a library in a sequence on both strands.
+///
+/// Features match if at least `min_identity` of their bases, or residues of
+/// protein signatures translated in all six frames, are identical.
+/// Overlapping matches of the same feature are reported once, keeping the best.
+/// On circular sequences, features may span the origin.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::{prelude::*, uni::CommonFeatures};
+///
+/// let seq = DnaSequence::from_str("GGTAATACGACTCACTATAGGCC").unwrap();
+/// let features = find_features(&seq.iupac_iter().collect::<Vec<_>>(), false, &CommonFeatures, 0.9);
+/// assert_eq!(features[0].text, "T7 promoter");
+/// assert_eq!((features[0].start, features[0].end), (2, 20));
+/// assert_eq!(features[0].strand, Strand::Forward);
+/// ```
+pub fn find_features(
+    seq: &[IupacNucleotide],
+    circular: bool,
+    features: &[CommonFeature],
+    min_identity: f64,
+) -> Vec<Annotation> {
+    let len = seq.len();
+    let reverse = reverse_complement(seq);
+    let mut annotations = Vec::new();
+    for feature in features {
+        let m = feature.len();
+        let mut hits = [(Strand::Forward, seq), (Strand::Reverse, &reverse[..])]
+            .into_iter()
+            .flat_map(|(strand, target)| {
+                find_on_strand(target, circular, feature, min_identity)
+                    .into_iter()
+                    .map(move |hit| {
+                        // Translate starts on the bottom strand back to the top strand
+                        let start = match strand {
+                            Strand::Reverse => (2 * len - hit.start - m) % len,
+                            _ => hit.start,
+                        };
+                        (start, strand, hit.identity)
+                    })
+            })
+            .collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)));
+
+        let mut found = Vec::<Annotation>::new();
+        for (start, strand, identity) in hits {
+            if (0..m).any(|i| found.iter().any(|ann| ann.contains((start + i) % len))) {
+                continue;
+            }
+            let mut ann = Annotation::new(start, (start + m - 1) % len, None, &feature.name)
+                .with_kind(feature.kind.clone())
+                .with_strand(strand);
+            if identity < 1_f64 {
+                ann = ann.with_qualifier("note", format!("{:.1}% identity", identity * 100_f64));
+            }
+            found.push(ann);
+        }
+        annotations.extend(found);
+    }
+    annotations.sort_by_key(|ann| ann.start);
+    annotations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_features;
+    use crate::{
+        seq::{FeatureType, Strand},
+        test_utils::{iupac, letters, reverse_complement_letters},
+        uni::{CommonFeature, CommonFeatures, FeatureSignature},
+    };
+
+    #[test]
+    fn test_find_nucleotide_features_on_both_strands() {
+        let seq = format!(
+            "GG{}CCCC{}AA",
+            "TAATACGACTCACTATAGG",
+            reverse_complement_letters("CAGGAAACAGCTATGAC")
+        );
+        let features = find_features(&iupac(&seq), false, &CommonFeatures, 1.0);
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0].text, "T7 promoter");
+        assert_eq!(features[0].kind, FeatureType::Promoter);
+        assert_eq!(features[1].text, "M13 rev");
+        assert_eq!((features[1].start, features[1].end), (25, 41));
+        assert_eq!(features[1].strand, Strand::Reverse);
+        assert_eq!(features[1].kind, FeatureType::PrimerBind);
+    }
+
+    #[test]
+    fn test_find_near_exact_features() {
+        // T7 promoter with a single mismatch
+        let seq = iupac("GGTAATACGACTCACTTTAGGCC");
+        assert!(find_features(&seq, false, &CommonFeatures, 1.0).is_empty());
+        let features = find_features(&seq, false, &CommonFeatures, 0.9);
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0].qualifier("note"), Some("94.7% identity"));
+    }
+
+    #[test]
+    fn test_find_protein_features() {
+        // FLAG tag on the top strand, 10x His tag on the bottom strand
+        let flag = "GATTACAAGGATGACGACGATAAG";
+        let his = "CAT".repeat(10);
+        let seq = format!("A{}TT{}", flag, reverse_complement_letters(&his));
+        let features = find_features(&iupac(&seq), false, &CommonFeatures, 0.9);
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[0].text, "FLAG");
+        assert_eq!((features[0].start, features[0].end), (1, 24));
+        assert_eq!(features[1].text, "6xHis");
+        assert_eq!(features[1].strand, Strand::Reverse);
+        // Overlapping matches within the repeat are reported once
+        assert_eq!((features[1].start, features[1].end), (27, 44));
+    }
+
+    #[test]
+    fn test_find_features_spanning_origin() {
+        let library = [CommonFeature::protein("FLAG", FeatureType::Cds, "DYKDDDDK")];
+        let flag = "GATTACAAGGATGACGACGATAAG";
+        let seq = iupac(&format!("{}CCCCCC{}", &flag[10..], &flag[..10]));
+        assert!(find_features(&seq, false, &library, 1.0).is_empty());
+        let features = find_features(&seq, true, &library, 1.0);
+        assert_eq!(features.len(), 1);
+        assert_eq!((features[0].start, features[0].end), (20, 13));
+        assert!(features[0].wraps_origin());
+    }
+
+    #[test]
+    fn test_find_puc_ori_and_lacz_alpha() {
+        // Both lie on the bottom strand of pUC19, the origin here with two point mutations
+        let ori = match &CommonFeatures
+            .iter()
+            .find(|f| f.name == "pMB1/ColE1 ori")
+            .unwrap()
+            .signature
+        {
+            FeatureSignature::Nucleotide(signature) => letters(signature),
+            FeatureSignature::Protein(_) => unreachable!(),
+        };
+        let ori = format!("{}G{}C{}", &ori[..100], &ori[101..400], &ori[401..]);
+        let lacz = concat!(
+            "CTGGCCGTCGTTTTACAACGTCGTGACTGGGAAAACCCTGGCGTTACCCAACTTAATCGCCTTGCAGCACATCCCCCTTTCG",
+            "CCAGCTGGCGTAATAGCGAAGAGGCCCGCACCGATCGCCCTTCCCAACAGTTGCGCAGCCTGAATGGCGAATGGCGCCTGAT",
+            "GCGGTATTTTCTCCTTACGCATCTGTGCGGTATTTCACACCGCATATGGTGCACTCTCAGTACAATCTGCTCTGATGCCGCATAG",
+        );
+        let seq = format!(
+            "{}GGATCC{}",
+            reverse_complement_letters(lacz),
+            reverse_complement_letters(&ori)
+        );
+        let features = find_features(&iupac(&seq), false, &CommonFeatures, 0.9);
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0].text, "lacZα");
+        assert_eq!(features[0].kind, FeatureType::Cds);
+        assert_eq!(features[0].strand, Strand::Reverse);
+        // The M13 forward primer binds within lacZα
+        assert_eq!(features[1].text, "M13 fwd");
+        assert_eq!(features[2].text, "pMB1/ColE1 ori");
+        assert_eq!(features[2].kind, FeatureType::RepOrigin);
+        assert_eq!(features[2].strand, Strand::Reverse);
+        assert_eq!((features[2].start, features[2].end), (255, 843));
+        assert_eq!(features[2].qualifier("note"), Some("99.7% identity"));
+    }
+}
@@ -0,0 +1,487 @@
+use anyhow::{anyhow, bail};
+use bio::alphabets::dna;
+use bio::data_structures::bwt::{bwt, less, Less, Occ, BWT};
+use bio::data_structures::fmindex::{FMIndex, FMIndexable};
+use bio::data_structures::suffix_array::{suffix_array, RawSuffixArray};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    imp::{Import, MultiFastaFile},
+    seq::{reverse_complement, Strand},
+    traits::{ToLetter, TryFromLetter},
+    uni::IupacNucleotide,
+};
+
+const INDEX_MAGIC: &[u8; 8] = b"DNAFMIDX";
+
+const INDEX_VERSION: u32 = 1;
+
+/// Sampling rate of the occurrence table, trading memory for lookup speed
+const OCC_SAMPLING: u32 = 32;
+
+/// Most mismatches allowed in index lookups, the search space grows exponentially with them
+pub const MAX_INDEX_MISMATCHES: usize = 3;
+
+/// A record of an indexed reference
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedSequence {
+    pub name: String,
+    /// Start of the record in the indexed text
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// An FM-index over all records of a FASTA reference
+pub struct ReferenceIndex {
+    pub name: String,
+    pub sequences: Vec<IndexedSequence>,
+    /// Records separated and terminated by `$`
+    text: Vec<u8>,
+    sa: RawSuffixArray,
+    fm: FMIndex<BWT, Less, Occ>,
+}
+
+/// An occurrence of a pattern in a reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexHit {
+    pub sequence: String,
+    /// First base of the occurrence on the top strand of the record
+    pub position: usize,
+    pub strand: Strand,
+    pub mismatches: usize,
+}
+
+/// Result of a lookup in a reference index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSearch {
+    pub forward_count: usize,
+    pub reverse_count: usize,
+    /// Located occurrences, at most the requested number
+    pub hits: Vec<IndexHit>,
+}
+
+/// Reads the fields of a saved index
+struct IndexReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> IndexReader<'a> {
+    fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        if self.data.len() < n {
+            bail!("truncated index");
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn read_u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn read_u64(&mut self) -> anyhow::Result<usize> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?).try_into()?)
+    }
+}
+
+impl ReferenceIndex {
+    fn new(name: &str, sequences: Vec<IndexedSequence>, text: Vec<u8>, sa: RawSuffixArray) -> Self {
+        let alphabet = dna::n_alphabet();
+        let bwt = bwt(&text, &sa);
+        let less = less(&bwt, &alphabet);
+        let occ = Occ::new(&bwt, OCC_SAMPLING, &alphabet);
+        ReferenceIndex {
+            name: name.to_string(),
+            sequences,
+            text,
+            sa,
+            fm: FMIndex::new(bwt, less, occ),
+        }
+    }
+
+    /// Build an index over all records of a FASTA file.
+    /// Ambiguous bases are indexed as `N` and never match.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let index = ReferenceIndex::build("ref", ">a\nGGATCCAA\n>b\nTTGGATCC\n").unwrap();
+    /// let search = index.search("GGATCC", 0, 10).unwrap();
+    /// // BamHI sites are palindromic and found on both strands
+    /// assert_eq!((search.forward_count, search.reverse_count), (2, 2));
+    /// assert_eq!((search.hits[0].sequence.as_str(), search.hits[0].position), ("a", 0));
+    /// ```
+    pub fn build(name: &str, fasta: &str) -> anyhow::Result<Self> {
+        let fasta = MultiFastaFile::import(fasta)?;
+        if fasta.records.is_empty() {
+            bail!("no sequences found in the reference");
+        }
+        let mut sequences = Vec::new();
+        let mut text = Vec::new();
+        for record in fasta.records.iter() {
+            let offset = text.len();
+            for c in record.sequence.chars().filter(|c| !c.is_whitespace()) {
+                let base = match IupacNucleotide::try_from_letter(c.to_ascii_uppercase()) {
+                    Ok(IupacNucleotide::A) => b'A',
+                    Ok(IupacNucleotide::C) => b'C',
+                    Ok(IupacNucleotide::G) => b'G',
+                    Ok(IupacNucleotide::T) => b'T',
+                    Ok(_) => b'N',
+                    Err(e) => bail!("{}: {}", record.id(), e),
+                };
+                text.push(base);
+            }
+            sequences.push(IndexedSequence {
+                name: record.id().to_string(),
+                offset,
+                len: text.len() - offset,
+            });
+            text.push(b'$');
+        }
+        let sa = suffix_array(&text);
+        Ok(Self::new(name, sequences, text, sa))
+    }
+
+    /// Number of indexed bases, without separators
+    pub fn total_len(&self) -> usize {
+        self.text.len() - self.sequences.len()
+    }
+
+    /// Serialize the index: a header with the records, followed by the text and its suffix array.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let header = serde_json::to_vec(&self.sequences).unwrap_or_default();
+        let mut data = Vec::with_capacity(32 + header.len() + self.text.len() * 9);
+        data.extend_from_slice(INDEX_MAGIC);
+        data.extend_from_slice(&INDEX_VERSION.to_le_bytes());
+        data.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        data.extend_from_slice(&header);
+        data.extend_from_slice(&(self.text.len() as u64).to_le_bytes());
+        data.extend_from_slice(&self.text);
+        for pos in self.sa.iter() {
+            data.extend_from_slice(&(*pos as u64).to_le_bytes());
+        }
+        data
+    }
+
+    /// Read an index written by `to_bytes`, rebuilding the FM-index from the stored suffix array.
+    ///
+    /// The records must tile the text, each followed by a single `$`, and the suffix array
+    /// must be a permutation of the text positions.
+    pub fn from_bytes(name: &str, data: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = IndexReader { data };
+        if reader.take(8).map_err(|_| anyhow!("not an index"))? != INDEX_MAGIC {
+            bail!("not an index");
+        }
+        let version = reader.read_u32()?;
+        if version != INDEX_VERSION {
+            bail!("unsupported index version {version}");
+        }
+        let header_len = reader.read_u64()?;
+        let header = reader.take(header_len)?;
+        let sequences: Vec<IndexedSequence> =
+            serde_json::from_slice(header).map_err(|e| anyhow!("invalid index header: {e}"))?;
+        let text_len = reader.read_u64()?;
+        let text = reader.take(text_len)?.to_vec();
+
+        if sequences.is_empty() {
+            bail!("index without sequences");
+        }
+        let mut next = 0;
+        for sequence in sequences.iter() {
+            let end = sequence.offset.checked_add(sequence.len);
+            if sequence.offset != next || end.is_none_or(|end| end >= text_len) {
+                bail!("invalid offset of {} in the index header", sequence.name);
+            }
+            let record = &text[sequence.offset..sequence.offset + sequence.len];
+            if record.iter().any(|c| !b"ACGTN".contains(c))
+                || text[sequence.offset + sequence.len] != b'$'
+            {
+                bail!("invalid bases of {} in the index", sequence.name);
+            }
+            next = sequence.offset + sequence.len + 1;
+        }
+        if next != text_len {
+            bail!("indexed text does not match the index header");
+        }
+
+        if reader.data.len() != text_len.saturating_mul(8) {
+            bail!("suffix array does not match the indexed text");
+        }
+        let mut seen = vec![false; text_len];
+        let mut sa = RawSuffixArray::with_capacity(text_len);
+        for _ in 0..text_len {
+            let pos = reader.read_u64()?;
+            if pos >= text_len || std::mem::replace(&mut seen[pos], true) {
+                bail!("invalid suffix array");
+            }
+            sa.push(pos);
+        }
+        Ok(Self::new(name, sequences, text, sa))
+    }
+
+    /// Suffix array intervals of all occurrences of `pattern` with at most `max_mismatches`,
+    /// extending the pattern backwards from its end.
+    fn intervals(
+        &self,
+        pattern: &[u8],
+        remaining: usize,
+        (lower, upper): (usize, usize),
+        mismatches: usize,
+        max_mismatches: usize,
+        found: &mut Vec<(usize, usize, usize)>,
+    ) {
+        if remaining == 0 {
+            found.push((lower, upper, mismatches));
+            return;
+        }
+        let expected = IupacNucleotide::try_from_letter(pattern[remaining - 1] as char).ok();
+        for (base, nucleotide) in [
+            (b'A', IupacNucleotide::A),
+            (b'C', IupacNucleotide::C),
+            (b'G', IupacNucleotide::G),
+            (b'T', IupacNucleotide::T),
+        ] {
+            let mismatch = !expected.is_some_and(|e| e.matches(&nucleotide));
+            if mismatch && mismatches == max_mismatches {
+                continue;
+            }
+            let less = self.fm.less(base);
+            let next_lower = less
+                + match lower {
+                    0 => 0,
+                    l => self.fm.occ(l - 1, base),
+                };
+            let next_upper = less + self.fm.occ(upper - 1, base);
+            if next_lower < next_upper {
+                self.intervals(
+                    pattern,
+                    remaining - 1,
+                    (next_lower, next_upper),
+                    mismatches + usize::from(mismatch),
+                    max_mismatches,
+                    found,
+                );
+            }
+        }
+    }
+
+    /// Record and position within it of a position of the indexed text
+    fn locate(&self, pos: usize) -> (usize, usize) {
+        let i = self.sequences.partition_point(|s| s.offset <= pos) - 1;
+        (i, pos - self.sequences[i].offset)
+    }
+
+    /// Indexed bases of a record, ambiguous ones as `N`
+    pub fn sequence(&self, record: usize) -> &[u8] {
+        let sequence = &self.sequences[record];
+        &self.text[sequence.offset..sequence.offset + sequence.len]
+    }
+
+    /// Exact occurrences of an `ACGT` seed on the top strand, as record and position pairs.
+    /// Seeds occurring more than `max_occurrences` times are considered repeats and yield none.
+    pub fn seed(&self, seed: &[u8], max_occurrences: usize) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        self.intervals(seed, seed.len(), (0, self.text.len()), 0, 0, &mut found);
+        if found
+            .iter()
+            .map(|(lower, upper, _)| upper - lower)
+            .sum::<usize>()
+            > max_occurrences
+        {
+            return Vec::new();
+        }
+        found
+            .into_iter()
+            .flat_map(|(lower, upper, _)| lower..upper)
+            .map(|i| self.locate(self.sa[i]))
+            .collect()
+    }
+
+    /// Count and locate occurrences of a pattern, which may contain IUPAC codes,
+    /// on both strands with at most `max_mismatches` substitutions.
+    /// At most `limit` occurrences are located, all of them are counted.
+    pub fn search(
+        &self,
+        pattern: &str,
+        max_mismatches: usize,
+        limit: usize,
+    ) -> anyhow::Result<IndexSearch> {
+        let pattern = pattern.trim().to_ascii_uppercase();
+        if pattern.is_empty() {
+            bail!("empty pattern");
+        }
+        let nucleotides = pattern
+            .chars()
+            .map(|c| match c {
+                '-' => Err(anyhow!("invalid nucleotide '{c}' in pattern")),
+                c => IupacNucleotide::try_from_letter(c)
+                    .map_err(|_| anyhow!("invalid nucleotide '{c}' in pattern")),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let allowed = MAX_INDEX_MISMATCHES.min(nucleotides.len() - 1);
+        if max_mismatches > allowed {
+            bail!("at most {allowed} mismatches are allowed for this pattern");
+        }
+
+        let mut search = IndexSearch {
+            forward_count: 0,
+            reverse_count: 0,
+            hits: Vec::new(),
+        };
+        for strand in [Strand::Forward, Strand::Reverse] {
+            let query = match strand {
+                Strand::Reverse => reverse_complement(&nucleotides),
+                _ => nucleotides.clone(),
+            };
+            let query = query
+                .iter()
+                .map(|n| n.to_letter() as u8)
+                .collect::<Vec<_>>();
+            let mut found = Vec::new();
+            let all = (0, self.text.len());
+            self.intervals(&query, query.len(), all, 0, max_mismatches, &mut found);
+            found.sort_by_key(|(_, _, mismatches)| *mismatches);
+
+            let count = found.iter().map(|(lower, upper, _)| upper - lower).sum();
+            match strand {
+                Strand::Reverse => search.reverse_count = count,
+                _ => search.forward_count = count,
+            }
+            for (lower, upper, mismatches) in found {
+                for i in lower..upper {
+                    if search.hits.len() >= limit {
+                        break;
+                    }
+                    let (record, position) = self.locate(self.sa[i]);
+                    search.hits.push(IndexHit {
+                        sequence: self.sequences[record].name.to_owned(),
+                        position,
+                        strand,
+                        mismatches,
+                    });
+                }
+            }
+        }
+        search.hits.sort_by(|a, b| {
+            (a.mismatches, &a.sequence, a.position).cmp(&(b.mismatches, &b.sequence, b.position))
+        });
+        Ok(search)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReferenceIndex;
+    use crate::seq::Strand;
+
+    const REFERENCE: &str = ">chr1\nACGTTTGACCANNGAATTCAAGG\n>chr2\nCCTTGAATTCGGCATTTACGACC\n";
+
+    fn hits(
+        index: &ReferenceIndex,
+        pattern: &str,
+        mismatches: usize,
+    ) -> Vec<(String, usize, Strand)> {
+        index
+            .search(pattern, mismatches, 100)
+            .unwrap()
+            .hits
+            .into_iter()
+            .map(|hit| (hit.sequence, hit.position, hit.strand))
+            .collect()
+    }
+
+    #[test]
+    fn test_build_index() {
+        let index = ReferenceIndex::build("ref", REFERENCE).unwrap();
+        assert_eq!(index.sequences.len(), 2);
+        assert_eq!(
+            (index.sequences[1].offset, index.sequences[1].len),
+            (24, 23)
+        );
+        assert_eq!(index.total_len(), 46);
+        assert_eq!(index.sequence(0), b"ACGTTTGACCANNGAATTCAAGG");
+        assert!(ReferenceIndex::build("ref", ">a\nAC1G\n").is_err());
+        assert!(ReferenceIndex::build("ref", "").is_err());
+    }
+
+    #[test]
+    fn test_search_index() {
+        let index = ReferenceIndex::build("ref", REFERENCE).unwrap();
+        // EcoRI sites are palindromic
+        let search = index.search("GAATTC", 0, 100).unwrap();
+        assert_eq!((search.forward_count, search.reverse_count), (2, 2));
+        // Bottom strand occurrences are reported at their top strand position
+        assert_eq!(
+            hits(&index, "GGTCGTAAATG", 0),
+            [("chr2".to_string(), 12, Strand::Reverse)]
+        );
+        assert_eq!(
+            hits(&index, "TTTGAC", 0),
+            [("chr1".to_string(), 3, Strand::Forward)]
+        );
+        // IUPAC codes, mismatches and the limit of located hits
+        assert_eq!(hits(&index, "TTTRAC", 0), hits(&index, "TTTGAC", 0));
+        assert!(hits(&index, "TTTCAC", 0).is_empty());
+        let search = index.search("TTTCAC", 1, 100).unwrap();
+        assert_eq!(search.hits[0].mismatches, 1);
+        assert_eq!(index.search("A", 0, 3).unwrap().hits.len(), 3);
+        // Ambiguous reference bases never match
+        assert!(hits(&index, "CCANNGAA", 0).is_empty());
+        assert!(index.search("ACG", 3, 100).is_err());
+        assert!(index.search("AC-G", 0, 100).is_err());
+        assert!(index.search(" ", 0, 100).is_err());
+    }
+
+    #[test]
+    fn test_seed_index() {
+        let index = ReferenceIndex::build("ref", REFERENCE).unwrap();
+        let mut seeds = index.seed(b"GAATTC", 10);
+        seeds.sort();
+        assert_eq!(seeds, [(0, 13), (1, 4)]);
+        assert!(index.seed(b"GAATTC", 1).is_empty());
+    }
+
+    #[test]
+    fn test_index_round_trip() {
+        let index = ReferenceIndex::build("ref", REFERENCE).unwrap();
+        let loaded = ReferenceIndex::from_bytes("copy", &index.to_bytes()).unwrap();
+        assert_eq!(loaded.name, "copy");
+        assert_eq!(loaded.sequences, index.sequences);
+        for pattern in ["GAATTC", "TTTGAC", "ACGNNA", "CC"] {
+            assert_eq!(
+                loaded.search(pattern, 1, 100).unwrap(),
+                index.search(pattern, 1, 100).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_reject_corrupt_index() {
+        let index = ReferenceIndex::build("ref", REFERENCE).unwrap();
+        let data = index.to_bytes();
+        let load = |data: &[u8]| ReferenceIndex::from_bytes("ref", data);
+        assert!(load(&data[..data.len() - 1]).is_err());
+        assert!(load(&data[..10]).is_err());
+        assert!(load(b"DNAFMIDY").is_err());
+
+        // Records not starting at the beginning of the text would be located before it
+        let header = serde_json::to_vec(&index.sequences).unwrap();
+        let shifted = String::from_utf8(header.clone())
+            .unwrap()
+            .replace("\"offset\":0", "\"offset\":1");
+        let corrupt = [&data[..20], shifted.as_bytes(), &data[20 + header.len()..]].concat();
+        assert!(load(&corrupt).is_err());
+
+        // Suffix array positions outside of the text
+        let mut corrupt = data.clone();
+        let last = corrupt.len() - 8;
+        corrupt[last..].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(load(&corrupt).is_err());
+        // Repeated suffix array positions
+        let mut corrupt = data.clone();
+        let first = corrupt[last - 8..last].to_vec();
+        corrupt[last..].copy_from_slice(&first);
+        assert!(load(&corrupt).is_err());
+    }
+}
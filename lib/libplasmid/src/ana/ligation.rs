@@ -0,0 +1,338 @@
+use crate::{
+    seq::{reverse_complement, Annotation, DnaSequence},
+    traits::ToLetter,
+    uni::{IupacNucleotide, RestrictionEnzyme},
+};
+
+use super::{digest, Fragment, FragmentEnd, Overhang};
+
+/// Orientation of an insert relative to the vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InsertOrientation {
+    Forward,
+    Reverse,
+}
+
+/// A circular construct of a vector backbone and an insert.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LigationProduct {
+    pub orientation: InsertOrientation,
+    /// Top strand of the construct, starting with the backbone
+    pub sequence: Vec<IupacNucleotide>,
+    /// First construct position of the insert
+    pub insert_start: usize,
+    /// Construct position after the last base of the insert
+    pub insert_end: usize,
+    /// Features of vector and insert contained in the construct, and the insert itself
+    pub annotations: Vec<Annotation>,
+}
+
+/// Result of a restriction-ligation cloning.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ligation {
+    /// Vector fragment receiving the insert
+    pub backbone: Fragment,
+    pub insert: Fragment,
+    /// Products of all orientations the insert ligates in
+    pub products: Vec<LigationProduct>,
+}
+
+impl Ligation {
+    /// Whether the ends allow a single insert orientation only.
+    pub fn is_directional(&self) -> bool {
+        self.products.len() == 1
+    }
+}
+
+/// Whether the right end of a fragment can be ligated to the left end of another one.
+///
+/// Blunt ends ligate to each other, sticky ends of the same type if their
+/// overhangs are complementary, e.g. those of BamHI and BglII.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::prelude::{*, IupacNucleotide::*};
+///
+/// let cut = |overhang| FragmentEnd::Cut { enzyme: String::new(), overhang };
+/// // BamHI G^GATCC and BglII A^GATCT leave the same GATC overhang
+/// let right = cut(Overhang::FivePrime(vec![G, A, T, C]));
+/// let left = cut(Overhang::FivePrime(vec![G, A, T, C]));
+/// assert!(ends_compatible(&right, &left));
+/// assert!(!ends_compatible(&right, &cut(Overhang::Blunt)));
+/// ```
+pub fn ends_compatible(right: &FragmentEnd, left: &FragmentEnd) -> bool {
+    match (right, left) {
+        (FragmentEnd::Cut { overhang: a, .. }, FragmentEnd::Cut { overhang: b, .. }) => {
+            match (a, b) {
+                (Overhang::Blunt, Overhang::Blunt) => true,
+                (Overhang::FivePrime(a), Overhang::FivePrime(b))
+                | (Overhang::ThreePrime(a), Overhang::ThreePrime(b)) => *b == reverse_complement(a),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+fn describe_end(end: &FragmentEnd) -> String {
+    let letters = |seq: &[IupacNucleotide]| seq.iter().map(|n| n.to_letter()).collect::<String>();
+    match end {
+        FragmentEnd::Terminus => "terminus".to_string(),
+        FragmentEnd::Cut { enzyme, overhang } => match overhang {
+            Overhang::Blunt => format!("{} (blunt)", enzyme),
+            Overhang::FivePrime(seq) => format!("{} (5' {})", enzyme, letters(seq)),
+            Overhang::ThreePrime(seq) => format!("{} (3' {})", enzyme, letters(seq)),
+        },
+    }
+}
+
+/// Fragments with cut ends on both sides.
+fn cut_fragments(sequence: &DnaSequence, enzymes: &[&RestrictionEnzyme]) -> Vec<Fragment> {
+    let is_cut = |end: &FragmentEnd| matches!(end, FragmentEnd::Cut { .. });
+    digest(sequence.as_nucleotides(), sequence.topology(), enzymes)
+        .into_iter()
+        .filter(|fragment| is_cut(&fragment.left) && is_cut(&fragment.right))
+        .collect()
+}
+
+/// Move annotations of a template onto a fragment of it, placed at `offset`.
+/// Annotations not entirely contained in the fragment are dropped,
+/// reversed ones move to the opposite strand.
+fn carry_annotations(
+    annotations: &[Annotation],
+    template_len: usize,
+    fragment: &Fragment,
+    // Length of the fragment bottom strand measured from its top strand start,
+    // if the fragment is inserted reversed
+    reverse: Option<usize>,
+    offset: usize,
+) -> Vec<Annotation> {
+    let relative = |pos: usize| (pos + template_len - fragment.start) % template_len;
+    annotations
+        .iter()
+        .filter_map(|ann| {
+            let (start, end) = (relative(ann.start), relative(ann.end));
+            if start > end || end >= fragment.len() {
+                return None;
+            }
+            let mut ann = ann.clone();
+            ann.map_positions(relative);
+            ann.needle = ann.needle.filter(|needle| *needle <= fragment.len());
+            if let Some(len) = reverse {
+                // Mirror positions on the reversed fragment of length `len`
+                if len < end + 1 {
+                    return None;
+                }
+                let mirror = |pos: usize| len - pos - 1;
+                (ann.start, ann.end) = (mirror(ann.end), mirror(ann.start));
+                ann.needle = ann.needle.and_then(|needle| len.checked_sub(needle));
+                ann.segments = ann
+                    .segments
+                    .iter()
+                    .rev()
+                    .map(|(start, end)| (mirror(*end), mirror(*start)))
+                    .collect();
+                ann.strand = ann.strand.reverse();
+            }
+            ann.map_positions(|pos| pos + offset);
+            Some(ann)
+        })
+        .collect()
+}
+
+/// Simulate a restriction-ligation cloning of an insert into a vector.
+///
+/// Both sequences are digested with their enzymes, taking their topology into
+/// account. The largest fragment of the vector with cut ends on both sides is
+/// used as backbone. The insert is the largest such fragment of a linear insert,
+/// e.g. a PCR product, or the smallest of a circular donor plasmid.
+/// The insert is ligated into the backbone in every orientation its ends allow,
+/// carrying over the annotations of both sequences.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::{prelude::*, uni::find_restriction_enzyme};
+///
+/// let mut vector = DnaSequence::from_str("GAATTCAAAAAGGATCCTTTTTTTTTTTTTTT").unwrap();
+/// vector.set_topology(Topology::Circular);
+/// let insert = DnaSequence::from_str("CCGAATTCGCGCGCGGATCCGG").unwrap();
+/// let enzymes = [
+///     find_restriction_enzyme("EcoRI").unwrap(),
+///     find_restriction_enzyme("BamHI").unwrap(),
+/// ];
+/// let ligation = restriction_ligation(&vector, &enzymes, &insert, &enzymes).unwrap();
+/// assert!(ligation.is_directional());
+/// assert_eq!(ligation.products[0].orientation, InsertOrientation::Forward);
+/// ```
+pub fn restriction_ligation(
+    vector: &DnaSequence,
+    vector_enzymes: &[&RestrictionEnzyme],
+    insert: &DnaSequence,
+    insert_enzymes: &[&RestrictionEnzyme],
+) -> anyhow::Result<Ligation> {
+    let backbone = cut_fragments(vector, vector_enzymes)
+        .into_iter()
+        .max_by_key(|fragment| fragment.len())
+        .ok_or_else(|| anyhow!("The vector is not cut by the given enzymes"))?;
+    let inserts = cut_fragments(insert, insert_enzymes);
+    let insert_fragment = match insert.is_circular() {
+        true => inserts.into_iter().min_by_key(|fragment| fragment.len()),
+        false => inserts.into_iter().max_by_key(|fragment| fragment.len()),
+    }
+    .ok_or_else(|| anyhow!("The insert is not released by the given enzymes"))?;
+
+    let vector_len = vector.as_nucleotides().len();
+    let insert_len = insert.as_nucleotides().len();
+    let mut products = Vec::new();
+    for orientation in [InsertOrientation::Forward, InsertOrientation::Reverse] {
+        let oriented = match orientation {
+            InsertOrientation::Forward => insert_fragment.clone(),
+            InsertOrientation::Reverse => insert_fragment.reverse_complement(),
+        };
+        if !ends_compatible(&backbone.right, &oriented.left)
+            || !ends_compatible(&oriented.right, &backbone.left)
+        {
+            continue;
+        }
+        let mut sequence = backbone.sequence.clone();
+        sequence.extend_from_slice(&oriented.sequence);
+        let insert_start = backbone.len();
+        let insert_end = sequence.len();
+
+        let mut annotations =
+            carry_annotations(vector.as_annotations(), vector_len, &backbone, None, 0);
+        let reverse = match orientation {
+            InsertOrientation::Forward => None,
+            // The reversed top strand ends where the bottom strand of the insert starts
+            InsertOrientation::Reverse => Some(match &insert_fragment.right {
+                FragmentEnd::Cut {
+                    overhang: Overhang::FivePrime(seq),
+                    ..
+                } => insert_fragment.len() + seq.len(),
+                FragmentEnd::Cut {
+                    overhang: Overhang::ThreePrime(seq),
+                    ..
+                } => insert_fragment.len().saturating_sub(seq.len()),
+                _ => insert_fragment.len(),
+            }),
+        };
+        annotations.extend(carry_annotations(
+            insert.as_annotations(),
+            insert_len,
+            &insert_fragment,
+            reverse,
+            insert_start,
+        ));
+        let label = match orientation {
+            InsertOrientation::Forward => "Insert (forward)",
+            InsertOrientation::Reverse => "Insert (reverse)",
+        };
+        annotations.push(Annotation::new(insert_start, insert_end - 1, None, label));
+
+        products.push(LigationProduct {
+            orientation,
+            sequence,
+            insert_start,
+            insert_end,
+            annotations,
+        });
+    }
+
+    if products.is_empty() {
+        bail!(
+            "Incompatible ends: vector {} ... {}, insert {} ... {}",
+            describe_end(&backbone.left),
+            describe_end(&backbone.right),
+            describe_end(&insert_fragment.left),
+            describe_end(&insert_fragment.right),
+        );
+    }
+    Ok(Ligation {
+        backbone,
+        insert: insert_fragment,
+        products,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ends_compatible, restriction_ligation, InsertOrientation};
+    use crate::{
+        seq::{Annotation, DnaSequence, Strand},
+        test_utils::{circular, letters},
+        uni::find_restriction_enzyme,
+    };
+
+    #[test]
+    fn test_directional_cloning() {
+        let mut vector = circular("GAATTCAAAAAGGATCCTTTTTTTTTTTTTTT");
+        vector
+            .as_mut_annotations()
+            .push(Annotation::new(23, 30, None, "ori"));
+        let mut insert = DnaSequence::from_str("CCGAATTCGCGCGCGGATCCGG").unwrap();
+        insert
+            .as_mut_annotations()
+            .push(Annotation::new(8, 12, None, "gene"));
+        let enzymes = [
+            find_restriction_enzyme("EcoRI").unwrap(),
+            find_restriction_enzyme("BamHI").unwrap(),
+        ];
+        let ligation = restriction_ligation(&vector, &enzymes, &insert, &enzymes).unwrap();
+        assert!(ligation.is_directional());
+        let product = &ligation.products[0];
+        assert_eq!(
+            letters(&product.sequence),
+            "GATCCTTTTTTTTTTTTTTTGAATTCGCGCGCG"
+        );
+        assert_eq!((product.insert_start, product.insert_end), (21, 33));
+        let text = |t: &str| product.annotations.iter().find(|a| a.text == t).cloned();
+        let ori = text("ori").unwrap();
+        assert_eq!((ori.start, ori.end), (11, 18));
+        let gene = text("gene").unwrap();
+        assert_eq!((gene.start, gene.end), (26, 30));
+        assert!(text("Insert (forward)").is_some());
+    }
+
+    #[test]
+    fn test_single_enzyme_both_orientations() {
+        let vector = circular("GAATTCAAAAAAAAAAAAAAAAAAAAAAAAAA");
+        let mut insert = DnaSequence::from_str("CCGAATTCGGGCCCAAAGAATTCGG").unwrap();
+        insert
+            .as_mut_annotations()
+            .push(Annotation::new(8, 10, None, "GGG").with_strand(Strand::Forward));
+        let ecori = [find_restriction_enzyme("EcoRI").unwrap()];
+        let ligation = restriction_ligation(&vector, &ecori, &insert, &ecori).unwrap();
+        assert!(!ligation.is_directional());
+        let reverse = &ligation.products[1];
+        assert_eq!(reverse.orientation, InsertOrientation::Reverse);
+        let insert_seq = letters(&reverse.sequence[reverse.insert_start..]);
+        assert_eq!(insert_seq, "AATTCTTTGGGCCCG");
+        // GGG of the insert becomes CCC on the reversed top strand
+        let ann = reverse
+            .annotations
+            .iter()
+            .find(|a| a.text == "GGG")
+            .unwrap();
+        assert_eq!(letters(&reverse.sequence[ann.start..=ann.end]), "CCC");
+        assert_eq!(ann.strand, Strand::Reverse);
+    }
+
+    #[test]
+    fn test_blunt_and_incompatible_ends() {
+        let vector = circular("CCCGGGAAAAAAAAAAAAAAAA");
+        let insert = DnaSequence::from_str("TTCCCGGGTTTTGATATCTT").unwrap();
+        let smai = find_restriction_enzyme("SmaI").unwrap();
+        let ecorv = find_restriction_enzyme("EcoRV").unwrap();
+        let ligation = restriction_ligation(&vector, &[smai], &insert, &[smai, ecorv]).unwrap();
+        assert_eq!(ligation.products.len(), 2);
+        assert!(ends_compatible(
+            &ligation.backbone.right,
+            &ligation.insert.left
+        ));
+
+        let ecori = find_restriction_enzyme("EcoRI").unwrap();
+        let sticky = DnaSequence::from_str("GAATTCTTTTGAATTC").unwrap();
+        let error = restriction_ligation(&vector, &[smai], &sticky, &[ecori]).unwrap_err();
+        assert!(error.to_string().starts_with("Incompatible ends"));
+    }
+}
@@ -0,0 +1,664 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail};
+use bio::io::fastq;
+
+use super::ReferenceIndex;
+use crate::{
+    seq::reverse_complement,
+    traits::{ToLetter, TryFromLetter},
+    uni::IupacNucleotide,
+};
+
+/// Length of the exact seeds looked up in the reference index
+pub const DEFAULT_SEED_LEN: usize = 19;
+
+/// Shortest seeds allowed, shorter ones occur by chance in small genomes already
+pub const MIN_SEED_LEN: usize = 11;
+
+/// Smallest local alignment score for a read to be reported as mapped
+pub const DEFAULT_MIN_SCORE: i32 = 30;
+
+const MATCH_SCORE: i32 = 1;
+const MISMATCH_PENALTY: i32 = 4;
+const AMBIGUOUS_PENALTY: i32 = 1;
+const GAP_OPEN_PENALTY: i32 = 6;
+const GAP_EXTEND_PENALTY: i32 = 1;
+
+/// Diagonals explored on each side of a seeded one during extension
+const BAND_WIDTH: usize = 16;
+
+/// Seeds occurring more often than this are considered repeats and skipped
+const MAX_SEED_OCCURRENCES: usize = 256;
+
+/// Seed clusters extended per read
+const MAX_CANDIDATES: usize = 4;
+
+const MAX_MAPQ: u8 = 60;
+
+/// SAM flag of reads without an alignment
+pub const FLAG_UNMAPPED: u16 = 0x4;
+
+/// SAM flag of reads aligned to the bottom strand
+pub const FLAG_REVERSE: u16 = 0x10;
+
+const NEG_INF: i32 = i32::MIN / 2;
+
+/// A read after mapping, with its sequence and qualities oriented as on the top strand
+pub struct MappedRead {
+    pub name: String,
+    pub flag: u16,
+    /// Record of the reference the read is aligned to
+    pub record: Option<usize>,
+    /// First aligned reference position, 0-based
+    pub position: usize,
+    pub mapq: u8,
+    /// CIGAR operations with their lengths, soft clips included
+    pub cigar: Vec<(usize, char)>,
+    pub sequence: Vec<u8>,
+    pub qualities: Vec<u8>,
+    /// Mismatched, inserted and deleted bases
+    pub edit_distance: usize,
+    pub score: i32,
+    /// Score of the best alignment at another locus
+    pub suboptimal_score: Option<i32>,
+}
+
+/// Reads mapped to a reference
+pub struct Mapping {
+    pub reference: Arc<ReferenceIndex>,
+    pub reads: Vec<MappedRead>,
+}
+
+/// Summary of a mapping
+pub struct MappingStats {
+    pub reads: usize,
+    pub mapped: usize,
+    pub mapped_percent: f64,
+    /// Mean depth over all reference bases
+    pub mean_coverage: f64,
+    /// Reference bases covered by at least one read
+    pub covered_percent: f64,
+    /// Edit distance per aligned read base
+    pub error_rate: f64,
+    pub mean_mapq: f64,
+}
+
+/// A local alignment of a read to a stretch of reference
+struct LocalAlignment {
+    score: i32,
+    read_start: usize,
+    read_end: usize,
+    ref_start: usize,
+    ref_end: usize,
+    /// `M`, `I` or `D` for each aligned column
+    columns: Vec<char>,
+    mismatches: usize,
+}
+
+/// A local alignment placed on a reference record
+struct Candidate {
+    record: usize,
+    reverse: bool,
+    alignment: LocalAlignment,
+}
+
+fn substitution_score(a: u8, b: u8) -> i32 {
+    let unambiguous = |c: u8| matches!(c, b'A' | b'C' | b'G' | b'T');
+    match (unambiguous(a), unambiguous(b)) {
+        (true, true) if a == b => MATCH_SCORE,
+        (true, true) => -MISMATCH_PENALTY,
+        _ => -AMBIGUOUS_PENALTY,
+    }
+}
+
+/// Smith–Waterman alignment with affine gaps, restricted to the `2 * band + 1` diagonals
+/// around `diagonal`, the expected offset of the read start in the reference.
+fn banded_smith_waterman(
+    read: &[u8],
+    reference: &[u8],
+    diagonal: isize,
+    band: usize,
+) -> Option<LocalAlignment> {
+    let (n, m) = (read.len(), reference.len());
+    let width = 2 * band + 1;
+    let lowest = diagonal - band as isize;
+    let cell = |i: usize, j: usize| -> Option<usize> {
+        let k = j as isize - i as isize - lowest;
+        (0..width as isize)
+            .contains(&k)
+            .then(|| i * width + k as usize)
+    };
+    let at = |matrix: &[i32], i: usize, j: usize| cell(i, j).map_or(NEG_INF, |c| matrix[c]);
+    let gap_open = GAP_OPEN_PENALTY + GAP_EXTEND_PENALTY;
+
+    // Best local scores ending in a match, a deletion and an insertion
+    let mut h = vec![0; (n + 1) * width];
+    let mut e = vec![NEG_INF; (n + 1) * width];
+    let mut f = vec![NEG_INF; (n + 1) * width];
+    let mut best = (0, 0, 0);
+    for i in 1..=n {
+        for k in 0..width {
+            let j = i as isize + lowest + k as isize;
+            if j < 1 || j > m as isize {
+                continue;
+            }
+            let j = j as usize;
+            let c = i * width + k;
+            e[c] = (at(&h, i, j - 1) - gap_open).max(at(&e, i, j - 1) - GAP_EXTEND_PENALTY);
+            f[c] = (at(&h, i - 1, j) - gap_open).max(at(&f, i - 1, j) - GAP_EXTEND_PENALTY);
+            let matched = at(&h, i - 1, j - 1) + substitution_score(read[i - 1], reference[j - 1]);
+            h[c] = matched.max(e[c]).max(f[c]).max(0);
+            if h[c] > best.0 {
+                best = (h[c], i, j);
+            }
+        }
+    }
+    if best.0 == 0 {
+        return None;
+    }
+
+    let (score, read_end, ref_end) = best;
+    let (mut i, mut j) = (read_end, ref_end);
+    let mut columns = Vec::new();
+    let mut mismatches = 0;
+    let mut state = 'M';
+    loop {
+        let c = cell(i, j)?;
+        match state {
+            'D' => {
+                columns.push('D');
+                if e[c] != at(&e, i, j - 1) - GAP_EXTEND_PENALTY {
+                    state = 'M';
+                }
+                j -= 1;
+            }
+            'I' => {
+                columns.push('I');
+                if f[c] != at(&f, i - 1, j) - GAP_EXTEND_PENALTY {
+                    state = 'M';
+                }
+                i -= 1;
+            }
+            _ if i == 0 || j == 0 || h[c] == 0 => break,
+            _ => {
+                let substitution = substitution_score(read[i - 1], reference[j - 1]);
+                if h[c] == at(&h, i - 1, j - 1) + substitution {
+                    columns.push('M');
+                    mismatches += usize::from(substitution != MATCH_SCORE);
+                    i -= 1;
+                    j -= 1;
+                } else if h[c] == e[c] {
+                    state = 'D';
+                } else {
+                    state = 'I';
+                }
+            }
+        }
+    }
+    columns.reverse();
+    Some(LocalAlignment {
+        score,
+        read_start: i,
+        read_end,
+        ref_start: j,
+        ref_end,
+        columns,
+        mismatches,
+    })
+}
+
+/// Seed the read on both strands, cluster seeds by diagonal and extend the best clusters
+fn candidates(
+    reference: &ReferenceIndex,
+    read: &[u8],
+    reverse_read: &[u8],
+    seed_len: usize,
+) -> Vec<Candidate> {
+    if read.len() < seed_len {
+        return Vec::new();
+    }
+    let step = (seed_len / 2).max(1);
+    let mut offsets = (0..=read.len() - seed_len)
+        .step_by(step)
+        .collect::<Vec<_>>();
+    if offsets.last() != Some(&(read.len() - seed_len)) {
+        offsets.push(read.len() - seed_len);
+    }
+
+    // Seeds as record, strand and diagonal
+    let mut seeds = Vec::new();
+    for (reverse, query) in [(false, read), (true, reverse_read)] {
+        for &offset in offsets.iter() {
+            let seed = &query[offset..offset + seed_len];
+            if !seed.iter().all(|b| matches!(b, b'A' | b'C' | b'G' | b'T')) {
+                continue;
+            }
+            for (record, position) in reference.seed(seed, MAX_SEED_OCCURRENCES) {
+                seeds.push((record, reverse, position as isize - offset as isize));
+            }
+        }
+    }
+    seeds.sort_unstable();
+
+    // Clusters as seed count, record, strand and diagonal range
+    let mut clusters: Vec<(usize, usize, bool, isize, isize)> = Vec::new();
+    for (record, reverse, diagonal) in seeds {
+        match clusters.last_mut() {
+            Some(cluster)
+                if (cluster.1, cluster.2) == (record, reverse)
+                    && diagonal - cluster.3 <= BAND_WIDTH as isize =>
+            {
+                cluster.0 += 1;
+                cluster.4 = diagonal;
+            }
+            _ => clusters.push((1, record, reverse, diagonal, diagonal)),
+        }
+    }
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.0));
+
+    clusters
+        .into_iter()
+        .take(MAX_CANDIDATES)
+        .filter_map(|(_, record, reverse, lowest, highest)| {
+            let query = match reverse {
+                true => reverse_read,
+                false => read,
+            };
+            let sequence = reference.sequence(record);
+            let band = BAND_WIDTH + (highest - lowest) as usize / 2;
+            let diagonal = lowest + (highest - lowest) / 2;
+            let start = (diagonal - band as isize).max(0) as usize;
+            let end = ((diagonal + (query.len() + band) as isize) as usize).min(sequence.len());
+            if start >= end {
+                return None;
+            }
+            let mut alignment = banded_smith_waterman(
+                query,
+                &sequence[start..end],
+                diagonal - start as isize,
+                band,
+            )?;
+            alignment.ref_start += start;
+            alignment.ref_end += start;
+            Some(Candidate {
+                record,
+                reverse,
+                alignment,
+            })
+        })
+        .collect()
+}
+
+/// Mapping quality from the best score and the best score at another locus
+fn mapping_quality(score: i32, suboptimal_score: Option<i32>) -> u8 {
+    let suboptimal = suboptimal_score.unwrap_or(0).max(0);
+    if suboptimal >= score {
+        return 0;
+    }
+    (250 * (score - suboptimal) / score).min(MAX_MAPQ as i32) as u8
+}
+
+fn cigar(alignment: &LocalAlignment, read_len: usize) -> Vec<(usize, char)> {
+    let mut cigar = Vec::new();
+    if alignment.read_start > 0 {
+        cigar.push((alignment.read_start, 'S'));
+    }
+    for column in alignment.columns.iter() {
+        match cigar.last_mut() {
+            Some((len, op)) if op == column => *len += 1,
+            _ => cigar.push((1, *column)),
+        }
+    }
+    if alignment.read_end < read_len {
+        cigar.push((read_len - alignment.read_end, 'S'));
+    }
+    cigar
+}
+
+/// Map a read to both strands of the reference, soft clipping unaligned read ends.
+/// Reads without an alignment scoring at least `min_score` are reported as unmapped.
+pub fn map_read(
+    reference: &ReferenceIndex,
+    name: &str,
+    read: &[IupacNucleotide],
+    qualities: &[u8],
+    seed_len: usize,
+    min_score: i32,
+) -> MappedRead {
+    let letters =
+        |read: &[IupacNucleotide]| read.iter().map(|n| n.to_letter() as u8).collect::<Vec<_>>();
+    let reverse_read = letters(&reverse_complement(read));
+    let read = letters(read);
+    let mut candidates = candidates(reference, &read, &reverse_read, seed_len);
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.alignment.score));
+
+    let mut mapped = MappedRead {
+        name: name.to_string(),
+        flag: FLAG_UNMAPPED,
+        record: None,
+        position: 0,
+        mapq: 0,
+        cigar: Vec::new(),
+        sequence: read.clone(),
+        qualities: qualities.to_vec(),
+        edit_distance: 0,
+        score: 0,
+        suboptimal_score: None,
+    };
+    let Some(best) = candidates
+        .first()
+        .filter(|c| c.alignment.score >= min_score)
+    else {
+        return mapped;
+    };
+
+    // Alignments overlapping the best one are the same hit found from another seed cluster
+    let suboptimal_score = candidates[1..]
+        .iter()
+        .filter(|c| {
+            (c.record, c.reverse) != (best.record, best.reverse)
+                || c.alignment.ref_end <= best.alignment.ref_start
+                || c.alignment.ref_start >= best.alignment.ref_end
+        })
+        .map(|c| c.alignment.score)
+        .next();
+    let alignment = &best.alignment;
+    let gaps = alignment.columns.iter().filter(|c| **c != 'M').count();
+
+    mapped.flag = match best.reverse {
+        true => FLAG_REVERSE,
+        false => 0,
+    };
+    if best.reverse {
+        mapped.sequence = reverse_read;
+        mapped.qualities.reverse();
+    }
+    mapped.record = Some(best.record);
+    mapped.position = alignment.ref_start;
+    mapped.mapq = mapping_quality(alignment.score, suboptimal_score);
+    mapped.cigar = cigar(alignment, read.len());
+    mapped.edit_distance = alignment.mismatches + gaps;
+    mapped.score = alignment.score;
+    mapped.suboptimal_score = suboptimal_score;
+    mapped
+}
+
+impl MappedRead {
+    pub fn is_mapped(&self) -> bool {
+        self.flag & FLAG_UNMAPPED == 0
+    }
+}
+
+impl Mapping {
+    /// Alignments in SAM format, with a header listing the reference records
+    /// and the program that mapped the reads
+    pub fn sam(&self, program: &str, version: &str) -> String {
+        let mut sam = String::from("@HD\tVN:1.6\tSO:unsorted\n");
+        for sequence in self.reference.sequences.iter() {
+            sam += &format!("@SQ\tSN:{}\tLN:{}\n", sequence.name, sequence.len);
+        }
+        sam += &format!("@PG\tID:{program}\tPN:{program}\tVN:{version}\n");
+
+        let text = |bytes: &[u8]| match bytes.is_empty() {
+            true => "*".to_string(),
+            false => String::from_utf8_lossy(bytes).to_string(),
+        };
+        for read in self.reads.iter() {
+            let (sequence, qualities) = (text(&read.sequence), text(&read.qualities));
+            match read.record {
+                Some(record) => {
+                    let cigar = read
+                        .cigar
+                        .iter()
+                        .map(|(len, op)| format!("{len}{op}"))
+                        .collect::<String>();
+                    sam += &format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}\t*\t0\t0\t{}\t{}\tNM:i:{}\tAS:i:{}",
+                        read.name,
+                        read.flag,
+                        self.reference.sequences[record].name,
+                        read.position + 1,
+                        read.mapq,
+                        cigar,
+                        sequence,
+                        qualities,
+                        read.edit_distance,
+                        read.score
+                    );
+                    if let Some(suboptimal_score) = read.suboptimal_score {
+                        sam += &format!("\tXS:i:{suboptimal_score}");
+                    }
+                    sam.push('\n');
+                }
+                None => {
+                    sam += &format!(
+                        "{}\t{}\t*\t0\t0\t*\t*\t0\t0\t{}\t{}\n",
+                        read.name, read.flag, sequence, qualities
+                    );
+                }
+            }
+        }
+        sam
+    }
+
+    /// Mapped fraction, coverage and error rate of the mapping
+    pub fn stats(&self) -> MappingStats {
+        let reference_len = self.reference.total_len();
+        let mut covered = self
+            .reference
+            .sequences
+            .iter()
+            .map(|sequence| vec![false; sequence.len])
+            .collect::<Vec<_>>();
+        let (mut aligned_bases, mut read_bases, mut errors, mut mapq) = (0, 0, 0, 0);
+        let mut mapped = 0;
+        for read in self.reads.iter().filter(|read| read.is_mapped()) {
+            let Some(record) = read.record else {
+                continue;
+            };
+            mapped += 1;
+            mapq += read.mapq as usize;
+            errors += read.edit_distance;
+            let mut position = read.position;
+            for &(len, op) in read.cigar.iter() {
+                match op {
+                    'M' => {
+                        covered[record][position..position + len].fill(true);
+                        aligned_bases += len;
+                        read_bases += len;
+                        position += len;
+                    }
+                    'I' => read_bases += len,
+                    'D' => position += len,
+                    _ => (),
+                }
+            }
+        }
+        let ratio = |a: usize, b: usize| match b {
+            0 => 0.0,
+            b => a as f64 / b as f64,
+        };
+        let covered_bases = covered.iter().flatten().filter(|c| **c).count();
+        MappingStats {
+            reads: self.reads.len(),
+            mapped,
+            mapped_percent: 100.0 * ratio(mapped, self.reads.len()),
+            mean_coverage: ratio(aligned_bases, reference_len),
+            covered_percent: 100.0 * ratio(covered_bases, reference_len),
+            error_rate: ratio(errors, read_bases),
+            mean_mapq: ratio(mapq, mapped),
+        }
+    }
+}
+
+/// Map FASTQ reads to a reference index.
+///
+/// Exact seeds are looked up in the FM-index of the reference and extended
+/// with a banded Smith–Waterman alignment, soft clipping unaligned read ends.
+pub fn map_fastq(
+    reference: Arc<ReferenceIndex>,
+    fastq: &str,
+    seed_len: usize,
+    min_score: i32,
+) -> anyhow::Result<Mapping> {
+    if seed_len < MIN_SEED_LEN {
+        bail!("seeds must be at least {MIN_SEED_LEN} bases long");
+    }
+    let min_score = min_score.max(1);
+    let records = fastq::Reader::new(fastq.trim().as_bytes())
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow!("invalid FASTQ: {e}"))?;
+    if let Some(error) = records.iter().find_map(|record| record.check().err()) {
+        bail!("invalid FASTQ: {error}");
+    }
+
+    let mut reads = Vec::with_capacity(records.len());
+    for record in records.iter() {
+        let read = record
+            .seq()
+            .iter()
+            .map(|c| match c.to_ascii_uppercase() {
+                b'-' => None,
+                c => IupacNucleotide::try_from_letter(c as char).ok(),
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| anyhow!("invalid FASTQ: invalid base in read {}", record.id()))?;
+        reads.push(map_read(
+            &reference,
+            record.id(),
+            &read,
+            record.qual(),
+            seed_len,
+            min_score,
+        ));
+    }
+    Ok(Mapping { reference, reads })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{map_fastq, map_read, Mapping, FLAG_REVERSE, FLAG_UNMAPPED};
+    use crate::{
+        ana::ReferenceIndex,
+        test_utils::{iupac, reverse_complement_letters},
+    };
+
+    /// A reference without repeats, from a linear congruential generator
+    fn reference() -> String {
+        let mut state = 12345_u32;
+        (0..400)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                b"ACGT"[(state >> 16) as usize % 4] as char
+            })
+            .collect()
+    }
+
+    fn index() -> ReferenceIndex {
+        ReferenceIndex::build("ref", &format!(">chr\n{}\n", reference())).unwrap()
+    }
+
+    fn cigar(index: &ReferenceIndex, read: &str) -> (u16, usize, String, usize) {
+        let mapped = map_read(index, "read", &iupac(read), &[], 19, 30);
+        let cigar = mapped
+            .cigar
+            .iter()
+            .map(|(len, op)| format!("{len}{op}"))
+            .collect();
+        (mapped.flag, mapped.position, cigar, mapped.edit_distance)
+    }
+
+    #[test]
+    fn test_map_exact_and_mismatched_reads() {
+        let (reference, index) = (reference(), index());
+        let read = &reference[100..160];
+        assert_eq!(cigar(&index, read), (0, 100, "60M".to_string(), 0));
+        let other = match &read[30..31] {
+            "A" => "C",
+            _ => "A",
+        };
+        let mismatched = format!("{}{}{}", &read[..30], other, &read[31..]);
+        assert_eq!(cigar(&index, &mismatched), (0, 100, "60M".to_string(), 1));
+        // Unaligned read ends are soft clipped
+        let clipped = format!("TTTTTTTTTT{}", read);
+        assert_eq!(cigar(&index, &clipped), (0, 100, "10S60M".to_string(), 0));
+    }
+
+    #[test]
+    fn test_map_reads_with_indels() {
+        let (reference, index) = (reference(), index());
+        let read = format!("{}GGG{}", &reference[200..230], &reference[230..260]);
+        let (flag, position, cigar_string, edit_distance) = cigar(&index, &read);
+        assert_eq!((flag, position, edit_distance), (0, 200, 3));
+        assert_eq!(cigar_string, "30M3I30M");
+        let read = format!("{}{}", &reference[200..230], &reference[234..264]);
+        let (flag, position, cigar_string, edit_distance) = cigar(&index, &read);
+        assert_eq!((flag, position, edit_distance), (0, 200, 4));
+        assert_eq!(cigar_string, "30M4D30M");
+    }
+
+    #[test]
+    fn test_map_reverse_and_unmapped_reads() {
+        let (reference, index) = (reference(), index());
+        let read = reverse_complement_letters(&reference[300..360]);
+        assert_eq!(
+            cigar(&index, &read),
+            (FLAG_REVERSE, 300, "60M".to_string(), 0)
+        );
+        // The read is reported as on the top strand
+        let mapped = map_read(&index, "read", &iupac(&read), &[], 19, 30);
+        assert_eq!(mapped.sequence, &reference.as_bytes()[300..360]);
+        assert_eq!(cigar(&index, &"A".repeat(60)).0, FLAG_UNMAPPED);
+        assert_eq!(cigar(&index, "ACGT").0, FLAG_UNMAPPED);
+    }
+
+    #[test]
+    fn test_mapping_to_sam() {
+        let reference = reference();
+        let fastq = format!(
+            "@r1\n{}\n+\n{}\n@r2\n{}\n+\n{}\n",
+            &reference[10..40],
+            "I".repeat(30),
+            "A".repeat(30),
+            "#".repeat(30)
+        );
+        let mapping: Mapping = map_fastq(Arc::new(index()), &fastq, 19, 20).unwrap();
+        let sam = mapping.sam("test", "1.0");
+        let lines = sam.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[..3],
+            [
+                "@HD\tVN:1.6\tSO:unsorted",
+                "@SQ\tSN:chr\tLN:400",
+                "@PG\tID:test\tPN:test\tVN:1.0"
+            ]
+        );
+        assert_eq!(
+            lines[3],
+            format!(
+                "r1\t0\tchr\t11\t60\t30M\t*\t0\t0\t{}\t{}\tNM:i:0\tAS:i:30",
+                &reference[10..40],
+                "I".repeat(30)
+            )
+        );
+        assert_eq!(
+            lines[4],
+            format!(
+                "r2\t4\t*\t0\t0\t*\t*\t0\t0\t{}\t{}",
+                "A".repeat(30),
+                "#".repeat(30)
+            )
+        );
+        let stats = mapping.stats();
+        assert_eq!((stats.reads, stats.mapped), (2, 1));
+        assert_eq!(stats.covered_percent, 7.5);
+
+        assert!(map_fastq(Arc::new(index()), &fastq, 10, 20).is_err());
+        assert!(map_fastq(Arc::new(index()), "@r\nAC-T\n+\nIIII\n", 19, 20).is_err());
+        assert!(map_fastq(Arc::new(index()), "@r\nACGT\n+\nIII\n", 19, 20).is_err());
+    }
+}
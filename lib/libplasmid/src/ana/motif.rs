@@ -0,0 +1,649 @@
+use std::{collections::HashMap, iter::Peekable, str::Chars};
+
+use serde::Serialize;
+
+use super::features::translate;
+use crate::{
+    seq::{reverse_complement, Annotation, Strand},
+    traits::*,
+    uni::IupacNucleotide,
+};
+
+/// Longest motif that can be searched, in positions of the pattern
+pub const MAX_MOTIF_LEN: usize = 64;
+
+/// Most fixed-length patterns a motif with variable repeats may expand to
+const MAX_EXPANSIONS: usize = 1024;
+
+/// Alphabet a motif is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MotifKind {
+    /// Searched on both strands of the sequence
+    Nucleotide,
+    /// Searched in all six reading frames of the sequence
+    Protein,
+}
+
+/// Symbols allowed at a position of a motif.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SymbolClass {
+    Any,
+    OneOf(Vec<char>),
+    NoneOf(Vec<char>),
+}
+
+impl SymbolClass {
+    fn matches(&self, kind: MotifKind, symbol: char) -> bool {
+        let contains = |letters: &[char]| match kind {
+            MotifKind::Protein => letters.contains(&symbol),
+            // Ambiguous bases of the sequence match any of their bases
+            MotifKind::Nucleotide => match IupacNucleotide::try_from_letter(symbol) {
+                Ok(n) => letters
+                    .iter()
+                    .filter_map(|l| IupacNucleotide::try_from_letter(*l).ok())
+                    .any(|l| l.matches(&n)),
+                Err(_) => false,
+            },
+        };
+        match self {
+            SymbolClass::Any => true,
+            SymbolClass::OneOf(letters) => contains(letters),
+            SymbolClass::NoneOf(letters) => !contains(letters),
+        }
+    }
+}
+
+/// A position of a motif, repeated `min` to `max` times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MotifElement {
+    class: SymbolClass,
+    min: usize,
+    max: usize,
+}
+
+/// A sequence pattern to search for.
+///
+/// Nucleotide motifs are written as IUPAC codes, or in a regex-like syntax with
+/// `.` for any base, `[AC]` and `[^AC]` for sets of bases, `?`, `{n}` and `{n,m}`
+/// for repeats and `^`/`$` anchoring the motif at the start or end of the sequence.
+/// Protein motifs use the PROSITE syntax, e.g. `C-x(2,4)-C-x(3)-[LIVMFYWC]-x(8)-H-x(3,5)-H`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Motif {
+    pub pattern: String,
+    pub kind: MotifKind,
+    elements: Vec<MotifElement>,
+    anchored_start: bool,
+    anchored_end: bool,
+}
+
+fn nucleotide_letter(c: char) -> anyhow::Result<char> {
+    let c = c.to_ascii_uppercase();
+    match c != '-' && IupacNucleotide::try_from_letter(c).is_ok() {
+        true => Ok(c),
+        false => bail!("Invalid nucleotide '{}' in motif", c),
+    }
+}
+
+fn residue_letter(c: char) -> anyhow::Result<char> {
+    match c.is_ascii_uppercase() {
+        true => Ok(c),
+        false => bail!("Invalid amino acid '{}' in motif", c),
+    }
+}
+
+/// Parse the letters of a set up to its closing bracket.
+fn parse_set<F>(
+    chars: &mut Peekable<Chars<'_>>,
+    close: char,
+    letter: F,
+) -> anyhow::Result<Vec<char>>
+where
+    F: Fn(char) -> anyhow::Result<char>,
+{
+    let mut letters = Vec::new();
+    loop {
+        match chars.next() {
+            Some(c) if c == close => break,
+            Some(c) => letters.push(letter(c)?),
+            None => bail!("Unclosed set in motif, expected '{}'", close),
+        }
+    }
+    if letters.is_empty() {
+        bail!("Empty set in motif");
+    }
+    Ok(letters)
+}
+
+/// Parse `n` or `n,m` up to the closing bracket of a repeat.
+fn parse_repeat(chars: &mut Peekable<Chars<'_>>, close: char) -> anyhow::Result<(usize, usize)> {
+    let mut repeat = String::new();
+    loop {
+        match chars.next() {
+            Some(c) if c == close => break,
+            Some(c) => repeat.push(c),
+            None => bail!("Unclosed repeat in motif, expected '{}'", close),
+        }
+    }
+    let count = |n: &str| {
+        n.trim()
+            .parse::<usize>()
+            .map_err(|_| anyhow!("Invalid repeat '{}' in motif", repeat))
+    };
+    let (min, max) = match repeat.split_once(',') {
+        Some((min, max)) => (count(min)?, count(max)?),
+        None => (count(&repeat)?, count(&repeat)?),
+    };
+    if min > max {
+        bail!("Invalid repeat '{}' in motif", repeat);
+    }
+    Ok((min, max))
+}
+
+impl Motif {
+    fn new(
+        pattern: &str,
+        kind: MotifKind,
+        elements: Vec<MotifElement>,
+        anchored_start: bool,
+        anchored_end: bool,
+    ) -> anyhow::Result<Self> {
+        let motif = Motif {
+            pattern: pattern.to_string(),
+            kind,
+            elements,
+            anchored_start,
+            anchored_end,
+        };
+        if motif.min_len() == 0 {
+            bail!("Empty motif");
+        }
+        if motif.max_len() > MAX_MOTIF_LEN {
+            bail!("Motifs are limited to {} positions", MAX_MOTIF_LEN);
+        }
+        Ok(motif)
+    }
+
+    /// A nucleotide motif of IUPAC codes, e.g. `GAATTC` or `GGTCTCN`.
+    pub fn iupac(pattern: &str) -> anyhow::Result<Self> {
+        let elements = pattern
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| {
+                Ok(MotifElement {
+                    class: SymbolClass::OneOf(vec![nucleotide_letter(c)?]),
+                    min: 1,
+                    max: 1,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Self::new(pattern, MotifKind::Nucleotide, elements, false, false)
+    }
+
+    /// A nucleotide motif in the regex-like syntax, e.g. `^ATG[AG].{3,6}TAA`.
+    pub fn regex(pattern: &str) -> anyhow::Result<Self> {
+        let mut chars = pattern.trim().chars().peekable();
+        let anchored_start = chars.next_if_eq(&'^').is_some();
+        let mut anchored_end = false;
+        let mut elements = Vec::new();
+        while let Some(c) = chars.next() {
+            let class = match c {
+                '$' if chars.peek().is_none() => {
+                    anchored_end = true;
+                    continue;
+                }
+                '.' => SymbolClass::Any,
+                '[' => match chars.next_if_eq(&'^') {
+                    Some(_) => SymbolClass::NoneOf(parse_set(&mut chars, ']', nucleotide_letter)?),
+                    None => SymbolClass::OneOf(parse_set(&mut chars, ']', nucleotide_letter)?),
+                },
+                '*' | '+' => bail!("Unbounded repeats are not supported, use {{n,m}}"),
+                c if c.is_ascii_alphabetic() => SymbolClass::OneOf(vec![nucleotide_letter(c)?]),
+                c => bail!("Unexpected '{}' in motif", c),
+            };
+            let (min, max) = match chars.peek() {
+                Some('?') => {
+                    chars.next();
+                    (0, 1)
+                }
+                Some('{') => {
+                    chars.next();
+                    parse_repeat(&mut chars, '}')?
+                }
+                _ => (1, 1),
+            };
+            elements.push(MotifElement { class, min, max });
+        }
+        Self::new(
+            pattern,
+            MotifKind::Nucleotide,
+            elements,
+            anchored_start,
+            anchored_end,
+        )
+    }
+
+    /// A protein motif in PROSITE syntax, e.g. `<M-x(2)-[ST]-{P}-K>`.
+    pub fn prosite(pattern: &str) -> anyhow::Result<Self> {
+        let pattern = pattern.trim();
+        let mut body = pattern.strip_suffix('.').unwrap_or(pattern);
+        let anchored_start = body.starts_with('<');
+        let anchored_end = body.ends_with('>');
+        body = body.trim_start_matches('<').trim_end_matches('>');
+
+        let mut elements = Vec::new();
+        for token in body.split('-') {
+            let mut chars = token.trim().chars().peekable();
+            let class = match chars.next() {
+                Some('x' | 'X') => SymbolClass::Any,
+                Some('[') => SymbolClass::OneOf(parse_set(&mut chars, ']', residue_letter)?),
+                Some('{') => SymbolClass::NoneOf(parse_set(&mut chars, '}', residue_letter)?),
+                Some(c) => SymbolClass::OneOf(vec![residue_letter(c)?]),
+                None => bail!("Empty element in motif"),
+            };
+            let (min, max) = match chars.next() {
+                Some('(') => parse_repeat(&mut chars, ')')?,
+                Some(c) => bail!("Unexpected '{}' in motif", c),
+                None => (1, 1),
+            };
+            if let Some(c) = chars.next() {
+                bail!("Unexpected '{}' in motif", c);
+            }
+            elements.push(MotifElement { class, min, max });
+        }
+        Self::new(
+            pattern,
+            MotifKind::Protein,
+            elements,
+            anchored_start,
+            anchored_end,
+        )
+    }
+
+    /// Fewest positions, bases or residues, a match of the motif spans without errors.
+    pub fn min_len(&self) -> usize {
+        self.elements.iter().map(|e| e.min).sum()
+    }
+
+    /// Most positions, bases or residues, a match of the motif spans without errors.
+    pub fn max_len(&self) -> usize {
+        self.elements.iter().map(|e| e.max).sum()
+    }
+
+    /// All fixed-length patterns the repeats of the motif allow.
+    fn expand(&self) -> anyhow::Result<Vec<Vec<&SymbolClass>>> {
+        let mut patterns = vec![Vec::new()];
+        for element in self.elements.iter() {
+            let count = patterns.len() * (element.max - element.min + 1);
+            if count > MAX_EXPANSIONS {
+                bail!("Motif has too many variable repeats");
+            }
+            patterns = patterns
+                .iter()
+                .flat_map(|pattern| {
+                    (element.min..=element.max).map(move |n| {
+                        let mut pattern = pattern.clone();
+                        pattern.extend(std::iter::repeat_n(&element.class, n));
+                        pattern
+                    })
+                })
+                .collect();
+        }
+        patterns.retain(|pattern| !pattern.is_empty());
+        Ok(patterns)
+    }
+}
+
+/// A match of a motif.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MotifHit {
+    /// First base on the top strand
+    pub start: usize,
+    /// Last base on the top strand (inclusive), smaller than `start` if the match spans the origin
+    pub end: usize,
+    pub strand: Strand,
+    /// Number of mismatches, insertions and deletions
+    pub distance: usize,
+    /// Matched bases, or residues of protein motifs, read along the strand
+    pub matched: String,
+}
+
+impl MotifHit {
+    pub fn to_annotation(&self, label: &str) -> Annotation {
+        let mut ann = Annotation::new(self.start, self.end, None, label).with_strand(self.strand);
+        if self.distance > 0 {
+            ann = ann.with_qualifier("note", format!("edit distance {}", self.distance));
+        }
+        ann
+    }
+}
+
+/// A match within a searched text, `end` inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TextHit {
+    start: usize,
+    end: usize,
+    distance: usize,
+}
+
+/// End positions of approximate matches of `pattern` in `text` with at most `max_errors`
+/// mismatches, insertions and deletions, using Myers' bit-parallel algorithm.
+fn myers<F>(len: usize, matches: F, text: &[char], max_errors: usize) -> Vec<(usize, usize)>
+where
+    F: Fn(usize, char) -> bool,
+{
+    let mask = match len {
+        MAX_MOTIF_LEN => u64::MAX,
+        len => (1 << len) - 1,
+    };
+    let last = 1_u64 << (len - 1);
+    let mut peq = HashMap::<char, u64>::new();
+    let (mut pv, mut mv, mut score) = (mask, 0_u64, len);
+    let mut ends = Vec::new();
+    for (j, c) in text.iter().enumerate() {
+        let eq = *peq
+            .entry(*c)
+            .or_insert_with(|| (0..len).filter(|&i| matches(i, *c)).map(|i| 1 << i).sum());
+        let xv = eq | mv;
+        let xh = (((eq & pv).wrapping_add(pv)) ^ pv) | eq;
+        let mut ph = mv | !(xh | pv);
+        let mut mh = pv & xh;
+        if ph & last != 0 {
+            score += 1;
+        } else if mh & last != 0 {
+            score -= 1;
+        }
+        ph <<= 1;
+        mh <<= 1;
+        pv = (mh | !(xv | ph)) & mask;
+        mv = ph & xv & mask;
+        if score <= max_errors {
+            ends.push((j, score));
+        }
+    }
+    ends
+}
+
+/// Start of the best match of `pattern` ending at `end`, found by aligning backwards.
+fn match_start<F>(len: usize, matches: F, text: &[char], end: usize, max_errors: usize) -> TextHit
+where
+    F: Fn(usize, char) -> bool,
+{
+    let window = (len + max_errors).min(end + 1);
+    // Distances of the pattern suffixes to the text ending at `end`
+    let mut row = (0..=window).collect::<Vec<_>>();
+    for i in 1..=len {
+        let mut next = vec![i; window + 1];
+        for j in 1..=window {
+            let substitution = row[j - 1] + usize::from(!matches(len - i, text[end + 1 - j]));
+            next[j] = substitution.min(row[j] + 1).min(next[j - 1] + 1);
+        }
+        row = next;
+    }
+    // Prefer the match closest to the length of the pattern
+    let best = (0..=window)
+        .min_by_key(|&j| (row[j], j.abs_diff(len)))
+        .unwrap_or(len);
+    TextHit {
+        start: end + 1 - best,
+        end,
+        distance: row[best],
+    }
+}
+
+/// Search a motif in `text`, which continues circularly after its first `len` symbols.
+fn search_text(
+    motif: &Motif,
+    text: &[char],
+    len: usize,
+    max_errors: usize,
+) -> anyhow::Result<Vec<TextHit>> {
+    let mut hits = Vec::new();
+    for pattern in motif.expand()? {
+        let matches = |i: usize, c: char| pattern[i].matches(motif.kind, c);
+        for (end, _) in myers(pattern.len(), matches, text, max_errors) {
+            let hit = match_start(pattern.len(), matches, text, end, max_errors);
+            if hit.start >= len || hit.end + 1 - hit.start > len || hit.start > hit.end {
+                continue;
+            }
+            if (motif.anchored_start && hit.start != 0)
+                || (motif.anchored_end && hit.end + 1 != len)
+            {
+                continue;
+            }
+            hits.push(hit);
+        }
+    }
+    Ok(hits)
+}
+
+/// Text to search, continued past the end of circular sequences by up to `extra` symbols.
+fn extend<T: Copy>(seq: &[T], circular: bool, extra: usize) -> Vec<T> {
+    match circular {
+        true => [seq, &seq[..extra.min(seq.len())]].concat(),
+        false => seq.to_vec(),
+    }
+}
+
+/// Find all matches of a motif on both strands of a sequence.
+///
+/// Matches may contain up to `max_errors` mismatches, insertions and deletions.
+/// Protein motifs are searched in all six reading frames.
+/// Approximate matches overlapping a better match on the same strand are not reported.
+/// On circular sequences, matches may span the origin.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// let seq = DnaSequence::from_str("AAGAATTCAAGGATCCAA").unwrap();
+/// let seq = seq.iupac_iter().collect::<Vec<_>>();
+/// let motif = Motif::regex("G[AG]ATCC").unwrap();
+/// let hits = search_motif(&seq, false, &motif, 0).unwrap();
+/// assert_eq!((hits[0].start, hits[0].end), (10, 15));
+///
+/// let hits = search_motif(&seq, false, &Motif::iupac("GAATTC").unwrap(), 0).unwrap();
+/// assert_eq!(hits.len(), 2); // palindromic site, on both strands
+/// ```
+pub fn search_motif(
+    seq: &[IupacNucleotide],
+    circular: bool,
+    motif: &Motif,
+    max_errors: usize,
+) -> anyhow::Result<Vec<MotifHit>> {
+    if max_errors >= motif.min_len() {
+        bail!(
+            "At most {} errors are allowed for this motif",
+            motif.min_len() - 1
+        );
+    }
+    let len = seq.len();
+    let reverse = reverse_complement(seq);
+    // Candidate matches as strand, first and last base along the strand, distance and matched text
+    let mut found = Vec::<(Strand, usize, usize, usize, String)>::new();
+    for (strand, bases) in [(Strand::Forward, seq), (Strand::Reverse, &reverse[..])] {
+        match motif.kind {
+            MotifKind::Nucleotide => {
+                let extra = (motif.max_len() + max_errors).saturating_sub(1);
+                let text = extend(bases, circular, extra)
+                    .iter()
+                    .map(|n| n.to_letter())
+                    .collect::<Vec<_>>();
+                for hit in search_text(motif, &text, len, max_errors)? {
+                    let matched = text[hit.start..=hit.end].iter().collect();
+                    found.push((strand, hit.start, hit.end, hit.distance, matched));
+                }
+            }
+            MotifKind::Protein => {
+                let extra = (motif.max_len() + max_errors) * 3 + 2;
+                let text = extend(bases, circular, extra);
+                for frame in 0..3.min(len) {
+                    let residues = text[frame..]
+                        .chunks_exact(3)
+                        .map(translate)
+                        .collect::<Vec<_>>();
+                    // Codons starting within the sequence
+                    let codons = match circular {
+                        true => (len - frame).div_ceil(3),
+                        false => (len - frame) / 3,
+                    };
+                    for hit in search_text(motif, &residues, codons, max_errors)? {
+                        let (start, end) = (frame + hit.start * 3, frame + hit.end * 3 + 2);
+                        if end - start >= len {
+                            continue;
+                        }
+                        let matched = residues[hit.start..=hit.end].iter().collect();
+                        found.push((strand, start, end, hit.distance, matched));
+                    }
+                }
+            }
+        }
+    }
+    found.sort_by(|a, b| a.3.cmp(&b.3).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+    found.dedup_by(|a, b| (a.0, a.1, a.2) == (b.0, b.1, b.2));
+
+    let overlaps = |a: (usize, usize), b: (usize, usize)| {
+        let shifts: &[usize] = match circular {
+            true => &[0, len],
+            false => &[0],
+        };
+        shifts
+            .iter()
+            .any(|&s| (a.0 + s <= b.1 && b.0 <= a.1 + s) || (b.0 + s <= a.1 && a.0 <= b.1 + s))
+    };
+    let mut kept = Vec::<(Strand, usize, usize, usize, String)>::new();
+    for hit in found {
+        let better = kept
+            .iter()
+            .any(|k| k.0 == hit.0 && k.3 < hit.3 && overlaps((k.1, k.2), (hit.1, hit.2)));
+        if !better {
+            kept.push(hit);
+        }
+    }
+
+    let mut hits = kept
+        .into_iter()
+        .map(|(strand, start, end, distance, matched)| {
+            let (start, end) = match strand {
+                Strand::Reverse => ((2 * len - 1 - end) % len, len - 1 - start),
+                _ => (start, end % len),
+            };
+            MotifHit {
+                start,
+                end,
+                strand,
+                distance,
+                matched,
+            }
+        })
+        .collect::<Vec<_>>();
+    hits.sort_by_key(|hit| (hit.start, hit.strand == Strand::Reverse, hit.distance));
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{search_motif, Motif};
+    use crate::{seq::Strand, test_utils::iupac};
+
+    fn spans(seq: &str, circular: bool, motif: &Motif, max_errors: usize) -> Vec<(usize, usize)> {
+        search_motif(&iupac(seq), circular, motif, max_errors)
+            .unwrap()
+            .iter()
+            .map(|hit| (hit.start, hit.end))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_motifs() {
+        assert_eq!(Motif::iupac("GGTCTCN").unwrap().min_len(), 7);
+        let motif = Motif::regex("^ATG[^T].{3,6}TA?A$").unwrap();
+        assert_eq!((motif.min_len(), motif.max_len()), (9, 13));
+        assert!(Motif::regex("AT+").is_err());
+        assert!(Motif::regex("A[CG").is_err());
+        assert!(Motif::iupac("AXA").is_err());
+        let motif = Motif::prosite("C-x(2,4)-C-x(3)-[LIVMFYWC]-x(8)-H-x(3,5)-H.").unwrap();
+        assert_eq!((motif.min_len(), motif.max_len()), (21, 25));
+        assert!(Motif::prosite("C-x(4,2)").is_err());
+        assert!(Motif::prosite("C--H").is_err());
+        assert!(Motif::iupac(&"A".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_search_iupac_on_both_strands() {
+        // BsaI GGTCTC on the top strand and on the bottom strand
+        let seq = "AAGGTCTCAAAGAGACCAA";
+        let hits = search_motif(&iupac(seq), false, &Motif::iupac("GGTCTC").unwrap(), 0).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(
+            (hits[0].start, hits[0].end, hits[0].strand),
+            (2, 7, Strand::Forward)
+        );
+        assert_eq!(
+            (hits[1].start, hits[1].end, hits[1].strand),
+            (11, 16, Strand::Reverse)
+        );
+        assert_eq!(hits[1].matched, "GGTCTC");
+        // Ambiguity codes in the motif
+        assert_eq!(
+            spans("AACCGGAA", false, &Motif::iupac("SSSS").unwrap(), 0).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_search_with_errors() {
+        let motif = Motif::iupac("GATTACA").unwrap();
+        // Mismatch, deletion and insertion
+        for seq in ["CCGATTCCACC", "CCGATTCACC", "CCGATTTACACC"] {
+            assert!(spans(seq, false, &motif, 0).is_empty());
+            let hits = search_motif(&iupac(seq), false, &motif, 1).unwrap();
+            assert_eq!(hits.len(), 1, "{}", seq);
+            assert_eq!((hits[0].start, hits[0].distance), (2, 1));
+        }
+        assert!(search_motif(&iupac("GATTACA"), false, &motif, 7).is_err());
+    }
+
+    #[test]
+    fn test_search_regex_anchors_and_repeats() {
+        let motif = Motif::regex("ATG.{2,4}TAA").unwrap();
+        assert_eq!(spans("CCATGCCCTAACC", false, &motif, 0), [(2, 10)]);
+        let motif = Motif::regex("^ATG").unwrap();
+        assert_eq!(spans("ATGATG", false, &motif, 0), [(0, 2)]);
+        let motif = Motif::regex("CAT$").unwrap();
+        assert_eq!(spans("CATCAT", false, &motif, 0), [(3, 5)]);
+    }
+
+    #[test]
+    fn test_search_circular() {
+        let motif = Motif::iupac("GGAATT").unwrap();
+        assert!(spans("ATTCCCCGGA", false, &motif, 0).is_empty());
+        // AATTCC on the top strand matches on the bottom strand
+        assert_eq!(spans("ATTCCCCGGA", true, &motif, 0), [(7, 2), (9, 4)]);
+    }
+
+    #[test]
+    fn test_search_prosite() {
+        // N-glycosylation site N-{P}-[ST]-{P} in M N G T A on the bottom strand
+        let motif = Motif::prosite("N-{P}-[ST]-{P}.").unwrap();
+        let cds = "ATGAACGGTACCGCG";
+        let reverse = cds
+            .chars()
+            .rev()
+            .map(|c| match c {
+                'A' => 'T',
+                'T' => 'A',
+                'G' => 'C',
+                _ => 'G',
+            })
+            .collect::<String>();
+        let hits = search_motif(&iupac(&format!("C{}", reverse)), false, &motif, 0).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].start, hits[0].end), (1, 12));
+        assert_eq!(hits[0].strand, Strand::Reverse);
+        assert_eq!(hits[0].matched, "NGTA");
+        // Proline at the excluded position
+        let hits = search_motif(&iupac("AACCCGACCGCG"), false, &motif, 0).unwrap();
+        assert!(hits.is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+use crate::{seq::reverse_complement, traits::ToIupac, uni::IupacNucleotide};
+
+/// Parameters of an in-silico PCR.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcrParameters {
+    /// Maximum weighted number of mismatches of a primer binding site
+    pub max_mismatches: usize,
+    /// Number of 3' terminal bases whose mismatches are weighted more strictly
+    pub three_prime_length: usize,
+    /// Weight of a mismatch within the 3' terminal bases
+    pub three_prime_weight: usize,
+    /// Maximum size of an amplified product
+    pub max_product_size: usize,
+}
+
+impl Default for PcrParameters {
+    fn default() -> Self {
+        Self {
+            max_mismatches: 2,
+            three_prime_length: 5,
+            three_prime_weight: 3,
+            max_product_size: 5000,
+        }
+    }
+}
+
+/// One of the two primers of a PCR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PcrPrimer {
+    Forward,
+    Reverse,
+}
+
+/// A primer binding site on the template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimerSite {
+    pub primer: PcrPrimer,
+    /// First template position covered by the primer
+    pub start: usize,
+    /// Template position after the last base covered by the primer
+    pub end: usize,
+    /// Whether the primer anneals to the bottom strand and extends along the top strand
+    pub forward_strand: bool,
+    /// Number of mismatching bases
+    pub mismatches: usize,
+}
+
+/// A predicted PCR product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Amplicon {
+    /// Primer site on the top strand, extending to the right
+    pub left: PrimerSite,
+    /// Primer site on the bottom strand, extending to the left
+    pub right: PrimerSite,
+    /// First template position of the product
+    pub start: usize,
+    /// Template position after the last base of the product
+    pub end: usize,
+    /// Product sequence, with the primer sequences at both ends
+    pub sequence: Vec<IupacNucleotide>,
+}
+
+impl Amplicon {
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+}
+
+/// Number of mismatches of a primer (5' to 3') against a site of the same strand,
+/// or `None` if their weighted sum exceeds the maximum.
+fn weighted_mismatches(
+    site: &[IupacNucleotide],
+    primer: &[IupacNucleotide],
+    params: &PcrParameters,
+) -> Option<usize> {
+    let three_prime_start = primer.len().saturating_sub(params.three_prime_length);
+    let mut mismatches = 0;
+    let mut weighted = 0;
+    for (i, (p, s)) in primer.iter().zip(site).enumerate() {
+        if !p.matches(s) {
+            mismatches += 1;
+            weighted += if i >= three_prime_start {
+                params.three_prime_weight
+            } else {
+                1
+            };
+            if weighted > params.max_mismatches {
+                return None;
+            }
+        }
+    }
+    Some(mismatches)
+}
+
+/// Find all binding sites of a primer on both strands of the template.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::prelude::{*, IupacNucleotide::*};
+///
+/// let template = [A, A, C, G, T, T, T, A, C, G, A];
+/// let sites = primer_sites(&template, &[A, C, G], PcrPrimer::Forward, &PcrParameters {
+///     max_mismatches: 0,
+///     ..PcrParameters::default()
+/// });
+/// // ACG on the top strand at 1 and 7, CGT on the bottom strand at 2
+/// assert_eq!(sites.len(), 3);
+/// ```
+pub fn primer_sites<T>(
+    template: &[T],
+    primer: &[IupacNucleotide],
+    which: PcrPrimer,
+    params: &PcrParameters,
+) -> Vec<PrimerSite>
+where
+    T: ToIupac,
+{
+    let template = template.iter().map(|n| n.to_iupac()).collect::<Vec<_>>();
+    let len = template.len();
+    let n = primer.len();
+    if n == 0 || n > len {
+        return Vec::new();
+    }
+    let mut sites = Vec::new();
+    // Primer sequence found on the top strand, extends to the right
+    for (start, site) in template.windows(n).enumerate() {
+        if let Some(mismatches) = weighted_mismatches(site, primer, params) {
+            sites.push(PrimerSite {
+                primer: which,
+                start,
+                end: start + n,
+                forward_strand: true,
+                mismatches,
+            });
+        }
+    }
+    // Primer sequence found on the bottom strand, extends to the left
+    let template_rc = reverse_complement(&template);
+    for (rc_start, site) in template_rc.windows(n).enumerate() {
+        if let Some(mismatches) = weighted_mismatches(site, primer, params) {
+            sites.push(PrimerSite {
+                primer: which,
+                start: len - rc_start - n,
+                end: len - rc_start,
+                forward_strand: false,
+                mismatches,
+            });
+        }
+    }
+    sites
+}
+
+/// Predict the products a primer pair amplifies from a linear template.
+///
+/// Primers may contain IUPAC ambiguity codes and bind to either strand,
+/// so products primed by a single primer on both ends are reported as well.
+/// Product sequences carry the primer sequences at both ends.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// let template = DnaSequence::from_str("CCCCATGGCAAGCTTTTTTTTTGGATCCAGACCCC").unwrap();
+/// let forward = [IupacNucleotide::A, IupacNucleotide::T, IupacNucleotide::G, IupacNucleotide::G];
+/// let reverse = DnaSequence::from_str("TCTGGATCC").unwrap().iupac_iter().collect::<Vec<_>>();
+/// let params = PcrParameters { max_mismatches: 0, ..PcrParameters::default() };
+/// let amplicons = in_silico_pcr(template.as_nucleotides(), &forward, &reverse, &params);
+/// assert_eq!(amplicons.len(), 1);
+/// assert_eq!((amplicons[0].start, amplicons[0].end), (4, 31));
+/// ```
+pub fn in_silico_pcr<T>(
+    template: &[T],
+    forward: &[IupacNucleotide],
+    reverse: &[IupacNucleotide],
+    params: &PcrParameters,
+) -> Vec<Amplicon>
+where
+    T: ToIupac,
+{
+    let template = template.iter().map(|n| n.to_iupac()).collect::<Vec<_>>();
+    let primer_seq = |which: PcrPrimer| match which {
+        PcrPrimer::Forward => forward,
+        PcrPrimer::Reverse => reverse,
+    };
+
+    let mut sites = primer_sites(&template, forward, PcrPrimer::Forward, params);
+    sites.extend(primer_sites(&template, reverse, PcrPrimer::Reverse, params));
+    let (left, right): (Vec<_>, Vec<_>) = sites.into_iter().partition(|s| s.forward_strand);
+
+    let mut amplicons = Vec::new();
+    for l in left.iter() {
+        for r in right.iter() {
+            if r.start < l.end || r.end - l.start > params.max_product_size {
+                continue;
+            }
+            let mut sequence = primer_seq(l.primer).to_vec();
+            sequence.extend_from_slice(&template[l.end..r.start]);
+            sequence.extend(reverse_complement(primer_seq(r.primer)));
+            amplicons.push(Amplicon {
+                left: l.clone(),
+                right: r.clone(),
+                start: l.start,
+                end: r.end,
+                sequence,
+            });
+        }
+    }
+    amplicons.sort_by_key(|a| (a.start, a.end));
+    amplicons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{in_silico_pcr, primer_sites, PcrParameters, PcrPrimer};
+    use crate::{seq::DnaSequence, test_utils::iupac};
+
+    const TEMPLATE: &str = "GGGGACTGACTAGCATCGATTTTTTTTTTTTTTTTTTTTGCATGCCAGTTTCGGGG";
+
+    #[test]
+    fn test_in_silico_pcr_exact() {
+        let template = DnaSequence::from_str(TEMPLATE).unwrap();
+        let amplicons = in_silico_pcr(
+            template.as_nucleotides(),
+            &iupac("ACTGACTAGC"),
+            &iupac("CGAAACTGGC"),
+            &PcrParameters::default(),
+        );
+        assert_eq!(amplicons.len(), 1);
+        let amplicon = &amplicons[0];
+        assert_eq!((amplicon.start, amplicon.end), (4, 53));
+        assert_eq!(amplicon.len(), 49);
+        assert_eq!(amplicon.left.primer, PcrPrimer::Forward);
+        assert_eq!(amplicon.right.primer, PcrPrimer::Reverse);
+        assert_eq!(
+            amplicon.sequence,
+            iupac(&TEMPLATE[4..53]),
+            "an exact match reproduces the template"
+        );
+    }
+
+    #[test]
+    fn test_in_silico_pcr_degenerate_primer() {
+        let template = DnaSequence::from_str(TEMPLATE).unwrap();
+        let amplicons = in_silico_pcr(
+            template.as_nucleotides(),
+            &iupac("ACNGAYTAGC"),
+            &iupac("CGAAACTGGC"),
+            &PcrParameters {
+                max_mismatches: 0,
+                ..PcrParameters::default()
+            },
+        );
+        assert_eq!(amplicons.len(), 1);
+        assert_eq!(amplicons[0].left.mismatches, 0);
+        assert_eq!(amplicons[0].sequence[..4], iupac("ACNG"));
+    }
+
+    #[test]
+    fn test_in_silico_pcr_three_prime_mismatch() {
+        let template = DnaSequence::from_str(TEMPLATE).unwrap();
+        let params = PcrParameters::default();
+        // One mismatch at the 5' end is tolerated
+        let five_prime = in_silico_pcr(
+            template.as_nucleotides(),
+            &iupac("TCTGACTAGC"),
+            &iupac("CGAAACTGGC"),
+            &params,
+        );
+        assert_eq!(five_prime.len(), 1);
+        assert_eq!(five_prime[0].left.mismatches, 1);
+        // One mismatch at the 3' end is not
+        let three_prime = in_silico_pcr(
+            template.as_nucleotides(),
+            &iupac("ACTGACTAGG"),
+            &iupac("CGAAACTGGC"),
+            &params,
+        );
+        assert!(three_prime.is_empty());
+    }
+
+    #[test]
+    fn test_in_silico_pcr_max_product_size() {
+        let template = DnaSequence::from_str(TEMPLATE).unwrap();
+        let amplicons = in_silico_pcr(
+            template.as_nucleotides(),
+            &iupac("ACTGACTAGC"),
+            &iupac("CGAAACTGGC"),
+            &PcrParameters {
+                max_product_size: 40,
+                ..PcrParameters::default()
+            },
+        );
+        assert!(amplicons.is_empty());
+    }
+
+    #[test]
+    fn test_primer_sites_both_strands() {
+        let template = DnaSequence::from_str(TEMPLATE).unwrap();
+        let sites = primer_sites(
+            template.as_nucleotides(),
+            &iupac("CGAAACTGGC"),
+            PcrPrimer::Reverse,
+            &PcrParameters::default(),
+        );
+        assert_eq!(sites.len(), 1);
+        assert!(!sites[0].forward_strand);
+        assert_eq!((sites[0].start, sites[0].end), (43, 53));
+    }
+}
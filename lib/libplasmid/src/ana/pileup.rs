@@ -0,0 +1,402 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail};
+
+use super::Mapping;
+
+/// A column of a pairwise alignment, with the positions in the aligned sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlignmentStep {
+    /// Bases of both sequences aligned to each other
+    Align { x: usize, y: usize },
+    /// Base of the first sequence facing a gap
+    Delete { x: usize },
+    /// Base of the second sequence facing a gap
+    Insert { y: usize },
+}
+
+/// SAM flags of alignments left out of pileups: unmapped, secondary, failing QC and duplicates
+const SAM_SKIPPED_FLAGS: u16 = 0x4 | 0x100 | 0x200 | 0x400;
+
+/// Reads covering a reference position
+#[derive(Clone, Default)]
+pub struct PileupColumn {
+    /// Reads with a base aligned to the position
+    pub depth: u32,
+    /// Aligned `A`, `C`, `G`, `T` and other bases
+    pub bases: [u32; 5],
+    /// Reads with an insertion right after the position
+    pub insertions: u32,
+    /// Reads with the position deleted
+    pub deletions: u32,
+    /// Sequences inserted right after the position, with their read counts
+    pub inserted: BTreeMap<Vec<u8>, u32>,
+    /// Lengths of deletions starting at the position, with their read counts
+    pub deleted: BTreeMap<usize, u32>,
+}
+
+/// Pileup along a reference record
+pub struct Pileup {
+    pub name: String,
+    pub columns: Vec<PileupColumn>,
+}
+
+/// Pileups of all records of a reference
+#[derive(Default)]
+pub struct Pileups {
+    pub references: Vec<Pileup>,
+}
+
+impl PileupColumn {
+    fn add_insertion(&mut self, bases: Vec<u8>) {
+        self.insertions += 1;
+        *self.inserted.entry(bases).or_default() += 1;
+    }
+
+    fn add_deletion(&mut self, len: usize) {
+        *self.deleted.entry(len).or_default() += 1;
+    }
+
+    /// Reads with a base aligned to or deleted at the position
+    pub fn spanning(&self) -> u32 {
+        self.depth + self.deletions
+    }
+}
+
+impl Pileup {
+    pub fn depth(&self) -> Vec<u32> {
+        self.columns.iter().map(|column| column.depth).collect()
+    }
+
+    pub fn mean_depth(&self) -> f64 {
+        match self.columns.len() {
+            0 => 0.0,
+            len => self.columns.iter().map(|c| c.depth as f64).sum::<f64>() / len as f64,
+        }
+    }
+
+    /// Percentage of positions with at least one aligned base
+    pub fn covered_percent(&self) -> f64 {
+        match self.columns.len() {
+            0 => 0.0,
+            len => 100.0 * self.columns.iter().filter(|c| c.depth > 0).count() as f64 / len as f64,
+        }
+    }
+}
+
+impl Pileups {
+    /// Empty pileups of reference records with their lengths
+    pub fn new(references: impl IntoIterator<Item = (String, usize)>) -> Self {
+        Pileups {
+            references: references
+                .into_iter()
+                .map(|(name, len)| Pileup {
+                    name,
+                    columns: vec![PileupColumn::default(); len],
+                })
+                .collect(),
+        }
+    }
+
+    /// Add a read aligned from `position` on with CIGAR operations, `sequence` may be empty if unknown.
+    /// The read must lie within the reference and its sequence must match the CIGAR length.
+    fn add_read(
+        &mut self,
+        reference: usize,
+        mut position: usize,
+        cigar: &[(usize, char)],
+        sequence: &[u8],
+    ) -> anyhow::Result<()> {
+        let length = |ops: &[char]| {
+            cigar
+                .iter()
+                .filter(|(_, op)| ops.contains(op))
+                .try_fold(0_usize, |sum, (len, _)| sum.checked_add(*len))
+                .ok_or_else(|| anyhow!("invalid CIGAR"))
+        };
+        let reference_len = length(&['M', '=', 'X', 'D', 'N'])?;
+        let query_len = length(&['M', '=', 'X', 'I', 'S'])?;
+        if !sequence.is_empty() && sequence.len() != query_len {
+            bail!(
+                "SEQ length {} does not match the CIGAR length {query_len}",
+                sequence.len()
+            );
+        }
+        let columns = &mut self.references[reference].columns;
+        if position
+            .checked_add(reference_len)
+            .is_none_or(|end| end > columns.len())
+        {
+            bail!(
+                "alignment runs past the end of {}",
+                self.references[reference].name
+            );
+        }
+
+        let mut offset = 0;
+        for &(len, op) in cigar.iter() {
+            match op {
+                'M' | '=' | 'X' => {
+                    for i in 0..len {
+                        let column = &mut columns[position + i];
+                        column.depth += 1;
+                        column.bases[base_index(sequence.get(offset + i))] += 1;
+                    }
+                    position += len;
+                    offset += len;
+                }
+                'I' => {
+                    if position > 0 {
+                        columns[position - 1].add_insertion(match sequence.is_empty() {
+                            true => vec![b'N'; len],
+                            false => sequence[offset..offset + len].to_ascii_uppercase(),
+                        });
+                    }
+                    offset += len;
+                }
+                'D' => {
+                    match columns.get_mut(position) {
+                        Some(column) => column.add_deletion(len),
+                        None => bail!("deletion past the end of the alignment"),
+                    }
+                    columns[position..position + len]
+                        .iter_mut()
+                        .for_each(|column| column.deletions += 1);
+                    position += len;
+                }
+                'N' => position += len,
+                'S' => offset += len,
+                'H' | 'P' => (),
+                other => bail!("invalid CIGAR operation '{other}'"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Pileups of the primary alignments of a SAM file, sized by its `@SQ` header lines.
+    /// Alignments must lie within the reference lengths of the header.
+    pub fn from_sam(sam: &str) -> anyhow::Result<Self> {
+        let mut pileups = Pileups::new(sam.lines().filter_map(|line| {
+            let fields = line.strip_prefix("@SQ\t")?.split('\t').collect::<Vec<_>>();
+            let field = |tag: &str| fields.iter().find_map(|f| f.strip_prefix(tag));
+            Some((field("SN:")?.to_string(), field("LN:")?.parse().ok()?))
+        }));
+
+        for (i, line) in sam.lines().enumerate() {
+            if line.starts_with('@') || line.trim().is_empty() {
+                continue;
+            }
+            let invalid = |field: &str| anyhow!("invalid {field} on SAM line {}", i + 1);
+            let fields = line.split('\t').collect::<Vec<_>>();
+            if fields.len() < 11 {
+                bail!("expected 11 fields on SAM line {}", i + 1);
+            }
+            let flag = fields[1].parse::<u16>().map_err(|_| invalid("FLAG"))?;
+            if flag & SAM_SKIPPED_FLAGS != 0 || fields[2] == "*" || fields[5] == "*" {
+                continue;
+            }
+            let position = fields[3].parse::<usize>().map_err(|_| invalid("POS"))?;
+            let cigar = parse_cigar(fields[5]).ok_or_else(|| invalid("CIGAR"))?;
+            let sequence = match fields[9] {
+                "*" => &[],
+                sequence => sequence.as_bytes(),
+            };
+            let reference = pileups
+                .references
+                .iter()
+                .position(|r| r.name == fields[2])
+                .ok_or_else(|| {
+                    anyhow!(
+                        "reference {} on SAM line {} has no @SQ line",
+                        fields[2],
+                        i + 1
+                    )
+                })?;
+            pileups
+                .add_read(reference, position.saturating_sub(1), &cigar, sequence)
+                .map_err(|e| anyhow!("{e} on SAM line {}", i + 1))?;
+        }
+        Ok(pileups)
+    }
+
+    /// Pileups of reads mapped with the read mapper
+    pub fn from_mapping(mapping: &Mapping) -> anyhow::Result<Self> {
+        let mut pileups = Pileups::new(
+            mapping
+                .reference
+                .sequences
+                .iter()
+                .map(|sequence| (sequence.name.to_owned(), sequence.len)),
+        );
+        for read in mapping.reads.iter() {
+            if let Some(record) = read.record {
+                pileups.add_read(record, read.position, &read.cigar, &read.sequence)?;
+            }
+        }
+        Ok(pileups)
+    }
+
+    /// Add a pairwise alignment of a reference record, the first sequence, to a read.
+    /// Gaps before the first and after the last aligned base are not part of the read.
+    pub fn add_alignment(&mut self, reference: usize, steps: &[AlignmentStep], read: &[u8]) {
+        let aligned = |step: &AlignmentStep| matches!(step, AlignmentStep::Align { .. });
+        let (Some(first), Some(last)) = (
+            steps.iter().position(aligned),
+            steps.iter().rposition(aligned),
+        ) else {
+            return;
+        };
+
+        let columns = &mut self.references[reference].columns;
+        // Latest aligned reference position, and the indel following it
+        let mut previous = 0;
+        let mut insertion = Vec::new();
+        let mut deletion: Option<(usize, usize)> = None;
+        for step in steps[first..=last].iter() {
+            match *step {
+                AlignmentStep::Align { x, y } => {
+                    if !insertion.is_empty() {
+                        columns[previous].add_insertion(std::mem::take(&mut insertion));
+                    }
+                    if let Some((start, len)) = deletion.take() {
+                        columns[start].add_deletion(len);
+                    }
+                    if columns.len() <= x {
+                        columns.resize(x + 1, PileupColumn::default());
+                    }
+                    columns[x].depth += 1;
+                    columns[x].bases[base_index(read.get(y))] += 1;
+                    previous = x;
+                }
+                AlignmentStep::Delete { x } => {
+                    if columns.len() <= x {
+                        columns.resize(x + 1, PileupColumn::default());
+                    }
+                    columns[x].deletions += 1;
+                    match deletion.as_mut() {
+                        Some((_, len)) => *len += 1,
+                        None => deletion = Some((x, 1)),
+                    }
+                }
+                AlignmentStep::Insert { y } => {
+                    insertion.push(read.get(y).map_or(b'N', u8::to_ascii_uppercase))
+                }
+            }
+        }
+    }
+
+    /// Depth as bedGraph, one line per run of positions with the same depth
+    pub fn bedgraph(&self) -> String {
+        let mut bedgraph = String::new();
+        for pileup in self.references.iter() {
+            let depth = pileup.depth();
+            let mut start = 0;
+            for end in 1..=depth.len() {
+                if end == depth.len() || depth[end] != depth[start] {
+                    bedgraph += &format!("{}\t{}\t{}\t{}\n", pileup.name, start, end, depth[start]);
+                    start = end;
+                }
+            }
+        }
+        bedgraph
+    }
+}
+
+/// Index of a base in the counts of a pileup column
+fn base_index(base: Option<&u8>) -> usize {
+    match base.map(u8::to_ascii_uppercase) {
+        Some(b'A') => 0,
+        Some(b'C') => 1,
+        Some(b'G') => 2,
+        Some(b'T') => 3,
+        _ => 4,
+    }
+}
+
+/// CIGAR operations with their lengths, none if malformed or of zero length
+fn parse_cigar(cigar: &str) -> Option<Vec<(usize, char)>> {
+    let mut operations = Vec::new();
+    let mut len = String::new();
+    for c in cigar.chars() {
+        match c {
+            '0'..='9' => len.push(c),
+            op => match std::mem::take(&mut len).parse().ok()? {
+                0 => return None,
+                len => operations.push((len, op)),
+            },
+        }
+    }
+    len.is_empty().then_some(operations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AlignmentStep, Pileups};
+
+    const HEADER: &str = "@SQ\tSN:chr\tLN:10\n";
+
+    #[test]
+    fn test_pileup_from_sam() {
+        let sam = HEADER.to_string()
+            + "r1\t0\tchr\t2\t60\t2S3M2I2M1D2M\t*\t0\t0\tNNACGTTGTTA\t*\n"
+            + "r2\t16\tchr\t1\t60\t4M\t*\t0\t0\tAACG\t*\n"
+            // Unmapped and secondary alignments are skipped
+            + "r3\t4\t*\t0\t0\t*\t*\t0\t0\tACGT\t*\n"
+            + "r4\t256\tchr\t1\t0\t4M\t*\t0\t0\tACGT\t*\n";
+        let pileups = Pileups::from_sam(&sam).unwrap();
+        let pileup = &pileups.references[0];
+        assert_eq!(pileup.depth(), [1, 2, 2, 2, 1, 1, 0, 1, 1, 0]);
+        assert_eq!(pileup.columns[1].bases, [2, 0, 0, 0, 0]);
+        assert_eq!(pileup.columns[3].inserted.get(b"TT".as_slice()), Some(&1));
+        assert_eq!(pileup.columns[6].deleted.get(&1), Some(&1));
+        assert_eq!(pileup.columns[6].spanning(), 1);
+        assert_eq!(pileup.covered_percent(), 80.0);
+        assert_eq!(
+            pileups.bedgraph(),
+            "chr\t0\t1\t1\nchr\t1\t4\t2\nchr\t4\t6\t1\nchr\t6\t7\t0\nchr\t7\t9\t1\nchr\t9\t10\t0\n"
+        );
+    }
+
+    #[test]
+    fn test_reject_malformed_sam() {
+        let load = |line: &str| Pileups::from_sam(&format!("{HEADER}{line}\n")).err();
+        // SEQ shorter than the CIGAR
+        assert!(load("r\t0\tchr\t1\t60\t2M3I\t*\t0\t0\tAC\t*").is_some());
+        // Alignments past the reference end
+        assert!(load("r\t0\tchr\t9\t60\t3M\t*\t0\t0\tACG\t*").is_some());
+        assert!(load("r\t0\tchr\t18446744073709551615\t60\t3M\t*\t0\t0\tACG\t*").is_some());
+        assert!(load("r\t0\tchr\t9\t60\t2M\t*\t0\t0\tAC\t*").is_none());
+        // Zero-length operations, here a deletion at the reference end
+        assert!(load("r\t0\tchr\t7\t60\t4M0D\t*\t0\t0\tACGT\t*").is_some());
+        assert!(load("r\t0\tchr\t1\t60\t0M2M\t*\t0\t0\tAC\t*").is_some());
+        // References without an @SQ line
+        assert!(load("r\t0\tother\t1\t60\t2M\t*\t0\t0\tAC\t*").is_some());
+        assert!(load("r\t0\tchr\t1\t60\t2Q\t*\t0\t0\tAC\t*").is_some());
+        assert!(load("r\t0\tchr\t1\t60\t2M").is_some());
+    }
+
+    #[test]
+    fn test_pileup_from_alignment() {
+        use AlignmentStep::*;
+
+        let mut pileups = Pileups::new([("chr".to_string(), 6)]);
+        // A read aligned from the second reference base, with an insertion and a deletion
+        let steps = [
+            Delete { x: 0 },
+            Align { x: 1, y: 0 },
+            Insert { y: 1 },
+            Align { x: 2, y: 2 },
+            Delete { x: 3 },
+            Align { x: 4, y: 3 },
+            Delete { x: 5 },
+        ];
+        pileups.add_alignment(0, &steps, b"cgta");
+        let pileup = &pileups.references[0];
+        assert_eq!(pileup.depth(), [0, 1, 1, 0, 1, 0]);
+        assert_eq!(pileup.columns[1].inserted.get(b"G".as_slice()), Some(&1));
+        assert_eq!(pileup.columns[3].deleted.get(&1), Some(&1));
+        // Gaps outside the read are not deletions
+        assert_eq!(pileup.columns[0].spanning(), 0);
+        assert_eq!(pileup.columns[5].spanning(), 0);
+    }
+}
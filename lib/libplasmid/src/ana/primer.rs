@@ -0,0 +1,175 @@
+use crate::{dna::DnaNucleotide, seq::DnaSequence};
+
+use super::thermo::*;
+
+/// Number of G/C bases among the last five bases of the 3' end.
+///
+/// One to three G/C bases at the 3' end promote specific binding,
+/// more than three promote mispriming.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::{ana::gc_clamp, prelude::DnaNucleotide::*};
+///
+/// assert_eq!(gc_clamp(&[A, A, A, T, T, G, C]), 2);
+/// ```
+pub fn gc_clamp(seq: &[DnaNucleotide]) -> usize {
+    use DnaNucleotide::*;
+    seq.iter()
+        .rev()
+        .take(5)
+        .filter(|n| [G, C].contains(n))
+        .count()
+}
+
+/// Physical and thermodynamic properties of a single primer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrimerProperties {
+    /// Primer length in nucleotides
+    pub length: usize,
+    /// Fraction of G and C bases
+    pub gc_content: f32,
+    /// Nearest-neighbor melting temperature (°C)
+    pub tm: f64,
+    /// Wallace rule/GC content melting temperature (°C)
+    pub tm_basic: f64,
+    /// Number of G/C bases among the last five bases of the 3' end
+    pub gc_clamp: usize,
+    /// Most stable self-dimer (kcal/mol)
+    pub self_dimer_dg: f64,
+    /// Most stable self-dimer with a paired 3' end (kcal/mol)
+    pub self_three_prime_dg: f64,
+    /// Most stable hairpin (kcal/mol)
+    pub hairpin_dg: f64,
+}
+
+impl PrimerProperties {
+    /// Compute the properties of a primer under the given reaction conditions.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let primer = DnaSequence::from_str("AGCGGATAACAATTTCACACAGGA").unwrap();
+    /// let props = PrimerProperties::compute(primer.as_nucleotides(), &TmConditions::default());
+    /// assert_eq!(props.length, 24);
+    /// assert_eq!(props.gc_clamp, 3);
+    /// ```
+    pub fn compute(seq: &[DnaNucleotide], conditions: &TmConditions) -> Self {
+        use DnaNucleotide::*;
+        let gc = seq.iter().filter(|n| [G, C].contains(n)).count();
+        Self {
+            length: seq.len(),
+            gc_content: if seq.is_empty() {
+                0_f32
+            } else {
+                gc as f32 / seq.len() as f32
+            },
+            tm: tm_nearest_neighbor(seq, conditions),
+            tm_basic: tm_basic(seq),
+            gc_clamp: gc_clamp(seq),
+            self_dimer_dg: dimer_dg(seq, seq),
+            self_three_prime_dg: three_prime_dimer_dg(seq, seq),
+            hairpin_dg: hairpin_dg(seq),
+        }
+    }
+}
+
+/// Properties of a forward/reverse primer pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrimerPairProperties {
+    pub forward: PrimerProperties,
+    pub reverse: PrimerProperties,
+    /// Absolute difference of the nearest-neighbor melting temperatures (°C)
+    pub tm_difference: f64,
+    /// Most stable heterodimer (kcal/mol)
+    pub dimer_dg: f64,
+    /// Most stable heterodimer with a paired 3' end of either primer (kcal/mol)
+    pub three_prime_dg: f64,
+}
+
+impl PrimerPairProperties {
+    /// Compute the properties of a primer pair under the given reaction conditions.
+    /// Both primers are given 5' to 3'.
+    pub fn compute(
+        forward: &[DnaNucleotide],
+        reverse: &[DnaNucleotide],
+        conditions: &TmConditions,
+    ) -> Self {
+        let fwd = PrimerProperties::compute(forward, conditions);
+        let rev = PrimerProperties::compute(reverse, conditions);
+        Self {
+            tm_difference: (fwd.tm - rev.tm).abs(),
+            dimer_dg: dimer_dg(forward, reverse),
+            three_prime_dg: three_prime_dimer_dg(forward, reverse)
+                .min(three_prime_dimer_dg(reverse, forward)),
+            forward: fwd,
+            reverse: rev,
+        }
+    }
+}
+
+impl DnaSequence {
+    /// Compute the melting temperature (°C) of the sequence as an oligonucleotide.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let seq = DnaSequence::from_str("ATGC").unwrap();
+    /// let tm = seq.melting_temperature(TmMethod::Wallace, &TmConditions::default());
+    /// assert_eq!(tm, 12.0);
+    /// ```
+    pub fn melting_temperature(&self, method: TmMethod, conditions: &TmConditions) -> f64 {
+        melting_temperature(self.as_nucleotides(), method, conditions)
+    }
+
+    /// Compute the primer properties of the sequence.
+    pub fn primer_properties(&self, conditions: &TmConditions) -> PrimerProperties {
+        PrimerProperties::compute(self.as_nucleotides(), conditions)
+    }
+
+    /// Compute the properties of the sequence as forward primer paired with `reverse`.
+    pub fn primer_pair_properties(
+        &self,
+        reverse: &DnaSequence,
+        conditions: &TmConditions,
+    ) -> PrimerPairProperties {
+        PrimerPairProperties::compute(self.as_nucleotides(), reverse.as_nucleotides(), conditions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PrimerPairProperties, PrimerProperties};
+    use crate::{ana::TmConditions, seq::DnaSequence};
+
+    #[test]
+    fn test_primer_properties_self_complementary() {
+        let primer = DnaSequence::from_str("GAATTCGAATTC").unwrap();
+        let props = primer.primer_properties(&TmConditions::default());
+        assert!(props.self_dimer_dg < 0.0);
+        assert!(props.self_three_prime_dg < 0.0);
+    }
+
+    #[test]
+    fn test_primer_properties_empty() {
+        let props = PrimerProperties::compute(&[], &TmConditions::default());
+        assert_eq!(props.length, 0);
+        assert_eq!(props.gc_content, 0.0);
+        assert_eq!(props.hairpin_dg, 0.0);
+    }
+
+    #[test]
+    fn test_primer_pair_properties() {
+        let fwd = DnaSequence::from_str("GTAAAACGACGGCCAGT").unwrap();
+        let rev = DnaSequence::from_str("CAGGAAACAGCTATGAC").unwrap();
+        let pair = PrimerPairProperties::compute(
+            fwd.as_nucleotides(),
+            rev.as_nucleotides(),
+            &TmConditions::default(),
+        );
+        assert!((pair.tm_difference - (pair.forward.tm - pair.reverse.tm).abs()).abs() < 1e-12);
+        assert!(pair.three_prime_dg >= pair.dimer_dg);
+    }
+}
@@ -0,0 +1,367 @@
+use std::ops::Range;
+
+use crate::{dna::DnaNucleotide, seq::reverse_complement, traits::Nucleotide};
+
+/// Molar gas constant (cal/K·mol)
+const R: f64 = 1.9872;
+
+/// 37 °C in Kelvin
+const T37: f64 = 310.15;
+
+/// Free energy of duplex initiation at 37 °C (kcal/mol)
+const INIT_DG: f64 = 1.96;
+
+/// Unified nearest-neighbor parameters (ΔH kcal/mol, ΔS cal/K·mol) of a
+/// 5'-XY-3' stack paired with its complement (SantaLucia, 1998).
+fn nn_params(a: DnaNucleotide, b: DnaNucleotide) -> (f64, f64) {
+    use DnaNucleotide::*;
+    match (a, b) {
+        (A, A) | (T, T) => (-7.9, -22.2),
+        (A, T) => (-7.2, -20.4),
+        (T, A) => (-7.2, -21.3),
+        (C, A) | (T, G) => (-8.5, -22.7),
+        (G, T) | (A, C) => (-8.4, -22.4),
+        (C, T) | (A, G) => (-7.8, -21.0),
+        (G, A) | (T, C) => (-8.2, -22.2),
+        (C, G) => (-10.6, -27.2),
+        (G, C) => (-9.8, -24.4),
+        (G, G) | (C, C) => (-8.0, -19.9),
+    }
+}
+
+/// Initiation parameters (ΔH kcal/mol, ΔS cal/K·mol) of a terminal base pair.
+fn terminal_params(n: DnaNucleotide) -> (f64, f64) {
+    use DnaNucleotide::*;
+    match n {
+        G | C => (0.1, -2.8),
+        A | T => (2.3, 4.1),
+    }
+}
+
+/// Free energy of a nearest-neighbor stack at 37 °C (kcal/mol).
+fn nn_dg(a: DnaNucleotide, b: DnaNucleotide) -> f64 {
+    let (dh, ds) = nn_params(a, b);
+    dh - T37 * ds / 1000_f64
+}
+
+/// Free energy of hairpin loop initiation at 37 °C (kcal/mol).
+fn hairpin_loop_dg(len: usize) -> f64 {
+    match len {
+        0..=2 => f64::INFINITY,
+        3 | 4 => 3.5,
+        5 => 3.3,
+        6 => 4.0,
+        7 => 4.2,
+        8 => 4.3,
+        9 => 4.5,
+        10 => 4.6,
+        // Jacobson-Stockmayer extrapolation
+        _ => 4.6 + 2.44 * R * T37 / 1000_f64 * (len as f64 / 10_f64).ln(),
+    }
+}
+
+/// Number of G and C bases of a DNA strand.
+fn gc_count(seq: &[DnaNucleotide]) -> usize {
+    use DnaNucleotide::*;
+    seq.iter().filter(|n| [G, C].contains(n)).count()
+}
+
+/// Method used to compute the melting temperature of an oligonucleotide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TmMethod {
+    /// Nearest-neighbor thermodynamics with salt correction (SantaLucia, 1998)
+    NearestNeighbor,
+    /// Wallace rule, 2 °C per A/T and 4 °C per G/C, for oligos below 14 nt
+    Wallace,
+    /// GC content based estimate, falling back to the Wallace rule below 14 nt
+    Basic,
+}
+
+/// Reaction conditions for melting temperature calculations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TmConditions {
+    /// Monovalent cation (Na⁺/K⁺) concentration in mM
+    pub monovalent: f64,
+    /// Mg²⁺ concentration in mM
+    pub magnesium: f64,
+    /// dNTP concentration in mM
+    pub dntp: f64,
+    /// Oligonucleotide concentration in nM
+    pub oligo: f64,
+}
+
+impl Default for TmConditions {
+    fn default() -> Self {
+        Self {
+            monovalent: 50_f64,
+            magnesium: 0_f64,
+            dntp: 0_f64,
+            oligo: 250_f64,
+        }
+    }
+}
+
+impl TmConditions {
+    /// Sodium-equivalent concentration in M, counting free Mg²⁺ as
+    /// 120·√[Mg²⁺] (von Ahsen et al., 2001).
+    pub fn sodium_equivalent(&self) -> f64 {
+        let free_mg = (self.magnesium - self.dntp).max(0_f64);
+        (self.monovalent + 120_f64 * free_mg.sqrt()).max(f64::MIN_POSITIVE) / 1000_f64
+    }
+}
+
+/// Melting temperature (°C) using the Wallace rule.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::{ana::tm_wallace, prelude::DnaNucleotide::*};
+///
+/// assert_eq!(tm_wallace(&[A, T, G, C]), 12.0);
+/// ```
+pub fn tm_wallace(seq: &[DnaNucleotide]) -> f64 {
+    let gc = gc_count(seq);
+    (2 * (seq.len() - gc) + 4 * gc) as f64
+}
+
+/// Melting temperature (°C) from GC content, using the Wallace rule below 14 nt.
+pub fn tm_basic(seq: &[DnaNucleotide]) -> f64 {
+    if seq.len() < 14 {
+        return tm_wallace(seq);
+    }
+    64.9 + 41_f64 * (gc_count(seq) as f64 - 16.4) / seq.len() as f64
+}
+
+/// Melting temperature (°C) using unified nearest-neighbor parameters.
+///
+/// Oligos shorter than two nucleotides have no stacks and fall back to the Wallace rule.
+pub fn tm_nearest_neighbor(seq: &[DnaNucleotide], conditions: &TmConditions) -> f64 {
+    if seq.len() < 2 {
+        return tm_wallace(seq);
+    }
+
+    let (mut dh, mut ds) = seq
+        .windows(2)
+        .map(|w| nn_params(w[0], w[1]))
+        .fold((0_f64, 0_f64), |(dh, ds), (h, s)| (dh + h, ds + s));
+    for terminal in [seq[0], seq[seq.len() - 1]] {
+        let (h, s) = terminal_params(terminal);
+        dh += h;
+        ds += s;
+    }
+
+    // Salt correction of the entropy term
+    ds += 0.368 * (seq.len() - 1) as f64 * conditions.sodium_equivalent().ln();
+
+    // Self-complementary oligos form homoduplexes
+    let self_complementary = reverse_complement(seq) == seq;
+    let x = if self_complementary {
+        ds += -1.4;
+        1_f64
+    } else {
+        4_f64
+    };
+
+    let ct = conditions.oligo / 1e9;
+    dh * 1000_f64 / (ds + R * (ct / x).ln()) - 273.15
+}
+
+/// Melting temperature (°C) of an oligonucleotide using the given method.
+pub fn melting_temperature(
+    seq: &[DnaNucleotide],
+    method: TmMethod,
+    conditions: &TmConditions,
+) -> f64 {
+    match method {
+        TmMethod::NearestNeighbor => tm_nearest_neighbor(seq, conditions),
+        TmMethod::Wallace => tm_wallace(seq),
+        TmMethod::Basic => tm_basic(seq),
+    }
+}
+
+/// Runs of complementary bases, as ranges of `a`, for every antiparallel offset of `a` against `b`.
+fn antiparallel_runs(a: &[DnaNucleotide], b: &[DnaNucleotide]) -> Vec<Vec<Range<usize>>> {
+    let b_rev = b.iter().rev().collect::<Vec<_>>();
+    let (la, lb) = (a.len() as isize, b.len() as isize);
+    (-(lb - 1)..la)
+        .map(|offset| {
+            let mut runs = Vec::new();
+            let mut start: Option<usize> = None;
+            let from = offset.max(0) as usize;
+            let to = la.min(lb + offset) as usize;
+            for i in from..to {
+                let paired = *b_rev[(i as isize - offset) as usize] == a[i].complement();
+                match (paired, start) {
+                    (true, None) => start = Some(i),
+                    (false, Some(s)) => {
+                        runs.push(s..i);
+                        start = None;
+                    }
+                    _ => (),
+                }
+            }
+            if let Some(s) = start {
+                runs.push(s..to);
+            }
+            runs
+        })
+        .collect()
+}
+
+/// Free energy of a helix formed by `run` with its perfect complement, without initiation.
+fn helix_dg(seq: &[DnaNucleotide], run: &Range<usize>) -> f64 {
+    seq[run.clone()].windows(2).map(|w| nn_dg(w[0], w[1])).sum()
+}
+
+/// Free energy (kcal/mol at 37 °C) of the most stable duplex two oligos can form.
+///
+/// Every helix of at least two base pairs along an antiparallel alignment
+/// contributes its stacking energy. Returns `0` if no stable duplex exists.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::{ana::dimer_dg, prelude::DnaNucleotide::*};
+///
+/// // GAATTC is its own reverse complement
+/// assert!(dimer_dg(&[G, A, A, T, T, C], &[G, A, A, T, T, C]) < -3.0);
+/// assert_eq!(dimer_dg(&[A, A, A, A], &[A, A, A, A]), 0.0);
+/// ```
+pub fn dimer_dg(a: &[DnaNucleotide], b: &[DnaNucleotide]) -> f64 {
+    antiparallel_runs(a, b)
+        .iter()
+        .map(|runs| {
+            let stacks = runs
+                .iter()
+                .filter(|run| run.len() >= 2)
+                .map(|run| helix_dg(a, run))
+                .sum::<f64>();
+            if stacks < 0_f64 {
+                stacks + INIT_DG
+            } else {
+                0_f64
+            }
+        })
+        .fold(0_f64, f64::min)
+}
+
+/// Free energy (kcal/mol at 37 °C) of the most stable duplex in which the
+/// 3' terminal base of `a` is paired with `b`. Such duplexes can be extended
+/// by a polymerase and form primer-dimers.
+pub fn three_prime_dimer_dg(a: &[DnaNucleotide], b: &[DnaNucleotide]) -> f64 {
+    antiparallel_runs(a, b)
+        .iter()
+        .filter_map(|runs| runs.iter().find(|run| run.end == a.len() && run.len() >= 2))
+        .map(|run| (helix_dg(a, run) + INIT_DG).min(0_f64))
+        .fold(0_f64, f64::min)
+}
+
+/// Free energy (kcal/mol at 37 °C) of the most stable hairpin of an oligo,
+/// with a stem of at least three base pairs and a loop of at least three bases.
+/// Returns `0` if no stable hairpin exists.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::{ana::hairpin_dg, prelude::DnaNucleotide::*};
+///
+/// let hairpin = [G, C, G, C, G, A, A, A, C, G, C, G, C];
+/// assert!(hairpin_dg(&hairpin) < 0.0);
+/// assert_eq!(hairpin_dg(&[A, A, A, A, A, A, A, A]), 0.0);
+/// ```
+pub fn hairpin_dg(seq: &[DnaNucleotide]) -> f64 {
+    let mut best = 0_f64;
+    for i in 0..seq.len() {
+        for j in (i + 1..seq.len()).rev() {
+            // Grow the stem inwards from the outer base pair (i, j)
+            let mut stem = 0;
+            let mut stacks = 0_f64;
+            while i + stem < j - stem && seq[i + stem] == seq[j - stem].complement() {
+                if stem > 0 {
+                    stacks += nn_dg(seq[i + stem - 1], seq[i + stem]);
+                }
+                stem += 1;
+                let loop_len = (j - stem + 1).saturating_sub(i + stem);
+                if stem >= 3 && loop_len >= 3 {
+                    best = best.min(stacks + hairpin_loop_dg(loop_len));
+                }
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dna::DnaNucleotide, traits::TryFromLetter};
+
+    fn dna(s: &str) -> Vec<DnaNucleotide> {
+        s.chars()
+            .map(|c| DnaNucleotide::try_from_letter(c).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_tm_basic_long_oligo() {
+        // 64.9 + 41 * (10 - 16.4) / 20
+        let tm = tm_basic(&dna("ATGCATGCATGCATGCATGC"));
+        assert!((tm - 51.78).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tm_nearest_neighbor_range() {
+        let tm = tm_nearest_neighbor(&dna("AGCGGATAACAATTTCACACAGGA"), &TmConditions::default());
+        assert!(tm > 55.0 && tm < 70.0, "unexpected Tm {}", tm);
+    }
+
+    #[test]
+    fn test_tm_nearest_neighbor_salt() {
+        let seq = dna("ATGCGTACGTTAGCCATG");
+        let low_salt = TmConditions::default();
+        let with_mg = TmConditions {
+            magnesium: 1.5,
+            dntp: 0.2,
+            ..TmConditions::default()
+        };
+        assert!(tm_nearest_neighbor(&seq, &with_mg) > tm_nearest_neighbor(&seq, &low_salt));
+    }
+
+    #[test]
+    fn test_tm_nearest_neighbor_oligo_concentration() {
+        let seq = dna("ATGCGTACGTTAGCCATG");
+        let dilute = TmConditions {
+            oligo: 50.0,
+            ..TmConditions::default()
+        };
+        assert!(
+            tm_nearest_neighbor(&seq, &TmConditions::default())
+                > tm_nearest_neighbor(&seq, &dilute)
+        );
+    }
+
+    #[test]
+    fn test_sodium_equivalent_dntp_chelates_mg() {
+        let conditions = TmConditions {
+            monovalent: 50.0,
+            magnesium: 1.0,
+            dntp: 2.0,
+            ..TmConditions::default()
+        };
+        assert!((conditions.sodium_equivalent() - 0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_three_prime_dimer() {
+        // The 3' end GAATTC of the first oligo anneals to the second
+        let a = dna("TTTTTTGAATTC");
+        let b = dna("GAATTCAAAAAA");
+        assert!(three_prime_dimer_dg(&a, &b) < 0.0);
+        // No 3' complementarity if the complementary stretch is at the 5' end
+        let c = dna("GAATTCTTTTTT");
+        assert_eq!(three_prime_dimer_dg(&c, &dna("CCCCCCCCCCCC")), 0.0);
+    }
+
+    #[test]
+    fn test_hairpin_loop_too_small() {
+        // Stem GCG/CGC with a loop of two bases cannot fold
+        assert_eq!(hairpin_dg(&dna("GCGAACGC")), 0.0);
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+
+use super::{AlignmentStep, Pileups};
+
+/// Fewest reads spanning a position to call variants at it
+pub const DEFAULT_MIN_DEPTH: u32 = 5;
+
+/// Smallest fraction of spanning reads supporting a called allele
+pub const DEFAULT_MIN_FREQUENCY: f64 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VariantKind {
+    Snv,
+    Insertion,
+    Deletion,
+}
+
+/// A difference between a sample and a reference, with VCF-style alleles
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub chrom: String,
+    /// 0-based position of the first base of the reference allele
+    pub position: usize,
+    pub reference: String,
+    pub alternate: String,
+    pub kind: VariantKind,
+    /// Reads spanning the variant
+    pub depth: u32,
+    /// Reads supporting the alternate allele
+    pub count: u32,
+    pub frequency: f64,
+}
+
+impl std::fmt::Display for VariantKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            VariantKind::Snv => write!(f, "snv"),
+            VariantKind::Insertion => write!(f, "ins"),
+            VariantKind::Deletion => write!(f, "del"),
+        }
+    }
+}
+
+/// Shift an indel of `bases`, deleted from or inserted before `start`,
+/// to its leftmost equivalent position in the reference.
+fn left_normalize(reference: &[u8], mut start: usize, bases: &[u8]) -> (usize, Vec<u8>) {
+    let mut bases = bases.to_vec();
+    while start > 0 && bases.last() == Some(&reference[start - 1]) {
+        bases.rotate_right(1);
+        start -= 1;
+    }
+    (start, bases)
+}
+
+/// VCF position and alleles of a left-normalized indel, anchored on the preceding base,
+/// or the following one at the start of the reference.
+fn indel_alleles(
+    reference: &[u8],
+    kind: VariantKind,
+    start: usize,
+    bases: &[u8],
+) -> Option<(usize, String, String)> {
+    let (start, bases) = left_normalize(reference, start, bases);
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).to_string();
+    let deleted = match kind {
+        VariantKind::Deletion => bases.len(),
+        _ => 0,
+    };
+    let (position, anchor, before) = match start {
+        0 => (0, *reference.get(deleted)?, false),
+        start => (start - 1, reference[start - 1], true),
+    };
+    let with_anchor = |bases: &[u8]| match before {
+        true => [&[anchor], bases].concat(),
+        false => [bases, &[anchor]].concat(),
+    };
+    match kind {
+        VariantKind::Deletion => Some((position, text(&with_anchor(&bases)), text(&[anchor]))),
+        _ => Some((position, text(&[anchor]), text(&with_anchor(&bases)))),
+    }
+}
+
+fn variant(
+    chrom: &str,
+    (position, reference, alternate): (usize, String, String),
+    kind: VariantKind,
+    depth: u32,
+    count: u32,
+) -> Variant {
+    Variant {
+        chrom: chrom.to_string(),
+        position,
+        reference,
+        alternate,
+        kind,
+        depth,
+        count,
+        frequency: match depth {
+            0 => 0.0,
+            depth => count as f64 / depth as f64,
+        },
+    }
+}
+
+/// Differences of a sample sequence to a reference sequence, from the steps of their
+/// pairwise alignment. Indels are left-normalized and anchored as in VCF.
+pub fn diff_alignment(
+    chrom: &str,
+    reference: &[u8],
+    sample: &[u8],
+    steps: &[AlignmentStep],
+) -> Vec<Variant> {
+    let mut variants = Vec::new();
+    // Next reference position, and the indel in progress
+    let mut next = 0;
+    let mut indel: Option<(VariantKind, usize, Vec<u8>)> = None;
+    let flush = |indel: &mut Option<(VariantKind, usize, Vec<u8>)>, variants: &mut Vec<Variant>| {
+        if let Some((kind, start, bases)) = indel.take() {
+            if let Some(alleles) = indel_alleles(reference, kind, start, &bases) {
+                variants.push(variant(chrom, alleles, kind, 1, 1));
+            }
+        }
+    };
+    for step in steps.iter().copied() {
+        let (kind, base) = match step {
+            AlignmentStep::Align { x, y } => {
+                flush(&mut indel, &mut variants);
+                next = x + 1;
+                if reference[x] != sample[y] {
+                    let alleles = (
+                        x,
+                        (reference[x] as char).to_string(),
+                        (sample[y] as char).to_string(),
+                    );
+                    variants.push(variant(chrom, alleles, VariantKind::Snv, 1, 1));
+                }
+                continue;
+            }
+            AlignmentStep::Delete { x } => (VariantKind::Deletion, reference[x]),
+            AlignmentStep::Insert { y } => (VariantKind::Insertion, sample[y]),
+        };
+        match indel.as_mut() {
+            Some((current, _, bases)) if *current == kind => bases.push(base),
+            _ => {
+                flush(&mut indel, &mut variants);
+                indel = Some((kind, next, vec![base]));
+            }
+        }
+        if kind == VariantKind::Deletion {
+            next += 1;
+        }
+    }
+    flush(&mut indel, &mut variants);
+    variants.sort_by_key(|v| (v.position, v.kind));
+    variants
+}
+
+/// Call SNVs and left-normalized indels from pileups against the records of their reference
+pub fn call_pileups(
+    pileups: &Pileups,
+    references: &[(String, String)],
+    min_depth: u32,
+    min_frequency: f64,
+) -> anyhow::Result<Vec<Variant>> {
+    let mut variants = Vec::new();
+    for pileup in pileups.references.iter() {
+        let Some((_, reference)) = references.iter().find(|(name, _)| *name == pileup.name) else {
+            bail!("no reference sequence for '{}'", pileup.name);
+        };
+        let reference = reference.as_bytes();
+
+        // Allele counts and largest depth by normalized position and alleles,
+        // as reads may place the same indel differently
+        let mut alleles: BTreeMap<(usize, String, String), (VariantKind, u32, u32)> =
+            BTreeMap::new();
+        for (position, column) in pileup.columns.iter().enumerate() {
+            let Some(&base) = reference.get(position) else {
+                break;
+            };
+            let spanning = column.spanning();
+            for (i, alternate) in b"ACGT".iter().enumerate() {
+                if *alternate != base && column.bases[i] > 0 && base != b'N' {
+                    let key = (
+                        position,
+                        (base as char).to_string(),
+                        (*alternate as char).to_string(),
+                    );
+                    alleles.insert(key, (VariantKind::Snv, column.bases[i], spanning));
+                }
+            }
+            let mut add = |kind, start, bases: &[u8], count| {
+                if let Some(key) = indel_alleles(reference, kind, start, bases) {
+                    let entry = alleles.entry(key).or_insert((kind, 0, 0));
+                    entry.1 += count;
+                    entry.2 = entry.2.max(spanning);
+                }
+            };
+            for (bases, count) in column.inserted.iter() {
+                add(VariantKind::Insertion, position + 1, bases, *count);
+            }
+            for (len, count) in column.deleted.iter() {
+                if let Some(bases) = reference.get(position..position + len) {
+                    add(VariantKind::Deletion, position, bases, *count);
+                }
+            }
+        }
+
+        variants.extend(
+            alleles
+                .into_iter()
+                .map(|(alleles, (kind, count, depth))| {
+                    variant(&pileup.name, alleles, kind, depth.max(count), count)
+                })
+                .filter(|v| v.depth >= min_depth && v.frequency >= min_frequency),
+        );
+    }
+    Ok(variants)
+}
+
+/// Variants in VCF 4.3 format, with contigs for the given records and their lengths
+/// and the program that called them as source
+pub fn to_vcf(variants: &[Variant], contigs: &[(String, usize)], source: &str) -> String {
+    let mut vcf = String::from("##fileformat=VCFv4.3\n");
+    vcf += &format!("##source={source}\n");
+    for (name, len) in contigs.iter() {
+        vcf += &format!("##contig=<ID={name},length={len}>\n");
+    }
+    vcf += concat!(
+        "##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Reads spanning the variant\">\n",
+        "##INFO=<ID=AO,Number=A,Type=Integer,Description=\"Reads supporting the alternate allele\">\n",
+        "##INFO=<ID=AF,Number=A,Type=Float,Description=\"Alternate allele frequency\">\n",
+        "##INFO=<ID=TYPE,Number=A,Type=String,Description=\"Variant type: snv, ins or del\">\n",
+        "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n",
+    );
+    for v in variants.iter() {
+        vcf += &format!(
+            "{}\t{}\t.\t{}\t{}\t.\tPASS\tDP={};AO={};AF={:.3};TYPE={}\n",
+            v.chrom,
+            v.position + 1,
+            v.reference,
+            v.alternate,
+            v.depth,
+            v.count,
+            v.frequency,
+            v.kind
+        );
+    }
+    vcf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call_pileups, diff_alignment, to_vcf, Variant, VariantKind};
+    use crate::ana::{AlignmentStep, Pileups};
+
+    /// VCF records of variants called from five copies of a read aligned at the first base
+    fn call(reference: &str, cigar: &str, read: &str) -> Vec<String> {
+        let sam = format!("@SQ\tSN:chr\tLN:{}\n", reference.len())
+            + &format!("r\t0\tchr\t1\t60\t{cigar}\t*\t0\t0\t{read}\t*\n").repeat(5);
+        let pileups = Pileups::from_sam(&sam).unwrap();
+        let references = [("chr".to_string(), reference.to_string())];
+        records(&call_pileups(&pileups, &references, 5, 0.2).unwrap())
+    }
+
+    fn records(variants: &[Variant]) -> Vec<String> {
+        to_vcf(variants, &[], "test")
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_call_snv() {
+        let calls = call("ACGTTTTTGCATGCCA", "16M", "ACGTTTTTGCATACCA");
+        assert_eq!(
+            calls,
+            ["chr\t13\t.\tG\tA\t.\tPASS\tDP=5;AO=5;AF=1.000;TYPE=snv"]
+        );
+    }
+
+    #[test]
+    fn test_call_insertion() {
+        // The inserted A follows the A at position 11 and is shifted before it
+        let calls = call("ACGTTTTTGCATGCCA", "11M1I5M", "ACGTTTTTGCAATGCCA");
+        assert_eq!(
+            calls,
+            ["chr\t10\t.\tC\tCA\t.\tPASS\tDP=5;AO=5;AF=1.000;TYPE=ins"]
+        );
+    }
+
+    #[test]
+    fn test_call_deletion_in_homopolymer() {
+        // Deleting the last T of the run is the same as deleting the first one
+        let calls = call("ACGTTTTTGCATGCCA", "6M1D9M", "ACGTTTTGCATGCCA");
+        assert_eq!(
+            calls,
+            ["chr\t3\t.\tGT\tG\t.\tPASS\tDP=5;AO=5;AF=1.000;TYPE=del"]
+        );
+        // Reads placing the deletion differently support the same allele
+        let sam = "@SQ\tSN:chr\tLN:16\n".to_string()
+            + &"r\t0\tchr\t1\t60\t4M1D11M\t*\t0\t0\tACGTTTTGCATGCCA\t*\n".repeat(3)
+            + &"r\t0\tchr\t1\t60\t7M1D8M\t*\t0\t0\tACGTTTTGCATGCCA\t*\n".repeat(2);
+        let pileups = Pileups::from_sam(&sam).unwrap();
+        let references = [("chr".to_string(), "ACGTTTTTGCATGCCA".to_string())];
+        let variants = call_pileups(&pileups, &references, 5, 0.2).unwrap();
+        assert_eq!(
+            records(&variants),
+            ["chr\t3\t.\tGT\tG\t.\tPASS\tDP=5;AO=5;AF=1.000;TYPE=del"]
+        );
+    }
+
+    #[test]
+    fn test_call_variants_at_the_start() {
+        // A deletion at the first base is anchored on the following one
+        let calls = call("TTGCATGCCAGT", "1D11M", "TGCATGCCAGT");
+        assert_eq!(
+            calls,
+            ["chr\t1\t.\tTT\tT\t.\tPASS\tDP=5;AO=5;AF=1.000;TYPE=del"]
+        );
+        let calls = call("TTGCATGCCAGT", "12M", "GTGCATGCCAGT");
+        assert_eq!(
+            calls,
+            ["chr\t1\t.\tT\tG\t.\tPASS\tDP=5;AO=5;AF=1.000;TYPE=snv"]
+        );
+    }
+
+    #[test]
+    fn test_call_filters_and_unknown_references() {
+        let references = [("chr".to_string(), "ACGTTTTTGCATGCCA".to_string())];
+        let sam = "@SQ\tSN:chr\tLN:16\n".to_string()
+            + "r\t0\tchr\t1\t60\t16M\t*\t0\t0\tACGTTTTTGCATACCA\t*\n"
+            + &"r\t0\tchr\t1\t60\t16M\t*\t0\t0\tACGTTTTTGCATGCCA\t*\n".repeat(9);
+        let pileups = Pileups::from_sam(&sam).unwrap();
+        assert!(call_pileups(&pileups, &references, 5, 0.2)
+            .unwrap()
+            .is_empty());
+        let variants = call_pileups(&pileups, &references, 5, 0.1).unwrap();
+        assert_eq!(
+            records(&variants),
+            ["chr\t13\t.\tG\tA\t.\tPASS\tDP=10;AO=1;AF=0.100;TYPE=snv"]
+        );
+        assert!(call_pileups(&pileups, &[], 5, 0.2).is_err());
+    }
+
+    #[test]
+    fn test_diff_alignment() {
+        use AlignmentStep::*;
+
+        // GTGCT against TGCA: an insertion before the first base and a mismatch at the last
+        let steps = [
+            Insert { y: 0 },
+            Align { x: 0, y: 1 },
+            Align { x: 1, y: 2 },
+            Align { x: 2, y: 3 },
+            Align { x: 3, y: 4 },
+        ];
+        let variants = diff_alignment("seq", b"TGCA", b"GTGCT", &steps);
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0].kind, VariantKind::Insertion);
+        assert_eq!(
+            records(&variants),
+            [
+                "seq\t1\t.\tT\tGT\t.\tPASS\tDP=1;AO=1;AF=1.000;TYPE=ins",
+                "seq\t4\t.\tA\tT\t.\tPASS\tDP=1;AO=1;AF=1.000;TYPE=snv",
+            ]
+        );
+
+        // AACCA against AACCCA: a deletion of the last C shifted onto the first
+        let steps = [
+            Align { x: 0, y: 0 },
+            Align { x: 1, y: 1 },
+            Align { x: 2, y: 2 },
+            Align { x: 3, y: 3 },
+            Delete { x: 4 },
+            Align { x: 5, y: 4 },
+        ];
+        let variants = diff_alignment("seq", b"AACCCA", b"AACCA", &steps);
+        assert_eq!(
+            records(&variants),
+            ["seq\t2\t.\tAC\tA\t.\tPASS\tDP=1;AO=1;AF=1.000;TYPE=del"]
+        );
+    }
+
+    #[test]
+    fn test_vcf_header() {
+        let vcf = to_vcf(&[], &[("chr".to_string(), 16)], "test-1.0");
+        let lines = vcf.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[..3],
+            [
+                "##fileformat=VCFv4.3",
+                "##source=test-1.0",
+                "##contig=<ID=chr,length=16>"
+            ]
+        );
+        assert!(lines
+            .last()
+            .unwrap()
+            .starts_with("#CHROM\tPOS\tID\tREF\tALT"));
+    }
+}
@@ -63,6 +63,37 @@ impl Eaa {
     pub fn is_eaa(c: &char) -> bool {
         Self::all_as_str().contains(*c)
     }
+
+    /// Three letter code as used in HGVS protein notation, e.g. `Ter` for stop.
+    pub fn to_three_letter(&self) -> &'static str {
+        use self::Eaa::*;
+        match self {
+            Ala => "Ala",
+            Any => "Xaa",
+            Arg => "Arg",
+            Asn => "Asn",
+            Asp => "Asp",
+            Asx => "Asx",
+            Cys => "Cys",
+            Gap => "-",
+            Gln => "Gln",
+            Glu => "Glu",
+            Gly => "Gly",
+            His => "His",
+            Ile => "Ile",
+            Leu => "Leu",
+            Lys => "Lys",
+            Met => "Met",
+            Phe => "Phe",
+            Pro => "Pro",
+            Ser => "Ser",
+            Ter => "Ter",
+            Thr => "Thr",
+            Trp => "Trp",
+            Tyr => "Tyr",
+            Val => "Val",
+        }
+    }
 }
 
 impl TryFromLetter for Eaa {
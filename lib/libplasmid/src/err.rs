@@ -42,6 +42,11 @@ pub enum PlasmidError {
     InvalidAminoAcid {
         char: char,
     },
+    InvalidRegion {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
 }
 
 impl std::fmt::Display for PlasmidError {
@@ -69,6 +74,11 @@ impl std::fmt::Display for PlasmidError {
                 nucleotide_type.allowed_letters()
             ),
             InvalidAminoAcid { char } => write!(f, "Invalid amino acid: {}", char),
+            InvalidRegion { start, end, len } => write!(
+                f,
+                "Invalid region: {}..{}. Should lie within a sequence of length {}",
+                start, end, len
+            ),
         }
     }
 }
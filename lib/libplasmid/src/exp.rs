@@ -1,5 +1,13 @@
+pub mod coverage_plot;
 pub mod export;
+pub mod gc_plot;
+pub mod gel;
 pub mod svg;
+pub mod trace_plot;
 
+pub use self::coverage_plot::*;
 pub use self::export::*;
+pub use self::gc_plot::*;
+pub use self::gel::*;
 pub use self::svg::*;
+pub use self::trace_plot::*;
@@ -0,0 +1,153 @@
+use super::{xml_escape, Export};
+
+/// Most columns drawn in a coverage track, longer references are binned.
+const MAX_COVERAGE_BINS: usize = 700;
+
+/// Linear track of per-base read depth along a reference.
+pub struct CoverageTrackExport {
+    pub name: String,
+    pub depth: Vec<u32>,
+}
+
+impl CoverageTrackExport {
+    pub fn new(name: &str, depth: &[u32]) -> Self {
+        CoverageTrackExport {
+            name: name.to_string(),
+            depth: depth.to_vec(),
+        }
+    }
+
+    /// Mean depth of consecutive bins, at most `MAX_COVERAGE_BINS` of them.
+    fn bins(&self) -> Vec<f32> {
+        let size = self.depth.len().div_ceil(MAX_COVERAGE_BINS).max(1);
+        self.depth
+            .chunks(size)
+            .map(|bin| bin.iter().map(|d| *d as f32).sum::<f32>() / bin.len() as f32)
+            .collect()
+    }
+
+    fn export_coverage_track(&self) -> String {
+        let len = self.depth.len();
+        let bins = self.bins();
+
+        // Basic parameters
+        let w = 800_f32; // width
+        let h = 240_f32; // height
+        let margin = 50_f32;
+        let track_w = w - margin * 2_f32;
+        let track_h = h - margin * 2_f32;
+        let baseline = margin + track_h;
+
+        let max = self.depth.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let mean = match len {
+            0 => 0_f32,
+            _ => self.depth.iter().map(|d| *d as f32).sum::<f32>() / len as f32,
+        };
+        let scale = |depth: f32| baseline - depth / max * track_h;
+
+        // Step outline of the binned depth, closed along the baseline
+        let bin_w = track_w / bins.len().max(1) as f32;
+        let mut points = vec![format!("{},{}", margin, baseline)];
+        for (i, depth) in bins.iter().enumerate() {
+            let x = margin + i as f32 * bin_w;
+            points.push(format!("{},{}", x, scale(*depth)));
+            points.push(format!("{},{}", x + bin_w, scale(*depth)));
+        }
+        points.push(format!(
+            "{},{}",
+            margin + bins.len() as f32 * bin_w,
+            baseline
+        ));
+
+        let track = format!(
+            concat!(
+                r###"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="none" stroke="#999" />"###,
+                r###"<polygon points="{points}" fill="hsla(210, 60%, 55%, 0.8)" stroke="hsla(210, 60%, 35%, 1)" stroke-width="0.5" />"###,
+                r###"<line x1="{x}" y1="{mean_y}" x2="{x2}" y2="{mean_y}" stroke="#999" stroke-dasharray="4 4" />"###,
+                r###"<text x="{x}" y="{title_y}" font-family="sans-serif" font-size="14">Coverage of {name} (mean {mean:.1}x)</text>"###,
+                r###"<text x="{label_x}" y="{max_y}" font-family="sans-serif" font-size="10" text-anchor="end">{max}</text>"###,
+                r###"<text x="{label_x}" y="{baseline}" font-family="sans-serif" font-size="10" text-anchor="end">0</text>"###,
+            ),
+            x = margin,
+            y = margin,
+            w = track_w,
+            h = track_h,
+            x2 = margin + track_w,
+            points = points.join(" "),
+            mean_y = scale(mean),
+            title_y = margin - 6_f32,
+            name = xml_escape(&self.name),
+            mean = mean,
+            label_x = margin - 4_f32,
+            max_y = margin + 10_f32,
+            max = max,
+            baseline = baseline,
+        );
+
+        let axis = format!(
+            concat!(
+                r###"<text x="{x1}" y="{y}" font-family="sans-serif" font-size="10">1</text>"###,
+                r###"<text x="{x2}" y="{y}" font-family="sans-serif" font-size="10" text-anchor="end">{len} bp</text>"###,
+            ),
+            x1 = margin,
+            x2 = margin + track_w,
+            y = h - margin / 2_f32,
+            len = len,
+        );
+
+        // Assemble final svg
+        format!(
+            r###"<svg width="{width}" height="{height}" viewBox="0 0 {width} {height}"><rect x="0" y="0" width="{width}" height="{height}" fill="white" />{track}{axis}</svg>"###,
+            width = w,
+            height = h,
+            track = track,
+            axis = axis,
+        )
+    }
+}
+
+impl Export for CoverageTrackExport {
+    type Output = String;
+
+    fn export(&self) -> String {
+        self.export_coverage_track()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoverageTrackExport, MAX_COVERAGE_BINS};
+    use crate::exp::Export;
+
+    #[test]
+    fn test_coverage_bins() {
+        let track = CoverageTrackExport::new("short", &[0, 2, 4]);
+        assert_eq!(track.bins(), [0.0, 2.0, 4.0]);
+
+        let track = CoverageTrackExport::new("long", &vec![3; MAX_COVERAGE_BINS * 2 + 1]);
+        let bins = track.bins();
+        assert!(bins.len() <= MAX_COVERAGE_BINS);
+        assert!(bins.iter().all(|depth| *depth == 3.0));
+    }
+
+    #[test]
+    fn test_coverage_track_export() {
+        let track = CoverageTrackExport::new("pUC19", &[0, 1, 2, 2, 1]).export();
+        assert!(track.starts_with("<svg"));
+        assert_eq!(track.matches("<polygon").count(), 1);
+        assert!(track.contains("Coverage of pUC19 (mean 1.2x)"));
+        assert!(track.contains("5 bp"));
+    }
+
+    #[test]
+    fn test_coverage_track_escapes_name() {
+        let track = CoverageTrackExport::new("chr<1>&", &[1]).export();
+        assert!(track.contains("Coverage of chr&lt;1&gt;&amp; (mean"));
+    }
+
+    #[test]
+    fn test_coverage_track_empty() {
+        let track = CoverageTrackExport::new("empty", &[]).export();
+        assert!(track.contains("0 bp"));
+    }
+}
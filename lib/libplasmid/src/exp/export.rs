@@ -1,5 +1,35 @@
+use std::borrow::Cow;
+
 pub trait Export {
     type Output;
 
     fn export(&self) -> Self::Output;
 }
+
+/// Escape text interpolated into XML, e.g. feature names in SVG `<text>` and `<title>` elements
+/// or attribute values.
+///
+/// # Example
+/// ```rust
+/// use plasmid::exp::xml_escape;
+///
+/// assert_eq!(xml_escape("lacZ"), "lacZ");
+/// assert_eq!(xml_escape("A&B <\"x\">"), "A&amp;B &lt;&quot;x&quot;&gt;");
+/// ```
+pub fn xml_escape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
@@ -0,0 +1,213 @@
+use crate::{ana::GcWindows, traits::ToIupac, uni::IupacNucleotide};
+
+use super::Export;
+
+/// Pick a window size that yields roughly a hundred points for a sequence.
+pub fn default_gc_window(len: usize) -> usize {
+    (len / 100).max(1)
+}
+
+/// Running sum of the GC skew of consecutive windows.
+///
+/// The minimum of the cumulative skew usually marks the origin of replication
+/// of a circular genome, the maximum its terminus.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::exp::cumulative_gc_skew;
+///
+/// assert_eq!(cumulative_gc_skew(&[1.0, -0.5, 0.25]), [1.0, 0.5, 0.75]);
+/// ```
+pub fn cumulative_gc_skew(gc_skew: &[f32]) -> Vec<f32> {
+    gc_skew
+        .iter()
+        .scan(0_f32, |sum, skew| {
+            *sum += skew;
+            Some(*sum)
+        })
+        .collect()
+}
+
+/// Linear chart of sliding-window GC content and cumulative GC skew.
+pub struct GcPlotExport {
+    pub window: usize,
+    pub sequence: Vec<IupacNucleotide>,
+}
+
+impl GcPlotExport {
+    pub fn new<T>(window: usize, sequence: &[T]) -> Self
+    where
+        T: ToIupac,
+    {
+        GcPlotExport {
+            window,
+            sequence: sequence.iter().map(|n| n.to_iupac()).collect(),
+        }
+    }
+
+    /// Build an SVG polyline through `values`, scaled into the given box.
+    fn polyline(
+        positions: &[f32],
+        values: &[f32],
+        (x, y, w, h): (f32, f32, f32, f32),
+        (min, max): (f32, f32),
+        color: &str,
+    ) -> String {
+        let range = if max - min <= f32::EPSILON {
+            1_f32
+        } else {
+            max - min
+        };
+        let points = positions
+            .iter()
+            .zip(values)
+            .map(|(p, v)| format!("{},{}", x + p * w, y + h - (v - min) / range * h))
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            r###"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="1.5" />"###,
+            points = points,
+            color = color,
+        )
+    }
+
+    /// Build a labelled panel frame with a dashed reference line at `reference`.
+    fn panel(
+        title: &str,
+        (x, y, w, h): (f32, f32, f32, f32),
+        (min, max): (f32, f32),
+        reference: f32,
+    ) -> String {
+        let range = if max - min <= f32::EPSILON {
+            1_f32
+        } else {
+            max - min
+        };
+        let ref_y = y + h - (reference - min) / range * h;
+        format!(
+            concat!(
+                r###"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="none" stroke="#999" />"###,
+                r###"<line x1="{x}" y1="{ref_y}" x2="{x2}" y2="{ref_y}" stroke="#999" stroke-dasharray="4 4" />"###,
+                r###"<text x="{x}" y="{title_y}" font-family="sans-serif" font-size="14">{title}</text>"###,
+                r###"<text x="{label_x}" y="{max_y}" font-family="sans-serif" font-size="10" text-anchor="end">{max:.2}</text>"###,
+                r###"<text x="{label_x}" y="{min_y}" font-family="sans-serif" font-size="10" text-anchor="end">{min:.2}</text>"###,
+            ),
+            x = x,
+            y = y,
+            w = w,
+            h = h,
+            x2 = x + w,
+            ref_y = ref_y,
+            title_y = y - 6_f32,
+            title = title,
+            label_x = x - 4_f32,
+            max_y = y + 10_f32,
+            min_y = y + h,
+            max = max,
+            min = min,
+        )
+    }
+
+    fn export_gc_plot(&self) -> String {
+        let len = self.sequence.len();
+        let windows = GcWindows::compute(&self.sequence, self.window, self.window);
+        let cumulative_skew = cumulative_gc_skew(&windows.gc_skew);
+
+        // Basic parameters
+        let w = 800_f32; // width
+        let h = 400_f32; // height
+        let margin = 50_f32;
+        let panel_w = w - margin * 2_f32;
+        let panel_h = (h - margin * 3_f32) / 2_f32;
+        let gc_box = (margin, margin, panel_w, panel_h);
+        let skew_box = (margin, margin * 2_f32 + panel_h, panel_w, panel_h);
+
+        // Relative position of every window center
+        let positions = windows
+            .positions
+            .iter()
+            .map(|p| (*p as f32 + self.window as f32 / 2_f32) / len.max(1) as f32)
+            .collect::<Vec<_>>();
+
+        let mean_gc = if windows.gc_content.is_empty() {
+            0_f32
+        } else {
+            windows.gc_content.iter().sum::<f32>() / windows.gc_content.len() as f32
+        };
+        let bounds = |values: &[f32]| {
+            values
+                .iter()
+                .fold((0_f32, 0_f32), |(min, max), v| (min.min(*v), max.max(*v)))
+        };
+        let gc_bounds = (0_f32, 1_f32);
+        let skew_bounds = bounds(&cumulative_skew);
+
+        let axis = format!(
+            concat!(
+                r###"<text x="{x1}" y="{y}" font-family="sans-serif" font-size="10">1</text>"###,
+                r###"<text x="{x2}" y="{y}" font-family="sans-serif" font-size="10" text-anchor="end">{len} bp</text>"###,
+            ),
+            x1 = margin,
+            x2 = margin + panel_w,
+            y = h - margin / 2_f32,
+            len = len,
+        );
+
+        // Assemble final svg
+        format!(
+            r###"<svg width="{width}" height="{height}" viewBox="0 0 {width} {height}"><rect x="0" y="0" width="{width}" height="{height}" fill="white" />{gc_panel}{gc_line}{skew_panel}{skew_line}{axis}</svg>"###,
+            width = w,
+            height = h,
+            gc_panel = Self::panel("GC content", gc_box, gc_bounds, mean_gc),
+            gc_line = Self::polyline(
+                &positions,
+                &windows.gc_content,
+                gc_box,
+                gc_bounds,
+                "hsla(0, 0%, 20%, 1)"
+            ),
+            skew_panel = Self::panel("Cumulative GC skew", skew_box, skew_bounds, 0_f32),
+            skew_line = Self::polyline(
+                &positions,
+                &cumulative_skew,
+                skew_box,
+                skew_bounds,
+                "hsla(124, 50%, 40%, 1)"
+            ),
+            axis = axis,
+        )
+    }
+}
+
+impl Export for GcPlotExport {
+    type Output = String;
+
+    fn export(&self) -> String {
+        self.export_gc_plot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cumulative_gc_skew, default_gc_window, GcPlotExport};
+    use crate::{exp::Export, uni::IupacNucleotide::*};
+
+    #[test]
+    fn test_default_gc_window() {
+        assert_eq!(default_gc_window(0), 1);
+        assert_eq!(default_gc_window(5000), 50);
+    }
+
+    #[test]
+    fn test_cumulative_gc_skew_empty() {
+        assert!(cumulative_gc_skew(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_gc_plot_export() {
+        let plot = GcPlotExport::new(2, &[G, G, A, T, C, C]).export();
+        assert!(plot.starts_with("<svg"));
+        assert_eq!(plot.matches("<polyline").count(), 2);
+        assert!(plot.contains("6 bp"));
+    }
+}
@@ -0,0 +1,204 @@
+use super::{xml_escape, Export};
+
+/// DNA size standard loaded next to the samples of a gel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GelLadder {
+    /// 1 kb ladder, 500 bp to 10 kb
+    #[default]
+    OneKb,
+    /// 1 kb plus ladder, 100 bp to 10 kb
+    OneKbPlus,
+    /// 100 bp ladder, 100 bp to 1.5 kb
+    HundredBp,
+}
+
+impl GelLadder {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GelLadder::OneKb => "1 kb",
+            GelLadder::OneKbPlus => "1 kb Plus",
+            GelLadder::HundredBp => "100 bp",
+        }
+    }
+
+    /// Band sizes of the ladder in bp, largest first.
+    pub fn sizes(&self) -> &'static [usize] {
+        match self {
+            GelLadder::OneKb => &[10000, 8000, 6000, 5000, 4000, 3000, 2000, 1500, 1000, 500],
+            GelLadder::OneKbPlus => &[
+                10000, 8000, 6000, 5000, 4000, 3000, 2000, 1500, 1200, 1000, 900, 800, 700, 600,
+                500, 400, 300, 200, 100,
+            ],
+            GelLadder::HundredBp => &[
+                1517, 1200, 1000, 900, 800, 700, 600, 500, 400, 300, 200, 100,
+            ],
+        }
+    }
+}
+
+/// A sample lane of a gel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GelLane {
+    pub label: String,
+    /// Fragment sizes in bp
+    pub fragments: Vec<usize>,
+}
+
+impl GelLane {
+    pub fn new<T>(label: T, fragments: &[usize]) -> Self
+    where
+        T: AsRef<str>,
+    {
+        Self {
+            label: label.as_ref().to_string(),
+            fragments: fragments.to_vec(),
+        }
+    }
+}
+
+/// Simulated agarose gel of a size ladder followed by sample lanes.
+///
+/// Bands migrate log-linearly with their size, their brightness scales with
+/// the mass of DNA, i.e. size times the number of comigrating fragments.
+pub struct GelExport {
+    pub ladder: GelLadder,
+    pub lanes: Vec<GelLane>,
+}
+
+impl GelExport {
+    pub fn new(ladder: GelLadder, lanes: Vec<GelLane>) -> Self {
+        Self { ladder, lanes }
+    }
+
+    /// Vertical band position of a fragment size, between `top` and `top + height`.
+    fn migration(&self, size: usize, top: f32, height: f32) -> f32 {
+        let sizes = self.ladder.sizes();
+        let max = (sizes[0] as f32 * 1.5).log10();
+        let min = (*sizes.last().unwrap() as f32 / 1.5).log10();
+        let size = (size.max(1) as f32).log10().clamp(min, max);
+        top + (max - size) / (max - min) * height
+    }
+
+    fn band(&self, x: f32, y: f32, width: f32, opacity: f32) -> String {
+        format!(
+            r###"<rect x="{x}" y="{y}" width="{width}" height="4" rx="1.5" fill="white" fill-opacity="{opacity:.2}" />"###,
+            x = x,
+            y = y - 2_f32,
+            width = width,
+            opacity = opacity,
+        )
+    }
+
+    fn export_gel(&self) -> String {
+        // Basic parameters
+        let lane_w = 60_f32; // lane width
+        let gap = 20_f32; // space between lanes
+        let margin = 60_f32;
+        let top = 50_f32;
+        let height = 400_f32;
+        let lanes = self.lanes.len() + 1;
+        let w = margin * 2_f32 + lanes as f32 * lane_w + (lanes - 1) as f32 * gap;
+        let h = top + height + 30_f32;
+        let lane_x = |i: usize| margin + i as f32 * (lane_w + gap);
+
+        // Ladder lane with size labels
+        let mut elements = vec![format!(
+            r###"<text x="{x}" y="{y}" font-family="sans-serif" font-size="11" fill="white" text-anchor="middle">{label}</text>"###,
+            x = lane_x(0) + lane_w / 2_f32,
+            y = top - 20_f32,
+            label = self.ladder.name(),
+        )];
+        for size in self.ladder.sizes() {
+            let y = self.migration(*size, top, height);
+            elements.push(self.band(lane_x(0), y, lane_w, 0.8));
+            elements.push(format!(
+                r###"<text x="{x}" y="{y}" font-family="sans-serif" font-size="10" fill="white" text-anchor="end">{size}</text>"###,
+                x = lane_x(0) - 6_f32,
+                y = y + 3_f32,
+                size = size,
+            ));
+        }
+
+        // Sample lanes, band brightness is the mass of a band relative to the largest fragment
+        let max_size = self
+            .lanes
+            .iter()
+            .flat_map(|lane| lane.fragments.iter())
+            .max()
+            .copied()
+            .unwrap_or(1)
+            .max(1) as f32;
+        for (i, lane) in self.lanes.iter().enumerate() {
+            let x = lane_x(i + 1);
+            elements.push(format!(
+                r###"<text x="{x}" y="{y}" font-family="sans-serif" font-size="11" fill="white" text-anchor="middle">{label}</text>"###,
+                x = x + lane_w / 2_f32,
+                y = top - 20_f32,
+                label = xml_escape(&lane.label),
+            ));
+            let mut sizes = lane.fragments.clone();
+            sizes.sort_unstable();
+            sizes.dedup();
+            for size in sizes {
+                let copies = lane.fragments.iter().filter(|f| **f == size).count();
+                let mass = (size * copies) as f32 / max_size;
+                let y = self.migration(size, top, height);
+                elements.push(self.band(x, y, lane_w, 0.35 + 0.65 * mass.min(1_f32)));
+            }
+        }
+
+        // Wells
+        let wells = (0..lanes)
+            .map(|i| {
+                format!(
+                    r###"<rect x="{x}" y="{y}" width="{w}" height="6" fill="none" stroke="hsla(0, 0%, 60%, 1)" />"###,
+                    x = lane_x(i),
+                    y = top - 10_f32,
+                    w = lane_w,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Assemble final svg
+        format!(
+            r###"<svg width="{width}" height="{height}" viewBox="0 0 {width} {height}"><rect x="0" y="0" width="{width}" height="{height}" fill="hsla(0, 0%, 12%, 1)" />{wells}{elements}</svg>"###,
+            width = w,
+            height = h,
+            wells = wells.join(""),
+            elements = elements.join(""),
+        )
+    }
+}
+
+impl Export for GelExport {
+    type Output = String;
+
+    fn export(&self) -> String {
+        self.export_gel()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GelExport, GelLadder, GelLane};
+    use crate::exp::Export;
+
+    #[test]
+    fn test_gel_migration_order() {
+        let gel = GelExport::new(GelLadder::OneKb, vec![]);
+        let large = gel.migration(8000, 0_f32, 100_f32);
+        let small = gel.migration(500, 0_f32, 100_f32);
+        assert!(large < small);
+        assert!(gel.migration(1, 0_f32, 100_f32) <= 100_f32);
+    }
+
+    #[test]
+    fn test_gel_export() {
+        let lanes = vec![GelLane::new("EcoRI", &[3000, 1500, 1500])];
+        let svg = GelExport::new(GelLadder::HundredBp, lanes).export();
+        assert!(svg.starts_with("<svg"));
+        // One band per ladder size, two distinct sample bands
+        assert_eq!(svg.matches(r#"height="4""#).count(), 12 + 2);
+        assert!(svg.contains(">EcoRI</text>"));
+    }
+}
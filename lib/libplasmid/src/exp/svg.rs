@@ -1,8 +1,13 @@
 use std::collections::HashMap;
 
-use crate::{traits::ToIupac, uni::IupacNucleotide};
+use crate::{
+    ana::GcWindows,
+    seq::{Annotation, FeatureType, Strand},
+    traits::ToIupac,
+    uni::IupacNucleotide,
+};
 
-use super::Export;
+use super::{cumulative_gc_skew, xml_escape, Export};
 
 #[derive(Debug)]
 pub enum SvgRenderMode {
@@ -13,29 +18,56 @@ pub enum SvgRenderMode {
 #[derive(Debug)]
 pub struct SvgExportConfig {
     pub render_mode: SvgRenderMode,
+    /// Window size of the GC content/skew ring drawn inside circular maps
+    pub gc_window: Option<usize>,
 }
 
 impl SvgExportConfig {
     pub fn new(render_mode: SvgRenderMode) -> Self {
-        Self { render_mode }
+        Self {
+            render_mode,
+            gc_window: None,
+        }
     }
 
     pub fn circular() -> Self {
         Self {
             render_mode: SvgRenderMode::Circular,
+            gc_window: None,
         }
     }
 
     pub fn linear() -> Self {
         Self {
             render_mode: SvgRenderMode::Linear,
+            gc_window: None,
         }
     }
+
+    /// Draw GC content and cumulative GC skew as inner rings of a circular map.
+    pub fn with_gc_ring(mut self, window: usize) -> Self {
+        self.gc_window = Some(window);
+        self
+    }
+}
+
+/// How a feature is drawn on a map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeatureShape {
+    /// Thick bar, e.g. an origin of replication
+    Bar,
+    /// Thick bar pointing in the direction of the strand, e.g. a CDS
+    Arrow,
+    /// Thin bar pointing in the direction of the strand
+    Primer,
+    /// Thick bar closed by a perpendicular line at its 3' end
+    Terminator,
 }
 
 pub struct SvgExport {
     pub config: SvgExportConfig,
     pub sequence: Vec<IupacNucleotide>,
+    pub annotations: Vec<Annotation>,
 }
 
 impl SvgExport {
@@ -46,9 +78,16 @@ impl SvgExport {
         SvgExport {
             config,
             sequence: sequence.iter().map(|n| n.to_iupac()).collect(),
+            annotations: Vec::new(),
         }
     }
 
+    /// Draw the given annotations as a feature track of the map.
+    pub fn with_annotations(mut self, annotations: &[Annotation]) -> Self {
+        self.annotations.extend_from_slice(annotations);
+        self
+    }
+
     fn color_table() -> HashMap<IupacNucleotide, &'static str> {
         use IupacNucleotide::*;
         HashMap::from([
@@ -124,17 +163,479 @@ impl SvgExport {
             buf
         };
 
+        // Inner GC content/skew rings
+        let gc_ring = match self.config.gc_window {
+            Some(window) => self.gc_ring(window, cx, cy),
+            None => String::new(),
+        };
+
+        // Annotation arcs inside the sequence ring
+        let annotations = self.annotation_track(r - 38_f32, cx, cy);
+
         // Assemble final svg
         format!(
-            r###"<svg width="{width}" height="{height}" viewBox="0 0 {width} {height}">{segments}</svg>"###,
+            r###"<svg width="{width}" height="{height}" viewBox="0 0 {width} {height}">{segments}{gc_ring}{annotations}</svg>"###,
             width = w,
             height = h,
-            segments = segments.join("")
+            segments = segments.join(""),
+            gc_ring = gc_ring,
+            annotations = annotations,
+        )
+    }
+
+    /// Assign every annotation to the first lane where it does not overlap another one.
+    /// Annotations spanning the origin occupy both ends of their lane.
+    fn annotation_lanes(&self) -> Vec<usize> {
+        let len = self.sequence.len();
+        let mut order = (0..self.annotations.len()).collect::<Vec<_>>();
+        order.sort_by_key(|i| self.annotations[*i].start);
+        let mut lane_spans = Vec::<Vec<(usize, usize)>>::new();
+        let mut lanes = vec![0; self.annotations.len()];
+        for i in order {
+            let ann = &self.annotations[i];
+            let spans = if ann.wraps_origin() {
+                vec![(ann.start, len.max(ann.start)), (0, ann.end)]
+            } else {
+                vec![(ann.start, ann.end)]
+            };
+            let overlaps = |taken: &Vec<(usize, usize)>| {
+                taken
+                    .iter()
+                    .any(|(s, e)| spans.iter().any(|(start, end)| start <= e && s <= end))
+            };
+            let lane = match lane_spans.iter().position(|taken| !overlaps(taken)) {
+                Some(lane) => lane,
+                None => {
+                    lane_spans.push(Vec::new());
+                    lane_spans.len() - 1
+                }
+            };
+            lane_spans[lane].extend(spans);
+            lanes[i] = lane;
+        }
+        lanes
+    }
+
+    /// Color and shape of a feature, `i` picks a fallback color for untyped ones.
+    /// A color given by the annotation takes precedence.
+    fn feature_style(ann: &Annotation, i: usize) -> (String, FeatureShape) {
+        let palette = [
+            "hsla(205, 70%, 45%, 1)",
+            "hsla(28, 80%, 50%, 1)",
+            "hsla(280, 45%, 50%, 1)",
+            "hsla(170, 60%, 35%, 1)",
+            "hsla(340, 65%, 50%, 1)",
+        ];
+        let (color, shape) = match ann.kind {
+            FeatureType::Cds => ("hsla(48, 85%, 50%, 1)", FeatureShape::Arrow),
+            FeatureType::Gene | FeatureType::MRna => {
+                ("hsla(205, 70%, 45%, 1)", FeatureShape::Arrow)
+            }
+            FeatureType::Promoter => ("hsla(124, 50%, 40%, 1)", FeatureShape::Arrow),
+            FeatureType::Terminator => ("hsla(0, 65%, 45%, 1)", FeatureShape::Terminator),
+            FeatureType::RepOrigin => ("hsla(28, 80%, 50%, 1)", FeatureShape::Bar),
+            FeatureType::PrimerBind => ("hsla(280, 45%, 50%, 1)", FeatureShape::Primer),
+            FeatureType::Rbs | FeatureType::ProteinBind => {
+                ("hsla(170, 60%, 35%, 1)", FeatureShape::Bar)
+            }
+            _ => (palette[i % palette.len()], FeatureShape::Bar),
+        };
+        // Direction is unknown for unstranded features
+        let shape = match (shape, ann.strand) {
+            (FeatureShape::Arrow | FeatureShape::Terminator, Strand::Unknown) => FeatureShape::Bar,
+            (shape, _) => shape,
+        };
+        (
+            ann.color
+                .as_deref()
+                .map(|color| xml_escape(color).into_owned())
+                .unwrap_or_else(|| color.to_string()),
+            shape,
+        )
+    }
+
+    /// Segments of an annotation as increasing positions with an exclusive end,
+    /// segments past the origin of the sequence continue beyond its length.
+    fn unwrapped_spans(ann: &Annotation, len: usize) -> Vec<(usize, usize)> {
+        let mut offset = 0;
+        let mut last = 0;
+        ann.spans()
+            .into_iter()
+            .map(|(start, end)| {
+                if start + offset < last {
+                    offset += len;
+                }
+                let start = start + offset;
+                if end + offset < start {
+                    offset += len;
+                }
+                last = end + offset + 1;
+                (start, last)
+            })
+            .collect()
+    }
+
+    /// Generate labelled arcs for all annotations, stacked inwards from `outer_r`.
+    /// Each feature type gets its own shape, and cut positions (`needle`)
+    /// are drawn as radial ticks.
+    fn annotation_track(&self, outer_r: f32, cx: f32, cy: f32) -> String {
+        let len = self.sequence.len();
+        if len == 0 || self.annotations.is_empty() {
+            return String::new();
+        }
+        let lane_width = 8_f32;
+        let stroke_width = 5_f32;
+        let head_width = 4_f32;
+
+        // Helper function to project a sequence position onto a circle
+        let deg_to_rad = std::f32::consts::PI / 180_f32;
+        let point = |pos: f32, r: f32| {
+            let rad = deg_to_rad * (pos / len as f32 * 360_f32);
+            (rad.cos() * r + cx, rad.sin() * r + cy)
+        };
+        let arc = |start: f32, end: f32, r: f32, color: &str, width: f32| {
+            let (x1, y1) = point(start, r);
+            let (x2, y2) = point(end, r);
+            let large_arc = if end - start > len as f32 / 2_f32 {
+                1
+            } else {
+                0
+            };
+            format!(
+                r###"<path d="M {x1} {y1} A {r} {r} 0 {large_arc} 1 {x2} {y2}" fill="none" stroke="{color}" stroke-width="{width}" />"###,
+                x1 = x1,
+                y1 = y1,
+                x2 = x2,
+                y2 = y2,
+                r = r,
+                large_arc = large_arc,
+                color = color,
+                width = width,
+            )
+        };
+        // Arrow head with its tip at `tip`, pointing towards `tip` from `base`
+        let head = |base: f32, tip: f32, r: f32, color: &str, width: f32| {
+            let (x1, y1) = point(base, r - width);
+            let (x2, y2) = point(base, r + width);
+            let (x3, y3) = point(tip, r);
+            format!(
+                r###"<path d="M {} {} L {} {} L {} {} Z" fill="{}" />"###,
+                x1, y1, x2, y2, x3, y3, color
+            )
+        };
+
+        let lanes = self.annotation_lanes();
+        let mut buf = Vec::<String>::new();
+        for (i, (ann, lane)) in self.annotations.iter().zip(lanes).enumerate() {
+            let (color, shape) = Self::feature_style(ann, i);
+            let r = outer_r - lane as f32 * lane_width;
+            let spans = Self::unwrapped_spans(ann, len)
+                .into_iter()
+                .map(|(start, end)| (start as f32, end as f32))
+                .collect::<Vec<_>>();
+            let (first, last) = (spans[0], spans[spans.len() - 1]);
+            let reverse = ann.strand == Strand::Reverse;
+            let width = match shape {
+                FeatureShape::Primer => 2_f32,
+                _ => stroke_width,
+            };
+
+            let mut shapes = Vec::<String>::new();
+            // Introns of joined features as thin connecting arcs
+            for pair in spans.windows(2) {
+                shapes.push(arc(pair[0].1, pair[1].0, r, &color, 1_f32));
+            }
+            for (j, (start, end)) in spans.iter().enumerate() {
+                let pointed = match reverse {
+                    true => j == 0,
+                    false => j == spans.len() - 1,
+                };
+                match shape {
+                    FeatureShape::Arrow | FeatureShape::Primer if pointed => {
+                        let head_len = (len as f32 * 0.02).max(1_f32).min(end - start);
+                        let (body, base, tip) = match reverse {
+                            true => ((start + head_len, *end), start + head_len, *start),
+                            false => ((*start, end - head_len), end - head_len, *end),
+                        };
+                        shapes.push(arc(body.0, body.1, r, &color, width));
+                        shapes.push(head(base, tip, r, &color, head_width));
+                    }
+                    _ => shapes.push(arc(*start, *end, r, &color, width)),
+                }
+            }
+            if shape == FeatureShape::Terminator {
+                let tip = if reverse { first.0 } else { last.1 };
+                let (x1, y1) = point(tip, r - stroke_width);
+                let (x2, y2) = point(tip, r + stroke_width);
+                shapes.push(format!(
+                    r###"<path d="M {} {} L {} {}" stroke="{}" stroke-width="2" />"###,
+                    x1, y1, x2, y2, color
+                ));
+            }
+            buf.push(format!(
+                r###"<g><title>{text}</title>{shapes}</g>"###,
+                text = xml_escape(&ann.text),
+                shapes = shapes.join(""),
+            ));
+            if let Some(needle) = ann.needle {
+                let (nx1, ny1) = point(needle as f32, r - stroke_width);
+                let (nx2, ny2) = point(needle as f32, r + stroke_width);
+                buf.push(format!(
+                    r###"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="1.5" />"###,
+                    nx1, ny1, nx2, ny2, color
+                ));
+            }
+            let (tx, ty) = point((first.0 + last.1) / 2_f32, r - lane_width - 4_f32);
+            buf.push(format!(
+                r###"<text x="{}" y="{}" font-family="sans-serif" font-size="9" text-anchor="middle" fill="{}">{}</text>"###,
+                tx,
+                ty,
+                color,
+                xml_escape(&ann.text)
+            ));
+        }
+        buf.join("")
+    }
+
+    /// Generate GC content deviation bars and a cumulative GC skew trace
+    /// as two rings inside the circular sequence.
+    fn gc_ring(&self, window: usize, cx: f32, cy: f32) -> String {
+        let len = self.sequence.len();
+        let windows = GcWindows::compute(&self.sequence, window, window);
+        if windows.positions.is_empty() {
+            return String::new();
+        }
+        let cumulative_skew = cumulative_gc_skew(&windows.gc_skew);
+
+        let gc_r = 180_f32; // baseline radius of the GC content ring
+        let skew_r = 120_f32; // baseline radius of the GC skew ring
+        let amplitude = 30_f32;
+
+        let mean_gc = windows.gc_content.iter().sum::<f32>() / windows.gc_content.len() as f32;
+        let max_deviation = windows
+            .gc_content
+            .iter()
+            .map(|gc| (gc - mean_gc).abs())
+            .fold(f32::EPSILON, f32::max);
+        let max_skew = cumulative_skew
+            .iter()
+            .map(|skew| skew.abs())
+            .fold(f32::EPSILON, f32::max);
+
+        // Helper function to project a sequence position onto a circle
+        let deg_to_rad = std::f32::consts::PI / 180_f32;
+        let point = |pos: f32, r: f32| {
+            let rad = deg_to_rad * (pos / len as f32 * 360_f32);
+            (rad.cos() * r + cx, rad.sin() * r + cy)
+        };
+        let center = |start: usize| start as f32 + window as f32 / 2_f32;
+
+        // GC content above/below average as radial bars
+        let bar_width = (2_f32 * std::f32::consts::PI * gc_r / windows.positions.len() as f32)
+            .clamp(0.5, 10_f32);
+        let bars = windows
+            .positions
+            .iter()
+            .zip(&windows.gc_content)
+            .map(|(start, gc)| {
+                let deviation = (gc - mean_gc) / max_deviation * amplitude;
+                let (x1, y1) = point(center(*start), gc_r);
+                let (x2, y2) = point(center(*start), gc_r + deviation);
+                let color = if deviation >= 0_f32 {
+                    "hsla(0, 0%, 20%, 1)"
+                } else {
+                    "hsla(0, 0%, 60%, 1)"
+                };
+                format!(
+                    r###"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" />"###,
+                    x1, y1, x2, y2, color, bar_width
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Cumulative GC skew as a closed trace
+        let trace = windows
+            .positions
+            .iter()
+            .zip(&cumulative_skew)
+            .enumerate()
+            .map(|(i, (start, skew))| {
+                let (x, y) = point(center(*start), skew_r + skew / max_skew * amplitude);
+                format!("{} {} {}", if i == 0 { "M" } else { "L" }, x, y)
+            })
+            .collect::<Vec<_>>();
+
+        format!(
+            concat!(
+                r###"<circle cx="{cx}" cy="{cy}" r="{gc_r}" fill="none" stroke="hsla(0, 0%, 80%, 1)" />"###,
+                r###"{bars}"###,
+                r###"<circle cx="{cx}" cy="{cy}" r="{skew_r}" fill="none" stroke="hsla(0, 0%, 80%, 1)" stroke-dasharray="4 4" />"###,
+                r###"<path d="{trace} Z" fill="none" stroke="hsla(124, 50%, 40%, 1)" stroke-width="1.5" />"###,
+            ),
+            cx = cx,
+            cy = cy,
+            gc_r = gc_r,
+            skew_r = skew_r,
+            bars = bars.join(""),
+            trace = trace.join(" "),
         )
     }
 
     fn export_linear_sequence(&self) -> String {
-        todo!()
+        let len = self.sequence.len().max(1);
+
+        // Basic parameters
+        let w = 800_f32; // width
+        let margin = 20_f32;
+        let backbone_y = 30_f32;
+        let lane_height = 24_f32;
+        let bar_height = 8_f32;
+        let head_width = 4_f32;
+
+        // Helper function to project a sequence position onto the backbone
+        let x = |pos: usize| margin + pos as f32 / len as f32 * (w - 2_f32 * margin);
+
+        let lanes = self.annotation_lanes();
+        let lane_count = lanes.iter().max().map_or(0, |lane| lane + 1);
+        let h = backbone_y + 20_f32 + lane_count as f32 * lane_height;
+
+        let backbone = format!(
+            concat!(
+                r###"<line x1="{x1}" y1="{y}" x2="{x2}" y2="{y}" stroke="hsla(0, 0%, 40%, 1)" stroke-width="3" />"###,
+                r###"<text x="{x1}" y="{ty}" font-family="sans-serif" font-size="9" text-anchor="start">1</text>"###,
+                r###"<text x="{x2}" y="{ty}" font-family="sans-serif" font-size="9" text-anchor="end">{len}</text>"###,
+            ),
+            x1 = x(0),
+            x2 = x(len),
+            y = backbone_y,
+            ty = backbone_y - 8_f32,
+            len = self.sequence.len(),
+        );
+
+        let mut buf = Vec::<String>::new();
+        for (i, (ann, lane)) in self.annotations.iter().zip(lanes).enumerate() {
+            let (color, shape) = Self::feature_style(ann, i);
+            let y = backbone_y + 20_f32 + lane as f32 * lane_height;
+            let reverse = ann.strand == Strand::Reverse;
+            let height = match shape {
+                FeatureShape::Primer => 2_f32,
+                _ => bar_height,
+            };
+            let spans = Self::unwrapped_spans(ann, len);
+
+            // Segments spanning the origin are split at the ends of the backbone
+            let pieces = spans
+                .iter()
+                .flat_map(|(start, end)| {
+                    let turn = start / len * len;
+                    let (start, end) = (start - turn, end - turn);
+                    match end > len {
+                        true => vec![(start, len), (0, end - len)],
+                        false => vec![(start, end)],
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let mut shapes = Vec::<String>::new();
+            // Introns of joined features as thin connecting lines
+            for pair in spans.windows(2) {
+                let (from, to) = (pair[0].1, pair[1].0);
+                if (from - 1) / len == to / len {
+                    let turn = to / len * len;
+                    shapes.push(format!(
+                        r###"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="1" />"###,
+                        x(from - turn), y, x(to - turn), y, color
+                    ));
+                }
+            }
+            for (j, (start, end)) in pieces.iter().enumerate() {
+                let pointed = match reverse {
+                    true => j == 0,
+                    false => j == pieces.len() - 1,
+                };
+                let (mut x1, mut x2) = (x(*start), x(*end));
+                if pointed && matches!(shape, FeatureShape::Arrow | FeatureShape::Primer) {
+                    let head_len = 8_f32.min(x2 - x1);
+                    let (base, tip) = match reverse {
+                        true => (x1 + head_len, x1),
+                        false => (x2 - head_len, x2),
+                    };
+                    shapes.push(format!(
+                        r###"<path d="M {} {} L {} {} L {} {} Z" fill="{}" />"###,
+                        base,
+                        y - head_width,
+                        base,
+                        y + head_width,
+                        tip,
+                        y,
+                        color
+                    ));
+                    match reverse {
+                        true => x1 = base,
+                        false => x2 = base,
+                    }
+                }
+                shapes.push(format!(
+                    r###"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" />"###,
+                    x1,
+                    y - height / 2_f32,
+                    x2 - x1,
+                    height,
+                    color
+                ));
+            }
+            if shape == FeatureShape::Terminator {
+                let (start, end) = match reverse {
+                    true => pieces[0],
+                    false => pieces[pieces.len() - 1],
+                };
+                let tx = if reverse { x(start) } else { x(end) };
+                shapes.push(format!(
+                    r###"<path d="M {} {} L {} {}" stroke="{}" stroke-width="2" />"###,
+                    tx,
+                    y - bar_height,
+                    tx,
+                    y + bar_height,
+                    color
+                ));
+            }
+            buf.push(format!(
+                r###"<g><title>{text}</title>{shapes}</g>"###,
+                text = xml_escape(&ann.text),
+                shapes = shapes.join(""),
+            ));
+            if let Some(needle) = ann.needle {
+                buf.push(format!(
+                    r###"<line x1="{x}" y1="{}" x2="{x}" y2="{}" stroke="{}" stroke-width="1.5" />"###,
+                    y - bar_height,
+                    y + bar_height,
+                    color,
+                    x = x(needle.min(len)),
+                ));
+            }
+            let (first, last) = (spans[0], spans[spans.len() - 1]);
+            let center = match last.1 > len {
+                // Label features spanning the origin next to their start
+                true => (first.0 + len) / 2,
+                false => (first.0 + last.1) / 2,
+            };
+            buf.push(format!(
+                r###"<text x="{}" y="{}" font-family="sans-serif" font-size="9" text-anchor="middle" fill="{}">{}</text>"###,
+                x(center),
+                y + bar_height + 6_f32,
+                color,
+                xml_escape(&ann.text)
+            ));
+        }
+
+        // Assemble final svg
+        format!(
+            r###"<svg width="{width}" height="{height}" viewBox="0 0 {width} {height}">{backbone}{annotations}</svg>"###,
+            width = w,
+            height = h,
+            backbone = backbone,
+            annotations = buf.join(""),
+        )
     }
 }
 
@@ -148,3 +649,145 @@ impl Export for SvgExport {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{FeatureShape, SvgExport, SvgExportConfig};
+    use crate::{
+        exp::Export,
+        seq::{Annotation, FeatureType, Strand},
+        uni::IupacNucleotide::*,
+    };
+
+    #[test]
+    fn test_circular_export_without_gc_ring() {
+        let svg = SvgExport::new(SvgExportConfig::circular(), &[A, T, G, C]).export();
+        assert!(!svg.contains("<circle"));
+    }
+
+    #[test]
+    fn test_circular_export_with_annotations() {
+        let annotations = [
+            Annotation::new(0, 1, None, "foo"),
+            Annotation::new(1, 2, Some(2), "bar"),
+            Annotation::new(3, 3, None, "baz"),
+        ];
+        let export = SvgExport::new(SvgExportConfig::circular(), &[A, T, G, C])
+            .with_annotations(&annotations);
+        assert_eq!(export.annotation_lanes(), [0, 1, 0]);
+        let svg = export.export();
+        assert_eq!(svg.matches("<title>").count(), 3);
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert!(svg.contains(">bar</text>"));
+    }
+
+    #[test]
+    fn test_export_escapes_annotation_text() {
+        let annotations = [Annotation::new(0, 1, None, "A&B <tag>").with_color("\"red")];
+        for config in [SvgExportConfig::circular(), SvgExportConfig::linear()] {
+            let svg = SvgExport::new(config, &[A, T, G, C])
+                .with_annotations(&annotations)
+                .export();
+            assert!(svg.contains("<title>A&amp;B &lt;tag&gt;</title>"));
+            assert!(svg.contains(">A&amp;B &lt;tag&gt;</text>"));
+            assert!(svg.contains("fill=\"&quot;red\""));
+            assert!(!svg.contains("A&B"));
+        }
+    }
+
+    #[test]
+    fn test_circular_export_with_wrapping_annotation() {
+        let annotations = [
+            Annotation::new(6, 1, None, "ori"),
+            Annotation::new(0, 0, None, "foo"),
+            Annotation::new(3, 4, None, "bar"),
+        ];
+        let export = SvgExport::new(SvgExportConfig::circular(), &[A, T, G, C, A, T, G, C])
+            .with_annotations(&annotations);
+        // "ori" overlaps "foo" across the origin, but not "bar"
+        assert_eq!(export.annotation_lanes(), [1, 0, 0]);
+        let svg = export.export();
+        assert_eq!(svg.matches("<title>").count(), 3);
+    }
+
+    #[test]
+    fn test_circular_export_with_gc_ring() {
+        let config = SvgExportConfig::circular().with_gc_ring(2);
+        let svg = SvgExport::new(config, &[G, G, A, T, C, C]).export();
+        assert_eq!(svg.matches("<circle").count(), 2);
+        assert_eq!(svg.matches("<line").count(), 3);
+    }
+
+    #[test]
+    fn test_feature_styles() {
+        let cds = Annotation::new(0, 2, None, "orf").with_kind(FeatureType::Cds);
+        assert_eq!(SvgExport::feature_style(&cds, 0).1, FeatureShape::Bar);
+        let cds = cds.with_strand(Strand::Forward).with_color("#000000");
+        assert_eq!(
+            SvgExport::feature_style(&cds, 0),
+            ("#000000".to_string(), FeatureShape::Arrow)
+        );
+        let primer = Annotation::new(0, 2, None, "P1").with_kind(FeatureType::PrimerBind);
+        assert_eq!(SvgExport::feature_style(&primer, 0).1, FeatureShape::Primer);
+    }
+
+    #[test]
+    fn test_unwrapped_spans() {
+        let ann = Annotation::new(0, 0, None, "foo").with_segments(&[(6, 7), (0, 1), (3, 4)]);
+        assert_eq!(
+            SvgExport::unwrapped_spans(&ann, 8),
+            [(6, 8), (8, 10), (11, 13)]
+        );
+        let ori = Annotation::new(6, 1, None, "ori");
+        assert_eq!(SvgExport::unwrapped_spans(&ori, 8), [(6, 10)]);
+    }
+
+    #[test]
+    fn test_circular_export_with_feature_types() {
+        let annotations = [
+            Annotation::new(0, 3, None, "orf")
+                .with_kind(FeatureType::Cds)
+                .with_strand(Strand::Forward),
+            Annotation::new(4, 5, None, "T1")
+                .with_kind(FeatureType::Terminator)
+                .with_strand(Strand::Reverse),
+            Annotation::new(0, 0, None, "exons")
+                .with_kind(FeatureType::MRna)
+                .with_strand(Strand::Reverse)
+                .with_segments(&[(1, 2), (5, 6)]),
+        ];
+        let svg = SvgExport::new(SvgExportConfig::circular(), &[A, T, G, C, A, T, G, C])
+            .with_annotations(&annotations)
+            .export();
+        assert_eq!(svg.matches("<title>").count(), 3);
+        // Two arrow heads and the terminator bar
+        assert_eq!(svg.matches(" Z\"").count(), 2);
+        assert_eq!(svg.matches("stroke-width=\"2\"").count(), 1);
+        // The intron of the joined feature
+        assert_eq!(svg.matches("stroke-width=\"1\"").count(), 1);
+    }
+
+    #[test]
+    fn test_linear_export() {
+        let annotations = [
+            Annotation::new(0, 3, Some(2), "orf")
+                .with_kind(FeatureType::Cds)
+                .with_strand(Strand::Forward),
+            Annotation::new(6, 1, None, "ori").with_kind(FeatureType::RepOrigin),
+            Annotation::new(0, 0, None, "exons")
+                .with_kind(FeatureType::MRna)
+                .with_strand(Strand::Reverse)
+                .with_segments(&[(4, 4), (6, 7)]),
+        ];
+        let svg = SvgExport::new(SvgExportConfig::linear(), &[A, T, G, C, A, T, G, C])
+            .with_annotations(&annotations)
+            .export();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<title>").count(), 3);
+        // "ori" is split at the origin, "orf" and "exons" have an arrow head each
+        assert_eq!(svg.matches("<rect").count(), 5);
+        assert_eq!(svg.matches(" Z\"").count(), 2);
+        // Backbone, needle and intron
+        assert_eq!(svg.matches("<line").count(), 3);
+    }
+}
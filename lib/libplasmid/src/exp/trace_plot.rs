@@ -0,0 +1,228 @@
+use crate::imp::{TraceFile, TRACE_BASES};
+
+use super::{xml_escape, Export};
+
+/// Horizontal space given to every base call of a chromatogram.
+const TRACE_BASE_WIDTH: f32 = 14_f32;
+
+/// Colors of the A, C, G and T channels, as in common trace viewers.
+const TRACE_COLORS: [&str; 4] = ["#2ca02c", "#1f77b4", "#222222", "#d62728"];
+
+/// Quality drawn as a full bar above the base calls.
+const MAX_BAR_QUALITY: f32 = 60_f32;
+
+/// Chromatogram of a Sanger trace with its base calls and their qualities.
+pub struct TraceExport {
+    pub trace: TraceFile,
+    /// First base call drawn
+    pub start: usize,
+    /// Base call after the last one drawn
+    pub end: usize,
+}
+
+impl TraceExport {
+    pub fn new(trace: &TraceFile) -> Self {
+        TraceExport {
+            trace: trace.clone(),
+            start: 0,
+            end: trace.len(),
+        }
+    }
+
+    /// Only draw the base calls in `start..end`, and the samples around them.
+    pub fn with_range(mut self, start: usize, end: usize) -> Self {
+        self.end = end.min(self.trace.len());
+        self.start = start.min(self.end);
+        self
+    }
+
+    /// Samples drawn, from halfway to the call before the range to halfway to the one after it.
+    fn sample_range(&self) -> (usize, usize) {
+        let peaks = &self.trace.peaks;
+        let from = match self.start {
+            0 => 0,
+            start => (peaks[start - 1] + peaks[start.min(peaks.len() - 1)]) / 2,
+        };
+        let to = match self.end {
+            end if end >= peaks.len() => self.trace.samples(),
+            end => (peaks[end.max(1) - 1] + peaks[end]) / 2 + 1,
+        };
+        (from, to.max(from))
+    }
+
+    fn export_trace(&self) -> String {
+        let calls = self.end - self.start;
+        let (from, to) = self.sample_range();
+
+        // Basic parameters
+        let margin = 40_f32;
+        let w = margin * 2_f32 + calls.max(10) as f32 * TRACE_BASE_WIDTH; // width
+        let h = 260_f32; // height
+        let bars_y = 30_f32;
+        let bars_h = 24_f32;
+        let calls_y = bars_y + bars_h + 16_f32;
+        let trace_y = calls_y + 24_f32;
+        let trace_h = h - trace_y - 16_f32;
+        let trace_w = w - margin * 2_f32;
+        let sample_x =
+            |sample: usize| margin + (sample - from) as f32 / (to - from).max(1) as f32 * trace_w;
+
+        let max = self
+            .trace
+            .channels
+            .iter()
+            .flat_map(|channel| channel[from..to].iter())
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1) as f32;
+        let traces = self
+            .trace
+            .channels
+            .iter()
+            .zip(TRACE_COLORS)
+            .map(|(channel, color)| {
+                let points = (from..to)
+                    .map(|s| {
+                        let y = trace_y + trace_h - channel[s] as f32 / max * trace_h;
+                        format!("{},{}", sample_x(s), y)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                format!(
+                    r###"<polyline points="{points}" fill="none" stroke="{color}" stroke-width="1" />"###,
+                    points = points,
+                    color = color,
+                )
+            })
+            .collect::<String>();
+
+        // Base calls at their peaks, under a bar of their quality
+        let mut calls_svg = String::new();
+        for (i, base) in self
+            .trace
+            .bases
+            .chars()
+            .enumerate()
+            .take(self.end)
+            .skip(self.start)
+        {
+            let x = sample_x(self.trace.peaks[i]);
+            let quality = self.trace.qualities[i] as f32;
+            let bar_h = quality.min(MAX_BAR_QUALITY) / MAX_BAR_QUALITY * bars_h;
+            let color = match TRACE_BASES.iter().position(|b| *b == base) {
+                Some(c) => TRACE_COLORS[c],
+                None => "#999",
+            };
+            calls_svg += &format!(
+                concat!(
+                    r###"<rect x="{bar_x}" y="{bar_y}" width="{bar_w}" height="{bar_h}" fill="hsla(210, 40%, 70%, 0.8)" />"###,
+                    r###"<text x="{x}" y="{calls_y}" font-family="monospace" font-size="12" text-anchor="middle" fill="{color}">{base}</text>"###,
+                ),
+                bar_x = x - TRACE_BASE_WIDTH * 0.4,
+                bar_y = bars_y + bars_h - bar_h,
+                bar_w = TRACE_BASE_WIDTH * 0.8,
+                bar_h = bar_h,
+                x = x,
+                calls_y = calls_y,
+                color = color,
+                base = xml_escape(&base.to_string()),
+            );
+            if (i + 1) % 10 == 0 {
+                calls_svg += &format!(
+                    r###"<text x="{x}" y="{y}" font-family="sans-serif" font-size="9" text-anchor="middle" fill="#666">{position}</text>"###,
+                    x = x,
+                    y = calls_y + 12_f32,
+                    position = i + 1,
+                );
+            }
+        }
+
+        let frame = format!(
+            concat!(
+                r###"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="none" stroke="#ccc" />"###,
+                r###"<text x="{x}" y="{title_y}" font-family="sans-serif" font-size="14">{name} ({start}-{end} of {len} bases)</text>"###,
+            ),
+            x = margin,
+            y = trace_y,
+            w = trace_w,
+            h = trace_h,
+            title_y = bars_y - 10_f32,
+            name = xml_escape(&self.trace.name),
+            start = self.start + 1,
+            end = self.end,
+            len = self.trace.len(),
+        );
+
+        // Assemble final svg
+        format!(
+            r###"<svg width="{width}" height="{height}" viewBox="0 0 {width} {height}"><rect x="0" y="0" width="{width}" height="{height}" fill="white" />{frame}{traces}{calls}</svg>"###,
+            width = w,
+            height = h,
+            frame = frame,
+            traces = traces,
+            calls = calls_svg,
+        )
+    }
+}
+
+impl Export for TraceExport {
+    type Output = String;
+
+    fn export(&self) -> String {
+        self.export_trace()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TraceExport;
+    use crate::{exp::Export, imp::TraceFile};
+
+    fn trace() -> TraceFile {
+        let bases = "ACGTN".repeat(4);
+        let channel = |c: usize| {
+            (0..60)
+                .map(|s| if s % 15 == c * 3 + 1 { 800 } else { 20 })
+                .collect::<Vec<_>>()
+        };
+        TraceFile {
+            name: "clone".to_string(),
+            qualities: (0..bases.len() as u8).map(|q| q * 3).collect(),
+            peaks: (0..bases.len()).map(|i| i * 3 + 1).collect(),
+            channels: [channel(0), channel(1), channel(2), channel(3)],
+            bases,
+        }
+    }
+
+    #[test]
+    fn test_trace_export() {
+        let svg = TraceExport::new(&trace()).export();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<polyline").count(), 4);
+        assert_eq!(svg.matches("font-family=\"monospace\"").count(), 20);
+        assert!(svg.contains("clone (1-20 of 20 bases)"));
+        assert!(svg.contains(">10</text>"));
+    }
+
+    #[test]
+    fn test_trace_export_escapes_name() {
+        let mut trace = trace();
+        trace.name = "<clone & co>".to_string();
+        let svg = TraceExport::new(&trace).export();
+        assert!(svg.contains("&lt;clone &amp; co&gt; (1-20 of 20 bases)"));
+    }
+
+    #[test]
+    fn test_trace_export_range() {
+        let export = TraceExport::new(&trace()).with_range(5, 10);
+        assert_eq!(export.sample_range(), (14, 30));
+        let svg = export.export();
+        assert_eq!(svg.matches("font-family=\"monospace\"").count(), 5);
+        assert!(svg.contains("clone (6-10 of 20 bases)"));
+
+        let export = TraceExport::new(&trace()).with_range(15, 100);
+        assert_eq!((export.start, export.end), (15, 20));
+        assert_eq!(export.sample_range(), (44, 60));
+    }
+}
@@ -1,5 +1,21 @@
+pub mod bed;
+pub mod compression;
+pub mod faidx;
 pub mod fasta;
+pub mod features;
+pub mod gff;
 pub mod import;
+pub mod rebase;
+pub mod snapgene;
+pub mod trace;
 
+pub use self::bed::*;
+pub use self::compression::*;
+pub use self::faidx::*;
 pub use self::fasta::*;
+pub use self::features::*;
+pub use self::gff::*;
 pub use self::import::*;
+pub use self::rebase::*;
+pub use self::snapgene::*;
+pub use self::trace::*;
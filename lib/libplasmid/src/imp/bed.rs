@@ -0,0 +1,411 @@
+use std::{fmt, ops::Range};
+
+use anyhow::{anyhow, bail};
+
+use super::{half_open_to_inclusive, inclusive_to_half_open, Import, SequenceFeatures};
+use crate::{
+    exp::Export,
+    seq::{Annotation, Strand},
+};
+
+/// A line of a BED file.
+///
+/// `start` and `end` are 0-based, `end` exclusive. Features of circular sequences that span the
+/// origin end past the length of the sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BedRecord {
+    pub chrom: String,
+    pub start: usize,
+    pub end: usize,
+    pub name: Option<String>,
+    pub score: Option<f64>,
+    pub strand: Strand,
+    /// Display color from the `itemRgb` column, e.g. `#ff0000`
+    pub color: Option<String>,
+    /// Bounds of the blocks of a BED12 line on the chromosome, empty for other lines
+    pub blocks: Vec<Range<usize>>,
+}
+
+/// A BED file with 3 to 12 columns, `track` and `browser` lines are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct BedFile {
+    pub records: Vec<BedRecord>,
+}
+
+impl BedRecord {
+    /// 0-based half-open range of the feature.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    fn parse(line: &str) -> anyhow::Result<Self> {
+        let columns = match line.contains('\t') {
+            true => line.split('\t').collect::<Vec<_>>(),
+            false => line.split_whitespace().collect::<Vec<_>>(),
+        };
+        if columns.len() < 3 {
+            bail!("expected at least 3 columns, found {}", columns.len());
+        }
+        let number = |column: &str| {
+            column
+                .parse::<usize>()
+                .map_err(|_| anyhow!("invalid number '{column}'"))
+        };
+        let (start, end) = (number(columns[1])?, number(columns[2])?);
+        if end < start {
+            bail!("interval {start}-{end} ends before it starts");
+        }
+        let name = columns
+            .get(3)
+            .filter(|name| **name != ".")
+            .map(|name| name.to_string());
+        let score = match columns.get(4) {
+            None | Some(&".") => None,
+            Some(score) => Some(
+                score
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("invalid score '{score}'"))?,
+            ),
+        };
+        let strand = match columns.get(5) {
+            Some(&"+") => Strand::Forward,
+            Some(&"-") => Strand::Reverse,
+            None | Some(&".") => Strand::Unknown,
+            Some(strand) => bail!("invalid strand '{strand}'"),
+        };
+        let color = match columns.get(8) {
+            None | Some(&"0") | Some(&".") => None,
+            Some(rgb) => {
+                let channels = rgb
+                    .split(',')
+                    .map(|c| c.trim().parse::<u8>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .ok()
+                    .filter(|c| c.len() == 3)
+                    .ok_or_else(|| anyhow!("invalid itemRgb '{rgb}'"))?;
+                Some(format!(
+                    "#{:02x}{:02x}{:02x}",
+                    channels[0], channels[1], channels[2]
+                ))
+            }
+        };
+        let mut blocks = Vec::new();
+        if columns.len() >= 12 {
+            let list = |column: &str| {
+                column
+                    .split(',')
+                    .filter(|v| !v.is_empty())
+                    .map(number)
+                    .collect::<anyhow::Result<Vec<_>>>()
+            };
+            let count = number(columns[9])?;
+            let (sizes, starts) = (list(columns[10])?, list(columns[11])?);
+            if sizes.len() != count || starts.len() != count {
+                bail!("expected {count} block sizes and starts");
+            }
+            for (size, offset) in sizes.into_iter().zip(starts) {
+                if start + offset + size > end {
+                    bail!("block {offset}+{size} ends past the end of the feature");
+                }
+                blocks.push(start + offset..start + offset + size);
+            }
+        }
+        Ok(BedRecord {
+            chrom: columns[0].to_string(),
+            start,
+            end,
+            name,
+            score,
+            strand,
+            color,
+            blocks,
+        })
+    }
+
+    /// Line of the record with the given number of columns, 6, 9 or 12.
+    fn to_line(&self, columns: usize) -> String {
+        let mut line = format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            self.chrom,
+            self.start,
+            self.end,
+            self.name.as_deref().unwrap_or("."),
+            self.score.unwrap_or(0.0),
+            match self.strand {
+                Strand::Forward => "+",
+                Strand::Reverse => "-",
+                Strand::Unknown => ".",
+            },
+        );
+        if columns >= 9 {
+            let rgb = match self.color.as_deref().and_then(parse_hex_color) {
+                Some([r, g, b]) => format!("{r},{g},{b}"),
+                None => "0".to_string(),
+            };
+            line += &format!("\t{}\t{}\t{rgb}", self.start, self.end);
+        }
+        if columns >= 12 {
+            let blocks = match self.blocks.is_empty() {
+                true => vec![self.range()],
+                false => self.blocks.clone(),
+            };
+            let list = |f: &dyn Fn(&Range<usize>) -> usize| {
+                blocks
+                    .iter()
+                    .map(|b| format!("{},", f(b)))
+                    .collect::<String>()
+            };
+            line += &format!(
+                "\t{}\t{}\t{}",
+                blocks.len(),
+                list(&|b| b.len()),
+                list(&|b| b.start - self.start),
+            );
+        }
+        line
+    }
+}
+
+impl fmt::Display for BedRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_line(6))
+    }
+}
+
+/// Red, green and blue channels of a `#rrggbb` color.
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+impl BedFile {
+    /// Add the annotations of sequence `chrom` of length `len` as records, joined annotations
+    /// with their segments as blocks.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let lac = Annotation::new(2, 10, None, "lac").with_strand(Strand::Reverse);
+    /// let mut bed = BedFile::default();
+    /// bed.add_annotations("pUC19", 2686, &[lac]);
+    /// assert_eq!(bed.records[0].to_string(), "pUC19\t2\t11\tlac\t0\t-");
+    /// ```
+    pub fn add_annotations(&mut self, chrom: &str, len: usize, annotations: &[Annotation]) {
+        for annotation in annotations.iter() {
+            let range = inclusive_to_half_open(annotation.start, annotation.end, len);
+            let blocks = annotation
+                .segments
+                .iter()
+                .map(|&(start, end)| {
+                    let block = inclusive_to_half_open(start, end, len);
+                    // Segments past the origin are after the start of the feature
+                    match block.start < range.start {
+                        true => block.start + len..block.end + len,
+                        false => block,
+                    }
+                })
+                .collect();
+            self.records.push(BedRecord {
+                chrom: chrom.to_string(),
+                start: range.start,
+                end: range.end,
+                name: Some(annotation.text.to_owned()).filter(|name| !name.is_empty()),
+                score: annotation.qualifier("score").and_then(|s| s.parse().ok()),
+                strand: annotation.strand,
+                color: annotation.color.to_owned(),
+                blocks,
+            });
+        }
+    }
+}
+
+impl Import for BedFile {
+    type Output = Self;
+
+    /// Import a BED file from a string.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let bed = BedFile::import("track name=genes\nchr1\t9\t20\tlacZ\t0\t-").unwrap();
+    /// assert_eq!(bed.records[0].range(), 9..20);
+    /// assert_eq!(bed.records[0].strand, Strand::Reverse);
+    /// ```
+    fn import<S>(s: S) -> anyhow::Result<Self::Output>
+    where
+        S: AsRef<str>,
+    {
+        let mut records = Vec::new();
+        for (number, line) in s.as_ref().lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty()
+                || line.starts_with('#')
+                || line.starts_with("track")
+                || line.starts_with("browser")
+            {
+                continue;
+            }
+            records.push(BedRecord::parse(line).map_err(|e| anyhow!("line {}: {e}", number + 1))?);
+        }
+        Ok(BedFile { records })
+    }
+}
+
+impl SequenceFeatures for BedFile {
+    fn seqids(&self) -> Vec<&str> {
+        let mut seqids: Vec<&str> = Vec::new();
+        for record in self.records.iter() {
+            if !seqids.contains(&record.chrom.as_str()) {
+                seqids.push(&record.chrom);
+            }
+        }
+        seqids
+    }
+
+    fn annotations(
+        &self,
+        seqid: &str,
+        len: usize,
+        circular: bool,
+    ) -> anyhow::Result<Vec<Annotation>> {
+        self.records
+            .iter()
+            .filter(|record| record.chrom == seqid)
+            .map(|record| {
+                let inclusive = |range: Range<usize>| {
+                    // Blocks past the origin of a circular sequence
+                    let range = match circular && range.start >= len {
+                        true => range.start - len..range.end - len,
+                        false => range,
+                    };
+                    half_open_to_inclusive(range, len, circular)
+                        .map_err(|e| anyhow!("{seqid}: {e}"))
+                };
+                let (start, end) = inclusive(record.range())?;
+                let text = match &record.name {
+                    Some(name) => name.to_owned(),
+                    None => format!("{}:{}-{}", record.chrom, record.start + 1, record.end),
+                };
+                let mut annotation =
+                    Annotation::new(start, end, None, text).with_strand(record.strand);
+                if record.blocks.len() > 1 {
+                    let segments = record
+                        .blocks
+                        .iter()
+                        .map(|block| inclusive(block.clone()))
+                        .collect::<anyhow::Result<Vec<_>>>()?;
+                    annotation = annotation.with_segments(&segments);
+                }
+                if let Some(score) = record.score {
+                    annotation = annotation.with_qualifier("score", score.to_string());
+                }
+                if let Some(color) = &record.color {
+                    annotation = annotation.with_color(color);
+                }
+                Ok(annotation)
+            })
+            .collect()
+    }
+}
+
+impl Export for BedFile {
+    type Output = String;
+
+    /// BED lines with as few columns as needed for all records: 6, 9 with colors or 12 with blocks.
+    fn export(&self) -> String {
+        let columns = match (
+            self.records.iter().any(|r| !r.blocks.is_empty()),
+            self.records.iter().any(|r| r.color.is_some()),
+        ) {
+            (true, _) => 12,
+            (false, true) => 9,
+            (false, false) => 6,
+        };
+        self.records
+            .iter()
+            .map(|record| record.to_line(columns) + "\n")
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BedFile;
+    use crate::{
+        exp::Export,
+        imp::{Import, SequenceFeatures},
+        seq::{Annotation, Strand},
+    };
+
+    const BED: &str = "browser position chr1:1-100
+track name=\"features\"
+chr1\t0\t10
+chr1\t5\t40\tgeneA\t960\t-\t5\t40\t255,0,0\t2\t10,15,\t0,20,
+chr2 3 9 geneB . +
+";
+
+    #[test]
+    fn test_bed_import() {
+        let bed = BedFile::import(BED).unwrap();
+        assert_eq!(bed.records.len(), 3);
+        assert_eq!(bed.records[0].range(), 0..10);
+        assert_eq!(bed.records[0].name, None);
+        assert_eq!(bed.records[1].score, Some(960.0));
+        assert_eq!(bed.records[1].color.as_deref(), Some("#ff0000"));
+        assert_eq!(bed.records[1].blocks, [5..15, 25..40]);
+        assert_eq!(bed.records[2].chrom, "chr2");
+        assert_eq!(bed.records[2].strand, Strand::Forward);
+        assert_eq!(bed.seqids(), ["chr1", "chr2"]);
+
+        assert!(BedFile::import("chr1\t10").is_err());
+        assert!(BedFile::import("chr1\t10\t5").is_err());
+        assert!(BedFile::import("chr1\t0\t10\tx\t0\t+\t0\t10\t0\t1\t20,\t0,").is_err());
+    }
+
+    #[test]
+    fn test_bed_annotations() {
+        let bed = BedFile::import(BED).unwrap();
+        let annotations = bed.annotations("chr1", 50, false).unwrap();
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].text, "chr1:1-10");
+        assert_eq!((annotations[0].start, annotations[0].end), (0, 9));
+        assert_eq!(annotations[1].segments, [(5, 14), (25, 39)]);
+        assert_eq!(annotations[1].qualifier("score"), Some("960"));
+        assert_eq!(annotations[1].color.as_deref(), Some("#ff0000"));
+        assert!(bed.annotations("chr1", 30, false).is_err());
+    }
+
+    #[test]
+    fn test_bed_export() {
+        let bed = BedFile::import(BED).unwrap();
+        let exported = bed.export();
+        assert_eq!(
+            exported.lines().collect::<Vec<_>>(),
+            [
+                "chr1\t0\t10\t.\t0\t.\t0\t10\t0\t1\t10,\t0,",
+                "chr1\t5\t40\tgeneA\t960\t-\t5\t40\t255,0,0\t2\t10,15,\t0,20,",
+                "chr2\t3\t9\tgeneB\t0\t+\t3\t9\t0\t1\t6,\t0,",
+            ]
+        );
+        assert_eq!(
+            BedFile::import(&exported).unwrap().records[1],
+            bed.records[1]
+        );
+
+        // Annotations across the origin of a circular sequence end past its length
+        let ori = Annotation::new(18, 5, None, "ori").with_segments(&[(18, 19), (2, 5)]);
+        let mut bed = BedFile::default();
+        bed.add_annotations("p1", 20, &[ori]);
+        assert_eq!(bed.records[0].range(), 18..26);
+        assert_eq!(bed.records[0].blocks, [18..20, 22..26]);
+        let annotations = bed.annotations("p1", 20, true).unwrap();
+        assert_eq!((annotations[0].start, annotations[0].end), (18, 5));
+        assert_eq!(annotations[0].segments, [(18, 19), (2, 5)]);
+    }
+}
@@ -0,0 +1,455 @@
+use std::{
+    borrow::Cow,
+    io::{ErrorKind, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use anyhow::{anyhow, bail};
+use flate2::{
+    read::{DeflateDecoder, MultiGzDecoder},
+    write::DeflateEncoder,
+    Crc,
+};
+
+/// First bytes of a gzip member, BGZF blocks included.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Size of the header of a BGZF block, up to its compressed data.
+const BGZF_HEADER_LEN: usize = 18;
+
+/// Uncompressed bytes put in a BGZF block, as by `bgzip`.
+const BGZF_BLOCK_DATA: usize = 0xff00;
+
+/// Empty block that ends a BGZF file.
+pub const BGZF_EOF: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Compression of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    /// Blocked gzip, as written by `bgzip`, which can be read from any block
+    Bgzf,
+}
+
+impl Compression {
+    /// Compression of file contents, told by their first bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// assert_eq!(Compression::detect(b">seq\nACGT"), Compression::None);
+    /// assert_eq!(Compression::detect(&bgzf_compress(b">seq\nACGT")), Compression::Bgzf);
+    /// ```
+    pub fn detect(bytes: &[u8]) -> Self {
+        if !bytes.starts_with(&GZIP_MAGIC) {
+            return Compression::None;
+        }
+        match bgzf_block_size(bytes) {
+            Some(_) => Compression::Bgzf,
+            None => Compression::Gzip,
+        }
+    }
+
+    /// Compression expected from the extension of a file: `.gz` for gzip, `.bgz` for BGZF.
+    pub fn from_extension<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let extension = path
+            .as_ref()
+            .extension()
+            .map(|e| e.to_string_lossy().to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("gz") | Some("gzip") => Compression::Gzip,
+            Some("bgz") | Some("bgzf") => Compression::Bgzf,
+            _ => Compression::None,
+        }
+    }
+}
+
+/// Total size of the BGZF block at the start of `bytes`, if it starts with one.
+fn bgzf_block_size(bytes: &[u8]) -> Option<usize> {
+    let header = bytes.get(..12)?;
+    // Deflate method with the FEXTRA flag
+    if header[..4] != [0x1f, 0x8b, 0x08, 0x04] {
+        return None;
+    }
+    let xlen = u16::from_le_bytes([header[10], header[11]]) as usize;
+    let mut extra = bytes.get(12..12 + xlen)?;
+    while extra.len() >= 4 {
+        let len = u16::from_le_bytes([extra[2], extra[3]]) as usize;
+        if extra[..2] == *b"BC" && len == 2 {
+            let size = u16::from_le_bytes([*extra.get(4)?, *extra.get(5)?]);
+            return Some(size as usize + 1);
+        }
+        extra = extra.get(4 + len..)?;
+    }
+    None
+}
+
+/// Size of the BGZF block read at `offset`, which must hold at least its header and trailer.
+fn checked_block_size(header: &[u8], offset: u64) -> std::io::Result<usize> {
+    match bgzf_block_size(header) {
+        Some(size) if size >= BGZF_HEADER_LEN + 8 => Ok(size),
+        _ => Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid BGZF block at offset {offset}"),
+        )),
+    }
+}
+
+/// Decompress gzip or BGZF compressed contents, other contents are returned as they are.
+///
+/// # Example
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// let compressed = bgzf_compress(b">seq\nACGT\n");
+/// assert_eq!(decompress(&compressed).unwrap().as_ref(), b">seq\nACGT\n");
+/// assert_eq!(decompress(b"ACGT").unwrap().as_ref(), b"ACGT");
+/// ```
+pub fn decompress(bytes: &[u8]) -> anyhow::Result<Cow<'_, [u8]>> {
+    match Compression::detect(bytes) {
+        Compression::None => Ok(Cow::Borrowed(bytes)),
+        // BGZF blocks are gzip members
+        Compression::Gzip | Compression::Bgzf => {
+            let mut decompressed = Vec::new();
+            MultiGzDecoder::new(bytes)
+                .read_to_end(&mut decompressed)
+                .map_err(|e| anyhow!("invalid gzip data: {e}"))?;
+            Ok(Cow::Owned(decompressed))
+        }
+    }
+}
+
+/// Read a file, decompressing it if it is gzip or BGZF compressed.
+pub fn read_file<P>(path: P) -> anyhow::Result<Vec<u8>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let bytes = std::fs::read(path)?;
+    if Compression::detect(&bytes) == Compression::None
+        && Compression::from_extension(path) != Compression::None
+    {
+        bail!("{} is not gzip compressed", path.display());
+    }
+    Ok(decompress(&bytes)?.into_owned())
+}
+
+/// Read a UTF-8 text file, decompressing it if it is gzip or BGZF compressed.
+pub fn read_text_file<P>(path: P) -> anyhow::Result<String>
+where
+    P: AsRef<Path>,
+{
+    Ok(String::from_utf8(read_file(path)?)?)
+}
+
+/// Compress data in BGZF blocks, ending with the empty EOF block.
+pub fn bgzf_compress(data: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::with_capacity(data.len() / 3 + BGZF_EOF.len());
+    for chunk in data.chunks(BGZF_BLOCK_DATA) {
+        let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        // Writing to a vector cannot fail
+        encoder.write_all(chunk).expect("in-memory deflate");
+        let cdata = encoder.finish().expect("in-memory deflate");
+        let mut crc = Crc::new();
+        crc.update(chunk);
+
+        let block_size = BGZF_HEADER_LEN + cdata.len() + 8;
+        compressed.extend_from_slice(&[0x1f, 0x8b, 0x08, 0x04, 0, 0, 0, 0, 0, 0xff, 6, 0]);
+        compressed.extend_from_slice(b"BC");
+        compressed.extend_from_slice(&2_u16.to_le_bytes());
+        compressed.extend_from_slice(&(block_size as u16 - 1).to_le_bytes());
+        compressed.extend_from_slice(&cdata);
+        compressed.extend_from_slice(&crc.sum().to_le_bytes());
+        compressed.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+    }
+    compressed.extend_from_slice(&BGZF_EOF);
+    compressed
+}
+
+/// Offsets of the blocks of a BGZF file, as in the `.gzi` index written by `bgzip -i`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GziIndex {
+    /// Compressed and uncompressed offsets of every block but the first, which starts at 0
+    pub blocks: Vec<(u64, u64)>,
+}
+
+impl GziIndex {
+    /// Index the blocks of a BGZF stream, reading only their headers and sizes.
+    pub fn build<R>(reader: &mut R) -> anyhow::Result<Self>
+    where
+        R: Read + Seek,
+    {
+        let end = reader.seek(SeekFrom::End(0))?;
+        let (mut compressed, mut uncompressed) = (0, 0);
+        let mut blocks = Vec::new();
+        while compressed < end {
+            if compressed > 0 {
+                blocks.push((compressed, uncompressed));
+            }
+            let mut header = [0; BGZF_HEADER_LEN];
+            reader.seek(SeekFrom::Start(compressed))?;
+            reader.read_exact(&mut header)?;
+            let size = checked_block_size(&header, compressed)?;
+            let mut isize = [0; 4];
+            reader.seek(SeekFrom::Start(compressed + size as u64 - 4))?;
+            reader.read_exact(&mut isize)?;
+            compressed += size as u64;
+            uncompressed += u32::from_le_bytes(isize) as u64;
+        }
+        // The EOF block is not indexed
+        if blocks.last().map(|b| b.1) == Some(uncompressed) {
+            blocks.pop();
+        }
+        Ok(GziIndex { blocks })
+    }
+
+    /// Read a `.gzi` index: the number of entries, then their compressed and uncompressed offsets,
+    /// all as little endian 64-bit integers.
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let number = |i: usize| {
+            bytes
+                .get(i * 8..i * 8 + 8)
+                .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+                .ok_or_else(|| anyhow!("truncated gzi index"))
+        };
+        let count = number(0)? as usize;
+        let blocks = (0..count)
+            .map(|i| Ok((number(1 + i * 2)?, number(2 + i * 2)?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(GziIndex { blocks })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = (self.blocks.len() as u64).to_le_bytes().to_vec();
+        for (compressed, uncompressed) in self.blocks.iter() {
+            bytes.extend_from_slice(&compressed.to_le_bytes());
+            bytes.extend_from_slice(&uncompressed.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Compressed and uncompressed offsets of the block holding an uncompressed offset.
+    pub fn block_of(&self, offset: u64) -> (u64, u64) {
+        match self.blocks.partition_point(|&(_, start)| start <= offset) {
+            0 => (0, 0),
+            i => self.blocks[i - 1],
+        }
+    }
+}
+
+/// Reader of BGZF compressed data with random access to its uncompressed bytes.
+///
+/// # Example
+/// ```rust
+/// use std::io::Cursor;
+/// use plasmid::prelude::*;
+///
+/// let data = ">chr1\n".to_string() + &"ACGT".repeat(50_000);
+/// let mut reader = BgzfReader::new(Cursor::new(bgzf_compress(data.as_bytes())), None).unwrap();
+/// assert_eq!(reader.read_at(6 + 4 * 40_000, 6).unwrap(), b"ACGTAC");
+/// ```
+pub struct BgzfReader<R> {
+    inner: R,
+    index: GziIndex,
+    /// Compressed offset of the last block read, with its data and compressed size
+    block: Option<(u64, Vec<u8>, u64)>,
+}
+
+impl<R> BgzfReader<R>
+where
+    R: Read + Seek,
+{
+    /// Read BGZF data with its `.gzi` index, built by scanning the blocks if none is given.
+    pub fn new(mut inner: R, index: Option<GziIndex>) -> anyhow::Result<Self> {
+        let index = match index {
+            Some(index) => index,
+            None => GziIndex::build(&mut inner)?,
+        };
+        Ok(BgzfReader {
+            inner,
+            index,
+            block: None,
+        })
+    }
+
+    pub fn index(&self) -> &GziIndex {
+        &self.index
+    }
+
+    /// Uncompressed data and compressed size of the block at a compressed offset.
+    fn read_block(&mut self, offset: u64) -> anyhow::Result<(&[u8], u64)> {
+        if self.block.as_ref().map(|b| b.0) != Some(offset) {
+            let mut header = [0; BGZF_HEADER_LEN];
+            self.inner.seek(SeekFrom::Start(offset))?;
+            if let Err(e) = self.inner.read_exact(&mut header) {
+                return match e.kind() {
+                    ErrorKind::UnexpectedEof => Ok((&[], 0)),
+                    _ => Err(e.into()),
+                };
+            }
+            let size = checked_block_size(&header, offset)?;
+            let mut block = vec![0; size];
+            block[..BGZF_HEADER_LEN].copy_from_slice(&header);
+            self.inner.read_exact(&mut block[BGZF_HEADER_LEN..])?;
+
+            let xlen = u16::from_le_bytes([header[10], header[11]]) as usize;
+            let cdata = block
+                .get(12 + xlen..size - 8)
+                .ok_or_else(|| anyhow!("invalid BGZF block at offset {offset}"))?;
+            let mut data = Vec::with_capacity(BGZF_BLOCK_DATA);
+            DeflateDecoder::new(cdata).read_to_end(&mut data)?;
+            let isize = u32::from_le_bytes(block[size - 4..].try_into().unwrap());
+            if data.len() != isize as usize {
+                bail!("corrupt BGZF block at offset {offset}");
+            }
+            self.block = Some((offset, data, size as u64));
+        }
+        let (_, data, size) = self.block.as_ref().unwrap();
+        Ok((data, *size))
+    }
+
+    /// Read `len` uncompressed bytes from an uncompressed offset, fewer at the end of the data.
+    pub fn read_at(&mut self, offset: u64, len: usize) -> anyhow::Result<Vec<u8>> {
+        let (mut compressed, mut start) = self.index.block_of(offset);
+        let mut bytes = Vec::with_capacity(len);
+        while bytes.len() < len {
+            let (data, size) = self.read_block(compressed)?;
+            if size == 0 {
+                break;
+            }
+            let end = start + data.len() as u64;
+            if end > offset {
+                let from = (offset + bytes.len() as u64 - start) as usize;
+                let to = data.len().min(from + len - bytes.len());
+                bytes.extend_from_slice(&data[from..to]);
+            }
+            compressed += size;
+            start = end;
+        }
+        Ok(bytes)
+    }
+}
+
+impl BgzfReader<std::fs::File> {
+    /// Open a BGZF file, with its `<path>.gzi` index if there is one.
+    pub fn open<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)?;
+        let mut gzi = path.as_os_str().to_owned();
+        gzi.push(".gzi");
+        let index = match std::fs::read(gzi) {
+            Ok(bytes) => Some(GziIndex::from_bytes(&bytes)?),
+            Err(_) => None,
+        };
+        Self::new(file, index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, ErrorKind, Write};
+
+    use flate2::write::GzEncoder;
+
+    use super::{bgzf_compress, decompress, BgzfReader, Compression, GziIndex, BGZF_EOF};
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_detect_compression() {
+        assert_eq!(Compression::detect(&gzip(b"ACGT")), Compression::Gzip);
+        assert_eq!(Compression::detect(&BGZF_EOF), Compression::Bgzf);
+        assert_eq!(Compression::detect(&[0x1f]), Compression::None);
+        assert_eq!(
+            Compression::from_extension("reads.fq.gz"),
+            Compression::Gzip
+        );
+        assert_eq!(
+            Compression::from_extension("hg38.fa.BGZ"),
+            Compression::Bgzf
+        );
+        assert_eq!(Compression::from_extension("hg38.fa"), Compression::None);
+    }
+
+    #[test]
+    fn test_decompress() {
+        let data = b">seq\nACGTACGT\n".repeat(10_000);
+        assert_eq!(decompress(&gzip(&data)).unwrap(), data);
+        assert_eq!(decompress(&bgzf_compress(&data)).unwrap(), data);
+
+        // Concatenated members, as written by `cat a.gz b.gz`
+        let concatenated = [gzip(b">a\nAC\n"), gzip(b">b\nGT\n")].concat();
+        assert_eq!(
+            decompress(&concatenated).unwrap().as_ref(),
+            b">a\nAC\n>b\nGT\n"
+        );
+
+        let mut truncated = gzip(&data);
+        truncated.truncate(truncated.len() / 2);
+        assert!(decompress(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_gzi_index() {
+        let data = (0..200_000).map(|i| b"ACGT"[i % 4]).collect::<Vec<_>>();
+        let compressed = bgzf_compress(&data);
+        let index = GziIndex::build(&mut Cursor::new(&compressed)).unwrap();
+        assert_eq!(index.blocks.len(), 3);
+        assert_eq!(index.blocks[0].1, 0xff00);
+        assert_eq!(index.block_of(100), (0, 0));
+        assert_eq!(index.block_of(0xff00 * 2 + 1), index.blocks[1]);
+        assert_eq!(GziIndex::from_bytes(&index.to_bytes()).unwrap(), index);
+        assert!(GziIndex::from_bytes(&[1, 0, 0, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_bgzf_random_access() {
+        let data = (0..200_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let mut reader = BgzfReader::new(Cursor::new(bgzf_compress(&data)), None).unwrap();
+        for (offset, len) in [
+            (0, 10),
+            (0xff00 - 3, 6),
+            (0xff00 * 3 - 5, 100),
+            (150_000, 70_000),
+        ] {
+            let end = data.len().min(offset + len);
+            assert_eq!(
+                reader.read_at(offset as u64, len).unwrap(),
+                &data[offset..end]
+            );
+        }
+        assert!(reader.read_at(300_000, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_reject_short_bgzf_blocks() {
+        let is_invalid_data = |e: anyhow::Error| {
+            e.downcast_ref::<std::io::Error>().map(|e| e.kind()) == Some(ErrorKind::InvalidData)
+        };
+        // Blocks of 1, 17 and 25 bytes, all too short for a header and trailer
+        for bsize in [0_u16, 16, 24] {
+            let mut block = BGZF_EOF.to_vec();
+            block[16..18].copy_from_slice(&bsize.to_le_bytes());
+            let err = GziIndex::build(&mut Cursor::new(&block)).unwrap_err();
+            assert!(is_invalid_data(err));
+            let index = GziIndex { blocks: Vec::new() };
+            let mut reader = BgzfReader::new(Cursor::new(&block), Some(index)).unwrap();
+            assert!(is_invalid_data(reader.read_at(0, 10).unwrap_err()));
+        }
+    }
+}
@@ -0,0 +1,576 @@
+use std::{
+    fmt,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    ops::Range,
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{anyhow, bail};
+use flate2::read::MultiGzDecoder;
+
+use super::{BgzfReader, Compression, Import};
+use crate::exp::Export;
+
+/// Line of a `.fai` index, locating a FASTA record in its file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaidxRecord {
+    /// Id of the record, its header up to the first whitespace
+    pub name: String,
+    /// Number of bases
+    pub length: u64,
+    /// Byte offset of the first base, in the uncompressed file
+    pub offset: u64,
+    /// Bases on each line
+    pub line_bases: u64,
+    /// Bytes of each line, line ending included
+    pub line_width: u64,
+}
+
+impl FaidxRecord {
+    /// Uncompressed byte offset of the base at 0-based position `pos`.
+    pub fn offset_of(&self, pos: u64) -> u64 {
+        match self.line_bases {
+            0 => self.offset,
+            bases => self.offset + pos / bases * self.line_width + pos % bases,
+        }
+    }
+}
+
+/// `.fai` index of a FASTA file, as written by `samtools faidx`.
+///
+/// All lines of a record but its last must have the same length, for the offset of any base
+/// to be computed from the index.
+///
+/// # Example
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// let fasta = ">chr1 first\nACGTA\nCGT\n>chr2\nGGGG\n";
+/// let index = FastaIndex::build(fasta.as_bytes()).unwrap();
+/// assert_eq!(index.export(), "chr1\t8\t12\t5\t6\nchr2\t4\t28\t4\t5\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FastaIndex {
+    pub records: Vec<FaidxRecord>,
+}
+
+impl FastaIndex {
+    /// Index uncompressed FASTA data, read line by line.
+    pub fn build<R>(mut reader: R) -> anyhow::Result<Self>
+    where
+        R: BufRead,
+    {
+        let mut records: Vec<FaidxRecord> = Vec::new();
+        // The current record ended with a shorter line, only empty lines may follow it
+        let mut short_line = false;
+        let mut offset = 0;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)? as u64;
+            if read == 0 {
+                break;
+            }
+            offset += read;
+            if line[0] == b'>' {
+                let header = std::str::from_utf8(&line[1..])?;
+                let name = header.split_whitespace().next().unwrap_or_default();
+                if records.iter().any(|record| record.name == name) {
+                    bail!("duplicate FASTA record {name}");
+                }
+                records.push(FaidxRecord {
+                    name: name.to_string(),
+                    length: 0,
+                    offset,
+                    line_bases: 0,
+                    line_width: 0,
+                });
+                short_line = false;
+                continue;
+            }
+            let bases = line
+                .iter()
+                .rev()
+                .skip_while(|&&b| b == b'\n' || b == b'\r')
+                .count() as u64;
+            let record = match records.last_mut() {
+                Some(record) => record,
+                None if bases == 0 => continue,
+                None => bail!("sequence found before the first FASTA header"),
+            };
+            if bases == 0 {
+                short_line = true;
+                continue;
+            }
+            if short_line {
+                bail!(
+                    "record {} has lines of different lengths, it cannot be indexed",
+                    record.name
+                );
+            }
+            if record.line_bases == 0 {
+                record.line_bases = bases;
+                record.line_width = read;
+            } else if bases > record.line_bases
+                || (bases == record.line_bases
+                    && read != record.line_width
+                    && line.ends_with(b"\n"))
+            {
+                bail!(
+                    "record {} has lines of different lengths, it cannot be indexed",
+                    record.name
+                );
+            } else if bases < record.line_bases {
+                short_line = true;
+            }
+            record.length += bases;
+        }
+        Ok(FastaIndex { records })
+    }
+
+    /// Index a FASTA file, decompressing it while it is read.
+    pub fn build_from_path<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let mut file = BufReader::new(std::fs::File::open(path)?);
+        match Compression::detect(file.fill_buf()?) {
+            Compression::None => Self::build(file),
+            _ => Self::build(BufReader::new(MultiGzDecoder::new(file))),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&FaidxRecord> {
+        self.records.iter().find(|record| record.name == name)
+    }
+
+    /// Record and 0-based half-open range of a region, e.g. `chr1:1000-2000`.
+    ///
+    /// Names containing a colon are matched whole before being split into a region.
+    pub fn locate(&self, region: &str) -> anyhow::Result<(&FaidxRecord, Range<u64>)> {
+        let region = match self.get(region.trim()) {
+            Some(_) => Region::whole(region.trim()),
+            None => region.parse::<Region>()?,
+        };
+        let record = self
+            .get(&region.seqid)
+            .ok_or_else(|| anyhow!("sequence {} not found in the index", region.seqid))?;
+        Ok((record, region.range(record.length)?))
+    }
+}
+
+impl Import for FastaIndex {
+    type Output = FastaIndex;
+
+    fn import<S>(s: S) -> anyhow::Result<Self::Output>
+    where
+        S: AsRef<str>,
+    {
+        let records = s
+            .as_ref()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                if fields.len() < 5 {
+                    bail!("invalid .fai line '{line}', expected 5 columns");
+                }
+                let number = |i: usize| {
+                    fields[i]
+                        .trim()
+                        .parse::<u64>()
+                        .map_err(|_| anyhow!("invalid .fai line '{line}'"))
+                };
+                let record = FaidxRecord {
+                    name: fields[0].to_string(),
+                    length: number(1)?,
+                    offset: number(2)?,
+                    line_bases: number(3)?,
+                    line_width: number(4)?,
+                };
+                // Only empty sequences have no lines
+                if record.length > 0
+                    && (record.line_bases == 0 || record.line_width < record.line_bases)
+                {
+                    bail!("invalid .fai line '{line}', expected line width >= line bases > 0");
+                }
+                Ok(record)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(FastaIndex { records })
+    }
+}
+
+impl Export for FastaIndex {
+    type Output = String;
+
+    fn export(&self) -> Self::Output {
+        self.records
+            .iter()
+            .map(|r| {
+                format!(
+                    "{}\t{}\t{}\t{}\t{}\n",
+                    r.name, r.length, r.offset, r.line_bases, r.line_width
+                )
+            })
+            .collect()
+    }
+}
+
+/// Region of a sequence, written `chr1`, `chr1:1000` or `chr1:1,000-2,000` with 1-based
+/// closed positions, as in `samtools faidx`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub seqid: String,
+    /// First position, 1-based, the start of the sequence if none
+    pub start: Option<u64>,
+    /// Last position, 1-based and included, the end of the sequence if none
+    pub end: Option<u64>,
+}
+
+impl Region {
+    /// A whole sequence.
+    pub fn whole(seqid: &str) -> Self {
+        Region {
+            seqid: seqid.to_string(),
+            start: None,
+            end: None,
+        }
+    }
+
+    /// 0-based half-open range of the region in a sequence of `length` bases.
+    /// Ends past the sequence are clipped to it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let region: Region = "chr1:1,000-2,000".parse().unwrap();
+    /// assert_eq!(region.range(5000).unwrap(), 999..2000);
+    /// assert_eq!(region.range(1500).unwrap(), 999..1500);
+    /// assert!(region.range(500).is_err());
+    /// ```
+    pub fn range(&self, length: u64) -> anyhow::Result<Range<u64>> {
+        let start = self.start.unwrap_or(1);
+        let end = self.end.unwrap_or(length).min(length);
+        if start == 0 {
+            bail!("region {self} starts at 0, positions are 1-based");
+        }
+        if start > length {
+            bail!("region {self} starts past the end of the sequence ({length} bases)");
+        }
+        if end + 1 < start {
+            bail!("region {self} ends before it starts");
+        }
+        Ok(start - 1..end)
+    }
+}
+
+impl FromStr for Region {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let position = |p: &str| -> anyhow::Result<Option<u64>> {
+            let p = p.trim().replace(',', "");
+            match p.is_empty() {
+                true => Ok(None),
+                false => p
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| anyhow!("invalid position '{p}' in region {s}")),
+            }
+        };
+        let region = match s.rsplit_once(':') {
+            Some((seqid, range)) if !seqid.is_empty() => match range.split_once('-') {
+                Some((start, end)) => Region {
+                    seqid: seqid.to_string(),
+                    start: position(start)?,
+                    end: position(end)?,
+                },
+                None => Region {
+                    seqid: seqid.to_string(),
+                    start: position(range)?,
+                    end: None,
+                },
+            },
+            _ => Region::whole(s),
+        };
+        if region.seqid.is_empty() {
+            bail!("region has no sequence name");
+        }
+        Ok(region)
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.seqid)?;
+        match (self.start, self.end) {
+            (None, None) => Ok(()),
+            (start, None) => write!(f, ":{}", start.unwrap_or(1)),
+            (start, Some(end)) => write!(f, ":{}-{end}", start.unwrap_or(1)),
+        }
+    }
+}
+
+enum FastaSource<R> {
+    Plain(R),
+    Bgzf(BgzfReader<R>),
+}
+
+/// FASTA file read through its `.fai` index, only the bytes of the requested regions are read.
+///
+/// The file can be uncompressed or BGZF compressed, other gzip files cannot be read at random.
+///
+/// # Example
+/// ```rust
+/// use std::io::Cursor;
+/// use plasmid::prelude::*;
+///
+/// let fasta = ">chr1\nACGTACGTAC\nGGGGCCCCAA\nTT\n";
+/// let mut indexed = IndexedFasta::new(Cursor::new(fasta), None).unwrap();
+/// assert_eq!(indexed.fetch_region("chr1:9-12").unwrap(), "ACGG");
+///
+/// let mut indexed = IndexedFasta::new(Cursor::new(bgzf_compress(fasta.as_bytes())), None).unwrap();
+/// assert_eq!(indexed.fetch("chr1", 18..22).unwrap(), "AATT");
+/// ```
+pub struct IndexedFasta<R> {
+    source: FastaSource<R>,
+    index: FastaIndex,
+}
+
+impl<R> IndexedFasta<R>
+where
+    R: Read + Seek,
+{
+    /// Read an uncompressed or BGZF FASTA source with its index, built by reading the whole
+    /// source if none is given.
+    pub fn new(mut inner: R, index: Option<FastaIndex>) -> anyhow::Result<Self> {
+        let mut head = Vec::new();
+        (&mut inner).take(28).read_to_end(&mut head)?;
+        inner.seek(SeekFrom::Start(0))?;
+        match Compression::detect(&head) {
+            Compression::None => {
+                let index = match index {
+                    Some(index) => index,
+                    None => {
+                        let index = FastaIndex::build(BufReader::new(&mut inner))?;
+                        inner.seek(SeekFrom::Start(0))?;
+                        index
+                    }
+                };
+                Ok(IndexedFasta {
+                    source: FastaSource::Plain(inner),
+                    index,
+                })
+            }
+            Compression::Bgzf => Self::from_bgzf(BgzfReader::new(inner, None)?, index),
+            Compression::Gzip => {
+                bail!("gzip compressed FASTA files cannot be indexed, compress them with bgzip")
+            }
+        }
+    }
+
+    /// Read a BGZF FASTA source with its index, built by decompressing the whole source if none
+    /// is given.
+    pub fn from_bgzf(mut reader: BgzfReader<R>, index: Option<FastaIndex>) -> anyhow::Result<Self> {
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let mut data = Vec::new();
+                let mut offset = 0;
+                loop {
+                    let chunk = reader.read_at(offset, 1 << 20)?;
+                    if chunk.is_empty() {
+                        break;
+                    }
+                    offset += chunk.len() as u64;
+                    data.extend(chunk);
+                }
+                FastaIndex::build(data.as_slice())?
+            }
+        };
+        Ok(IndexedFasta {
+            source: FastaSource::Bgzf(reader),
+            index,
+        })
+    }
+
+    pub fn index(&self) -> &FastaIndex {
+        &self.index
+    }
+
+    /// BGZF reader of the source, if it is compressed.
+    pub fn bgzf(&self) -> Option<&BgzfReader<R>> {
+        match &self.source {
+            FastaSource::Plain(_) => None,
+            FastaSource::Bgzf(reader) => Some(reader),
+        }
+    }
+
+    /// Bases of a 0-based half-open range of sequence `seqid`.
+    pub fn fetch(&mut self, seqid: &str, range: Range<u64>) -> anyhow::Result<String> {
+        let record = self
+            .index
+            .get(seqid)
+            .ok_or_else(|| anyhow!("sequence {seqid} not found in the index"))?;
+        if range.end > record.length || range.start > range.end {
+            bail!(
+                "range {}..{} is out of sequence {seqid} ({} bases)",
+                range.start,
+                range.end,
+                record.length
+            );
+        }
+        if range.is_empty() {
+            return Ok(String::new());
+        }
+        let from = record.offset_of(range.start);
+        let len = (record.offset_of(range.end - 1) + 1 - from) as usize;
+        let bytes = match &mut self.source {
+            FastaSource::Plain(inner) => {
+                let mut bytes = Vec::with_capacity(len);
+                inner.seek(SeekFrom::Start(from))?;
+                inner.take(len as u64).read_to_end(&mut bytes)?;
+                bytes
+            }
+            FastaSource::Bgzf(reader) => reader.read_at(from, len)?,
+        };
+        let bases: Vec<u8> = bytes
+            .into_iter()
+            .filter(|&b| b != b'\n' && b != b'\r')
+            .collect();
+        if bases.len() as u64 != range.end - range.start {
+            bail!("sequence {seqid} is truncated, the index does not match the file");
+        }
+        Ok(String::from_utf8(bases)?)
+    }
+
+    /// Bases of a region, e.g. `chr1:1000-2000`.
+    pub fn fetch_region(&mut self, region: &str) -> anyhow::Result<String> {
+        let (record, range) = self.index.locate(region)?;
+        let seqid = record.name.to_owned();
+        self.fetch(&seqid, range)
+    }
+}
+
+impl IndexedFasta<std::fs::File> {
+    /// Open a FASTA file with its `<path>.fai` index, and `<path>.gzi` if it is BGZF compressed.
+    /// Missing indexes are built by reading the file.
+    pub fn open<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut fai = path.as_os_str().to_owned();
+        fai.push(".fai");
+        let index = match std::fs::read_to_string(fai) {
+            Ok(contents) => Some(FastaIndex::import(contents)?),
+            Err(_) => None,
+        };
+        let mut file = std::fs::File::open(path)?;
+        let mut head = Vec::new();
+        (&mut file).take(28).read_to_end(&mut head)?;
+        match Compression::detect(&head) {
+            Compression::Bgzf => {
+                let index = match index {
+                    Some(index) => index,
+                    None => FastaIndex::build_from_path(path)?,
+                };
+                Self::from_bgzf(BgzfReader::open(path)?, Some(index))
+            }
+            _ => {
+                file.seek(SeekFrom::Start(0))?;
+                Self::new(file, index)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{FastaIndex, IndexedFasta, Region};
+    use crate::{exp::Export, imp::bgzf_compress, imp::Import};
+
+    fn fasta() -> String {
+        let mut fasta = String::new();
+        for (name, seq) in [("chr1", "ACGT".repeat(30)), ("chr2", "TTGCA".repeat(7))] {
+            fasta += &format!(">{name} test\n");
+            for line in seq.as_bytes().chunks(60) {
+                fasta += std::str::from_utf8(line).unwrap();
+                fasta += "\r\n";
+            }
+        }
+        fasta
+    }
+
+    #[test]
+    fn test_build_index() {
+        let index = FastaIndex::build(fasta().as_bytes()).unwrap();
+        assert_eq!(
+            index.export(),
+            "chr1\t120\t11\t60\t62\nchr2\t35\t146\t35\t37\n"
+        );
+        assert_eq!(FastaIndex::import(index.export()).unwrap(), index);
+        assert!(FastaIndex::import("chr1\t120\t6\t0\t0\n").is_err());
+        assert!(FastaIndex::import("chr1\t120\t6\t60\t59\n").is_err());
+        assert!(FastaIndex::import("empty\t0\t7\t0\t0\n").is_ok());
+
+        let uneven = ">chr1\nACGT\nAC\nACGT\n";
+        assert!(FastaIndex::build(uneven.as_bytes()).is_err());
+        let longer = ">chr1\nACGT\nACGTA\n";
+        assert!(FastaIndex::build(longer.as_bytes()).is_err());
+        let trailing = ">chr1\nACGT\nAC\n\n>chr2\nA\n";
+        assert_eq!(
+            FastaIndex::build(trailing.as_bytes())
+                .unwrap()
+                .records
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_parse_region() {
+        let region: Region = "chr1:1000-2000".parse().unwrap();
+        assert_eq!(region.seqid, "chr1");
+        assert_eq!((region.start, region.end), (Some(1000), Some(2000)));
+        assert_eq!(region.to_string(), "chr1:1000-2000");
+        assert_eq!("chr1".parse::<Region>().unwrap(), Region::whole("chr1"));
+        assert_eq!(
+            "chr1:50".parse::<Region>().unwrap().range(120).unwrap(),
+            49..120
+        );
+        assert!("chr1:a-b".parse::<Region>().is_err());
+        assert!(" ".parse::<Region>().is_err());
+        assert!("chr1:0-5".parse::<Region>().unwrap().range(120).is_err());
+    }
+
+    #[test]
+    fn test_fetch() {
+        let fasta = fasta();
+        let seq = "ACGT".repeat(30);
+        for data in [fasta.clone().into_bytes(), bgzf_compress(fasta.as_bytes())] {
+            let mut indexed = IndexedFasta::new(Cursor::new(data), None).unwrap();
+            assert_eq!(indexed.fetch("chr1", 55..65).unwrap(), &seq[55..65]);
+            assert_eq!(indexed.fetch_region("chr1:60-61").unwrap(), "TA");
+            assert_eq!(indexed.fetch_region("chr1").unwrap(), seq);
+            assert_eq!(indexed.fetch_region("chr2:34-100").unwrap(), "CA");
+            assert_eq!(indexed.fetch("chr2", 3..3).unwrap(), "");
+            assert!(indexed.fetch("chr3", 0..1).is_err());
+            assert!(indexed.fetch("chr2", 0..36).is_err());
+        }
+    }
+
+    #[test]
+    fn test_colon_in_name() {
+        let fasta = ">HLA:1\nACGT\n";
+        let mut indexed = IndexedFasta::new(Cursor::new(fasta), None).unwrap();
+        assert_eq!(indexed.fetch_region("HLA:1").unwrap(), "ACGT");
+        assert_eq!(indexed.fetch_region("HLA:1:2-3").unwrap(), "CG");
+    }
+}
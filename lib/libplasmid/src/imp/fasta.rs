@@ -15,6 +15,14 @@ pub struct TypedFastaFile<Item> {
     pub sequence: Vec<Item>,
 }
 
+/// A single record of a multi-FASTA file.
+pub type FastaRecord = FastaFile;
+
+/// A FASTA file containing any number of records.
+pub struct MultiFastaFile {
+    pub records: Vec<FastaRecord>,
+}
+
 /// A FASTA file containing an IUB/IUPAC DNA sequence.
 pub type FastaIupacFile = TypedFastaFile<IupacNucleotide>;
 
@@ -57,6 +65,42 @@ impl Import for FastaFile {
     }
 }
 
+impl Import for MultiFastaFile {
+    type Output = Self;
+
+    /// Import a multi-FASTA file from a string.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    /// let fasta = MultiFastaFile::import(">chr1 first\nATGC\nAA\n>chr2\nGGCC").unwrap();
+    /// assert_eq!(fasta.records.len(), 2);
+    /// assert_eq!(fasta.records[0].id(), "chr1");
+    /// assert_eq!(fasta.records[0].sequence, "ATGCAA");
+    /// assert_eq!(fasta.records[1].sequence, "GGCC");
+    /// ```
+    fn import<S>(s: S) -> anyhow::Result<Self::Output>
+    where
+        S: AsRef<str>,
+    {
+        let mut records = Vec::new();
+        for line in s.as_ref().lines().map(|l| l.trim_end()) {
+            if let Some(stripped) = line.strip_prefix('>') {
+                records.push(FastaRecord {
+                    description: stripped.to_string(),
+                    sequence: String::new(),
+                });
+            } else if !line.is_empty() {
+                match records.last_mut() {
+                    Some(record) => record.sequence.push_str(line),
+                    None => bail!("Invalid FASTA file: sequence found before the first header"),
+                }
+            }
+        }
+        Ok(MultiFastaFile { records })
+    }
+}
+
 impl<Item> Import for TypedFastaFile<Item>
 where
     Item: TryFromLetter,
@@ -87,6 +131,11 @@ where
 }
 
 impl FastaFile {
+    /// The identifier of the record, the first word of its description.
+    pub fn id(&self) -> &str {
+        self.description.split_whitespace().next().unwrap_or("")
+    }
+
     fn as_sequence<S>(&self) -> anyhow::Result<Vec<S>>
     where
         S: TryFromLetter,
@@ -144,7 +193,7 @@ mod tests {
         prelude::{DnaNucleotide, Eaa, IupacNucleotide, RnaNucleotide},
     };
 
-    use super::{FastaFile, Import, TypedFastaFile};
+    use super::{FastaFile, Import, MultiFastaFile, TypedFastaFile};
 
     #[test]
     fn test_fasta_import_valid() -> anyhow::Result<()> {
@@ -160,6 +209,21 @@ mod tests {
         FastaFile::import(">test\n>test\nATGC").unwrap();
     }
 
+    #[test]
+    fn test_multi_fasta_import_valid() -> anyhow::Result<()> {
+        let fasta = MultiFastaFile::import("\n>a\nAT\n\nGC\n>b desc\n>c\nTT\n")?;
+        let ids = fasta.records.iter().map(|r| r.id()).collect::<Vec<_>>();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert_eq!(fasta.records[0].sequence, "ATGC");
+        assert!(fasta.records[1].sequence.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_fasta_import_missing_header() {
+        assert!(MultiFastaFile::import("ATGC\n>a\nAT").is_err());
+    }
+
     #[test]
     fn test_fasta_file_as_iupac_sequence_valid() -> anyhow::Result<()> {
         use crate::uni::IupacNucleotide::*;
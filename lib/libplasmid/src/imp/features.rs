@@ -0,0 +1,146 @@
+use std::ops::Range;
+
+use anyhow::bail;
+
+use super::FastaRecord;
+use crate::{
+    seq::{Annotation, GeneticSequence},
+    traits::{Codon, Nucleotide, ToIupac, ToLetter, TryFromLetter},
+};
+
+/// Convert 1-based closed coordinates, as in GFF3 or GenBank, to a 0-based half-open range,
+/// as in BED.
+///
+/// # Example
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// assert_eq!(one_based_to_half_open(1, 10).unwrap(), 0..10);
+/// assert!(one_based_to_half_open(0, 10).is_err());
+/// ```
+pub fn one_based_to_half_open(start: usize, end: usize) -> anyhow::Result<Range<usize>> {
+    if start == 0 {
+        bail!("1-based coordinates start at 1, not 0");
+    }
+    if end < start {
+        bail!("interval {start}-{end} ends before it starts");
+    }
+    Ok(start - 1..end)
+}
+
+/// Convert a 0-based half-open range to 1-based closed coordinates.
+///
+/// # Example
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// assert_eq!(half_open_to_one_based(0..10), (1, 10));
+/// ```
+pub fn half_open_to_one_based(range: Range<usize>) -> (usize, usize) {
+    (range.start + 1, range.end)
+}
+
+/// Inclusive bounds of an annotation covering a 0-based half-open range of a sequence.
+///
+/// On circular sequences the range may end past the last base, the bounds then wrap the origin.
+pub fn half_open_to_inclusive(
+    range: Range<usize>,
+    len: usize,
+    circular: bool,
+) -> anyhow::Result<(usize, usize)> {
+    if range.end <= range.start {
+        bail!("interval {}..{} is empty", range.start, range.end);
+    }
+    if range.start >= len {
+        bail!(
+            "interval {}..{} starts past the end of the sequence ({len} bases)",
+            range.start,
+            range.end
+        );
+    }
+    match range.end <= len {
+        true => Ok((range.start, range.end - 1)),
+        false if circular && range.end - len <= range.start => {
+            Ok((range.start, range.end - len - 1))
+        }
+        false => bail!(
+            "interval {}..{} ends past the end of the sequence ({len} bases)",
+            range.start,
+            range.end
+        ),
+    }
+}
+
+/// 0-based half-open range of inclusive annotation bounds, ending past `len` if they wrap the origin.
+pub fn inclusive_to_half_open(start: usize, end: usize, len: usize) -> Range<usize> {
+    match end < start {
+        true => start..end + 1 + len,
+        false => start..end + 1,
+    }
+}
+
+/// Features of sequences referred to by their seqid, e.g. the records of a GFF3 or BED file.
+pub trait SequenceFeatures {
+    /// Ids of the sequences with features, in their order of appearance.
+    fn seqids(&self) -> Vec<&str>;
+
+    /// Features of sequence `seqid` as annotations, given the length and topology of the sequence.
+    fn annotations(
+        &self,
+        seqid: &str,
+        len: usize,
+        circular: bool,
+    ) -> anyhow::Result<Vec<Annotation>>;
+
+    /// Attach the features of sequence `seqid` to a sequence, returning how many were added.
+    fn annotate<B, C>(&self, seqid: &str, seq: &mut GeneticSequence<B, C>) -> anyhow::Result<usize>
+    where
+        B: Nucleotide + TryFromLetter + ToLetter + ToIupac + Copy,
+        C: Codon<B>,
+    {
+        let annotations = self.annotations(seqid, seq.as_nucleotides().len(), seq.is_circular())?;
+        let added = annotations.len();
+        seq.as_mut_annotations().extend(annotations);
+        Ok(added)
+    }
+
+    /// Features of every FASTA record, matched by the record id.
+    fn attach<'a>(
+        &self,
+        records: &'a [FastaRecord],
+    ) -> anyhow::Result<Vec<(&'a FastaRecord, Vec<Annotation>)>> {
+        records
+            .iter()
+            .map(|record| {
+                let annotations = self.annotations(record.id(), record.sequence.len(), false)?;
+                Ok((record, annotations))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{half_open_to_inclusive, inclusive_to_half_open};
+
+    #[test]
+    fn test_half_open_to_inclusive() {
+        assert_eq!(half_open_to_inclusive(2..5, 10, false).unwrap(), (2, 4));
+        assert_eq!(half_open_to_inclusive(0..10, 10, false).unwrap(), (0, 9));
+        assert_eq!(half_open_to_inclusive(8..12, 10, true).unwrap(), (8, 1));
+        assert!(half_open_to_inclusive(8..12, 10, false).is_err());
+        assert!(half_open_to_inclusive(5..5, 10, false).is_err());
+        assert!(half_open_to_inclusive(10..11, 10, true).is_err());
+        assert!(half_open_to_inclusive(2..13, 10, true).is_err());
+    }
+
+    #[test]
+    fn test_inclusive_to_half_open() {
+        assert_eq!(inclusive_to_half_open(2, 4, 10), 2..5);
+        assert_eq!(inclusive_to_half_open(8, 1, 10), 8..12);
+        assert_eq!(
+            half_open_to_inclusive(inclusive_to_half_open(8, 1, 10), 10, true).unwrap(),
+            (8, 1)
+        );
+    }
+}
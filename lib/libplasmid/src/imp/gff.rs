@@ -0,0 +1,550 @@
+use std::{fmt, ops::Range};
+
+use anyhow::{anyhow, bail};
+
+use super::{
+    half_open_to_inclusive, inclusive_to_half_open, one_based_to_half_open, FastaRecord, Import,
+    MultiFastaFile, SequenceFeatures,
+};
+use crate::{
+    exp::Export,
+    seq::{Annotation, FeatureType, Strand},
+};
+
+/// Width of the sequence lines written in the `##FASTA` section.
+const GFF3_FASTA_WIDTH: usize = 60;
+
+/// A feature line of a GFF3 file.
+///
+/// `start` and `end` are 1-based and inclusive. Features of circular sequences that span the
+/// origin end past the length of the sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gff3Record {
+    pub seqid: String,
+    /// Program or database the feature comes from, `.` if unknown
+    pub source: String,
+    /// Feature type, e.g. `gene`, `mRNA` or `CDS`
+    pub kind: String,
+    pub start: usize,
+    pub end: usize,
+    pub score: Option<f64>,
+    pub strand: Strand,
+    /// Bases to skip to the first codon of a CDS segment
+    pub phase: Option<u8>,
+    /// Attributes in their original order, percent escapes decoded
+    pub attributes: Vec<(String, String)>,
+}
+
+/// A GFF3 file, with the sequences of its `##FASTA` section if it has one.
+///
+/// Lines sharing an `ID` are a single feature with several segments, e.g. the exons of a CDS.
+#[derive(Default)]
+pub struct Gff3File {
+    /// `##sequence-region` directives: seqid, 1-based start and end
+    pub regions: Vec<(String, usize, usize)>,
+    pub records: Vec<Gff3Record>,
+    pub sequences: Vec<FastaRecord>,
+}
+
+impl Gff3Record {
+    /// Value of the first attribute with the given key.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// 0-based half-open range of the feature.
+    pub fn range(&self) -> Range<usize> {
+        self.start - 1..self.end
+    }
+
+    fn parse(line: &str) -> anyhow::Result<Self> {
+        let columns = line.split('\t').collect::<Vec<_>>();
+        if columns.len() != 9 {
+            bail!("expected 9 tab separated columns, found {}", columns.len());
+        }
+        let position = |column: &str| {
+            column
+                .parse::<usize>()
+                .map_err(|_| anyhow!("invalid position '{column}'"))
+        };
+        let range = one_based_to_half_open(position(columns[3])?, position(columns[4])?)?;
+        let score = match columns[5] {
+            "." => None,
+            score => Some(
+                score
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("invalid score '{score}'"))?,
+            ),
+        };
+        let strand = match columns[6] {
+            "+" => Strand::Forward,
+            "-" => Strand::Reverse,
+            "." | "?" => Strand::Unknown,
+            strand => bail!("invalid strand '{strand}'"),
+        };
+        let phase = match columns[7] {
+            "." => None,
+            "0" => Some(0),
+            "1" => Some(1),
+            "2" => Some(2),
+            phase => bail!("invalid phase '{phase}'"),
+        };
+        let mut attributes = Vec::new();
+        for attribute in columns[8].split(';').map(str::trim) {
+            if attribute.is_empty() || attribute == "." {
+                continue;
+            }
+            let (key, value) = attribute
+                .split_once('=')
+                .ok_or_else(|| anyhow!("invalid attribute '{attribute}'"))?;
+            attributes.push((percent_decode(key), percent_decode(value)));
+        }
+        Ok(Gff3Record {
+            seqid: percent_decode(columns[0]),
+            source: percent_decode(columns[1]),
+            kind: percent_decode(columns[2]),
+            start: range.start + 1,
+            end: range.end,
+            score,
+            strand,
+            phase,
+            attributes,
+        })
+    }
+}
+
+impl fmt::Display for Gff3Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attributes = match self.attributes.is_empty() {
+            true => ".".to_string(),
+            false => self
+                .attributes
+                .iter()
+                .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+                .collect::<Vec<_>>()
+                .join(";"),
+        };
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            percent_encode(&self.seqid),
+            percent_encode(&self.source),
+            percent_encode(&self.kind),
+            self.start,
+            self.end,
+            self.score.map_or(".".to_string(), |s| s.to_string()),
+            match self.strand {
+                Strand::Forward => "+",
+                Strand::Reverse => "-",
+                Strand::Unknown => ".",
+            },
+            self.phase.map_or(".".to_string(), |p| p.to_string()),
+            attributes,
+        )
+    }
+}
+
+/// Replace `%XX` escapes of a GFF3 column by the bytes they stand for.
+fn percent_decode(text: &str) -> String {
+    if !text.contains('%') {
+        return text.to_string();
+    }
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Escape the characters with a meaning in GFF3 columns. Commas are kept as they separate the
+/// values of multi-valued attributes.
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            ';' | '=' | '&' | '%' => encoded += &format!("%{:02X}", c as u8),
+            c if c.is_ascii_control() => encoded += &format!("%{:02X}", c as u8),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+impl Gff3File {
+    /// Add the annotations of sequence `seqid` of length `len` as features.
+    ///
+    /// Joined annotations become one line per segment sharing an `ID`, and the phase of every CDS
+    /// segment is derived from its offset in the coding sequence.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let cds = Annotation::new(2, 10, None, "orf")
+    ///     .with_kind(FeatureType::Cds)
+    ///     .with_strand(Strand::Forward);
+    /// let mut gff = Gff3File::default();
+    /// gff.add_annotations("pUC19", 2686, &[cds]);
+    /// assert_eq!(
+    ///     gff.records[0].to_string(),
+    ///     "pUC19\t.\tCDS\t3\t11\t.\t+\t0\tName=orf"
+    /// );
+    /// ```
+    pub fn add_annotations(&mut self, seqid: &str, len: usize, annotations: &[Annotation]) {
+        self.regions.push((seqid.to_string(), 1, len));
+        for (i, annotation) in annotations.iter().enumerate() {
+            let spans = annotation.spans();
+            let mut attributes = annotation.qualifiers.clone();
+            let named = annotation.qualifier("Name").is_some()
+                || annotation.qualifier("ID") == Some(annotation.text.as_str());
+            if !named && !annotation.text.is_empty() {
+                attributes.insert(0, ("Name".to_string(), annotation.text.to_owned()));
+            }
+            if annotation.qualifier("ID").is_none() && spans.len() > 1 {
+                let id = format!("{}{}", annotation.kind.as_str(), i + 1);
+                attributes.insert(0, ("ID".to_string(), id));
+            }
+
+            // Phases follow the direction of transcription
+            let is_cds = annotation.kind == FeatureType::Cds;
+            let mut phases = vec![None; spans.len()];
+            if is_cds {
+                let mut order = (0..spans.len()).collect::<Vec<_>>();
+                if annotation.strand == Strand::Reverse {
+                    order.reverse();
+                }
+                let codon_start = annotation
+                    .qualifier("codon_start")
+                    .and_then(|c| c.trim().parse::<usize>().ok())
+                    .filter(|c| (1..=3).contains(c))
+                    .unwrap_or(1);
+                let mut offset = 0;
+                for index in order {
+                    let phase = (3 - (offset + 3 - (codon_start - 1) % 3) % 3) % 3;
+                    phases[index] = Some(phase as u8);
+                    let (start, end) = spans[index];
+                    offset += inclusive_to_half_open(start, end, len).len();
+                }
+            }
+
+            for ((start, end), phase) in spans.into_iter().zip(phases) {
+                let range = inclusive_to_half_open(start, end, len);
+                self.records.push(Gff3Record {
+                    seqid: seqid.to_string(),
+                    source: ".".to_string(),
+                    kind: annotation.kind.as_str().to_string(),
+                    start: range.start + 1,
+                    end: range.end,
+                    score: None,
+                    strand: annotation.strand,
+                    phase,
+                    attributes: attributes.clone(),
+                });
+            }
+        }
+    }
+}
+
+impl Import for Gff3File {
+    type Output = Self;
+
+    /// Import a GFF3 file from a string.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let gff = Gff3File::import("##gff-version 3\nchr1\t.\tgene\t10\t20\t.\t-\t.\tID=gene1;Name=lacZ%3Balpha").unwrap();
+    /// assert_eq!(gff.records[0].range(), 9..20);
+    /// assert_eq!(gff.records[0].attribute("Name"), Some("lacZ;alpha"));
+    /// ```
+    fn import<S>(s: S) -> anyhow::Result<Self::Output>
+    where
+        S: AsRef<str>,
+    {
+        let mut gff = Gff3File::default();
+        let mut lines = s.as_ref().lines().enumerate();
+        for (number, line) in lines.by_ref() {
+            let line = line.trim_end_matches('\r');
+            if line == "##FASTA" || line.starts_with('>') {
+                let fasta = match line.starts_with('>') {
+                    true => format!("{line}\n"),
+                    false => String::new(),
+                };
+                let rest = lines.map(|(_, l)| l).collect::<Vec<_>>().join("\n");
+                gff.sequences = MultiFastaFile::import(fasta + &rest)?.records;
+                break;
+            }
+            if let Some(region) = line.strip_prefix("##sequence-region") {
+                let fields = region.split_whitespace().collect::<Vec<_>>();
+                let bounds = fields
+                    .get(1..3)
+                    .and_then(|b| Some((b[0].parse().ok()?, b[1].parse().ok()?)));
+                match (fields.first(), bounds) {
+                    (Some(seqid), Some((start, end))) => {
+                        gff.regions.push((seqid.to_string(), start, end))
+                    }
+                    _ => bail!("line {}: invalid sequence region '{line}'", number + 1),
+                }
+            } else if !line.starts_with('#') && !line.trim().is_empty() {
+                let record =
+                    Gff3Record::parse(line).map_err(|e| anyhow!("line {}: {e}", number + 1))?;
+                gff.records.push(record);
+            }
+        }
+        Ok(gff)
+    }
+}
+
+impl SequenceFeatures for Gff3File {
+    fn seqids(&self) -> Vec<&str> {
+        let mut seqids: Vec<&str> = Vec::new();
+        for record in self.records.iter() {
+            if !seqids.contains(&record.seqid.as_str()) {
+                seqids.push(&record.seqid);
+            }
+        }
+        seqids
+    }
+
+    /// Features of sequence `seqid` as annotations, lines sharing an `ID` joined into segments.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let gff = Gff3File::import(concat!(
+    ///     "chr1\t.\tCDS\t1\t3\t.\t+\t0\tID=cds1;Name=orf\n",
+    ///     "chr1\t.\tCDS\t7\t12\t.\t+\t0\tID=cds1;Name=orf\n",
+    /// )).unwrap();
+    /// let mut seq = DnaSequence::from_str("ATGCCCGGGTAA").unwrap();
+    /// assert_eq!(gff.annotate("chr1", &mut seq).unwrap(), 1);
+    /// let orf = &seq.as_annotations()[0];
+    /// assert_eq!((orf.start, orf.end), (0, 11));
+    /// assert_eq!(orf.segments, [(0, 2), (6, 11)]);
+    /// ```
+    fn annotations(
+        &self,
+        seqid: &str,
+        len: usize,
+        circular: bool,
+    ) -> anyhow::Result<Vec<Annotation>> {
+        // Records of the sequence, grouped by ID in their order of appearance
+        let mut features: Vec<Vec<&Gff3Record>> = Vec::new();
+        for record in self.records.iter().filter(|r| r.seqid == seqid) {
+            let id = record.attribute("ID");
+            match features
+                .iter_mut()
+                .find(|f| id.is_some() && f[0].attribute("ID") == id)
+            {
+                Some(feature) => feature.push(record),
+                None => features.push(vec![record]),
+            }
+        }
+
+        let mut annotations = Vec::with_capacity(features.len());
+        for mut feature in features {
+            feature.sort_by_key(|r| r.start);
+            let spans = feature
+                .iter()
+                .map(|r| half_open_to_inclusive(r.range(), len, circular))
+                .collect::<anyhow::Result<Vec<_>>>()
+                .map_err(|e| anyhow!("{seqid}: {e}"))?;
+            let first = feature[0];
+            let text = ["Name", "ID", "gene"]
+                .iter()
+                .find_map(|key| first.attribute(key))
+                .unwrap_or(&first.kind);
+            let mut annotation = Annotation::new(spans[0].0, spans[spans.len() - 1].1, None, text)
+                .with_kind(FeatureType::from(first.kind.as_str()))
+                .with_strand(first.strand);
+            if spans.len() > 1 {
+                annotation = annotation.with_segments(&spans);
+            }
+            annotation.qualifiers = first.attributes.clone();
+            annotations.push(annotation);
+        }
+        Ok(annotations)
+    }
+}
+
+impl Export for Gff3File {
+    type Output = String;
+
+    fn export(&self) -> String {
+        let mut gff = "##gff-version 3\n".to_string();
+        for (seqid, start, end) in self.regions.iter() {
+            gff += &format!(
+                "##sequence-region {} {start} {end}\n",
+                percent_encode(seqid)
+            );
+        }
+        for record in self.records.iter() {
+            gff += &record.to_string();
+            gff.push('\n');
+        }
+        if !self.sequences.is_empty() {
+            gff += "##FASTA\n";
+            for record in self.sequences.iter() {
+                gff += &format!(">{}\n", record.description);
+                let bases = record.sequence.as_bytes();
+                for line in bases.chunks(GFF3_FASTA_WIDTH) {
+                    gff += &String::from_utf8_lossy(line);
+                    gff.push('\n');
+                }
+            }
+        }
+        gff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{percent_decode, percent_encode, Gff3File};
+    use crate::{
+        exp::Export,
+        imp::{Import, SequenceFeatures},
+        seq::{Annotation, FeatureType, Strand},
+    };
+
+    const GFF3: &str = "##gff-version 3
+##sequence-region ctg1 1 30
+ctg1\tsrc\tgene\t2\t28\t.\t-\t.\tID=gene1;Name=abcD;Note=two%2C words
+ctg1\tsrc\tCDS\t2\t7\t.\t-\t2\tID=cds1;Parent=gene1
+ctg1\tsrc\tCDS\t12\t28\t.\t-\t0\tID=cds1;Parent=gene1
+# comment
+ctg2\tsrc\tpromoter\t5\t9\t0.5\t+\t.\t.
+##FASTA
+>ctg1 first contig
+ATGCATGCATGCATGCATGCATGCATGCAT
+>ctg2
+GGGGGCCCCC
+";
+
+    #[test]
+    fn test_gff3_import() {
+        let gff = Gff3File::import(GFF3).unwrap();
+        assert_eq!(gff.regions, [("ctg1".to_string(), 1, 30)]);
+        assert_eq!(gff.records.len(), 4);
+        assert_eq!(gff.records[0].attribute("Note"), Some("two, words"));
+        assert_eq!(gff.records[1].phase, Some(2));
+        assert_eq!(gff.records[3].score, Some(0.5));
+        assert!(gff.records[3].attributes.is_empty());
+        assert_eq!(gff.sequences.len(), 2);
+        assert_eq!(gff.sequences[0].id(), "ctg1");
+        assert_eq!(gff.sequences[1].sequence, "GGGGGCCCCC");
+        assert_eq!(gff.seqids(), ["ctg1", "ctg2"]);
+
+        assert!(Gff3File::import("ctg1\tsrc\tgene\t0\t5\t.\t+\t.\t.").is_err());
+        assert!(Gff3File::import("ctg1\tsrc\tgene\t1\t5\t.\t+\t.").is_err());
+        assert!(Gff3File::import("ctg1\tsrc\tgene\t1\t5\t.\tx\t.\t.").is_err());
+    }
+
+    #[test]
+    fn test_gff3_annotations() {
+        let gff = Gff3File::import(GFF3).unwrap();
+        let attached = gff.attach(&gff.sequences).unwrap();
+        let (record, annotations) = &attached[0];
+        assert_eq!(record.id(), "ctg1");
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].text, "abcD");
+        assert_eq!(annotations[0].kind, FeatureType::Gene);
+        assert_eq!((annotations[0].start, annotations[0].end), (1, 27));
+        assert_eq!(annotations[1].text, "cds1");
+        assert_eq!(annotations[1].strand, Strand::Reverse);
+        assert_eq!(annotations[1].segments, [(1, 6), (11, 27)]);
+        assert_eq!(annotations[1].qualifier("Parent"), Some("gene1"));
+        assert_eq!(attached[1].1[0].kind, FeatureType::Promoter);
+
+        assert!(gff.annotations("ctg1", 20, false).is_err());
+        assert!(gff.annotations("ctg3", 20, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_gff3_export() {
+        let gff = Gff3File::import(GFF3).unwrap();
+        let exported = gff.export();
+        assert!(exported.starts_with("##gff-version 3\n##sequence-region ctg1 1 30\n"));
+        assert!(exported.contains("Note=two, words"));
+        assert!(exported.contains("ctg2\tsrc\tpromoter\t5\t9\t0.5\t+\t.\t.\n"));
+        assert!(exported.ends_with(
+            "##FASTA\n>ctg1 first contig\nATGCATGCATGCATGCATGCATGCATGCAT\n>ctg2\nGGGGGCCCCC\n"
+        ));
+
+        let reimported = Gff3File::import(&exported).unwrap();
+        assert_eq!(reimported.records, gff.records);
+    }
+
+    #[test]
+    fn test_gff3_from_annotations() {
+        // A CDS in two exons on the reverse strand and a feature across the origin
+        let cds = Annotation::new(2, 16, None, "orf")
+            .with_kind(FeatureType::Cds)
+            .with_strand(Strand::Reverse)
+            .with_segments(&[(2, 5), (10, 16)]);
+        let ori = Annotation::new(18, 1, None, "ori").with_kind(FeatureType::RepOrigin);
+        let mut gff = Gff3File::default();
+        gff.add_annotations("p1", 20, &[cds, ori]);
+        let lines = gff
+            .records
+            .iter()
+            .map(|r| r.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "p1\t.\tCDS\t3\t6\t.\t-\t2\tID=CDS1;Name=orf",
+                "p1\t.\tCDS\t11\t17\t.\t-\t0\tID=CDS1;Name=orf",
+                "p1\t.\trep_origin\t19\t22\t.\t.\t.\tName=ori",
+            ]
+        );
+
+        let annotations = gff.annotations("p1", 20, true).unwrap();
+        assert_eq!(annotations[0].segments, [(2, 5), (10, 16)]);
+        assert_eq!((annotations[1].start, annotations[1].end), (18, 1));
+        assert!(gff.annotations("p1", 20, false).is_err());
+    }
+
+    #[test]
+    fn test_gff3_codon_start() {
+        let cds = |codon_start: &str| {
+            Annotation::new(0, 8, None, "orf")
+                .with_kind(FeatureType::Cds)
+                .with_qualifier("codon_start", codon_start)
+        };
+        let mut gff = Gff3File::default();
+        gff.add_annotations(
+            "p1",
+            20,
+            &[cds("2"), cds("3"), cds("0"), cds("4"), cds("x")],
+        );
+        let phases = gff.records.iter().map(|r| r.phase).collect::<Vec<_>>();
+        // Values outside 1..=3 fall back to 1
+        assert_eq!(phases, [Some(1), Some(2), Some(0), Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn test_percent_escapes() {
+        assert_eq!(percent_encode("a;b=c%\td,e"), "a%3Bb%3Dc%25%09d,e");
+        assert_eq!(percent_decode("a%3Bb%3dc%25%09d,e"), "a;b=c%\td,e");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -5,12 +5,18 @@ pub trait Import {
     where
         S: AsRef<str>;
 
+    /// Import raw file contents, text formats must be UTF-8. Gzip and BGZF compressed
+    /// contents are decompressed first.
+    fn import_bytes(bytes: &[u8]) -> anyhow::Result<Self::Output> {
+        Self::import(std::str::from_utf8(&super::decompress(bytes)?)?)
+    }
+
     fn import_from_file(file: std::fs::File) -> anyhow::Result<Self::Output> {
         use std::io::Read;
         let mut reader = std::io::BufReader::new(file);
-        let mut contents = String::new();
-        reader.read_to_string(&mut contents)?;
-        Self::import(contents)
+        let mut contents = Vec::new();
+        reader.read_to_end(&mut contents)?;
+        Self::import_bytes(&contents)
     }
 
     fn import_from_path<P>(path: P) -> anyhow::Result<Self::Output>
@@ -0,0 +1,337 @@
+use super::Import;
+use crate::{
+    traits::TryFromLetter,
+    uni::{CutMode, IupacNucleotide, Methylation, MethylationSite, RestrictionEnzyme},
+};
+
+/// A restriction enzyme database in one of the REBASE distribution formats.
+///
+/// Enzymes without a known cleavage position can not be used for digests and
+/// are skipped. Enzymes cutting on both sides of their site are reduced to
+/// their first pair of cuts.
+pub struct RebaseFile {
+    pub enzymes: Vec<RestrictionEnzyme>,
+}
+
+impl Import for RebaseFile {
+    type Output = Self;
+
+    /// Import a REBASE `withrefm` or EMBOSS `emboss_e` file from a string,
+    /// detecting the format by its `<1>` field tags.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let withrefm = "<1>BsaI\n<2>Eco31I\n<3>GGTCTC(1/5)\n<4>\n<5>Bacillus stearothermophilus 6-55\n<6>NEB\n<7>N\n<8>\n";
+    /// let rebase = RebaseFile::import(withrefm).unwrap();
+    /// assert_eq!(rebase.enzymes[0].name, "BsaI");
+    /// assert_eq!(rebase.enzymes[0].cut_offsets(), Some((7, 11)));
+    /// assert_eq!(rebase.enzymes[0].suppliers, ['N']);
+    /// ```
+    fn import<S>(s: S) -> anyhow::Result<Self::Output>
+    where
+        S: AsRef<str>,
+    {
+        let s = s.as_ref();
+        if s.lines().any(|line| line.starts_with("<1>")) {
+            Self::import_withrefm(s)
+        } else {
+            Self::import_emboss(s, None)
+        }
+    }
+}
+
+impl RebaseFile {
+    /// Import a REBASE `withrefm` file, including methylation sensitivity
+    /// and commercial suppliers.
+    pub fn import_withrefm(s: &str) -> anyhow::Result<Self> {
+        let mut enzymes = Vec::new();
+        let mut fields: Vec<String> = Vec::new();
+        let mut flush = |fields: &mut Vec<String>| -> anyhow::Result<()> {
+            if !fields.is_empty() {
+                let field = |i: usize| fields.get(i).map(|f| f.trim()).unwrap_or("");
+                if let Some(mut enzyme) = parse_enzyme(field(0), field(2))? {
+                    enzyme.methylation = parse_methylation(field(3))?;
+                    enzyme.suppliers = field(6).chars().filter(|c| c.is_alphabetic()).collect();
+                    enzymes.push(enzyme);
+                }
+                fields.clear();
+            }
+            Ok(())
+        };
+        for line in s.lines().map(|l| l.trim_end()) {
+            match tag(line) {
+                Some((1, value)) => {
+                    flush(&mut fields)?;
+                    fields.push(value.to_string());
+                }
+                Some((n, value)) if !fields.is_empty() => {
+                    fields.resize(n.max(fields.len()), String::new());
+                    fields[n - 1] = value.to_string();
+                }
+                // Header text before the first entry and reference lines
+                _ => {}
+            }
+        }
+        flush(&mut fields)?;
+        Ok(Self { enzymes })
+    }
+
+    /// Import an EMBOSS `emboss_e` file. Methylation sensitivity and suppliers
+    /// are taken from the matching `emboss_r` file, if given.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let emboss_e = "# REBASE version 306\nEcoRI\tGAATTC\t6\t2\t0\t1\t5\t0\t0\n";
+    /// let emboss_r = "EcoRI\nEscherichia coli RY13\n\n3(6)\nNEB\nBFN\n0\n//\n";
+    /// let rebase = RebaseFile::import_emboss(emboss_e, Some(emboss_r)).unwrap();
+    /// assert_eq!(rebase.enzymes[0].cut_offsets(), Some((1, 5)));
+    /// assert_eq!(rebase.enzymes[0].suppliers, ['B', 'F', 'N']);
+    /// ```
+    pub fn import_emboss(e: &str, r: Option<&str>) -> anyhow::Result<Self> {
+        let mut enzymes = Vec::new();
+        for line in e.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.len() < 7 {
+                bail!("Invalid emboss_e line: {}", line);
+            }
+            let number = |i: usize| -> anyhow::Result<isize> {
+                fields[i]
+                    .parse::<isize>()
+                    .map_err(|_| anyhow!("Invalid emboss_e line: {}", line))
+            };
+            if number(3)? == 0 {
+                continue;
+            }
+            // EMBOSS counts bases from 1 with no position 0, cuts lie after the given base
+            let offset = |cut: isize| if cut < 0 { cut + 1 } else { cut };
+            let cuts = (offset(number(5)?), offset(number(6)?));
+            enzymes.push(enzyme_with_cuts(fields[0], &parse_site(fields[1])?, cuts));
+        }
+
+        if let Some(r) = r {
+            for entry in r.split("//") {
+                let lines = entry
+                    .lines()
+                    .map(|l| l.trim_end())
+                    .skip_while(|l| l.trim().is_empty() || l.starts_with('#'))
+                    .collect::<Vec<_>>();
+                let Some(name) = lines.first() else {
+                    continue;
+                };
+                let field = |i: usize| lines.get(i).map(|f| f.trim()).unwrap_or("");
+                if let Some(enzyme) = enzymes.iter_mut().find(|e| e.name == name.trim()) {
+                    enzyme.methylation = parse_methylation(field(3))?;
+                    enzyme.suppliers = field(5).chars().filter(|c| c.is_alphabetic()).collect();
+                }
+            }
+        }
+        Ok(Self { enzymes })
+    }
+}
+
+/// Split a `withrefm` line into its field number and value.
+fn tag(line: &str) -> Option<(usize, &str)> {
+    let rest = line.strip_prefix('<')?;
+    let (n, value) = rest.split_once('>')?;
+    let n = n.parse::<usize>().ok().filter(|n| (1..=8).contains(n))?;
+    Some((n, value))
+}
+
+fn parse_site(site: &str) -> anyhow::Result<Vec<IupacNucleotide>> {
+    site.chars()
+        .map(|c| IupacNucleotide::try_from_letter(c.to_ascii_uppercase()))
+        .collect()
+}
+
+/// Parse a cut offset pair like `(1/5)`.
+fn parse_cut_pair(pair: &str) -> anyhow::Result<(isize, isize)> {
+    let invalid = || anyhow!("Invalid REBASE cut positions: {}", pair);
+    let (top, bottom) = pair
+        .trim_start_matches('(')
+        .trim_end_matches(')')
+        .split_once('/')
+        .ok_or_else(invalid)?;
+    Ok((
+        top.trim().parse().map_err(|_| invalid())?,
+        bottom.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+/// Parse a REBASE recognition sequence like `G^AATTC`, `GGTCTC(1/5)` or
+/// `(8/13)GCANNNNNTGC(12/7)`, returning `None` if the cleavage is unknown.
+fn parse_enzyme(name: &str, recognition: &str) -> anyhow::Result<Option<RestrictionEnzyme>> {
+    if name.is_empty() || recognition.is_empty() || recognition.contains('?') {
+        return Ok(None);
+    }
+    let (leading, rest) = match recognition.strip_prefix('(') {
+        Some(rest) => {
+            let (pair, rest) = rest
+                .split_once(')')
+                .ok_or_else(|| anyhow!("Invalid REBASE recognition sequence: {}", recognition))?;
+            (Some(parse_cut_pair(pair)?), rest)
+        }
+        None => (None, recognition),
+    };
+    let (site, trailing) = match rest.split_once('(') {
+        Some((site, pair)) => (site, Some(parse_cut_pair(pair)?)),
+        None => (rest, None),
+    };
+    let caret = site.find('^');
+    let site = parse_site(&site.replace('^', ""))?;
+    let n = site.len() as isize;
+
+    let cuts = match (caret, leading, trailing) {
+        (Some(caret), _, _) => (caret as isize, n - caret as isize),
+        (None, Some((top, bottom)), _) => (-top, -bottom),
+        (None, None, Some((top, bottom))) => (n + top, n + bottom),
+        (None, None, None) => return Ok(None),
+    };
+    Ok(Some(enzyme_with_cuts(name, &site, cuts)))
+}
+
+/// Create an enzyme from its top/bottom strand cuts. REBASE names nicking enzymes
+/// `Nt.` or `Nb.` after the cut strand, with the nick given as top strand cut.
+fn enzyme_with_cuts(
+    name: &str,
+    site: &[IupacNucleotide],
+    (top, bottom): (isize, isize),
+) -> RestrictionEnzyme {
+    let len = site.len() as isize;
+    let (top, bottom, mode) = if name.starts_with("Nt.") {
+        (Some(top), None, CutMode::N)
+    } else if name.starts_with("Nb.") {
+        (None, Some(top), CutMode::N)
+    } else if [top, bottom].iter().any(|cut| *cut < 0 || *cut > len) {
+        (Some(top), Some(bottom), CutMode::A)
+    } else {
+        (Some(top), Some(bottom), CutMode::M)
+    };
+    RestrictionEnzyme::new_with_cuts(name, site, top, bottom, mode)
+}
+
+/// Parse a methylation field like `2(5),-4(6)`.
+fn parse_methylation(field: &str) -> anyhow::Result<Vec<MethylationSite>> {
+    field
+        .split(',')
+        .map(|m| m.trim())
+        .filter(|m| !m.is_empty())
+        .map(|m| {
+            let invalid = || anyhow!("Invalid REBASE methylation site: {}", m);
+            let (position, code) = m
+                .trim_end_matches(')')
+                .split_once('(')
+                .ok_or_else(invalid)?;
+            Ok(MethylationSite {
+                position: position.parse().map_err(|_| invalid())?,
+                methylation: code
+                    .parse()
+                    .ok()
+                    .and_then(Methylation::from_code)
+                    .ok_or_else(invalid)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RebaseFile;
+    use crate::{
+        imp::Import,
+        uni::{CutMode, Methylation},
+    };
+
+    const WITHREFM: &str =
+        "REBASE version 306                                              withrefm.306
+
+REBASE codes for commercial sources of enzymes
+
+                B        Thermo Fisher Scientific
+                N        New England Biolabs
+
+<1>AloI
+<2>
+<3>(7/12)GAACNNNNNNTCC(12/7)
+<4>
+<5>Acinetobacter lwoffi
+<6>Ruta Kiaulakiene
+<7>F
+<8>Cesnaviciene, E., Petrusyte, M., Kazlauskiene, R., Maneliene, Z., Timinskas, A.,
+Lubys, A., Janulaitis, A., (2001) J. Mol. Biol., vol. 314, pp. 205-216.
+
+<1>EcoRI
+<2>
+<3>G^AATTC
+<4>3(6)
+<5>Escherichia coli RY13
+<6>R.N. Yoshimori
+<7>BFN
+<8>
+
+<1>M.EcoRII
+<2>
+<3>?
+<4>
+<5>
+<6>
+<7>
+<8>
+
+<1>Nb.BsmI
+<2>
+<3>GAATG^C
+<4>
+<5>Bacillus stearothermophilus NUB 36
+<6>NEB
+<7>N
+<8>
+";
+
+    #[test]
+    fn test_import_withrefm() {
+        let rebase = RebaseFile::import(WITHREFM).unwrap();
+        let names = rebase
+            .enzymes
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["AloI", "EcoRI", "Nb.BsmI"]);
+
+        let aloi = &rebase.enzymes[0];
+        assert_eq!(aloi.mode(), &CutMode::A);
+        assert_eq!(aloi.cut_offsets(), Some((-7, -12)));
+
+        let ecori = &rebase.enzymes[1];
+        assert_eq!(ecori.cut_offsets(), Some((1, 5)));
+        assert_eq!(ecori.methylation.len(), 1);
+        assert_eq!(ecori.methylation[0].position, 3);
+        assert_eq!(ecori.methylation[0].methylation, Methylation::N6Adenine);
+        assert_eq!(ecori.suppliers, ['B', 'F', 'N']);
+
+        let nb = &rebase.enzymes[2];
+        assert!(nb.is_nicking());
+        assert_eq!(nb.mode(), &CutMode::N);
+    }
+
+    #[test]
+    fn test_import_emboss() {
+        let emboss_e = "# comment\nAloI\tGAACNNNNNNTCC\t13\t4\t0\t-8\t-13\t25\t20\nFooI\tGATC\t4\t0\t0\t0\t0\t0\t0\n";
+        let rebase = RebaseFile::import(emboss_e).unwrap();
+        assert_eq!(rebase.enzymes.len(), 1);
+        assert_eq!(rebase.enzymes[0].cut_offsets(), Some((-7, -12)));
+        assert!(RebaseFile::import("EcoRI\tGAATTC\n").is_err());
+    }
+
+    #[test]
+    fn test_import_withrefm_invalid_methylation() {
+        let withrefm = "<1>EcoRI\n<3>G^AATTC\n<4>3(7)\n";
+        assert!(RebaseFile::import(withrefm).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+
+use super::{decompress, Import};
+use crate::seq::{Annotation, DnaSequence, FeatureType, Strand, Topology};
+
+/// Packet types of a SnapGene file.
+const COOKIE_PACKET: u8 = 0x09;
+const DNA_PACKET: u8 = 0x00;
+const PRIMERS_PACKET: u8 = 0x05;
+const FEATURES_PACKET: u8 = 0x0a;
+
+/// A SnapGene `.dna` file, imported as a `DnaSequence` with its features and primer binding sites.
+///
+/// The file is a series of packets, each a type byte and a big endian length followed by its
+/// contents. Besides the sequence only the features and primers are read, other packets such as
+/// notes, history or enzyme sets are skipped.
+pub struct SnapGeneFile;
+
+/// A start, end or empty tag of an XML document with its attributes.
+struct XmlTag {
+    name: String,
+    attributes: HashMap<String, String>,
+    /// `</name>`
+    closing: bool,
+}
+
+impl XmlTag {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(|v| v.as_str())
+    }
+}
+
+/// Replace XML character and entity references.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => match entity.strip_prefix("#x") {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity
+                    .strip_prefix('#')
+                    .and_then(|dec| dec.parse().ok())
+                    .and_then(char::from_u32),
+            },
+        };
+        match c {
+            Some(c) => {
+                unescaped.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Text of a qualifier value without the HTML markup SnapGene wraps notes in.
+fn strip_markup(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => stripped.push(c),
+            _ => {}
+        }
+    }
+    unescape(stripped.trim())
+}
+
+/// Tags of an XML document in order, skipping text, comments and declarations.
+fn xml_tags(xml: &str) -> anyhow::Result<Vec<XmlTag>> {
+    let mut tags = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        for (open, close) in [("!--", "-->"), ("![CDATA[", "]]>"), ("?", "?>")] {
+            if rest.starts_with(open) {
+                let end = rest
+                    .find(close)
+                    .ok_or_else(|| anyhow!("unterminated XML {open}"))?;
+                rest = &rest[end + close.len()..];
+            }
+        }
+        if !rest.starts_with(|c: char| c.is_alphabetic() || c == '/') {
+            continue;
+        }
+
+        // Find the end of the tag outside of quoted attribute values
+        let mut quote = None;
+        let end = rest
+            .char_indices()
+            .find(|(_, c)| match (quote, *c) {
+                (None, '"' | '\'') => {
+                    quote = Some(*c);
+                    false
+                }
+                (Some(q), c) if q == c => {
+                    quote = None;
+                    false
+                }
+                (None, '>') => true,
+                _ => false,
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(|| anyhow!("unterminated XML tag"))?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        let closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/').trim_end_matches('/');
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut attributes = HashMap::new();
+        let mut attrs = &tag[name_end..];
+        while let Some(eq) = attrs.find('=') {
+            let name = attrs[..eq].trim().to_string();
+            let value = attrs[eq + 1..].trim_start();
+            let Some(q) = value.chars().next().filter(|q| *q == '"' || *q == '\'') else {
+                bail!("unquoted XML attribute {name}");
+            };
+            let value_end = value[1..]
+                .find(q)
+                .ok_or_else(|| anyhow!("unterminated XML attribute {name}"))?;
+            attributes.insert(name, unescape(&value[1..value_end + 1]));
+            attrs = &value[value_end + 2..];
+        }
+        tags.push(XmlTag {
+            name: tag[..name_end].to_string(),
+            attributes,
+            closing,
+        });
+    }
+    Ok(tags)
+}
+
+/// 0-based inclusive bounds of a 1-based SnapGene range such as `1-861`.
+fn parse_range(range: &str, len: usize) -> anyhow::Result<(usize, usize)> {
+    let (start, end) = range
+        .split_once('-')
+        .ok_or_else(|| anyhow!("invalid SnapGene range {range}"))?;
+    let (start, end) = (start.trim().parse::<usize>()?, end.trim().parse::<usize>()?);
+    if start == 0 || end == 0 || start > len || end > len {
+        bail!("SnapGene range {range} outside of the {len} bp sequence");
+    }
+    Ok((start - 1, end - 1))
+}
+
+fn parse_features(xml: &str, len: usize) -> anyhow::Result<Vec<Annotation>> {
+    let mut features = Vec::new();
+    let mut feature: Option<Annotation> = None;
+    let mut segments = Vec::new();
+    let mut qualifier = String::new();
+    for tag in xml_tags(xml)? {
+        match (tag.name.as_str(), tag.closing) {
+            ("Feature", false) => {
+                let strand = match tag.attribute("directionality") {
+                    Some("1") => Strand::Forward,
+                    Some("2") => Strand::Reverse,
+                    _ => Strand::Unknown,
+                };
+                let kind = FeatureType::from(tag.attribute("type").unwrap_or("misc_feature"));
+                let name = tag.attribute("name").unwrap_or(kind.as_str()).to_string();
+                feature = Some(
+                    Annotation::new(0, 0, None, name)
+                        .with_kind(kind)
+                        .with_strand(strand),
+                );
+                segments.clear();
+            }
+            ("Segment", false) if tag.attribute("type") != Some("gap") => {
+                let Some(feature) = feature.as_mut() else {
+                    continue;
+                };
+                if let Some(range) = tag.attribute("range") {
+                    segments.push(parse_range(range, len)?);
+                }
+                if let (None, Some(color)) = (&feature.color, tag.attribute("color")) {
+                    feature.color = Some(color.to_string());
+                }
+            }
+            ("Q", false) => qualifier = tag.attribute("name").unwrap_or_default().to_string(),
+            ("V", false) => {
+                let value = ["text", "int", "predef"]
+                    .iter()
+                    .find_map(|key| tag.attribute(key));
+                if let (Some(feature), Some(value)) = (feature.as_mut(), value) {
+                    feature
+                        .qualifiers
+                        .push((qualifier.to_owned(), strip_markup(value)));
+                }
+            }
+            ("Feature", true) => {
+                let Some(mut annotation) = feature.take() else {
+                    continue;
+                };
+                match segments.as_slice() {
+                    [] => bail!("SnapGene feature {} has no range", annotation.text),
+                    [(start, end)] => (annotation.start, annotation.end) = (*start, *end),
+                    joined => annotation = annotation.with_segments(joined),
+                }
+                features.push(annotation);
+            }
+            _ => {}
+        }
+    }
+    Ok(features)
+}
+
+fn parse_primers(xml: &str, len: usize) -> anyhow::Result<Vec<Annotation>> {
+    let mut primers = Vec::new();
+    let mut primer: Option<(String, String)> = None;
+    for tag in xml_tags(xml)? {
+        match (tag.name.as_str(), tag.closing) {
+            ("Primer", false) => {
+                primer = Some((
+                    tag.attribute("name").unwrap_or("primer").to_string(),
+                    tag.attribute("sequence").unwrap_or_default().to_uppercase(),
+                ));
+            }
+            ("BindingSite", false) => {
+                let (Some((name, sequence)), Some(location)) = (&primer, tag.attribute("location"))
+                else {
+                    continue;
+                };
+                let (start, end) = parse_range(location, len)?;
+                let strand = match tag.attribute("boundStrand") {
+                    Some("1") => Strand::Reverse,
+                    _ => Strand::Forward,
+                };
+                let mut annotation = Annotation::new(start, end, None, name)
+                    .with_kind(FeatureType::PrimerBind)
+                    .with_strand(strand);
+                if !sequence.is_empty() {
+                    annotation = annotation.with_qualifier("note", format!("sequence: {sequence}"));
+                }
+                // Sites are repeated for every way the primer binds, e.g. with mismatches
+                if !primers.contains(&annotation) {
+                    primers.push(annotation);
+                }
+            }
+            ("Primer", true) => primer = None,
+            _ => {}
+        }
+    }
+    Ok(primers)
+}
+
+impl Import for SnapGeneFile {
+    type Output = DnaSequence;
+
+    fn import<S>(s: S) -> anyhow::Result<Self::Output>
+    where
+        S: AsRef<str>,
+    {
+        Self::import_bytes(s.as_ref().as_bytes())
+    }
+
+    /// Import the contents of a SnapGene `.dna` file, which may be gzip compressed.
+    fn import_bytes(bytes: &[u8]) -> anyhow::Result<Self::Output> {
+        let bytes = &decompress(bytes)?;
+        let mut packets = Vec::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let header = bytes
+                .get(offset..offset + 5)
+                .ok_or_else(|| anyhow!("truncated SnapGene packet"))?;
+            let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+            let data = bytes
+                .get(offset + 5..offset + 5 + len)
+                .ok_or_else(|| anyhow!("truncated SnapGene packet"))?;
+            packets.push((header[0], data));
+            offset += 5 + len;
+        }
+
+        match packets.first() {
+            Some((COOKIE_PACKET, cookie)) if cookie.starts_with(b"SnapGene") => {
+                if cookie.get(8..10) != Some(&[0, 1]) {
+                    bail!("SnapGene file does not contain DNA");
+                }
+            }
+            _ => bail!("not a SnapGene file"),
+        }
+        let (flags, bases) = packets
+            .iter()
+            .find(|(kind, _)| *kind == DNA_PACKET)
+            .and_then(|(_, data)| data.split_first())
+            .ok_or_else(|| anyhow!("SnapGene file has no sequence"))?;
+        let mut seq = DnaSequence::from_str(std::str::from_utf8(bases)?)?;
+        seq.set_topology(match flags & 1 {
+            1 => Topology::Circular,
+            _ => Topology::Linear,
+        });
+
+        let len = seq.as_nucleotides().len();
+        for (kind, data) in packets.iter() {
+            let annotations = match *kind {
+                FEATURES_PACKET => parse_features(std::str::from_utf8(data)?, len)?,
+                PRIMERS_PACKET => parse_primers(std::str::from_utf8(data)?, len)?,
+                _ => continue,
+            };
+            seq.as_mut_annotations().extend(annotations);
+        }
+        Ok(seq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unescape, xml_tags, SnapGeneFile};
+    use crate::{
+        imp::Import,
+        seq::{FeatureType, Strand, Topology},
+    };
+
+    const FEATURES: &[u8] = include_bytes!("../../fixtures/snapgene/features.dna");
+    const PRIMERS: &[u8] = include_bytes!("../../fixtures/snapgene/primers.dna");
+    const MINIMAL: &[u8] = include_bytes!("../../fixtures/snapgene/minimal.dna");
+    const PROTEIN: &[u8] = include_bytes!("../../fixtures/snapgene/protein.dna");
+
+    #[test]
+    fn test_xml_tags() {
+        let tags =
+            xml_tags(r#"<?xml version="1.0"?><!-- a > b --><A x='1' y="a>b"><B/></A>"#).unwrap();
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags[0].name, "A");
+        assert_eq!(tags[0].attribute("y"), Some("a>b"));
+        assert_eq!(tags[1].name, "B");
+        assert!(tags[2].closing);
+        assert_eq!(
+            unescape("&lt;b&gt; &amp; &#65;&#x42; &bogus"),
+            "<b> & AB &bogus"
+        );
+    }
+
+    #[test]
+    fn test_import_features() {
+        let seq = SnapGeneFile::import_bytes(FEATURES).unwrap();
+        assert_eq!(seq.topology(), Topology::Circular);
+        assert_eq!(seq.to_string().len(), 120);
+        let features = seq.as_annotations();
+        assert_eq!(features.len(), 4);
+
+        let cds = &features[0];
+        assert_eq!(cds.text, "orf1");
+        assert_eq!(cds.kind, FeatureType::Cds);
+        assert_eq!(cds.strand, Strand::Forward);
+        assert_eq!((cds.start, cds.end), (9, 38));
+        assert_eq!(cds.color.as_deref(), Some("#993366"));
+        assert_eq!(cds.qualifier("gene"), Some("orf1"));
+        assert_eq!(cds.qualifier("note"), Some("Encodes a short peptide & tag"));
+        assert_eq!(cds.qualifier("codon_start"), Some("1"));
+        assert_eq!(cds.qualifier("translation"), Some("MKGLQGGKQ"));
+
+        let promoter = &features[1];
+        assert_eq!(promoter.kind, FeatureType::Promoter);
+        assert_eq!(promoter.strand, Strand::Reverse);
+        assert_eq!((promoter.start, promoter.end), (49, 68));
+
+        // Spanning the origin
+        let ori = &features[2];
+        assert_eq!(ori.kind, FeatureType::RepOrigin);
+        assert_eq!((ori.start, ori.end), (109, 9));
+        assert!(ori.wraps_origin());
+
+        // Joined segments, without the gap between them
+        let joined = &features[3];
+        assert_eq!(joined.kind, FeatureType::Other("intron_lariat".to_string()));
+        assert_eq!(joined.strand, Strand::Unknown);
+        assert_eq!(joined.segments, [(69, 79), (89, 99)]);
+    }
+
+    #[test]
+    fn test_import_primers() {
+        let seq = SnapGeneFile::import_bytes(PRIMERS).unwrap();
+        assert_eq!(seq.topology(), Topology::Linear);
+        let primers = seq.as_annotations();
+        assert_eq!(primers.len(), 2);
+        assert_eq!(primers[0].text, "fwd");
+        assert_eq!(primers[0].kind, FeatureType::PrimerBind);
+        assert_eq!(primers[0].strand, Strand::Forward);
+        assert_eq!((primers[0].start, primers[0].end), (0, 19));
+        assert_eq!(
+            primers[0].qualifier("note"),
+            Some("sequence: ATGACCATGATTACGCCAAG")
+        );
+        assert_eq!(primers[1].text, "rev");
+        assert_eq!(primers[1].strand, Strand::Reverse);
+        assert_eq!((primers[1].start, primers[1].end), (40, 59));
+    }
+
+    #[test]
+    fn test_import_minimal() {
+        let seq = SnapGeneFile::import_bytes(MINIMAL).unwrap();
+        assert_eq!(seq.to_string(), "ATGCATGCAATT");
+        assert_eq!(seq.topology(), Topology::Linear);
+        assert!(seq.as_annotations().is_empty());
+    }
+
+    #[test]
+    fn test_reject_invalid_files() {
+        assert!(SnapGeneFile::import_bytes(PROTEIN).is_err());
+        assert!(SnapGeneFile::import_bytes(b">fasta\nACGT").is_err());
+        assert!(SnapGeneFile::import_bytes(&FEATURES[..FEATURES.len() - 3]).is_err());
+    }
+}
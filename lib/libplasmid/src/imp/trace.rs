@@ -0,0 +1,546 @@
+use std::ops::Range;
+
+use anyhow::{anyhow, bail};
+
+use super::decompress;
+use crate::{
+    traits::{Nucleotide, ToLetter, TryFromLetter},
+    uni::IupacNucleotide,
+};
+
+/// Order of the channels of a `TraceFile`.
+pub const TRACE_BASES: [char; 4] = ['A', 'C', 'G', 'T'];
+
+/// A Sanger chromatogram with its base calls, read from an ABIF (`.ab1`) or SCF file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceFile {
+    pub name: String,
+    /// Called bases, IUPAC letters
+    pub bases: String,
+    /// Phred quality of every base call
+    pub qualities: Vec<u8>,
+    /// Sample index of the peak of every base call
+    pub peaks: Vec<usize>,
+    /// Intensities of the A, C, G and T channels at every sample
+    pub channels: [Vec<u16>; 4],
+}
+
+fn be_u16(bytes: &[u8], offset: usize) -> anyhow::Result<u16> {
+    let b = bytes
+        .get(offset..offset + 2)
+        .ok_or_else(|| anyhow!("truncated trace file"))?;
+    Ok(u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn be_u32(bytes: &[u8], offset: usize) -> anyhow::Result<u32> {
+    let b = bytes
+        .get(offset..offset + 4)
+        .ok_or_else(|| anyhow!("truncated trace file"))?;
+    Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn slice(bytes: &[u8], offset: usize, len: usize) -> anyhow::Result<&[u8]> {
+    bytes
+        .get(offset..offset + len)
+        .ok_or_else(|| anyhow!("truncated trace file"))
+}
+
+/// An entry of the directory of an ABIF file.
+struct AbifEntry {
+    name: [u8; 4],
+    number: u32,
+    /// Element type, e.g. 2 for characters and 18 for a Pascal string
+    kind: u16,
+    elements: usize,
+    data: Vec<u8>,
+}
+
+impl AbifEntry {
+    fn read(bytes: &[u8], offset: usize) -> anyhow::Result<Self> {
+        let entry = slice(bytes, offset, 28)?;
+        let elements = be_u32(entry, 12)? as usize;
+        let size = be_u32(entry, 16)? as usize;
+        // Up to four bytes of data are stored in place of the data offset
+        let data = match size {
+            0..=4 => entry[20..20 + size].to_vec(),
+            _ => slice(bytes, be_u32(entry, 20)? as usize, size)?.to_vec(),
+        };
+        Ok(AbifEntry {
+            name: [entry[0], entry[1], entry[2], entry[3]],
+            number: be_u32(entry, 4)?,
+            kind: be_u16(entry, 8)?,
+            elements,
+            data,
+        })
+    }
+
+    /// Elements of a 16 bit array, negative values clamped to zero.
+    fn shorts(&self) -> Vec<u16> {
+        self.data
+            .chunks_exact(2)
+            .map(|b| i16::from_be_bytes([b[0], b[1]]).max(0) as u16)
+            .collect()
+    }
+
+    /// Text of a character array or a Pascal string.
+    fn text(&self) -> String {
+        let text = match self.kind {
+            18 if !self.data.is_empty() => &self.data[1..],
+            _ => &self.data[..],
+        };
+        String::from_utf8_lossy(text)
+            .trim_end_matches('\0')
+            .to_string()
+    }
+}
+
+/// Phred quality of a called base from the per-base probabilities of an SCF file.
+fn scf_quality(base: char, probabilities: [u8; 4]) -> u8 {
+    match TRACE_BASES.iter().position(|b| *b == base) {
+        Some(i) => probabilities[i],
+        None => probabilities.iter().copied().max().unwrap_or(0),
+    }
+}
+
+impl TraceFile {
+    /// Read a trace from the contents of an ABIF (`.ab1`) or SCF file, told apart by their magic bytes.
+    /// Gzip compressed traces, e.g. `.scf.gz`, are decompressed first.
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let bytes = &decompress(bytes)?;
+        match bytes.get(..4) {
+            Some(b"ABIF") => Self::from_abif(bytes),
+            Some(b".scf") => Self::from_scf(bytes),
+            _ => bail!("not an ABIF or SCF trace file"),
+        }
+    }
+
+    pub fn import_from_path<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let mut trace = Self::from_bytes(&std::fs::read(&path)?)?;
+        if trace.name.is_empty() {
+            if let Some(stem) = path.as_ref().file_stem() {
+                trace.name = stem.to_string_lossy().to_string();
+            }
+        }
+        Ok(trace)
+    }
+
+    /// Read an ABIF file, as written by Applied Biosystems sequencers.
+    ///
+    /// Edited base calls, qualities and peaks (`PBAS 2`, `PCON 2`, `PLOC 2`) are preferred over
+    /// the original ones, and the analyzed traces `DATA 9` to `12` are ordered by `FWO_ 1`.
+    pub fn from_abif(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.get(..4) != Some(b"ABIF") {
+            bail!("not an ABIF file");
+        }
+        let root = AbifEntry::read(bytes, 6)?;
+        let offset = be_u32(bytes, 6 + 20)? as usize;
+        let entries = (0..root.elements)
+            .map(|i| AbifEntry::read(bytes, offset + i * 28))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let find = |name: &[u8; 4], numbers: &[u32]| {
+            numbers.iter().find_map(|number| {
+                entries
+                    .iter()
+                    .find(|e| &e.name == name && e.number == *number)
+            })
+        };
+
+        let bases = find(b"PBAS", &[2, 1])
+            .ok_or_else(|| anyhow!("ABIF file has no base calls"))?
+            .text()
+            .to_ascii_uppercase();
+        let qualities = match find(b"PCON", &[2, 1]) {
+            Some(entry) => entry.data.clone(),
+            None => vec![0; bases.len()],
+        };
+        let peaks = find(b"PLOC", &[2, 1])
+            .ok_or_else(|| anyhow!("ABIF file has no peak locations"))?
+            .shorts()
+            .into_iter()
+            .map(|p| p as usize)
+            .collect::<Vec<_>>();
+        let order = find(b"FWO_", &[1])
+            .map(|entry| entry.text())
+            .unwrap_or_else(|| "GATC".to_string());
+        let mut channels: [Vec<u16>; 4] = Default::default();
+        for (i, base) in order.chars().take(4).enumerate() {
+            let channel = TRACE_BASES
+                .iter()
+                .position(|b| *b == base.to_ascii_uppercase())
+                .ok_or_else(|| anyhow!("unknown base {base} in ABIF channel order"))?;
+            channels[channel] = find(b"DATA", &[9 + i as u32])
+                .ok_or_else(|| anyhow!("ABIF file has no trace for {base}"))?
+                .shorts();
+        }
+        let name = find(b"SMPL", &[1])
+            .map(|entry| entry.text())
+            .unwrap_or_default();
+
+        Self::new(name, bases, qualities, peaks, channels)
+    }
+
+    /// Read an SCF file of version 2 or 3.
+    pub fn from_scf(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.get(..4) != Some(b".scf") {
+            bail!("not an SCF file");
+        }
+        let header = |field: usize| be_u32(bytes, 4 + field * 4).map(|v| v as usize);
+        let (samples, samples_offset) = (header(0)?, header(1)?);
+        let (base_count, bases_offset) = (header(2)?, header(5)?);
+        let version = String::from_utf8_lossy(slice(bytes, 36, 4)?).to_string();
+        let sample_size = match be_u32(bytes, 40)? {
+            1 => 1,
+            _ => 2,
+        };
+        let value = |offset: usize| match sample_size {
+            1 => bytes
+                .get(offset)
+                .map(|v| *v as u16)
+                .ok_or_else(|| anyhow!("truncated trace file")),
+            _ => be_u16(bytes, offset),
+        };
+
+        let mut channels: [Vec<u16>; 4] = Default::default();
+        let mut peaks = Vec::new();
+        let mut qualities = Vec::new();
+        let mut bases = String::new();
+        if version.as_str() >= "3.00" {
+            // Channels one after the other, each delta encoded twice
+            for (c, channel) in channels.iter_mut().enumerate() {
+                let start = samples_offset + c * samples * sample_size;
+                *channel = (0..samples)
+                    .map(|i| value(start + i * sample_size))
+                    .collect::<anyhow::Result<_>>()?;
+                for _ in 0..2 {
+                    let mut previous = 0_u16;
+                    for v in channel.iter_mut() {
+                        *v = v.wrapping_add(previous);
+                        previous = *v;
+                    }
+                }
+                if sample_size == 1 {
+                    channel.iter_mut().for_each(|v| *v &= 0xff);
+                }
+            }
+            // Peaks, the probabilities of each base, then the calls
+            for i in 0..base_count {
+                peaks.push(be_u32(bytes, bases_offset + i * 4)? as usize);
+                let probability = |c: usize| {
+                    slice(bytes, bases_offset + base_count * (4 + c) + i, 1).map(|b| b[0])
+                };
+                let probabilities = [
+                    probability(0)?,
+                    probability(1)?,
+                    probability(2)?,
+                    probability(3)?,
+                ];
+                let base = probability(4)? as char;
+                bases.push(base.to_ascii_uppercase());
+                qualities.push(scf_quality(base.to_ascii_uppercase(), probabilities));
+            }
+        } else {
+            // Samples with their four channels interleaved, and 12 byte base records
+            for i in 0..samples {
+                for (c, channel) in channels.iter_mut().enumerate() {
+                    channel.push(value(samples_offset + (i * 4 + c) * sample_size)?);
+                }
+            }
+            for i in 0..base_count {
+                let record = slice(bytes, bases_offset + i * 12, 12)?;
+                peaks.push(be_u32(record, 0)? as usize);
+                let base = (record[8] as char).to_ascii_uppercase();
+                bases.push(base);
+                qualities.push(scf_quality(
+                    base,
+                    [record[4], record[5], record[6], record[7]],
+                ));
+            }
+        }
+
+        Self::new(String::new(), bases, qualities, peaks, channels)
+    }
+
+    fn new(
+        name: String,
+        bases: String,
+        mut qualities: Vec<u8>,
+        peaks: Vec<usize>,
+        channels: [Vec<u16>; 4],
+    ) -> anyhow::Result<Self> {
+        if let Some(c) = bases
+            .chars()
+            .find(|c| *c == '-' || IupacNucleotide::try_from_letter(*c).is_err())
+        {
+            bail!("trace base call {:?} is not an IUPAC nucleotide", c);
+        }
+        let calls = bases.len();
+        if peaks.len() != calls {
+            bail!("trace has {} base calls but {} peaks", calls, peaks.len());
+        }
+        qualities.resize(calls, 0);
+        let samples = channels[0].len();
+        if channels.iter().any(|c| c.len() != samples) {
+            bail!("trace channels differ in length");
+        }
+        if peaks.iter().any(|p| *p >= samples) {
+            bail!("trace peak beyond its {} samples", samples);
+        }
+        Ok(TraceFile {
+            name,
+            bases,
+            qualities,
+            peaks,
+            channels,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.qualities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.qualities.is_empty()
+    }
+
+    /// Number of samples of each channel.
+    pub fn samples(&self) -> usize {
+        self.channels[0].len()
+    }
+
+    /// The trace of the opposite strand, e.g. for a read primed from the reverse strand.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::imp::TraceFile;
+    ///
+    /// let trace = TraceFile {
+    ///     name: "read".to_string(),
+    ///     bases: "AAG".to_string(),
+    ///     qualities: vec![10, 20, 30],
+    ///     peaks: vec![0, 2, 4],
+    ///     channels: [vec![9, 0, 9, 0, 0], vec![0; 5], vec![0, 0, 0, 0, 9], vec![0; 5]],
+    /// };
+    /// let reverse = trace.reverse_complement();
+    /// assert_eq!(reverse.bases, "CTT");
+    /// assert_eq!(reverse.qualities, [30, 20, 10]);
+    /// assert_eq!(reverse.peaks, [0, 2, 4]);
+    /// assert_eq!(reverse.channels[1], [9, 0, 0, 0, 0]);
+    /// assert_eq!(reverse.channels[3], [0, 0, 9, 0, 9]);
+    /// ```
+    pub fn reverse_complement(&self) -> Self {
+        let samples = self.samples();
+        let reversed = |channel: &Vec<u16>| channel.iter().rev().copied().collect::<Vec<_>>();
+        TraceFile {
+            name: self.name.to_owned(),
+            bases: self
+                .bases
+                .chars()
+                .rev()
+                .map(|base| {
+                    IupacNucleotide::try_from_letter(base)
+                        .map_or(base, |nucleotide| nucleotide.complement().to_letter())
+                })
+                .collect(),
+            qualities: self.qualities.iter().rev().copied().collect(),
+            peaks: self.peaks.iter().rev().map(|p| samples - 1 - p).collect(),
+            channels: [
+                reversed(&self.channels[3]),
+                reversed(&self.channels[2]),
+                reversed(&self.channels[1]),
+                reversed(&self.channels[0]),
+            ],
+        }
+    }
+
+    /// Range of base calls left after trimming low quality ends with the modified Mott algorithm,
+    /// i.e. the stretch with the highest sum of `quality - min_quality`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::imp::TraceFile;
+    ///
+    /// let trace = TraceFile {
+    ///     name: "read".to_string(),
+    ///     bases: "NACGTN".to_string(),
+    ///     qualities: vec![2, 40, 40, 5, 40, 3],
+    ///     peaks: vec![0, 1, 2, 3, 4, 5],
+    ///     channels: [vec![0; 6], vec![0; 6], vec![0; 6], vec![0; 6]],
+    /// };
+    /// assert_eq!(trace.quality_trim(20), 1..5);
+    /// assert_eq!(trace.quality_trim(50), 0..0);
+    /// ```
+    pub fn quality_trim(&self, min_quality: u8) -> Range<usize> {
+        let (mut best, mut best_range) = (0_i64, 0..0);
+        let (mut sum, mut start) = (0_i64, 0);
+        for (i, quality) in self.qualities.iter().enumerate() {
+            sum += *quality as i64 - min_quality as i64;
+            if sum <= 0 {
+                (sum, start) = (0, i + 1);
+            } else if sum > best {
+                (best, best_range) = (sum, start..i + 1);
+            }
+        }
+        best_range
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TraceFile;
+
+    /// Name, number, element type, element size and data of an ABIF directory entry
+    type Entry<'a> = (&'a [u8; 4], u32, u16, u16, Vec<u8>);
+
+    /// An ABIF file with the given directory entries.
+    fn abif(entries: &[Entry]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut directory = Vec::new();
+        let data_start = 128;
+        for (name, number, kind, size, bytes) in entries {
+            directory.extend_from_slice(&name[..]);
+            directory.extend_from_slice(&number.to_be_bytes());
+            directory.extend_from_slice(&kind.to_be_bytes());
+            directory.extend_from_slice(&size.to_be_bytes());
+            directory.extend_from_slice(&((bytes.len() / *size as usize) as u32).to_be_bytes());
+            directory.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+            if bytes.len() <= 4 {
+                let mut inline = bytes.clone();
+                inline.resize(4, 0);
+                directory.extend_from_slice(&inline);
+            } else {
+                directory.extend_from_slice(&((data_start + data.len()) as u32).to_be_bytes());
+                data.extend_from_slice(bytes);
+            }
+            directory.extend_from_slice(&0_u32.to_be_bytes());
+        }
+        let mut file = b"ABIF".to_vec();
+        file.extend_from_slice(&101_u16.to_be_bytes());
+        file.extend_from_slice(b"tdir");
+        file.extend_from_slice(&1_u32.to_be_bytes());
+        file.extend_from_slice(&1023_u16.to_be_bytes());
+        file.extend_from_slice(&28_u16.to_be_bytes());
+        file.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        file.extend_from_slice(&(directory.len() as u32).to_be_bytes());
+        file.extend_from_slice(&((data_start + data.len()) as u32).to_be_bytes());
+        file.resize(data_start, 0);
+        file.extend(data);
+        file.extend(directory);
+        file
+    }
+
+    fn shorts(values: &[u16]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_be_bytes()).collect()
+    }
+
+    /// A trace of `ACGT` with a peak every other sample, in `GATC` channel order.
+    fn sample_abif() -> Vec<u8> {
+        let channel = |peak: usize| {
+            (0..8)
+                .map(|i| if i == peak * 2 + 1 { 500 } else { 10 })
+                .collect::<Vec<_>>()
+        };
+        abif(&[
+            (b"DATA", 9, 4, 2, shorts(&channel(2))),
+            (b"DATA", 10, 4, 2, shorts(&channel(0))),
+            (b"DATA", 11, 4, 2, shorts(&channel(3))),
+            (b"DATA", 12, 4, 2, shorts(&channel(1))),
+            (b"FWO_", 1, 2, 1, b"GATC".to_vec()),
+            (b"PBAS", 1, 2, 1, b"ACGN".to_vec()),
+            (b"PBAS", 2, 2, 1, b"ACGT".to_vec()),
+            (b"PCON", 2, 2, 1, vec![40, 35, 30, 12]),
+            (b"PLOC", 2, 4, 2, shorts(&[1, 3, 5, 7])),
+            (b"SMPL", 1, 18, 1, b"\x05clone".to_vec()),
+        ])
+    }
+
+    #[test]
+    fn test_read_abif() {
+        let trace = TraceFile::from_bytes(&sample_abif()).unwrap();
+        assert_eq!(trace.name, "clone");
+        assert_eq!(trace.bases, "ACGT");
+        assert_eq!(trace.qualities, [40, 35, 30, 12]);
+        assert_eq!(trace.peaks, [1, 3, 5, 7]);
+        assert_eq!(trace.samples(), 8);
+        // The channel of each base peaks at its call
+        for (i, peak) in trace.peaks.iter().enumerate() {
+            assert_eq!(trace.channels[i][*peak], 500);
+        }
+    }
+
+    #[test]
+    fn test_read_scf_v3() {
+        let samples: [Vec<u16>; 4] = [
+            vec![0, 100, 4],
+            vec![3, 0, 50],
+            vec![7, 7, 7],
+            vec![1, 2, 3],
+        ];
+        let mut file = b".scf".to_vec();
+        let header = [3_u32, 128, 2, 0, 0, 128 + 24, 0, 0];
+        header.iter().for_each(|v| file.extend(v.to_be_bytes()));
+        file.extend(b"3.00");
+        file.extend(2_u32.to_be_bytes());
+        file.resize(128, 0);
+        for channel in samples.iter() {
+            // Delta encode twice, as the decoder adds back twice
+            let mut encoded = channel.clone();
+            for _ in 0..2 {
+                encoded = encoded
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| v.wrapping_sub(if i == 0 { 0 } else { encoded[i - 1] }))
+                    .collect();
+            }
+            encoded.iter().for_each(|v| file.extend(v.to_be_bytes()));
+        }
+        file.extend(1_u32.to_be_bytes());
+        file.extend(2_u32.to_be_bytes());
+        file.extend([30, 0, 0, 2, 0, 0, 0, 0, b'A', b'C']);
+
+        let trace = TraceFile::from_bytes(&file).unwrap();
+        assert_eq!(trace.channels, samples);
+        assert_eq!(trace.bases, "AC");
+        assert_eq!(trace.peaks, [1, 2]);
+        assert_eq!(trace.qualities, [30, 2]);
+    }
+
+    #[test]
+    fn test_read_scf_v2() {
+        let mut file = b".scf".to_vec();
+        let header = [2_u32, 128, 1, 0, 0, 128 + 8, 0, 0];
+        header.iter().for_each(|v| file.extend(v.to_be_bytes()));
+        file.extend(b"2.00");
+        file.extend(1_u32.to_be_bytes());
+        file.resize(128, 0);
+        file.extend([1, 2, 90, 4, 5, 6, 7, 8]);
+        file.extend(1_u32.to_be_bytes());
+        file.extend([0, 0, 25, 0, b'G', 0, 0, 0]);
+
+        let trace = TraceFile::from_bytes(&file).unwrap();
+        assert_eq!(
+            trace.channels,
+            [vec![1, 5], vec![2, 6], vec![90, 7], vec![4, 8]]
+        );
+        assert_eq!(trace.bases, "G");
+        assert_eq!(trace.qualities, [25]);
+    }
+
+    #[test]
+    fn test_reject_invalid_traces() {
+        assert!(TraceFile::from_bytes(b"not a trace").is_err());
+        let mut truncated = sample_abif();
+        truncated.truncate(100);
+        assert!(TraceFile::from_bytes(&truncated).is_err());
+
+        // Base calls must be IUPAC letters
+        for calls in [&b"AC\xc3\xa9"[..], b"AC-T", b"AC<T"] {
+            let mut file = sample_abif();
+            let at = file.windows(4).position(|w| w == b"ACGT").unwrap();
+            file[at..at + 4].copy_from_slice(&calls[..4]);
+            assert!(TraceFile::from_bytes(&file).is_err());
+        }
+    }
+}
@@ -5,6 +5,7 @@ extern crate anyhow;
 #[macro_use]
 extern crate lazy_static;
 
+pub mod ana;
 pub mod err;
 #[macro_use]
 pub mod traits;
@@ -15,5 +16,7 @@ pub mod imp;
 pub mod prelude;
 pub mod rna;
 pub mod seq;
+#[cfg(test)]
+mod test_utils;
 pub mod uni;
 pub mod vis;
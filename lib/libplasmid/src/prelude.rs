@@ -1,8 +1,28 @@
+pub use crate::ana::{
+    design_primers, digest, ends_compatible, find_features, gibson, golden_gate, in_silico_pcr,
+    primer_sites, restriction_ligation, search_motif, variant_effects, Amplicon, AssembledPart,
+    Assembly, AssemblyIssue, CodingEffect, DesignedPrimerPair, Fragment, FragmentEnd, GcWindows,
+    IndexHit, IndexSearch, IndexedSequence, InsertOrientation, Ligation, LigationProduct, Motif,
+    MotifHit, MotifKind, Overhang, PcrParameters, PcrPrimer, PrimerCandidate, PrimerConstraints,
+    PrimerPairProperties, PrimerProperties, PrimerSite, ReferenceIndex, SequenceStats,
+    SequenceVariant, TmConditions, TmMethod, VariantEffect,
+};
 pub use crate::dna::{DnaCodon, DnaNucleotide};
 pub use crate::eaa::{Eaa, QualifiedEaa};
-pub use crate::exp::{Export, SvgExport, SvgExportConfig, SvgRenderMode};
-pub use crate::imp::{FastaEaaFile, FastaFile, FastaIupacFile, Import, TypedFastaFile};
+pub use crate::exp::{
+    CoverageTrackExport, Export, GcPlotExport, GelExport, GelLadder, GelLane, SvgExport,
+    SvgExportConfig, SvgRenderMode, TraceExport,
+};
+pub use crate::imp::{
+    bgzf_compress, decompress, half_open_to_inclusive, half_open_to_one_based,
+    inclusive_to_half_open, one_based_to_half_open, read_file, read_text_file, BedFile, BedRecord,
+    BgzfReader, Compression, FaidxRecord, FastaEaaFile, FastaFile, FastaIndex, FastaIupacFile,
+    FastaRecord, Gff3File, Gff3Record, GziIndex, Import, IndexedFasta, MultiFastaFile, RebaseFile,
+    Region, SequenceFeatures, SnapGeneFile, TraceFile, TypedFastaFile,
+};
 pub use crate::rna::{RnaCodon, RnaNucleotide};
-pub use crate::seq::{Annotation, DnaSequence, RnaSequence};
+pub use crate::seq::{
+    reverse_complement, Annotation, DnaSequence, FeatureType, RnaSequence, Strand, Topology,
+};
 pub use crate::traits::{Codon, Nucleotide, NucleotideSequence};
-pub use crate::uni::{IupacNucleotide, RestrictionEnzymes};
+pub use crate::uni::{CommonFeatures, IupacNucleotide, RestrictionEnzymes};
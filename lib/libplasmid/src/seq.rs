@@ -1,9 +1,17 @@
 pub mod annotation;
+pub mod complement;
 pub mod dna;
+pub mod edit;
+pub mod feature;
 pub mod genetic_sequence;
 pub mod rna;
+pub mod topology;
 
 pub use self::annotation::*;
+pub use self::complement::*;
 pub use self::dna::*;
+pub use self::edit::*;
+pub use self::feature::*;
 pub use self::genetic_sequence::*;
 pub use self::rna::*;
+pub use self::topology::*;
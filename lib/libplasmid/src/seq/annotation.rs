@@ -1,13 +1,36 @@
 use std::borrow::Borrow;
 
+use serde::{Deserialize, Serialize};
+
 use crate::uni::RestrictionEnzyme;
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+use super::{FeatureType, Strand};
+
+/// A labelled feature of a sequence.
+///
+/// `start` and `end` are inclusive. On circular sequences an annotation
+/// may span the origin, in which case `end` is smaller than `start`.
+/// Joined locations, e.g. the exons of a CDS, are stored as `segments`
+/// and `start`/`end` then span all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Annotation {
     pub start: usize,
     pub needle: Option<usize>,
     pub end: usize,
     pub text: String,
+    #[serde(default)]
+    pub strand: Strand,
+    #[serde(default, rename = "type")]
+    pub kind: FeatureType,
+    /// GenBank style qualifiers in their original order, keys may repeat
+    #[serde(default)]
+    pub qualifiers: Vec<(String, String)>,
+    /// Display color, e.g. `#31849b`
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Inclusive bounds of joined segments, empty for a contiguous feature
+    #[serde(default)]
+    pub segments: Vec<(usize, usize)>,
 }
 
 impl Annotation {
@@ -20,6 +43,93 @@ impl Annotation {
             end,
             needle,
             text: text.as_ref().to_string(),
+            strand: Strand::Unknown,
+            kind: FeatureType::MiscFeature,
+            qualifiers: Vec::new(),
+            color: None,
+            segments: Vec::new(),
+        }
+    }
+
+    pub fn with_strand(mut self, strand: Strand) -> Self {
+        self.strand = strand;
+        self
+    }
+
+    pub fn with_kind(mut self, kind: FeatureType) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_qualifier<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.qualifiers
+            .push((key.as_ref().to_string(), value.as_ref().to_string()));
+        self
+    }
+
+    pub fn with_color<T>(mut self, color: T) -> Self
+    where
+        T: AsRef<str>,
+    {
+        self.color = Some(color.as_ref().to_string());
+        self
+    }
+
+    /// Join the annotation from multiple segments, given in sequence order.
+    /// `start` and `end` are set to the outer bounds of the segments.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let cds = Annotation::new(0, 0, None, "gene")
+    ///     .with_kind(FeatureType::Cds)
+    ///     .with_segments(&[(2, 10), (20, 30)]);
+    /// assert_eq!((cds.start, cds.end), (2, 30));
+    /// assert_eq!(cds.len(100), 20);
+    /// assert!(!cds.contains(15));
+    /// ```
+    pub fn with_segments(mut self, segments: &[(usize, usize)]) -> Self {
+        if let (Some(first), Some(last)) = (segments.first(), segments.last()) {
+            self.start = first.0;
+            self.end = last.1;
+        }
+        self.segments = segments.to_vec();
+        self
+    }
+
+    /// Value of the first qualifier with the given key.
+    pub fn qualifier(&self, key: &str) -> Option<&str> {
+        self.qualifiers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Inclusive bounds of all segments, or of the whole annotation if it is contiguous.
+    pub fn spans(&self) -> Vec<(usize, usize)> {
+        if self.segments.is_empty() {
+            vec![(self.start, self.end)]
+        } else {
+            self.segments.clone()
+        }
+    }
+
+    /// Apply `f` to every position of the annotation, e.g. to move it to another sequence.
+    pub fn map_positions<F>(&mut self, f: F)
+    where
+        F: Fn(usize) -> usize,
+    {
+        self.start = f(self.start);
+        self.end = f(self.end);
+        self.needle = self.needle.map(&f);
+        for (start, end) in self.segments.iter_mut() {
+            *start = f(*start);
+            *end = f(*end);
         }
     }
 
@@ -34,11 +144,62 @@ impl Annotation {
     {
         Annotation::new(start, end, needle, &enzyme.borrow().name)
     }
+
+    /// Whether the annotation spans the origin of a circular sequence.
+    pub fn wraps_origin(&self) -> bool {
+        self.end < self.start
+    }
+
+    /// Number of bases covered by the annotation on a sequence of length `seq_len`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// assert_eq!(Annotation::new(2, 4, None, "foo").len(10), 3);
+    /// assert_eq!(Annotation::new(8, 1, None, "bar").len(10), 4);
+    /// ```
+    pub fn len(&self, seq_len: usize) -> usize {
+        self.spans()
+            .into_iter()
+            .map(|(start, end)| match end < start {
+                true => seq_len - start + end + 1,
+                false => end - start + 1,
+            })
+            .sum()
+    }
+
+    /// Whether the annotation covers the given position.
+    pub fn contains(&self, pos: usize) -> bool {
+        self.spans()
+            .into_iter()
+            .any(|(start, end)| match end < start {
+                true => pos >= start || pos <= end,
+                false => (start..=end).contains(&pos),
+            })
+    }
+
+    /// Whether the annotation covers any position from `start` to `end` (inclusive).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let ori = Annotation::new(8, 1, None, "ori");
+    /// assert!(ori.overlaps(0, 3));
+    /// assert!(!ori.overlaps(2, 7));
+    /// ```
+    pub fn overlaps(&self, start: usize, end: usize) -> bool {
+        self.spans().into_iter().any(|(s, e)| match e < s {
+            true => s <= end || start <= e,
+            false => s <= end && start <= e,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::prelude::RestrictionEnzymes;
+    use crate::prelude::{FeatureType, RestrictionEnzymes, Strand};
 
     use super::Annotation;
 
@@ -63,4 +224,56 @@ mod tests {
         assert_eq!(ann.end, 5);
         assert_eq!(ann.text, "NdeI");
     }
+
+    #[test]
+    pub fn test_annotation_wraps_origin() {
+        let ann = Annotation::new(8, 1, None, "foo");
+        assert!(ann.wraps_origin());
+        assert!(ann.contains(9) && ann.contains(0) && ann.contains(1));
+        assert!(!ann.contains(2) && !ann.contains(7));
+        assert!(!Annotation::new(1, 8, None, "bar").contains(9));
+    }
+
+    #[test]
+    pub fn test_annotation_joined_segments() {
+        let ann = Annotation::new(0, 0, None, "foo").with_segments(&[(8, 9), (1, 2), (5, 5)]);
+        assert_eq!((ann.start, ann.end), (8, 5));
+        assert_eq!(ann.len(10), 5);
+        assert!(ann.contains(9) && ann.contains(2) && ann.contains(5));
+        assert!(!ann.contains(0) && !ann.contains(4) && !ann.contains(7));
+        assert!(ann.overlaps(3, 5));
+        assert!(!ann.overlaps(3, 4));
+    }
+
+    #[test]
+    pub fn test_annotation_qualifiers() {
+        let ann = Annotation::new(0, 10, None, "bla")
+            .with_kind(FeatureType::Cds)
+            .with_strand(Strand::Reverse)
+            .with_qualifier("gene", "bla")
+            .with_qualifier("note", "first")
+            .with_qualifier("note", "second");
+        assert_eq!(ann.qualifier("note"), Some("first"));
+        assert_eq!(ann.qualifier("product"), None);
+        assert_eq!(ann.qualifiers.len(), 3);
+    }
+
+    #[test]
+    pub fn test_annotation_serde() {
+        let ann = Annotation::new(0, 0, None, "bla")
+            .with_kind(FeatureType::Cds)
+            .with_strand(Strand::Reverse)
+            .with_qualifier("gene", "bla")
+            .with_color("#31849b")
+            .with_segments(&[(2, 10), (20, 30)]);
+        let json = serde_json::to_value(&ann).unwrap();
+        assert_eq!(json["type"], "CDS");
+        assert_eq!(json["strand"], "reverse");
+        assert_eq!(serde_json::from_value::<Annotation>(json).unwrap(), ann);
+
+        // Plain annotations only need a location and a label
+        let json = r#"{"start": 1, "end": 5, "needle": null, "text": "foo"}"#;
+        let ann = serde_json::from_str::<Annotation>(json).unwrap();
+        assert_eq!(ann, Annotation::new(1, 5, None, "foo"));
+    }
 }
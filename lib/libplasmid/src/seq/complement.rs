@@ -0,0 +1,16 @@
+use crate::traits::Nucleotide;
+
+/// Reverse complement of a strand, i.e. the opposite strand read 5' to 3'.
+///
+/// # Example
+/// ```rust
+/// use plasmid::prelude::{*, DnaNucleotide::*};
+///
+/// assert_eq!(reverse_complement(&[T, G, A, T, C, C]), [G, G, A, T, C, A]);
+/// ```
+pub fn reverse_complement<T>(seq: &[T]) -> Vec<T>
+where
+    T: Nucleotide,
+{
+    seq.iter().rev().map(|n| n.complement()).collect()
+}
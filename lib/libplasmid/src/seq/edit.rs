@@ -0,0 +1,174 @@
+use std::ops::Range;
+
+use super::Annotation;
+
+/// A recorded change of a sequence: `removed` bases at `start` were replaced by `inserted` ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceEdit<B> {
+    pub start: usize,
+    pub removed: Vec<B>,
+    pub inserted: Vec<B>,
+    /// Annotations of the sequence before the edit
+    pub annotations: Vec<Annotation>,
+}
+
+impl<B> SequenceEdit<B> {
+    /// Bases removed by the edit, as a range of the sequence before it.
+    pub fn removed_range(&self) -> Range<usize> {
+        self.start..self.start + self.removed.len()
+    }
+
+    /// Bases inserted by the edit, as a range of the sequence after it.
+    pub fn inserted_range(&self) -> Range<usize> {
+        self.start..self.start + self.inserted.len()
+    }
+}
+
+/// Move an inclusive span of positions over the replacement of `range` by `inserted` bases.
+/// Spans are trimmed to the bases that were kept, and dropped if none were.
+/// Spans enclosing the edit grow or shrink with it.
+fn edit_span(
+    (start, end): (usize, usize),
+    range: &Range<usize>,
+    inserted: usize,
+) -> Option<(usize, usize)> {
+    let shift = |pos: usize| pos - range.len() + inserted;
+    if end < range.start {
+        Some((start, end))
+    } else if start >= range.end {
+        Some((shift(start), shift(end)))
+    } else if range.is_empty() {
+        // Insertion inside of the span
+        Some((start, end + inserted))
+    } else {
+        let start = match start < range.start {
+            true => start,
+            false => range.start + inserted,
+        };
+        let end = match end >= range.end {
+            true => shift(end),
+            false => range.start.checked_sub(1)?,
+        };
+        (start <= end).then_some((start, end))
+    }
+}
+
+/// Move an annotation of a sequence of length `len` over the replacement
+/// of `range` by `inserted` bases, or `None` if nothing of it is left.
+///
+/// Annotations spanning the origin of a circular sequence are split at the origin,
+/// and joined again if both parts are kept next to it.
+pub(crate) fn edit_annotation(
+    ann: &Annotation,
+    len: usize,
+    range: &Range<usize>,
+    inserted: usize,
+) -> Option<Annotation> {
+    let new_len = len - range.len() + inserted;
+    let mut spans = Vec::<(usize, usize)>::new();
+    for (start, end) in ann.spans() {
+        if end < start {
+            let head = edit_span((start, len - 1), range, inserted);
+            let tail = edit_span((0, end), range, inserted);
+            // Bases inserted at the origin are inserted inside of the annotation
+            let at_origin = range.is_empty() && (range.start == 0 || range.start == len);
+            match (head, tail) {
+                (Some(head), Some(tail)) if at_origin || (head.1 + 1 == new_len && tail.0 == 0) => {
+                    spans.push((head.0, tail.1))
+                }
+                (head, tail) => spans.extend(head.into_iter().chain(tail)),
+            }
+        } else {
+            spans.extend(edit_span((start, end), range, inserted));
+        }
+    }
+    // Join segments the edit removed the gap between
+    spans.dedup_by(|next, prev| match prev.1 + 1 == next.0 {
+        true => {
+            prev.1 = next.1;
+            true
+        }
+        false => false,
+    });
+    let (first, last) = (*spans.first()?, *spans.last()?);
+
+    let mut ann = ann.clone();
+    ann.start = first.0;
+    ann.end = last.1;
+    ann.segments = match spans.len() {
+        1 => Vec::new(),
+        _ => spans,
+    };
+    // Cuts between two kept bases are kept
+    ann.needle = ann.needle.and_then(|needle| {
+        if needle >= range.end {
+            Some(needle - range.len() + inserted)
+        } else if needle <= range.start {
+            Some(needle)
+        } else {
+            None
+        }
+    });
+    Some(ann)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::edit_annotation;
+    use crate::seq::Annotation;
+
+    fn edit(
+        ann: &Annotation,
+        range: std::ops::Range<usize>,
+        inserted: usize,
+    ) -> Option<(usize, usize)> {
+        edit_annotation(ann, 20, &range, inserted).map(|ann| (ann.start, ann.end))
+    }
+
+    #[test]
+    fn test_edit_annotation() {
+        let ann = Annotation::new(5, 9, Some(7), "foo");
+        // Insertions before, inside and after
+        assert_eq!(edit(&ann, 5..5, 3), Some((8, 12)));
+        assert_eq!(edit(&ann, 6..6, 3), Some((5, 12)));
+        assert_eq!(edit(&ann, 10..10, 3), Some((5, 9)));
+        // Deletions trimming either end, inside and covering the annotation
+        assert_eq!(edit(&ann, 3..7, 0), Some((3, 5)));
+        assert_eq!(edit(&ann, 8..12, 0), Some((5, 7)));
+        assert_eq!(edit(&ann, 6..8, 0), Some((5, 7)));
+        assert_eq!(edit(&ann, 4..11, 0), None);
+        // Replacement of the start
+        assert_eq!(edit(&ann, 4..6, 5), Some((9, 12)));
+        // The cut is only kept if both sides of it are
+        let needle = |range, inserted| {
+            edit_annotation(&ann, 20, &range, inserted).and_then(|ann| ann.needle)
+        };
+        assert_eq!(needle(7..9, 0), Some(7));
+        assert_eq!(needle(6..8, 0), None);
+        assert_eq!(needle(0..2, 1), Some(6));
+    }
+
+    #[test]
+    fn test_edit_annotation_segments() {
+        let ann = Annotation::new(0, 0, None, "foo").with_segments(&[(2, 4), (8, 10), (14, 16)]);
+        let edited = edit_annotation(&ann, 20, &(7..12), 0).unwrap();
+        assert_eq!(edited.segments, [(2, 4), (9, 11)]);
+        assert_eq!((edited.start, edited.end), (2, 11));
+        let edited = edit_annotation(&ann, 20, &(3..15), 0).unwrap();
+        assert!(edited.segments.is_empty());
+        assert_eq!((edited.start, edited.end), (2, 4));
+    }
+
+    #[test]
+    fn test_edit_annotation_spanning_origin() {
+        let ori = Annotation::new(16, 3, None, "ori");
+        assert_eq!(edit(&ori, 1..1, 2), Some((18, 5)));
+        assert_eq!(edit(&ori, 8..10, 0), Some((14, 3)));
+        assert_eq!(edit(&ori, 18..20, 0), Some((16, 3)));
+        assert_eq!(edit(&ori, 0..0, 2), Some((18, 5)));
+        assert_eq!(edit(&ori, 20..20, 2), Some((16, 3)));
+        // Replacing the end of the sequence detaches the two parts
+        let edited = edit_annotation(&ori, 20, &(18..20), 3).unwrap();
+        assert_eq!(edited.segments, [(16, 17), (0, 3)]);
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Strand a feature is located on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strand {
+    /// Read 5' to 3' along the top strand
+    Forward,
+    /// Read 5' to 3' along the bottom strand
+    Reverse,
+    /// Not stranded or unknown, e.g. a restriction site
+    #[default]
+    Unknown,
+}
+
+impl Strand {
+    /// The strand of the reverse complement.
+    pub fn reverse(&self) -> Strand {
+        match self {
+            Strand::Forward => Strand::Reverse,
+            Strand::Reverse => Strand::Forward,
+            Strand::Unknown => Strand::Unknown,
+        }
+    }
+}
+
+/// Kind of a sequence feature, named after the GenBank feature keys.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::prelude::*;
+///
+/// assert_eq!(FeatureType::from("rep_origin"), FeatureType::RepOrigin);
+/// assert_eq!(FeatureType::from("LTR").to_string(), "LTR");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum FeatureType {
+    Cds,
+    Gene,
+    MRna,
+    Promoter,
+    Terminator,
+    Rbs,
+    RepOrigin,
+    PrimerBind,
+    ProteinBind,
+    PolyASignal,
+    Enhancer,
+    Source,
+    #[default]
+    MiscFeature,
+    /// Any other feature key
+    Other(String),
+}
+
+impl FeatureType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            FeatureType::Cds => "CDS",
+            FeatureType::Gene => "gene",
+            FeatureType::MRna => "mRNA",
+            FeatureType::Promoter => "promoter",
+            FeatureType::Terminator => "terminator",
+            FeatureType::Rbs => "RBS",
+            FeatureType::RepOrigin => "rep_origin",
+            FeatureType::PrimerBind => "primer_bind",
+            FeatureType::ProteinBind => "protein_bind",
+            FeatureType::PolyASignal => "polyA_signal",
+            FeatureType::Enhancer => "enhancer",
+            FeatureType::Source => "source",
+            FeatureType::MiscFeature => "misc_feature",
+            FeatureType::Other(key) => key,
+        }
+    }
+}
+
+impl From<&str> for FeatureType {
+    fn from(key: &str) -> Self {
+        match key {
+            "CDS" => FeatureType::Cds,
+            "gene" => FeatureType::Gene,
+            "mRNA" => FeatureType::MRna,
+            "promoter" => FeatureType::Promoter,
+            "terminator" => FeatureType::Terminator,
+            "RBS" => FeatureType::Rbs,
+            "rep_origin" => FeatureType::RepOrigin,
+            "primer_bind" => FeatureType::PrimerBind,
+            "protein_bind" => FeatureType::ProteinBind,
+            "polyA_signal" => FeatureType::PolyASignal,
+            "enhancer" => FeatureType::Enhancer,
+            "source" => FeatureType::Source,
+            "misc_feature" => FeatureType::MiscFeature,
+            other => FeatureType::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for FeatureType {
+    fn from(key: String) -> Self {
+        FeatureType::from(key.as_str())
+    }
+}
+
+impl From<FeatureType> for String {
+    fn from(kind: FeatureType) -> Self {
+        kind.as_str().to_string()
+    }
+}
+
+impl fmt::Display for FeatureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
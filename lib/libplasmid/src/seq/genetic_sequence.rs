@@ -1,16 +1,20 @@
 use std::{
     borrow::Borrow,
-    collections::VecDeque,
     marker::PhantomData,
-    ops::{Index, IndexMut},
+    ops::{Index, IndexMut, Range},
 };
 
 use crate::{
-    prelude::{IupacNucleotide, RestrictionEnzymes},
+    err::PlasmidError,
+    prelude::{
+        find_features, search_motif, variant_effects, CodingEffect, CommonFeatures, GcWindows,
+        IupacNucleotide, Motif, MotifHit, RestrictionEnzymes, SequenceStats, SequenceVariant,
+    },
     traits::*,
+    uni::RestrictionEnzyme,
 };
 
-use super::Annotation;
+use super::{edit_annotation, Annotation, FeatureType, SequenceEdit, Topology};
 
 pub struct GeneticSequence<B, C>
 where
@@ -19,6 +23,13 @@ where
 {
     sequence: Vec<B>,
     annotations: Vec<Annotation>,
+    topology: Topology,
+    /// Edits that can be undone, the latest last
+    history: Vec<SequenceEdit<B>>,
+    /// Undone edits that can be redone, the latest undone last
+    undone: Vec<SequenceEdit<B>>,
+    /// Maximum number of edits kept in the history, unlimited if `None`
+    history_limit: Option<usize>,
     phantom: PhantomData<C>,
 }
 
@@ -31,6 +42,10 @@ where
         Self {
             sequence: Vec::new(),
             annotations: Vec::new(),
+            topology: Topology::Linear,
+            history: Vec::new(),
+            undone: Vec::new(),
+            history_limit: None,
             phantom: PhantomData,
         }
     }
@@ -188,6 +203,29 @@ where
         Some(C::from_triplet_arr(seq))
     }
 
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Mark the sequence as linear or circular.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("ATGTTC").unwrap();
+    /// assert!(!seq.is_circular());
+    /// seq.set_topology(Topology::Circular);
+    /// assert!(seq.is_circular());
+    /// ```
+    pub fn set_topology(&mut self, topology: Topology) {
+        self.topology = topology
+    }
+
+    pub fn is_circular(&self) -> bool {
+        self.topology.is_circular()
+    }
+
     /// An iterator over the nucleotides of a genetic sequence.
     ///
     /// # Examples
@@ -266,6 +304,72 @@ where
         &mut self.annotations
     }
 
+    /// Annotations covering any position from `start` to `end` (inclusive).
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("ATGTTCTAA").unwrap();
+    /// seq.as_mut_annotations().push(Annotation::new(0, 2, None, "Start Codon"));
+    /// seq.as_mut_annotations().push(Annotation::new(6, 8, None, "Stop Codon"));
+    /// let found = seq.annotations_overlapping(2, 4).collect::<Vec<_>>();
+    /// assert_eq!(found.len(), 1);
+    /// assert_eq!(found[0].text, "Start Codon");
+    /// ```
+    pub fn annotations_overlapping(
+        &self,
+        start: usize,
+        end: usize,
+    ) -> impl Iterator<Item = &Annotation> + '_ {
+        self.annotations
+            .iter()
+            .filter(move |ann| ann.overlaps(start, end))
+    }
+
+    /// Annotations of the given feature type.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("ATGTTCTAA").unwrap();
+    /// let cds = Annotation::new(0, 8, None, "orf").with_kind(FeatureType::Cds);
+    /// seq.as_mut_annotations().push(cds);
+    /// seq.as_mut_annotations().push(Annotation::new(6, 8, None, "Stop Codon"));
+    /// assert_eq!(seq.annotations_of_type(&FeatureType::Cds).count(), 1);
+    /// ```
+    pub fn annotations_of_type<'a>(
+        &'a self,
+        kind: &'a FeatureType,
+    ) -> impl Iterator<Item = &'a Annotation> + 'a {
+        self.annotations.iter().filter(move |ann| ann.kind == *kind)
+    }
+
+    /// Annotations with a qualifier `key`, and if given, one with that exact `value`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("ATGTTCTAA").unwrap();
+    /// let cds = Annotation::new(0, 8, None, "orf").with_qualifier("gene", "foo");
+    /// seq.as_mut_annotations().push(cds);
+    /// assert_eq!(seq.annotations_with_qualifier("gene", None).count(), 1);
+    /// assert_eq!(seq.annotations_with_qualifier("gene", Some("bar")).count(), 0);
+    /// ```
+    pub fn annotations_with_qualifier<'a>(
+        &'a self,
+        key: &'a str,
+        value: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a Annotation> + 'a {
+        self.annotations.iter().filter(move |ann| {
+            ann.qualifiers
+                .iter()
+                .any(|(k, v)| k == key && value.is_none_or(|value| v == value))
+        })
+    }
+
     /// An iterator over the nucleotides of a genetic sequence.
     ///
     /// # Examples
@@ -421,14 +525,400 @@ where
         self.at_count() as f32 / self.gc_count() as f32
     }
 
+    /// Compute composition statistics of the sequence.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let seq = DnaSequence::from_str("ATGTTC").unwrap();
+    /// let stats = seq.stats();
+    /// assert_eq!(stats.length, 6);
+    /// assert!((stats.gc_content - seq.gc_ratio()).abs() <= std::f32::EPSILON);
+    /// ```
+    pub fn stats(&self) -> SequenceStats {
+        SequenceStats::compute(self.as_nucleotides())
+    }
+
+    /// Compute GC content and GC skew over a sliding window.
+    ///
+    /// # Example
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let seq = DnaSequence::from_str("GGAACC").unwrap();
+    /// let windows = seq.gc_windows(3, 3);
+    /// assert_eq!(windows.positions, [0, 3]);
+    /// ```
+    pub fn gc_windows(&self, window: usize, step: usize) -> GcWindows {
+        GcWindows::compute(self.as_nucleotides(), window, step)
+    }
+
+    /// Find all occurrences of a motif, which may contain IUPAC ambiguity
+    /// codes, on the top strand. Returns the start positions of the matches.
+    ///
+    /// On circular sequences matches may span the origin.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::{*, IupacNucleotide::*};
+    ///
+    /// let mut seq = DnaSequence::from_str("CCAAAAAAGG").unwrap();
+    /// assert!(seq.find_motif(&[G, G, S, C]).is_empty());
+    /// seq.set_topology(Topology::Circular);
+    /// assert_eq!(seq.find_motif(&[G, G, S, C]), [8]);
+    /// ```
+    pub fn find_motif(&self, motif: &[IupacNucleotide]) -> Vec<usize> {
+        let len = self.sequence.len();
+        if motif.is_empty() || motif.len() > len {
+            return Vec::new();
+        }
+        let starts = match self.topology {
+            Topology::Linear => len - motif.len() + 1,
+            Topology::Circular => len,
+        };
+        (0..starts)
+            .filter(|start| {
+                motif
+                    .iter()
+                    .enumerate()
+                    .all(|(i, m)| m.matches(&self.sequence[(start + i) % len].to_iupac()))
+            })
+            .collect()
+    }
+
+    /// Find all matches of an IUPAC, regex-like or PROSITE motif on both strands,
+    /// allowing up to `max_errors` mismatches, insertions and deletions, see `search_motif`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let seq = DnaSequence::from_str("CCGATTCCACC").unwrap();
+    /// let motif = Motif::iupac("GATTACA").unwrap();
+    /// assert!(seq.search_motif(&motif, 0).unwrap().is_empty());
+    /// let hits = seq.search_motif(&motif, 1).unwrap();
+    /// let ann = hits[0].to_annotation("GATTACA");
+    /// assert_eq!((ann.start, ann.end), (2, 8));
+    /// assert_eq!(ann.qualifier("note"), Some("edit distance 1"));
+    /// ```
+    pub fn search_motif(&self, motif: &Motif, max_errors: usize) -> anyhow::Result<Vec<MotifHit>> {
+        let seq = self.iupac_iter().collect::<Vec<_>>();
+        search_motif(&seq, self.is_circular(), motif, max_errors)
+    }
+
+    /// Effects of a variant on the CDS annotations of the sequence, see `variant_effects`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("ATGGGCCAGTAA").unwrap();
+    /// let mut cds = Annotation::new(0, 11, None, "orf");
+    /// cds.kind = FeatureType::Cds;
+    /// seq.as_mut_annotations().push(cds);
+    /// let effects = seq.variant_effects(&SequenceVariant::new(6, "C", "T")).unwrap();
+    /// assert_eq!(effects[0].effect, VariantEffect::Nonsense);
+    /// assert_eq!(effects[0].hgvs_c, "c.7C>T");
+    /// assert_eq!(effects[0].hgvs_p, "p.Gln3Ter");
+    /// ```
+    pub fn variant_effects(&self, variant: &SequenceVariant) -> anyhow::Result<Vec<CodingEffect>> {
+        let seq = self.iupac_iter().collect::<Vec<_>>();
+        variant_effects(&seq, self.is_circular(), &self.annotations, variant)
+    }
+
+    /// Rotate a circular sequence so that it starts at position `origin`.
+    ///
+    /// Annotations are moved along, and may span the new origin afterwards.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("AACCGG").unwrap();
+    /// seq.set_topology(Topology::Circular);
+    /// seq.as_mut_annotations().push(Annotation::new(0, 1, None, "AA"));
+    /// seq.set_origin(4).unwrap();
+    /// assert_eq!(seq.to_string(), "GGAACC");
+    /// let ann = seq.annotation_iter().next().unwrap();
+    /// assert_eq!((ann.start, ann.end), (2, 3));
+    /// ```
+    pub fn set_origin(&mut self, origin: usize) -> anyhow::Result<()> {
+        let len = self.sequence.len();
+        if !self.is_circular() {
+            bail!("Only circular sequences can be rotated to a new origin");
+        }
+        if origin >= len {
+            return Err(PlasmidError::InvalidRegion {
+                start: origin,
+                end: origin,
+                len,
+            }
+            .into());
+        }
+        self.sequence.rotate_left(origin);
+        let shift = |pos: usize| (pos + len - origin) % len;
+        for ann in self.annotations.iter_mut() {
+            ann.map_positions(shift);
+        }
+        // Recorded positions are meaningless after the rotation
+        self.history.clear();
+        self.undone.clear();
+        Ok(())
+    }
+
+    fn check_range(&self, range: &Range<usize>) -> anyhow::Result<()> {
+        let len = self.sequence.len();
+        if range.start > range.end || range.end > len {
+            return Err(PlasmidError::InvalidRegion {
+                start: range.start,
+                end: range.end,
+                len,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Replace the bases in `range` without recording the edit, returning the undo record.
+    fn splice(&mut self, range: Range<usize>, bases: Vec<B>) -> SequenceEdit<B> {
+        let len = self.sequence.len();
+        let annotations = self
+            .annotations
+            .iter()
+            .filter_map(|ann| edit_annotation(ann, len, &range, bases.len()))
+            .collect();
+        SequenceEdit {
+            start: range.start,
+            removed: self.sequence.splice(range, bases.iter().copied()).collect(),
+            inserted: bases,
+            annotations: std::mem::replace(&mut self.annotations, annotations),
+        }
+    }
+
+    /// Replace the bases in `range` with `bases`, returning the removed bases.
+    ///
+    /// Annotations after the edited region are shifted, annotations overlapping
+    /// it are trimmed to the bases they keep and dropped if they keep none.
+    /// Annotations enclosing the region grow or shrink with it.
+    /// The edit can be reverted with `undo`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::{*, DnaNucleotide::*};
+    ///
+    /// let mut seq = DnaSequence::from_str("AACCGGTT").unwrap();
+    /// seq.as_mut_annotations().push(Annotation::new(4, 7, None, "GGTT"));
+    /// let removed = seq.replace(2..4, &[A, A, A]).unwrap();
+    /// assert_eq!(removed, [C, C]);
+    /// assert_eq!(seq.to_string(), "AAAAAGGTT");
+    /// let ann = seq.annotation_iter().next().unwrap();
+    /// assert_eq!((ann.start, ann.end), (5, 8));
+    /// ```
+    pub fn replace(&mut self, range: Range<usize>, bases: &[B]) -> anyhow::Result<Vec<B>> {
+        self.check_range(&range)?;
+        let edit = self.splice(range, bases.to_vec());
+        let removed = edit.removed.clone();
+        self.history.push(edit);
+        self.undone.clear();
+        self.trim_history();
+        Ok(removed)
+    }
+
+    /// Insert `bases` before position `pos`, see `replace`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::{*, DnaNucleotide::*};
+    ///
+    /// let mut seq = DnaSequence::from_str("AATT").unwrap();
+    /// seq.insert(2, &[G, C]).unwrap();
+    /// assert_eq!(seq.to_string(), "AAGCTT");
+    /// assert!(seq.insert(7, &[G]).is_err());
+    /// ```
+    pub fn insert(&mut self, pos: usize, bases: &[B]) -> anyhow::Result<()> {
+        self.replace(pos..pos, bases)?;
+        Ok(())
+    }
+
+    /// Delete the bases in `range`, returning them, see `replace`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::{*, DnaNucleotide::*};
+    ///
+    /// let mut seq = DnaSequence::from_str("AAGCTT").unwrap();
+    /// assert_eq!(seq.delete(2..4).unwrap(), [G, C]);
+    /// assert_eq!(seq.to_string(), "AATT");
+    /// ```
+    pub fn delete(&mut self, range: Range<usize>) -> anyhow::Result<Vec<B>> {
+        self.replace(range, &[])
+    }
+
+    /// Edits made with `replace`, `insert` and `delete` that can be undone, the latest last.
+    pub fn history(&self) -> &[SequenceEdit<B>] {
+        &self.history
+    }
+
+    /// Undone edits that can be redone, the latest undone last.
+    pub fn undone(&self) -> &[SequenceEdit<B>] {
+        &self.undone
+    }
+
+    /// Keep at most `limit` edits in the history, dropping the oldest ones first.
+    /// Every edit holds a copy of the annotations before it, so long editing sessions
+    /// should set a limit.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::{*, DnaNucleotide::*};
+    ///
+    /// let mut seq = DnaSequence::from_str("AATT").unwrap();
+    /// seq.set_history_limit(Some(2));
+    /// for _ in 0..3 {
+    ///     seq.insert(0, &[G]).unwrap();
+    /// }
+    /// assert_eq!(seq.history().len(), 2);
+    /// assert!(seq.undo() && seq.undo() && !seq.undo());
+    /// assert_eq!(seq.to_string(), "GAATT");
+    /// ```
+    pub fn set_history_limit(&mut self, limit: Option<usize>) {
+        self.history_limit = limit;
+        self.trim_history();
+    }
+
+    fn trim_history(&mut self) {
+        if let Some(limit) = self.history_limit {
+            let excess = self.history.len().saturating_sub(limit);
+            self.history.drain(..excess);
+            let excess = self.undone.len().saturating_sub(limit);
+            self.undone.drain(..excess);
+        }
+    }
+
+    /// Revert an edit record, returning the record reverting it in turn.
+    fn revert(&mut self, edit: SequenceEdit<B>) -> Option<SequenceEdit<B>> {
+        let range = edit.inserted_range();
+        // The sequence may have been changed by other means since
+        if range.end > self.sequence.len()
+            || self.sequence[range.clone()]
+                .iter()
+                .zip(&edit.inserted)
+                .any(|(a, b)| a.to_letter() != b.to_letter())
+        {
+            return None;
+        }
+        let reverted = self.splice(range, edit.removed);
+        self.annotations = edit.annotations;
+        Some(reverted)
+    }
+
+    /// Undo the latest edit, restoring the bases and annotations before it.
+    /// Returns `false` if there is nothing to undo.
+    ///
+    /// Undoing edits is only possible as long as the sequence was not changed by other means,
+    /// rotating it with `set_origin` clears the history.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::{*, DnaNucleotide::*};
+    ///
+    /// let mut seq = DnaSequence::from_str("AATT").unwrap();
+    /// seq.insert(2, &[G, C]).unwrap();
+    /// assert!(seq.undo());
+    /// assert_eq!(seq.to_string(), "AATT");
+    /// assert!(seq.redo());
+    /// assert_eq!(seq.to_string(), "AAGCTT");
+    /// assert!(!seq.redo());
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.history.pop() else {
+            return false;
+        };
+        match self.revert(edit.clone()) {
+            Some(reverted) => {
+                self.undone.push(reverted);
+                true
+            }
+            None => {
+                self.history.push(edit);
+                false
+            }
+        }
+    }
+
+    /// Redo the latest undone edit. Returns `false` if there is nothing to redo,
+    /// any new edit discards the undone ones.
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.undone.pop() else {
+            return false;
+        };
+        match self.revert(edit.clone()) {
+            Some(reverted) => {
+                self.history.push(reverted);
+                true
+            }
+            None => {
+                self.undone.push(edit);
+                false
+            }
+        }
+    }
+
+    /// Extract the bases from `start` up to the exclusive `end` into a new linear sequence.
+    ///
+    /// Annotations are clipped to the extracted region and moved along.
+    /// On circular sequences, a region with a `start` after its `end` spans the origin.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("AACCGGTT").unwrap();
+    /// seq.as_mut_annotations().push(Annotation::new(1, 4, None, "ACCG"));
+    /// let sub = seq.subsequence(3, 6).unwrap();
+    /// assert_eq!(sub.to_string(), "CGG");
+    /// let ann = sub.annotation_iter().next().unwrap();
+    /// assert_eq!((ann.start, ann.end), (0, 1));
+    /// ```
+    pub fn subsequence(&self, start: usize, end: usize) -> anyhow::Result<GeneticSequence<B, C>> {
+        let len = self.sequence.len();
+        if self.is_circular() && start > end && start < len {
+            // Rotate a copy so the region no longer spans the origin
+            let mut rotated = GeneticSequence::<B, C>::new();
+            rotated.sequence = self.sequence.clone();
+            rotated.annotations = self.annotations.clone();
+            rotated.topology = Topology::Circular;
+            rotated.set_origin(start)?;
+            return rotated.subsequence(0, len - start + end);
+        }
+        let range = start..end;
+        self.check_range(&range)?;
+
+        let mut sub = GeneticSequence::<B, C>::new();
+        sub.sequence = self.sequence[range.clone()].to_vec();
+        sub.annotations = self
+            .annotations
+            .iter()
+            .filter_map(|ann| edit_annotation(ann, len, &(range.end..len), 0))
+            .filter_map(|ann| edit_annotation(&ann, range.end, &(0..range.start), 0))
+            // Features running through the origin outside of the region are cut in two
+            .filter(|ann| !ann.wraps_origin())
+            .collect();
+        Ok(sub)
+    }
+
     /// Annotate known restriction enzymes.
     ///
     /// The algorithm will iterate over the sequence multiple times
-    /// and try to find cut sites of known restriction enzymes.
+    /// and try to find cut sites of known restriction enzymes on both strands.
+    /// On circular sequences, sites spanning the origin are found as well
+    /// and annotated with an `end` smaller than their `start`.
     ///
-    /// All detected cut sites will be annotated with their corresponding
-    /// start-, stop-, and cut-positions. The cut position will be stored
-    /// inside of the `needle` variable of the `Annotation`.
+    /// All detected sites will be annotated with their corresponding
+    /// start- and (inclusive) end-positions. The top strand cut position
+    /// will be stored inside of the `needle` variable of the `Annotation`,
+    /// it is `None` for nicking enzymes that cut the bottom strand only.
     ///
     /// This function is relatively slow, don't run it on every insertion/deletion.
     ///
@@ -440,42 +930,71 @@ where
     /// seq.annotate_restriction_enzymes(); // should find NdeI: CA/TATG
     /// let ann = seq.as_annotations().first().unwrap();
     /// assert_eq!(ann.text, "NdeI");
-    /// assert_eq!(ann.start, 5);
-    /// assert_eq!(ann.needle, Some(7));
+    /// assert_eq!(ann.start, 6);
+    /// assert_eq!(ann.needle, Some(8));
     /// assert_eq!(ann.end, 11);
     /// ```
     pub fn annotate_restriction_enzymes(&mut self) {
-        let own_nucleotides = self.as_nucleotides();
+        self.annotate_restriction_enzymes_with(&RestrictionEnzymes)
+    }
+
+    /// Annotate the cut sites of a set of restriction enzymes, e.g. loaded from a REBASE file,
+    /// as `annotate_restriction_enzymes` does for the built-in ones.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let rebase = RebaseFile::import("<1>MyEnzI\n<2>\n<3>CA^TATG\n<4>\n<5>\n<6>\n<7>\n<8>\n").unwrap();
+    /// let mut seq = DnaSequence::from_str("ATGTTCCATATGTCTCGT").unwrap();
+    /// seq.annotate_restriction_enzymes_with(&rebase.enzymes);
+    /// assert_eq!(seq.as_annotations().len(), 1);
+    /// assert_eq!(seq.as_annotations()[0].text, "MyEnzI");
+    /// ```
+    pub fn annotate_restriction_enzymes_with(&mut self, enzymes: &[RestrictionEnzyme]) {
+        let len = self.sequence.len() as isize;
         let mut annotations: Vec<Annotation> = Vec::new();
-        for enzyme in RestrictionEnzymes.iter() {
-            let mut enzyme_seq = enzyme.before.clone();
-            enzyme_seq.extend(&enzyme.after);
-            let mut nucleotides: VecDeque<&B> = VecDeque::new();
-            for (own_index, own_nucleotide) in own_nucleotides.iter().enumerate() {
-                if nucleotides.len() == enzyme_seq.len() {
-                    nucleotides.pop_front();
-                }
-                nucleotides.push_back(own_nucleotide);
-                if nucleotides.len() == enzyme_seq.len() {
-                    let seq_matches = enzyme_seq
-                        .iter()
-                        .zip(nucleotides.iter().map(|n| n.to_iupac()))
-                        .all(|(n, m)| n.matches(&m));
-                    if seq_matches {
-                        let needle = own_index - nucleotides.len() + enzyme.before.len();
-                        let ann = Annotation::new_from_restriction_enzyme(
-                            own_index - nucleotides.len(),
-                            own_index,
-                            Some(needle),
-                            enzyme,
-                        );
-                        annotations.push(ann);
-                    }
-                }
+        for enzyme in enzymes.iter() {
+            for site in enzyme.find_sites(self.as_nucleotides(), self.is_circular()) {
+                let needle = match self.topology {
+                    Topology::Linear => site.top_cut.filter(|cut| (0..=len).contains(cut)),
+                    Topology::Circular => site.top_cut.map(|cut| cut.rem_euclid(len)),
+                };
+                let ann = Annotation::new_from_restriction_enzyme(
+                    site.start,
+                    (site.end - 1) % len as usize,
+                    needle.map(|cut| cut as usize),
+                    enzyme,
+                );
+                annotations.push(ann);
             }
         }
         self.annotations.extend(annotations);
     }
+
+    /// Annotate common plasmid features, e.g. promoters, resistance genes and tags.
+    ///
+    /// Both strands are searched for the bundled `CommonFeatures`, protein signatures
+    /// in all six reading frames. Features are found if at least `min_identity`
+    /// of their bases or residues match, see `DEFAULT_FEATURE_IDENTITY`.
+    /// Near-exact matches get a `note` qualifier with their identity.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::*;
+    ///
+    /// let mut seq = DnaSequence::from_str("CCTAATACGACTCACTATAGGGATTACAAGGATGACGACGATAAG").unwrap();
+    /// seq.annotate_common_features(0.9);
+    /// let ann = seq.annotations_of_type(&FeatureType::Promoter).next().unwrap();
+    /// assert_eq!(ann.text, "T7 promoter");
+    /// let ann = seq.annotations_of_type(&FeatureType::Cds).next().unwrap();
+    /// assert_eq!((ann.text.as_str(), ann.start, ann.end), ("FLAG", 21, 44));
+    /// ```
+    pub fn annotate_common_features(&mut self, min_identity: f64) {
+        let seq = self.iupac_iter().collect::<Vec<_>>();
+        let annotations = find_features(&seq, self.is_circular(), &CommonFeatures, min_identity);
+        self.annotations.extend(annotations);
+    }
 }
 
 impl<B, C> ToString for GeneticSequence<B, C>
@@ -528,7 +1047,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::seq::{DnaSequence, RnaSequence};
+    use crate::seq::{Annotation, DnaSequence, RnaSequence, Topology};
 
     #[test]
     fn test_rna_sequence_from_str() {
@@ -559,9 +1078,121 @@ mod tests {
         seq.annotate_restriction_enzymes();
         assert_eq!(seq.annotations.len(), 1);
         let ann = seq.annotations.first().unwrap();
-        assert_eq!(ann.start, 3);
-        assert_eq!(ann.needle, Some(5));
+        assert_eq!(ann.start, 4);
+        assert_eq!(ann.needle, Some(6));
         assert_eq!(ann.end, 9);
         assert_eq!(ann.text, "NdeI");
     }
+
+    #[test]
+    pub fn test_annotate_restriction_enzymes_reverse_strand() {
+        // BsaI site on the bottom strand, cutting upstream of it
+        let mut seq = DnaSequence::from_str("AAAATGCAGAGACCAA").unwrap();
+        seq.annotate_restriction_enzymes();
+        let ann = seq.annotations.iter().find(|a| a.text == "BsaI").unwrap();
+        assert_eq!((ann.start, ann.end), (8, 13));
+        assert_eq!(ann.needle, Some(3));
+    }
+
+    #[test]
+    pub fn test_annotate_restriction_enzymes_circular() {
+        // NdeI site CA|TATG spanning the origin
+        let mut seq = DnaSequence::from_str("TATGAAAACA").unwrap();
+        seq.annotate_restriction_enzymes();
+        assert!(!seq.annotations.iter().any(|a| a.text == "NdeI"));
+
+        seq.set_topology(Topology::Circular);
+        seq.annotate_restriction_enzymes();
+        let ann = seq.annotations.iter().find(|a| a.text == "NdeI").unwrap();
+        assert_eq!((ann.start, ann.end), (8, 3));
+        assert_eq!(ann.needle, Some(0));
+        assert!(ann.wraps_origin());
+    }
+
+    #[test]
+    pub fn test_set_origin() {
+        let mut seq = DnaSequence::from_str("TATGAAAACA").unwrap();
+        assert!(seq.set_origin(2).is_err());
+        seq.set_topology(Topology::Circular);
+        assert!(seq.set_origin(10).is_err());
+        seq.annotate_restriction_enzymes();
+        seq.set_origin(8).unwrap();
+        assert_eq!(seq.to_string(), "CATATGAAAA");
+        let ann = seq.annotations.iter().find(|a| a.text == "NdeI").unwrap();
+        assert_eq!((ann.start, ann.end, ann.needle), (0, 5, Some(2)));
+    }
+
+    #[test]
+    pub fn test_annotate_restriction_enzymes_at_start() {
+        let mut seq = DnaSequence::from_str("CATATGAAAA").unwrap();
+        seq.annotate_restriction_enzymes();
+        let ann = seq.annotations.iter().find(|a| a.text == "NdeI").unwrap();
+        assert_eq!(ann.start, 0);
+    }
+    #[test]
+    pub fn test_edit_annotations() {
+        use crate::dna::DnaNucleotide::*;
+
+        let mut seq = DnaSequence::from_str("AAAACATATGAAAA").unwrap();
+        seq.annotate_restriction_enzymes();
+        seq.insert(0, &[G, G]).unwrap();
+        let ann = seq.annotations.first().unwrap();
+        assert_eq!((ann.start, ann.end, ann.needle), (6, 11, Some(8)));
+        // Deleting the cut position drops it, but keeps the trimmed site
+        seq.delete(7..9).unwrap();
+        let ann = seq.annotations.first().unwrap();
+        assert_eq!((ann.start, ann.end, ann.needle), (6, 9, None));
+        seq.replace(5..12, &[C]).unwrap();
+        assert!(seq.annotations.is_empty());
+        assert_eq!(seq.to_string(), "GGAAACAA");
+    }
+
+    #[test]
+    pub fn test_undo_redo() {
+        use crate::dna::DnaNucleotide::*;
+
+        let mut seq = DnaSequence::from_str("AAAACATATGAAAA").unwrap();
+        seq.annotate_restriction_enzymes();
+        let annotations = seq.annotations.clone();
+        seq.delete(4..10).unwrap();
+        seq.insert(4, &[T]).unwrap();
+        assert_eq!(seq.history().len(), 2);
+        assert!(seq.undo());
+        assert!(seq.undo());
+        assert!(!seq.undo());
+        assert_eq!(seq.to_string(), "AAAACATATGAAAA");
+        assert_eq!(seq.annotations, annotations);
+        assert!(seq.redo());
+        assert_eq!(seq.to_string(), "AAAAAAAA");
+        assert!(seq.annotations.is_empty());
+        // A new edit discards the undone ones
+        seq.insert(0, &[G]).unwrap();
+        assert!(!seq.redo());
+        assert!(seq.undo());
+        assert_eq!(seq.to_string(), "AAAAAAAA");
+        assert!(seq.delete(4..9).is_err());
+    }
+
+    #[test]
+    pub fn test_subsequence() {
+        let mut seq = DnaSequence::from_str("TATGAAAACA").unwrap();
+        seq.set_topology(Topology::Circular);
+        seq.annotate_restriction_enzymes();
+        seq.as_mut_annotations()
+            .push(Annotation::new(3, 6, Some(5), "GAAA"));
+        let sub = seq.subsequence(5, 9).unwrap();
+        assert_eq!(sub.to_string(), "AAAC");
+        assert_eq!(sub.topology(), Topology::Linear);
+        let ann = sub.annotations.iter().find(|a| a.text == "GAAA").unwrap();
+        assert_eq!((ann.start, ann.end, ann.needle), (0, 1, Some(0)));
+        // Sites running through the origin outside of the region are cut in two
+        let sub = seq.subsequence(1, 9).unwrap();
+        assert!(!sub.annotations.iter().any(|a| a.text == "NdeI"));
+        // Regions spanning the origin keep the whole site
+        let sub = seq.subsequence(7, 5).unwrap();
+        assert_eq!(sub.to_string(), "ACATATGA");
+        let ann = sub.annotations.iter().find(|a| a.text == "NdeI").unwrap();
+        assert_eq!((ann.start, ann.end, ann.needle), (1, 6, Some(3)));
+        assert!(seq.subsequence(4, 11).is_err());
+    }
 }
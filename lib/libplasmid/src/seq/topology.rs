@@ -0,0 +1,15 @@
+/// Shape of a DNA molecule.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// Open molecule with two ends, e.g. a PCR product or a chromosome
+    #[default]
+    Linear,
+    /// Closed molecule whose last base is followed by its first, e.g. a plasmid
+    Circular,
+}
+
+impl Topology {
+    pub fn is_circular(&self) -> bool {
+        *self == Topology::Circular
+    }
+}
@@ -0,0 +1,31 @@
+//! Helpers shared by unit tests.
+
+use crate::{
+    seq::{reverse_complement, DnaSequence, Topology},
+    traits::*,
+    uni::IupacNucleotide,
+};
+
+/// Nucleotides of a sequence written with IUPAC letters.
+pub fn iupac(s: &str) -> Vec<IupacNucleotide> {
+    s.chars()
+        .map(|c| IupacNucleotide::try_from_letter(c).unwrap())
+        .collect()
+}
+
+/// Letters of a sequence of nucleotides.
+pub fn letters(seq: &[IupacNucleotide]) -> String {
+    seq.iter().map(|n| n.to_letter()).collect()
+}
+
+/// Reverse complement of a sequence written with IUPAC letters.
+pub fn reverse_complement_letters(s: &str) -> String {
+    letters(&reverse_complement(&iupac(s)))
+}
+
+/// Circular DNA sequence written with IUPAC letters.
+pub fn circular(s: &str) -> DnaSequence {
+    let mut seq = DnaSequence::from_str(s).unwrap();
+    seq.set_topology(Topology::Circular);
+    seq
+}
@@ -1,5 +1,7 @@
+pub mod common_features;
 pub mod iupac_nucleotide;
 pub mod restriction_enzymes;
 
+pub use self::common_features::*;
 pub use self::iupac_nucleotide::*;
 pub use self::restriction_enzymes::*;
@@ -0,0 +1,133 @@
+#![allow(non_upper_case_globals)]
+
+use crate::{seq::FeatureType, traits::TryFromLetter, uni::IupacNucleotide};
+
+/// Sequence a feature is recognized by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeatureSignature {
+    /// Nucleotides of the top strand, may contain IUPAC codes
+    Nucleotide(Vec<IupacNucleotide>),
+    /// One letter amino acid codes of the translated feature, `X` matches any residue
+    Protein(String),
+}
+
+/// A well known feature of cloning vectors, e.g. a promoter or a resistance gene.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommonFeature {
+    pub name: String,
+    pub kind: FeatureType,
+    pub signature: FeatureSignature,
+}
+
+impl CommonFeature {
+    pub fn nucleotide<T>(name: T, kind: FeatureType, sequence: &str) -> anyhow::Result<Self>
+    where
+        T: AsRef<str>,
+    {
+        let sequence = sequence
+            .chars()
+            .map(IupacNucleotide::try_from_letter)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            name: name.as_ref().to_string(),
+            kind,
+            signature: FeatureSignature::Nucleotide(sequence),
+        })
+    }
+
+    pub fn protein<T>(name: T, kind: FeatureType, sequence: &str) -> Self
+    where
+        T: AsRef<str>,
+    {
+        Self {
+            name: name.as_ref().to_string(),
+            kind,
+            signature: FeatureSignature::Protein(sequence.to_ascii_uppercase()),
+        }
+    }
+
+    /// Length of the feature in bases.
+    pub fn len(&self) -> usize {
+        match &self.signature {
+            FeatureSignature::Nucleotide(sequence) => sequence.len(),
+            FeatureSignature::Protein(sequence) => sequence.len() * 3,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+macro_rules! define_feature {
+    ($kind:ident; $name:expr => nt $sequence:expr) => {
+        CommonFeature::nucleotide($name, FeatureType::$kind, $sequence).unwrap()
+    };
+    ($kind:ident; $name:expr => aa $sequence:expr) => {
+        CommonFeature::protein($name, FeatureType::$kind, $sequence)
+    };
+}
+
+lazy_static! {
+    /// Bundled library of common plasmid features.
+    pub static ref CommonFeatures: Vec<CommonFeature> = vec![
+        // Promoters
+        define_feature!(Promoter; "T7 promoter" => nt "TAATACGACTCACTATAGG"),
+        define_feature!(Promoter; "T3 promoter" => nt "AATTAACCCTCACTAAAGG"),
+        define_feature!(Promoter; "SP6 promoter" => nt "ATTTAGGTGACACTATAG"),
+        define_feature!(Promoter; "lac promoter" => nt "TTTACACTTTATGCTTCCGGCTCGTATGTTG"),
+        define_feature!(ProteinBind; "lac operator" => nt "TTGTGAGCGGATAACAA"),
+        // Terminators
+        define_feature!(Terminator; "T7 terminator" => nt "CTAGCATAACCCCTTGGGGCCTCTAAACGGGTCTTGAGGGGTTTTTTG"),
+        // Sequencing primers
+        define_feature!(PrimerBind; "M13 fwd" => nt "GTAAAACGACGGCCAGT"),
+        define_feature!(PrimerBind; "M13 rev" => nt "CAGGAAACAGCTATGAC"),
+        // Origins of replication
+        define_feature!(RepOrigin; "pMB1/ColE1 ori" => nt concat!(
+            "TTGAGATCCTTTTTTTCTGCGCGTAATCTGCTGCTTGCAAACAAAAAAACCACCGCTACC",
+            "AGCGGTGGTTTGTTTGCCGGATCAAGAGCTACCAACTCTTTTTCCGAAGGTAACTGGCTT",
+            "CAGCAGAGCGCAGATACCAAATACTGTTCTTCTAGTGTAGCCGTAGTTAGGCCACCACTT",
+            "CAAGAACTCTGTAGCACCGCCTACATACCTCGCTCTGCTAATCCTGTTACCAGTGGCTGC",
+            "TGCCAGTGGCGATAAGTCGTGTCTTACCGGGTTGGACTCAAGACGATAGTTACCGGATAA",
+            "GGCGCAGCGGTCGGGCTGAACGGGGGGTTCGTGCACACAGCCCAGCTTGGAGCGAACGAC",
+            "CTACACCGAACTGAGATACCTACAGCGTGAGCTATGAGAAAGCGCCACGCTTCCCGAAGG",
+            "GAGAAAGGCGGACAGGTATCCGGTAAGCGGCAGGGTCGGAACAGGAGAGCGCACGAGGGA",
+            "GCTTCCAGGGGGAAACGCCTGGTATCTTTATAGTCCTGTCGGGTTTCGCCACCTCTGACT",
+            "TGAGCGTCGATTTTTGTGATGCTCGTCAGGGGGGCGGAGCCTATGGAAA",
+        )),
+        // Resistance markers
+        define_feature!(Cds; "AmpR" => aa concat!(
+            "MSIQHFRVALIPFFAAFCLPVFAHPETLVKVKDAEDQLGARVGYIELDLNSGKILESFRPE",
+            "ERFPMMSTFKVLLCGAVLSRVDAGQEQLGRRIHYSQNDLVEYSPVTEKHLTDGMTVRELCS",
+            "AAITMSDNTAANLLLTTIGGPKELTAFLHNMGDHVTRLDRWEPELNEAIPNDERDTTMPAA",
+            "MATTLRKLLTGELLTLASRQQLIDWMEADKVAGPLLRSALPAGWFIADKSGAGERGSRGII",
+            "AALGPDGKPSRIVVIYTTGSQATMDERNRQIAEIGASLIKHW",
+        )),
+        define_feature!(Cds; "NeoR/KanR" => aa concat!(
+            "MIEQDGLHAGSPAAWVERLFGYDWAQQTIGCSDAAVFRLSAQGRPVLFVKTDLSGALNELQ",
+            "DEAARLSWLATTGVPCAAVLDVVTEAGRDWLLLGEVPGQDLLSSHLAPAEKVSIMADAMRR",
+            "LHTLDPATCPFDHQAKHRIERARTRMEAGLVDQDDLDEEHQGLAPAELFARLKARMPDGED",
+            "LVVTHGDACLPNIMVENGRFSGFIDCGRLGVADRYQDIALATRDIAEELGGEWADRFLVLY",
+            "GIAAPDSQRIAFYRLLDEFF",
+        )),
+        define_feature!(Cds; "CmR" => aa concat!(
+            "MEKKITGYTTVDISQWHRKEHFEAFQSVAQCTYNQTVQLDITAFLKTVKKNKHKFYPAFIH",
+            "ILARLMNAHPEFRMAMKDGELVIWDSVHPCYTVFHEQTETFSSLWSEYHDDFRQFLHIYSQ",
+            "DVACYGENLAYFPKGFIENMFFVSANPWVSFTSFDLNVANMDNFFAPVFTMGKYYTQGDKV",
+            "LMPLAIQVHHAVCDGFHVGRMLNELQQYCDEWQGGA",
+        )),
+        // Reporters, lacZα is read behind the multiple cloning site of pUC vectors
+        define_feature!(Cds; "lacZα" => aa concat!(
+            "LAVVLQRRDWENPGVTQLNRLAAHPPFASWRNSEEARTDRPSQQLRSLNGEWRLMRYFLLT",
+            "HLCGISHRIWCTLSTICSDAA",
+        )),
+        // Protein tags
+        define_feature!(Cds; "6xHis" => aa "HHHHHH"),
+        define_feature!(Cds; "FLAG" => aa "DYKDDDDK"),
+        define_feature!(Cds; "HA" => aa "YPYDVPDYA"),
+        define_feature!(Cds; "Myc" => aa "EQKLISEEDL"),
+        define_feature!(Cds; "V5 tag" => aa "GKPIPNPLLGLDST"),
+        define_feature!(Cds; "Strep-tag II" => aa "WSHPQFEK"),
+        define_feature!(Cds; "T7 tag" => aa "MASMTGGQQMG"),
+    ];
+}
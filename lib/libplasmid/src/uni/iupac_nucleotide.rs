@@ -63,6 +63,37 @@ impl IupacNucleotide {
         }
     }
 
+    /// The unambiguous nucleotides represented by this code.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::prelude::IupacNucleotide::*;
+    ///
+    /// assert_eq!(R.bases(), &[A, G]);
+    /// assert!(Gap.bases().is_empty());
+    /// ```
+    pub fn bases(&self) -> &'static [IupacNucleotide] {
+        use self::IupacNucleotide::*;
+        match self {
+            A => &[A],
+            C => &[C],
+            G => &[G],
+            T => &[T],
+            W => &[A, T],
+            S => &[C, G],
+            M => &[A, C],
+            K => &[G, T],
+            R => &[A, G],
+            Y => &[C, T],
+            B => &[C, G, T],
+            D => &[A, G, T],
+            H => &[A, C, T],
+            V => &[A, C, G],
+            N => &[A, C, G, T],
+            Gap => &[],
+        }
+    }
+
     pub(crate) fn all_as_str() -> &'static str {
         "ACGTWSMKRYBVDHN-"
     }
@@ -1,40 +1,356 @@
 #![allow(non_upper_case_globals)]
 
-use crate::uni::{IupacNucleotide, IupacNucleotide::*};
+use crate::{
+    traits::{Nucleotide, ToIupac},
+    uni::{IupacNucleotide, IupacNucleotide::*},
+};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CutMode {
-    /// Cut in the middle
+    /// Cut in the middle of the site, between `before` and `after`
     M,
-    /// Cut before and after
+    /// Cut apart from the site (Type IIS)
     A,
+    /// Nick a single strand
+    N,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Chemical modification of a base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Methylation {
+    /// N4-methylcytosine
+    N4Cytosine,
+    /// 5-methylcytosine
+    C5Cytosine,
+    /// N6-methyladenine
+    N6Adenine,
+}
+
+impl Methylation {
+    /// REBASE code of the modification: 4, 5 or 6.
+    pub fn code(&self) -> u8 {
+        match self {
+            Methylation::N4Cytosine => 4,
+            Methylation::C5Cytosine => 5,
+            Methylation::N6Adenine => 6,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            4 => Some(Methylation::N4Cytosine),
+            5 => Some(Methylation::C5Cytosine),
+            6 => Some(Methylation::N6Adenine),
+            _ => None,
+        }
+    }
+}
+
+/// A methylated base of a recognition site that blocks cleavage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MethylationSite {
+    /// 1-based position within the site, negative for the bottom strand
+    pub position: isize,
+    pub methylation: Methylation,
+}
+
+/// REBASE codes and names of commercial enzyme suppliers.
+pub const SUPPLIERS: &[(char, &str)] = &[
+    ('B', "Thermo Fisher Scientific (Invitrogen)"),
+    ('C', "Minotech Biotechnology"),
+    ('E', "Agilent Technologies"),
+    ('F', "Thermo Fisher Scientific (Fermentas)"),
+    ('I', "SibEnzyme Ltd."),
+    ('J', "Nippon Gene Co., Ltd."),
+    ('K', "Takara Bio Inc."),
+    ('M', "Roche Applied Science"),
+    ('N', "New England Biolabs"),
+    ('O', "Toyobo Biochemicals"),
+    ('Q', "Molecular Biology Resources - CHIMERx"),
+    ('R', "Promega Corporation"),
+    ('S', "Sigma Chemical Corporation"),
+    ('V', "Vivantis Technologies"),
+    ('X', "EURx Ltd."),
+    ('Y', "SinaClon BioScience Co."),
+];
+
+/// Look up the name of a supplier by its REBASE code.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::uni::supplier_name;
+///
+/// assert_eq!(supplier_name('N'), Some("New England Biolabs"));
+/// assert_eq!(supplier_name('?'), None);
+/// ```
+pub fn supplier_name(code: char) -> Option<&'static str> {
+    SUPPLIERS
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RestrictionEnzyme {
     pub name: String,
     pub before: Vec<IupacNucleotide>,
     pub after: Vec<IupacNucleotide>,
+    /// Methylated bases blocking cleavage, empty if unknown
+    pub methylation: Vec<MethylationSite>,
+    /// REBASE codes of commercial suppliers, empty if unknown
+    pub suppliers: Vec<char>,
     mode: CutMode,
+    /// Top strand cut, relative to the start of the site
+    top_cut: Option<isize>,
+    /// Bottom strand cut, relative to the start of the site in top strand coordinates
+    bottom_cut: Option<isize>,
+}
+
+/// A recognition site of a restriction enzyme in a sequence.
+///
+/// Positions are given in top strand coordinates. On circular sequences
+/// `end` and the cut positions may exceed the sequence length, or cuts may
+/// lie before the origin, and have to be wrapped by the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestrictionSite {
+    /// First position of the site
+    pub start: usize,
+    /// Position after the last base of the site
+    pub end: usize,
+    /// Whether the site was found on the top strand
+    pub forward: bool,
+    /// Top strand cut position, `None` if the strand is not cut
+    pub top_cut: Option<isize>,
+    /// Bottom strand cut position, `None` if the strand is not cut
+    pub bottom_cut: Option<isize>,
 }
 
 impl RestrictionEnzyme {
+    /// Define an enzyme that cuts both strands within its site,
+    /// the top strand between `before` and `after`. The bottom strand
+    /// cut mirrors the top strand cut of the palindromic site.
     pub fn new(
         name: &str,
         before: &[IupacNucleotide],
         after: &[IupacNucleotide],
         mode: CutMode,
     ) -> Self {
+        let top = before.len() as isize;
+        let bottom = (before.len() + after.len()) as isize - top;
         Self {
             name: name.to_string(),
             before: Vec::from_iter(before.iter().copied()),
             after: Vec::from_iter(after.iter().copied()),
+            methylation: Vec::new(),
+            suppliers: Vec::new(),
             mode,
+            top_cut: Some(top),
+            bottom_cut: Some(bottom),
+        }
+    }
+
+    /// Define an enzyme with explicit cut offsets relative to the start of its site.
+    /// Offsets may lie outside of the site, a missing offset leaves the strand uncut.
+    pub fn new_with_cuts(
+        name: &str,
+        site: &[IupacNucleotide],
+        top_cut: Option<isize>,
+        bottom_cut: Option<isize>,
+        mode: CutMode,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            before: Vec::from_iter(site.iter().copied()),
+            after: Vec::new(),
+            methylation: Vec::new(),
+            suppliers: Vec::new(),
+            mode,
+            top_cut,
+            bottom_cut,
+        }
+    }
+
+    pub fn mode(&self) -> &CutMode {
+        &self.mode
+    }
+
+    /// The full recognition site, 5' to 3'.
+    pub fn site(&self) -> Vec<IupacNucleotide> {
+        let mut site = self.before.clone();
+        site.extend(&self.after);
+        site
+    }
+
+    /// Whether the site reads the same on both strands.
+    pub fn is_palindromic(&self) -> bool {
+        let site = self.site();
+        site.iter()
+            .zip(site.iter().rev())
+            .all(|(a, b)| *a == b.complement())
+    }
+
+    /// Whether the enzyme cuts a single strand only.
+    pub fn is_nicking(&self) -> bool {
+        self.top_cut.is_none() || self.bottom_cut.is_none()
+    }
+
+    /// Whether the enzyme cuts outside of its site.
+    pub fn is_type_iis(&self) -> bool {
+        let len = self.site().len() as isize;
+        [self.top_cut, self.bottom_cut]
+            .iter()
+            .flatten()
+            .any(|cut| *cut < 0 || *cut > len)
+    }
+
+    /// Cut positions on the top and bottom strand, relative to the start of the site,
+    /// or `None` for nicking enzymes.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::uni::find_restriction_enzyme;
+    ///
+    /// let ecori = find_restriction_enzyme("EcoRI").unwrap(); // G^AATT_C
+    /// assert_eq!(ecori.cut_offsets(), Some((1, 5)));
+    /// let bsai = find_restriction_enzyme("BsaI").unwrap(); // GGTCTC(1/5)
+    /// assert_eq!(bsai.cut_offsets(), Some((7, 11)));
+    /// ```
+    pub fn cut_offsets(&self) -> Option<(isize, isize)> {
+        Some((self.top_cut?, self.bottom_cut?))
+    }
+
+    /// Length of the single-stranded overhang left by the enzyme: positive for
+    /// 5' overhangs, negative for 3' overhangs, zero for blunt ends and `None`
+    /// for nicking enzymes.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::uni::find_restriction_enzyme;
+    ///
+    /// assert_eq!(find_restriction_enzyme("EcoRI").unwrap().overhang(), Some(4));
+    /// assert_eq!(find_restriction_enzyme("PstI").unwrap().overhang(), Some(-4));
+    /// assert_eq!(find_restriction_enzyme("SmaI").unwrap().overhang(), Some(0));
+    /// assert_eq!(find_restriction_enzyme("NbBssSI").unwrap().overhang(), None);
+    /// ```
+    pub fn overhang(&self) -> Option<isize> {
+        self.cut_offsets().map(|(top, bottom)| bottom - top)
+    }
+
+    /// Find all sites of the enzyme on both strands of a sequence.
+    ///
+    /// Non-palindromic sites are searched on the reverse complement as well,
+    /// where the cut positions are mirrored. Sites spanning the origin are found
+    /// if the sequence is circular.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use plasmid::{prelude::*, uni::find_restriction_enzyme};
+    ///
+    /// // BsaI site on the bottom strand: GAGACC = reverse complement of GGTCTC
+    /// let seq = DnaSequence::from_str("AAAATGCAGAGACCAA").unwrap();
+    /// let sites = find_restriction_enzyme("BsaI").unwrap().find_sites(seq.as_nucleotides(), false);
+    /// assert_eq!(sites.len(), 1);
+    /// assert!(!sites[0].forward);
+    /// assert_eq!((sites[0].top_cut, sites[0].bottom_cut), (Some(3), Some(7)));
+    /// ```
+    pub fn find_sites<T>(&self, sequence: &[T], circular: bool) -> Vec<RestrictionSite>
+    where
+        T: ToIupac,
+    {
+        let sequence = sequence.iter().map(|n| n.to_iupac()).collect::<Vec<_>>();
+        let len = sequence.len();
+        let site = self.site();
+        let n = site.len();
+        if n == 0 || n > len {
+            return Vec::new();
+        }
+        let site_rc = site
+            .iter()
+            .rev()
+            .map(|b| b.complement())
+            .collect::<Vec<_>>();
+        let mut orientations = vec![(true, site)];
+        if !self.is_palindromic() {
+            orientations.push((false, site_rc));
         }
+        let starts = if circular { len } else { len - n + 1 };
+
+        let mut sites = Vec::new();
+        for (forward, site) in orientations {
+            for start in 0..starts {
+                let matches = site
+                    .iter()
+                    .enumerate()
+                    .all(|(i, b)| b.matches(&sequence[(start + i) % len]));
+                if !matches {
+                    continue;
+                }
+                let offset = |cut: Option<isize>| match forward {
+                    true => cut.map(|cut| start as isize + cut),
+                    // Mirror the cut positions on the reverse complement
+                    false => cut.map(|cut| (start + n) as isize - cut),
+                };
+                let (top_cut, bottom_cut) = match forward {
+                    true => (offset(self.top_cut), offset(self.bottom_cut)),
+                    false => (offset(self.bottom_cut), offset(self.top_cut)),
+                };
+                sites.push(RestrictionSite {
+                    start,
+                    end: start + n,
+                    forward,
+                    top_cut,
+                    bottom_cut,
+                });
+            }
+        }
+        sites.sort_by_key(|site| site.start);
+        sites
     }
 }
 
+/// Look up a known restriction enzyme by its name, ignoring case and
+/// variant suffixes like `_v2` or `_HF` if there is no exact match.
+///
+/// # Examples
+/// ```rust
+/// use plasmid::uni::find_restriction_enzyme;
+///
+/// assert_eq!(find_restriction_enzyme("ecori").unwrap().name, "EcoRI");
+/// assert_eq!(find_restriction_enzyme("BsmBI").unwrap().name, "BsmBI_v2");
+/// assert_eq!(find_restriction_enzyme("Nb.BssSI").unwrap().name, "NbBssSI");
+/// assert!(find_restriction_enzyme("FooI").is_none());
+/// ```
+pub fn find_restriction_enzyme(name: &str) -> Option<&'static RestrictionEnzyme> {
+    find_restriction_enzyme_in(&RestrictionEnzymes, name)
+}
+
+/// Look up a restriction enzyme by its name in a list of enzymes,
+/// e.g. one loaded from a REBASE file, see [`find_restriction_enzyme`].
+/// Dots as in REBASE nicking enzyme names (`Nt.BstNBI`) are ignored.
+pub fn find_restriction_enzyme_in<'a>(
+    enzymes: &'a [RestrictionEnzyme],
+    name: &str,
+) -> Option<&'a RestrictionEnzyme> {
+    let normalize = |name: &str| name.replace('.', "").to_lowercase();
+    let name = normalize(name);
+    enzymes
+        .iter()
+        .find(|enzyme| normalize(&enzyme.name) == name)
+        .or_else(|| {
+            enzymes.iter().find(|enzyme| {
+                enzyme
+                    .name
+                    .split('_')
+                    .next()
+                    .is_some_and(|base| normalize(base) == name)
+            })
+        })
+}
+
 macro_rules! define_enzyme {
+    ($mode:ident; $name:ident: $site:expr, ($top:expr, $bottom:expr)) => {
+        RestrictionEnzyme::new_with_cuts(stringify!($name), &$site, $top, $bottom, CutMode::$mode)
+    };
     ($mode:ident; $name:ident: $before:expr, $after:expr) => {
         RestrictionEnzyme::new(stringify!($name), &$before, &$after, CutMode::$mode)
     };
@@ -65,7 +381,7 @@ lazy_static! {
         define_enzyme!(M; AseI: [A,T], [T,A,A,T]),
         define_enzyme!(M; SwaI: [A,T,T,T], [A,A,A,T]),
         define_enzyme!(M; MfeI: [C], [A,A,T,T,G]),
-        define_enzyme!(M; NbBssSI: [C,A,C,G,A,G], []),
+        define_enzyme!(N; NbBssSI: [C,A,C,G,A,G], (None, Some(5))),
         define_enzyme!(M; PmlI: [C,A,C], [G,T,G]),
         define_enzyme!(M; DraIII: [C,A,C,N,N,N], [G,T,G]),
         define_enzyme!(M; AleI_v2: [C,A,C,N,N], [N,N,G,T,G]),
@@ -93,7 +409,7 @@ lazy_static! {
         define_enzyme!(M; BtgI: [C], [C,R,Y,G,G]),
         define_enzyme!(M; NciI: [C,C], [S,G,G]),
         define_enzyme!(M; AvrII: [C], [C,T,A,G,G]),
-        define_enzyme!(M; NbBbvCI: [C,C,T,C,A,G,C], []),
+        define_enzyme!(N; NbBbvCI: [C,C,T,C,A,G,C], (None, Some(5))),
         define_enzyme!(M; SbfI: [C,C,T,G,C,A], [G,G]),
         define_enzyme!(M; Bsu36I: [C,C], [T,N,A,G,G]),
         define_enzyme!(M; EcoNI: [C,C,T,N,N], [N,N,N,A,G,G]),
@@ -106,7 +422,8 @@ lazy_static! {
         define_enzyme!(M; RsrII: [C,G], [G,W,C,C,G]),
         define_enzyme!(M; BsiEI: [C,G,R,Y], [C,G]),
         define_enzyme!(M; BsiWI: [C], [G,T,A,C,G]),
-        define_enzyme!(M; BsmBI_v2: [C,G,T,C,T,C], []),
+        define_enzyme!(A; BsmBI_v2: [C,G,T,C,T,C], (Some(7), Some(11))),
+        define_enzyme!(A; Esp3I: [C,G,T,C,T,C], (Some(7), Some(11))),
         define_enzyme!(M; Hpy99I: [C,G,W,C,G], []),
         define_enzyme!(M; MspA1I: [C,M,G], [C,K,G]),
         define_enzyme!(M; AbaSI: [C,N,N,N,N,N,N,N,N,N,N,N], [N,N,N,N,N,N,N,N,N,G]),
@@ -122,7 +439,8 @@ lazy_static! {
         define_enzyme!(M; BsoBI: [C], [Y,C,G,R,G]),
         define_enzyme!(M; AvaI: [C], [Y,C,G,R,G]),
         define_enzyme!(M; XmnI: [G,A,A,N,N], [N,N,T,T,C]),
-        define_enzyme!(M; NbBsmI: [G,A,A,T,G,C], []),
+        define_enzyme!(A; BbsI: [G,A,A,G,A,C], (Some(8), Some(12))),
+        define_enzyme!(N; NbBsmI: [G,A,A,T,G,C], (None, Some(5))),
         define_enzyme!(M; EcoRI: [G], [A,A,T,T,C]),
         define_enzyme!(M; AatII: [G,A,C,G,T], [C]),
         define_enzyme!(M; ZraI: [G,A,C], [G,T,C]),
@@ -141,8 +459,9 @@ lazy_static! {
         define_enzyme!(M; DpnI: [G,A], [T,C]),
         define_enzyme!(M; BsaBI: [G,A,T,N,N], [N,N,A,T,C]),
         define_enzyme!(M; TfiI: [G], [A,W,T,C]),
-        define_enzyme!(M; NbBsrDI: [G,C,A,A,T,G], []),
-        define_enzyme!(M; NbBtsI: [G,C,A,G,T,G], []),
+        define_enzyme!(N; NbBsrDI: [G,C,A,A,T,G], (None, Some(6))),
+        define_enzyme!(N; NbBtsI: [G,C,A,G,T,G], (None, Some(6))),
+        define_enzyme!(A; SapI: [G,C,T,C,T,T,C], (Some(8), Some(11))),
         define_enzyme!(M; BstAPI: [G,C,A,N,N,N,N], [N,T,G,C]),
         define_enzyme!(M; SphI: [G,C,A,T,G], [C]),
         define_enzyme!(M; SrfI: [G,C,C,C], [G,G,G,C]),
@@ -179,6 +498,7 @@ lazy_static! {
         define_enzyme!(M; Acc65I: [G], [G,T,A,C,C]),
         define_enzyme!(M; KpnI: [G,G,T,A,C], [C]),
         define_enzyme!(M; BstEII: [G], [G,T,N,A,C,C]),
+        define_enzyme!(A; BsaI: [G,G,T,C,T,C], (Some(7), Some(11))),
         define_enzyme!(M; AvaII: [G], [G,W,C,C]),
         define_enzyme!(M; BanI: [G], [G,Y,R,C,C]),
         define_enzyme!(M; BaeGI: [G,K,G,C,M], [C]),
@@ -186,7 +506,7 @@ lazy_static! {
         define_enzyme!(M; BanII: [G,R,G,C,Y], [C]),
         define_enzyme!(M; CviQI: [G], [T,A,C]),
         define_enzyme!(M; RsaI: [G,T], [A,C]),
-        define_enzyme!(M; BstZ17I: [G,T,A,T,A,C], []),
+        define_enzyme!(M; BstZ17I: [G,T,A], [T,A,C]),
         define_enzyme!(M; SalI: [G], [T,C,G,A,C]),
         define_enzyme!(M; ApaLI: [G], [T,G,C,A,C]),
         define_enzyme!(M; AccI: [G,T], [M,K,A,C]),
@@ -231,3 +551,58 @@ lazy_static! {
         define_enzyme!(M; EaeI: [Y], [G,G,C,C,R]),
     ];
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{find_restriction_enzyme, CutMode};
+    use crate::seq::DnaSequence;
+
+    #[test]
+    fn test_enzyme_classification() {
+        let ecori = find_restriction_enzyme("EcoRI").unwrap();
+        assert!(ecori.is_palindromic());
+        assert!(!ecori.is_nicking());
+        assert!(!ecori.is_type_iis());
+
+        let sapi = find_restriction_enzyme("SapI").unwrap();
+        assert_eq!(sapi.mode(), &CutMode::A);
+        assert!(!sapi.is_palindromic());
+        assert!(sapi.is_type_iis());
+        assert_eq!(sapi.overhang(), Some(3));
+
+        let nb = find_restriction_enzyme("NbBssSI").unwrap();
+        assert_eq!(nb.mode(), &CutMode::N);
+        assert!(nb.is_nicking());
+        assert_eq!(nb.cut_offsets(), None);
+    }
+
+    #[test]
+    fn test_find_sites_palindrome_once() {
+        let seq = DnaSequence::from_str("AAGAATTCAA").unwrap();
+        let ecori = find_restriction_enzyme("EcoRI").unwrap();
+        let sites = ecori.find_sites(seq.as_nucleotides(), false);
+        assert_eq!(sites.len(), 1);
+        assert_eq!((sites[0].top_cut, sites[0].bottom_cut), (Some(3), Some(7)));
+    }
+
+    #[test]
+    fn test_find_sites_nicking_reverse_strand() {
+        // CTCGTG is the reverse complement of the NbBssSI site CACGAG,
+        // the bottom strand nick becomes a top strand nick: C^TCGTG
+        let seq = DnaSequence::from_str("AACTCGTGAA").unwrap();
+        let nb = find_restriction_enzyme("NbBssSI").unwrap();
+        let sites = nb.find_sites(seq.as_nucleotides(), false);
+        assert_eq!(sites.len(), 1);
+        assert_eq!((sites[0].top_cut, sites[0].bottom_cut), (Some(3), None));
+    }
+
+    #[test]
+    fn test_find_sites_circular() {
+        let seq = DnaSequence::from_str("ATTCAAAAGA").unwrap();
+        let ecori = find_restriction_enzyme("EcoRI").unwrap();
+        assert!(ecori.find_sites(seq.as_nucleotides(), false).is_empty());
+        let sites = ecori.find_sites(seq.as_nucleotides(), true);
+        assert_eq!(sites.len(), 1);
+        assert_eq!((sites[0].start, sites[0].end), (8, 14));
+    }
+}
@@ -5,7 +5,8 @@ use rand::Rng;use std::io::{Error, ErrorKind};
 use std::fs;
 use std::fmt;
 use std::cmp;
-use std::io::{Write, BufReader, BufRead};
+use std::io::{Write, BufReader, BufRead, Read};
+use flate2::read::MultiGzDecoder;
 
 use rayon::prelude::*;
 use std::sync::{Arc, Mutex};
@@ -342,7 +343,7 @@ impl FASTA {
     }
     /// Returns and generates a FASTA given a path to a .fasta file
     pub fn read_fasta(path: &str) -> FASTA {    
-        let data = fs::read_to_string(path).unwrap();
+        let data = read_to_string(path).unwrap();
         let data: Vec<&str> = data.split('>').collect();
         let mut records: Vec<FastaRecord> = Vec::new();
     
@@ -362,7 +363,11 @@ impl FASTA {
     /// Returns and generates a FASTA given a path to a .fasta file (slow version)
     pub fn slow_read_fasta(path: &str) -> FASTA {    
         let file = fs::File::open(path).expect("path to file not found");
-        let reader = BufReader::new(file);
+        let mut reader = BufReader::new(file);
+        let reader: Box<dyn BufRead> = match reader.fill_buf().unwrap().starts_with(&GZIP_MAGIC) {
+            true => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+            false => Box::new(reader),
+        };
         let mut records = Vec::new();
         let mut temp_header = "".to_string();
         let mut temp_seq = "".to_string();
@@ -386,7 +391,7 @@ impl FASTA {
     }
     /// Returns and generates a FASTA given a path to a .fasta file (using rayon)
     pub fn rayon_read_fasta(path: &str) -> FASTA {
-        let data = fs::read_to_string(path).unwrap();
+        let data = read_to_string(path).unwrap();
         let data: Vec<&str> = data.split('>').collect();
     
         let mut records: Vec<FastaRecord> = Vec::new();
@@ -405,6 +410,20 @@ impl FASTA {
     }
 }
 
+/// First bytes of gzip (and bgzip) compressed files
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Reads a file to a string, decompressing it if it is gzip or bgzip compressed
+fn read_to_string(path: &str) -> Result<String, Error> {
+    let data = fs::read(path)?;
+    if !data.starts_with(&GZIP_MAGIC) {
+        return String::from_utf8(data).map_err(|e| Error::new(ErrorKind::InvalidData, e));
+    }
+    let mut text = String::new();
+    MultiGzDecoder::new(&data[..]).read_to_string(&mut text)?;
+    Ok(text)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -490,6 +509,14 @@ mod tests {
         let fasta = FASTA::rayon_read_fasta("data/haha-1.fasta");
         println!("{}", fasta);
     }
+    #[test]
+    fn test_gzipped_fasta() {
+        let fasta = FASTA::read_fasta("data/sars_cov_2.fa");
+        assert_eq!(FASTA::read_fasta("data/sars_cov_2.fa.gz").content, fasta.content);
+        assert_eq!(FASTA::rayon_read_fasta("data/sars_cov_2.fa.gz").content, fasta.content);
+        let slow = FASTA::slow_read_fasta("data/sars_cov_2.fa");
+        assert_eq!(FASTA::slow_read_fasta("data/sars_cov_2.fa.gz").content, slow.content);
+    }
 }
 
 
@@ -0,0 +1,243 @@
+use actix_web::{
+    post,
+    web::{Bytes, Either, Json},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::prelude::Strand;
+
+use crate::api::endpoints::dna::image_response;
+use crate::core::annotations::utils::{self as annotations, AnnotationSource};
+use crate::core::schema::Error;
+
+#[derive(Deserialize, ToSchema)]
+pub struct AnnotationQuery {
+    /// FASTA contents, optional if the GFF3 file has a `##FASTA` section
+    #[schema()]
+    fasta: Option<String>,
+    /// Path to a FASTA file on the server, if no contents are given
+    #[schema(example = "genomes/ecoli.fa")]
+    fasta_path: Option<String>,
+    /// GFF3 or BED contents
+    #[schema()]
+    features: Option<String>,
+    /// Path to a GFF3 or BED file on the server, if no contents are given
+    #[schema(example = "genomes/ecoli.gff3")]
+    features_path: Option<String>,
+    /// `gff3` or `bed`, defaults to `bed` for `.bed` files and `gff3` otherwise
+    #[schema()]
+    format: Option<String>,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    topology: Option<String>,
+    /// Sequence to query, all sequences by default
+    #[schema(example = "chr1")]
+    seqid: Option<String>,
+    /// First position of the region, defaults to the start of the sequence
+    #[schema(example = 1000)]
+    start: Option<usize>,
+    /// Last position of the region, defaults to the end of the sequence
+    #[schema(example = 2000)]
+    end: Option<usize>,
+    /// Positions of the region and features are 0-based half-open rather than 1-based closed
+    #[schema()]
+    zero_based: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AnnotationExport {
+    #[schema()]
+    fasta: Option<String>,
+    #[schema(example = "genomes/ecoli.fa")]
+    fasta_path: Option<String>,
+    #[schema()]
+    features: Option<String>,
+    #[schema(example = "genomes/ecoli.gff3")]
+    features_path: Option<String>,
+    #[schema()]
+    format: Option<String>,
+    #[schema()]
+    topology: Option<String>,
+    /// Format to write, `gff3` or `bed`
+    #[schema(example = "bed")]
+    output: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FeatureSegment {
+    #[schema()]
+    start: usize,
+    #[schema()]
+    end: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FeatureQualifier {
+    #[schema()]
+    key: String,
+    #[schema()]
+    value: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FeatureInfo {
+    #[schema()]
+    name: String,
+    /// Feature type, e.g. `gene`, `CDS` or `promoter`
+    #[schema()]
+    feature_type: String,
+    /// `forward`, `reverse` or `unknown`
+    #[schema()]
+    strand: String,
+    /// Bounds in the coordinates of the query, the end is past the sequence length across the origin
+    #[schema()]
+    start: usize,
+    #[schema()]
+    end: usize,
+    /// Segments of joined features, e.g. exons
+    #[schema()]
+    segments: Vec<FeatureSegment>,
+    /// GFF3 attributes
+    #[schema()]
+    qualifiers: Vec<FeatureQualifier>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AnnotatedSequenceInfo {
+    #[schema()]
+    seqid: String,
+    #[schema()]
+    length: usize,
+    #[schema()]
+    features: Vec<FeatureInfo>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AnnotationReport {
+    /// Whether positions are 0-based half-open rather than 1-based closed
+    #[schema()]
+    zero_based: bool,
+    #[schema()]
+    sequences: Vec<AnnotatedSequenceInfo>,
+    /// Seqids of features without a matching sequence
+    #[schema()]
+    unmatched: Vec<String>,
+}
+
+#[utoipa::path(
+    tag="Annotations",
+    responses(
+        (status = 200, description = "AnnotationReport", body = AnnotationReport),
+    ),
+    params(
+        ("AnnotationQuery" = AnnotationQuery, description = "FASTA sequences, their GFF3 or BED features and the region to query"),
+    )
+)]
+#[post("/annotations/features")]
+async fn annotations_features(
+    form: Json<AnnotationQuery>,
+) -> Either<Json<AnnotationReport>, Json<Error>> {
+    let form = form.into_inner();
+    let zero_based = form.zero_based.unwrap_or(false);
+    let source = AnnotationSource {
+        fasta: form.fasta,
+        fasta_path: form.fasta_path,
+        features: form.features,
+        features_path: form.features_path,
+        format: form.format,
+        topology: form.topology,
+    };
+    let set = match source.load() {
+        Ok(set) => set,
+        Err(error) => return Either::Right(Json(Error { error })),
+    };
+    match annotations::query_features(
+        &set,
+        form.seqid.as_deref(),
+        form.start,
+        form.end,
+        zero_based,
+    ) {
+        Ok(found) => Either::Left(Json(AnnotationReport {
+            zero_based,
+            sequences: found
+                .into_iter()
+                .map(|(sequence, features)| AnnotatedSequenceInfo {
+                    seqid: sequence.seqid.to_owned(),
+                    length: sequence.length,
+                    features: features
+                        .into_iter()
+                        .map(|ann| {
+                            let bounds = |start, end| {
+                                annotations::feature_bounds(start, end, sequence.length, zero_based)
+                            };
+                            let (start, end) = bounds(ann.start, ann.end);
+                            FeatureInfo {
+                                name: ann.text.to_owned(),
+                                feature_type: ann.kind.to_string(),
+                                strand: match ann.strand {
+                                    Strand::Forward => "forward",
+                                    Strand::Reverse => "reverse",
+                                    Strand::Unknown => "unknown",
+                                }
+                                .to_string(),
+                                start,
+                                end,
+                                segments: ann
+                                    .segments
+                                    .iter()
+                                    .map(|&(start, end)| {
+                                        let (start, end) = bounds(start, end);
+                                        FeatureSegment { start, end }
+                                    })
+                                    .collect(),
+                                qualifiers: ann
+                                    .qualifiers
+                                    .iter()
+                                    .map(|(key, value)| FeatureQualifier {
+                                        key: key.to_owned(),
+                                        value: value.to_owned(),
+                                    })
+                                    .collect(),
+                            }
+                        })
+                        .collect(),
+                })
+                .collect(),
+            unmatched: set.unmatched.to_owned(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Annotations",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("AnnotationExport" = AnnotationExport, description = "FASTA sequences, their GFF3 or BED features and the format to write them in"),
+    )
+)]
+#[post("/annotations/export")]
+async fn annotations_export(form: Json<AnnotationExport>) -> HttpResponse {
+    let form = form.into_inner();
+    let source = AnnotationSource {
+        fasta: form.fasta,
+        fasta_path: form.fasta_path,
+        features: form.features,
+        features_path: form.features_path,
+        format: form.format,
+        topology: form.topology,
+    };
+    image_response(
+        "text/plain",
+        annotations::parse_format(Some(&form.output), None).and_then(|output| {
+            source
+                .load()
+                .map(|set| Bytes::from(annotations::export_features(&set, output)))
+        }),
+    )
+}
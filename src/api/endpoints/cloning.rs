@@ -0,0 +1,470 @@
+use actix_web::{
+    post,
+    web::{Either, Json},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::prelude::{Assembly, FragmentEnd, InsertOrientation, IupacNucleotide, Overhang};
+use plasmid::traits::ToLetter;
+
+use super::dna::image_response;
+use crate::core::cloning;
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
+use crate::core::schema::Error;
+
+#[derive(Deserialize, ToSchema)]
+pub struct Digest {
+    /// DNA String or RegionReference
+    #[schema()]
+    dna: SequenceInput,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    topology: Option<String>,
+    /// Restriction enzyme names, e.g. `EcoRI`
+    #[schema()]
+    enzymes: Vec<String>,
+    /// Gel ladder, one of `1kb` (default), `1kb_plus` or `100bp`
+    #[schema()]
+    ladder: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DigestEnd {
+    /// Enzyme that created the end, none for the ends of a linear template
+    #[schema()]
+    enzyme: Option<String>,
+    /// `blunt`, `5'` or `3'`
+    #[schema()]
+    overhang_type: Option<String>,
+    /// Single-stranded overhang, 5' to 3'
+    #[schema()]
+    overhang: String,
+}
+
+impl DigestEnd {
+    fn new(end: FragmentEnd) -> Self {
+        let letters = |seq: &[IupacNucleotide]| {
+            seq.iter().map(|n| n.to_letter()).collect::<String>()
+        };
+        match end {
+            FragmentEnd::Terminus => DigestEnd {
+                enzyme: None,
+                overhang_type: None,
+                overhang: String::new(),
+            },
+            FragmentEnd::Cut { enzyme, overhang } => {
+                let (overhang_type, overhang) = match overhang {
+                    Overhang::Blunt => ("blunt", String::new()),
+                    Overhang::FivePrime(seq) => ("5'", letters(&seq)),
+                    Overhang::ThreePrime(seq) => ("3'", letters(&seq)),
+                };
+                DigestEnd {
+                    enzyme: Some(enzyme),
+                    overhang_type: Some(overhang_type.to_string()),
+                    overhang,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DigestFragment {
+    #[schema()]
+    start: usize,
+    #[schema()]
+    end: usize,
+    #[schema()]
+    length: usize,
+    #[schema()]
+    circular: bool,
+    #[schema()]
+    left: DigestEnd,
+    #[schema()]
+    right: DigestEnd,
+    #[schema()]
+    sequence: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DigestReport {
+    #[schema()]
+    fragments: Vec<DigestFragment>,
+}
+
+#[utoipa::path(
+    tag="Cloning",
+    responses(
+        (status = 200, description = "DigestReport", body = DigestReport),
+    ),
+    params(
+        ("Digest" = Digest, description = "DNA String, topology and restriction enzymes"),
+    )
+)]
+#[post("/cloning/digest")]
+async fn cloning_digest(form: Json<Digest>) -> Either<Json<DigestReport>, Json<Error>> {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    match cloning::utils::digest(dna, form.topology.to_owned(), &form.enzymes) {
+        Ok(fragments) => Either::Left(Json(DigestReport {
+            fragments: fragments
+                .into_iter()
+                .map(|fragment| DigestFragment {
+                    start: fragment.start,
+                    end: fragment.end,
+                    length: fragment.len(),
+                    circular: fragment.circular,
+                    sequence: fragment.sequence.iter().map(|n| n.to_letter()).collect(),
+                    left: DigestEnd::new(fragment.left),
+                    right: DigestEnd::new(fragment.right),
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Cloning",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("Digest" = Digest, description = "DNA String, topology, restriction enzymes and gel ladder"),
+    )
+)]
+#[post("/cloning/digest_gel_svg")]
+async fn cloning_digest_gel_svg(form: Json<Digest>) -> HttpResponse {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/svg+xml",
+        cloning::utils::digest_gel_svg(
+            dna,
+            form.topology.to_owned(),
+            &form.enzymes,
+            form.ladder.to_owned(),
+        ),
+    )
+}
+
+#[utoipa::path(
+    tag="Cloning",
+    responses(
+        (status = 200, description = "HttpResponse"),
+    ),
+    params(
+        ("Digest" = Digest, description = "DNA String, topology, restriction enzymes and gel ladder"),
+    )
+)]
+#[post("/cloning/digest_gel_png")]
+async fn cloning_digest_gel_png(form: Json<Digest>) -> HttpResponse {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/png",
+        cloning::utils::digest_gel_png(
+            dna,
+            form.topology.to_owned(),
+            &form.enzymes,
+            form.ladder.to_owned(),
+        ),
+    )
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct AssemblyPart {
+    /// DNA String or RegionReference
+    #[schema()]
+    dna: SequenceInput,
+    /// `linear` (default) or `circular`, e.g. for the destination vector
+    #[schema()]
+    topology: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GoldenGate {
+    /// Parts to assemble, starting with the destination vector
+    #[schema()]
+    parts: Vec<AssemblyPart>,
+    /// Type IIS restriction enzyme name, e.g. `BsaI`
+    #[schema()]
+    enzyme: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct Gibson {
+    /// Linear DNA Strings or RegionReferences to assemble, the product starts with the first one
+    #[schema()]
+    parts: Vec<SequenceInput>,
+    /// Minimum terminal homology in bp, defaults to 20
+    #[schema()]
+    min_overlap: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AssembledPart {
+    /// Index of the part in the request
+    #[schema()]
+    part: usize,
+    /// Whether the part was inserted as its reverse complement
+    #[schema()]
+    reverse: bool,
+    #[schema()]
+    start: usize,
+    #[schema()]
+    end: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AssemblyJunction {
+    #[schema()]
+    start: usize,
+    /// Last position of the junction (inclusive)
+    #[schema()]
+    end: usize,
+    #[schema()]
+    label: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AssemblyReport {
+    #[schema()]
+    sequence: String,
+    #[schema()]
+    length: usize,
+    /// `linear` or `circular`
+    #[schema()]
+    topology: String,
+    /// Whether all parts were joined into a single circular product
+    #[schema()]
+    complete: bool,
+    #[schema()]
+    parts: Vec<AssembledPart>,
+    #[schema()]
+    junctions: Vec<AssemblyJunction>,
+    /// Problems such as duplicate overhangs or missing overlaps
+    #[schema()]
+    errors: Vec<String>,
+}
+
+impl AssemblyReport {
+    fn new(assembly: Assembly) -> Self {
+        AssemblyReport {
+            length: assembly.len(),
+            complete: assembly.is_complete(),
+            sequence: assembly.sequence.iter().map(|n| n.to_letter()).collect(),
+            topology: match assembly.topology.is_circular() {
+                true => "circular",
+                false => "linear",
+            }
+            .to_string(),
+            parts: assembly
+                .parts
+                .iter()
+                .map(|p| AssembledPart {
+                    part: p.part,
+                    reverse: p.reverse,
+                    start: p.start,
+                    end: p.end,
+                })
+                .collect(),
+            junctions: assembly
+                .junctions
+                .into_iter()
+                .map(|j| AssemblyJunction {
+                    start: j.start,
+                    end: j.end,
+                    label: j.text,
+                })
+                .collect(),
+            errors: assembly.issues.iter().map(|i| i.to_string()).collect(),
+        }
+    }
+}
+
+#[utoipa::path(
+    tag="Cloning",
+    responses(
+        (status = 200, description = "AssemblyReport", body = AssemblyReport),
+    ),
+    params(
+        ("GoldenGate" = GoldenGate, description = "DNA parts and Type IIS restriction enzyme"),
+    )
+)]
+#[post("/cloning/golden_gate")]
+async fn cloning_golden_gate(form: Json<GoldenGate>) -> Either<Json<AssemblyReport>, Json<Error>> {
+    let dna = form.parts.iter().map(|part| part.dna.to_owned()).collect();
+    let dna = match faidx::sequences(dna).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let parts = dna
+        .into_iter()
+        .zip(form.parts.iter())
+        .map(|(dna, part)| (dna, part.topology.to_owned()))
+        .collect::<Vec<_>>();
+    match cloning::utils::golden_gate(&parts, form.enzyme.to_owned()) {
+        Ok(assembly) => Either::Left(Json(AssemblyReport::new(assembly))),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Cloning",
+    responses(
+        (status = 200, description = "AssemblyReport", body = AssemblyReport),
+    ),
+    params(
+        ("Gibson" = Gibson, description = "Linear DNA parts and minimum overlap"),
+    )
+)]
+#[post("/cloning/gibson")]
+async fn cloning_gibson(form: Json<Gibson>) -> Either<Json<AssemblyReport>, Json<Error>> {
+    let parts = match faidx::sequences(form.parts.to_owned()).await {
+        Ok(parts) => parts,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    match cloning::utils::gibson(&parts, form.min_overlap) {
+        Ok(assembly) => Either::Left(Json(AssemblyReport::new(assembly))),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct Ligate {
+    /// DNA String or RegionReference
+    #[schema()]
+    vector: SequenceInput,
+    /// `circular` (default) or `linear`
+    #[schema()]
+    vector_topology: Option<String>,
+    /// Restriction enzymes opening the vector, e.g. `EcoRI` and `BamHI`
+    #[schema()]
+    vector_enzymes: Vec<String>,
+    /// DNA String or RegionReference
+    #[schema()]
+    insert: SequenceInput,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    insert_topology: Option<String>,
+    /// Restriction enzymes releasing the insert, defaults to the vector enzymes
+    #[schema()]
+    insert_enzymes: Option<Vec<String>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LigationFeature {
+    #[schema()]
+    start: usize,
+    /// Last position of the feature (inclusive)
+    #[schema()]
+    end: usize,
+    #[schema()]
+    label: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LigationConstruct {
+    /// `forward` or `reverse`, the orientation of the insert
+    #[schema()]
+    orientation: String,
+    #[schema()]
+    sequence: String,
+    #[schema()]
+    length: usize,
+    #[schema()]
+    insert_start: usize,
+    #[schema()]
+    insert_end: usize,
+    /// Features of the vector and the insert, moved onto the construct
+    #[schema()]
+    features: Vec<LigationFeature>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LigationReport {
+    /// Whether the insert can only be ligated in a single orientation
+    #[schema()]
+    directional: bool,
+    #[schema()]
+    backbone_left: DigestEnd,
+    #[schema()]
+    backbone_right: DigestEnd,
+    #[schema()]
+    insert_left: DigestEnd,
+    #[schema()]
+    insert_right: DigestEnd,
+    #[schema()]
+    constructs: Vec<LigationConstruct>,
+}
+
+#[utoipa::path(
+    tag="Cloning",
+    responses(
+        (status = 200, description = "LigationReport", body = LigationReport),
+    ),
+    params(
+        ("Ligate" = Ligate, description = "Vector and insert DNA Strings with their restriction enzymes"),
+    )
+)]
+#[post("/cloning/ligate")]
+async fn cloning_ligate(form: Json<Ligate>) -> Either<Json<LigationReport>, Json<Error>> {
+    let vector = match faidx::sequence(form.vector.to_owned()).await {
+        Ok(vector) => vector,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let insert = match faidx::sequence(form.insert.to_owned()).await {
+        Ok(insert) => insert,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    match cloning::utils::ligate(
+        vector,
+        form.vector_topology.to_owned(),
+        &form.vector_enzymes,
+        insert,
+        form.insert_topology.to_owned(),
+        form.insert_enzymes.as_deref(),
+    ) {
+        Ok(ligation) => Either::Left(Json(LigationReport {
+            directional: ligation.is_directional(),
+            constructs: ligation
+                .products
+                .into_iter()
+                .map(|product| LigationConstruct {
+                    orientation: match product.orientation {
+                        InsertOrientation::Forward => "forward",
+                        InsertOrientation::Reverse => "reverse",
+                    }
+                    .to_string(),
+                    length: product.sequence.len(),
+                    sequence: product.sequence.iter().map(|n| n.to_letter()).collect(),
+                    insert_start: product.insert_start,
+                    insert_end: product.insert_end,
+                    features: product
+                        .annotations
+                        .into_iter()
+                        .map(|a| LigationFeature {
+                            start: a.start,
+                            end: a.end,
+                            label: a.text,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            backbone_left: DigestEnd::new(ligation.backbone.left),
+            backbone_right: DigestEnd::new(ligation.backbone.right),
+            insert_left: DigestEnd::new(ligation.insert.left),
+            insert_right: DigestEnd::new(ligation.insert.right),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
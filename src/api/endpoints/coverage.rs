@@ -0,0 +1,218 @@
+use actix_web::{
+    post,
+    web::{self, Bytes, Either, Json},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::api::endpoints::dna::image_response;
+use crate::core::coverage::utils::{self as coverage, CoverageSource};
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
+use crate::core::schema::Error;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CoverageInput {
+    /// Alignments in SAM format, with an `@SQ` header line for each reference
+    #[schema()]
+    sam: Option<String>,
+    /// Path to a SAM file on the server, if no alignments are given
+    #[schema(example = "samples/SRR0000001.sam")]
+    sam_path: Option<String>,
+    /// FASTA reference to map reads or align sequences to
+    #[schema()]
+    reference: Option<String>,
+    /// FASTQ reads, mapped to the reference with the read mapper
+    #[schema()]
+    reads: Option<String>,
+    /// Path to a FASTQ file on the server, if no reads are given
+    #[schema(example = "samples/SRR0000001.fastq")]
+    reads_path: Option<String>,
+    /// Sequences or RegionReferences aligned to the first reference record with Needleman–Wunsch
+    #[schema()]
+    sequences: Option<Vec<SequenceInput>>,
+    /// Reference record to draw, defaults to the first one
+    #[schema()]
+    record: Option<String>,
+}
+
+impl CoverageInput {
+    async fn to_source(&self) -> Result<CoverageSource, Error> {
+        Ok(CoverageSource {
+            sam: self.sam.to_owned(),
+            sam_path: self.sam_path.to_owned(),
+            reference: self.reference.to_owned(),
+            reads: self.reads.to_owned(),
+            reads_path: self.reads_path.to_owned(),
+            sequences: faidx::optional_sequences(self.sequences.to_owned()).await?,
+        })
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PileupPosition {
+    /// 0-based reference position
+    #[schema()]
+    position: usize,
+    /// Reads with a base aligned to the position
+    #[schema()]
+    depth: u32,
+    #[schema()]
+    a: u32,
+    #[schema()]
+    c: u32,
+    #[schema()]
+    g: u32,
+    #[schema()]
+    t: u32,
+    /// Ambiguous or unknown bases
+    #[schema()]
+    n: u32,
+    /// Reads with an insertion right after the position
+    #[schema()]
+    insertions: u32,
+    /// Reads with the position deleted
+    #[schema()]
+    deletions: u32,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PileupReference {
+    #[schema()]
+    name: String,
+    #[schema()]
+    length: usize,
+    #[schema()]
+    mean_depth: f64,
+    /// Percentage of positions with at least one aligned base
+    #[schema()]
+    covered_percent: f64,
+    #[schema()]
+    positions: Vec<PileupPosition>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PileupReport {
+    #[schema()]
+    references: Vec<PileupReference>,
+}
+
+#[utoipa::path(
+    tag="Coverage",
+    responses(
+        (status = 200, description = "PileupReport", body = PileupReport),
+    ),
+    params(
+        ("CoverageInput" = CoverageInput, description = "SAM alignments, or a reference with reads or sequences"),
+    )
+)]
+#[post("/coverage/pileup")]
+async fn coverage_pileup(form: Json<CoverageInput>) -> Either<Json<PileupReport>, Json<Error>> {
+    let source = match form.to_source().await {
+        Ok(source) => source,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let pileups = web::block(move || source.pileup())
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|pileups| pileups);
+    match pileups {
+        Ok(pileups) => Either::Left(Json(PileupReport {
+            references: pileups
+                .references
+                .iter()
+                .map(|pileup| PileupReference {
+                    name: pileup.name.to_owned(),
+                    length: pileup.columns.len(),
+                    mean_depth: pileup.mean_depth(),
+                    covered_percent: pileup.covered_percent(),
+                    positions: pileup
+                        .columns
+                        .iter()
+                        .enumerate()
+                        .map(|(position, column)| PileupPosition {
+                            position,
+                            depth: column.depth,
+                            a: column.bases[0],
+                            c: column.bases[1],
+                            g: column.bases[2],
+                            t: column.bases[3],
+                            n: column.bases[4],
+                            insertions: column.insertions,
+                            deletions: column.deletions,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Coverage",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("CoverageInput" = CoverageInput, description = "SAM alignments, or a reference with reads or sequences"),
+    )
+)]
+#[post("/coverage/bedgraph")]
+async fn coverage_bedgraph(form: Json<CoverageInput>) -> HttpResponse {
+    let source = match form.to_source().await {
+        Ok(source) => source,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    let bedgraph = web::block(move || source.pileup().map(|pileups| pileups.bedgraph()))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|bedgraph| bedgraph);
+    image_response("text/plain", bedgraph.map(Bytes::from))
+}
+
+#[utoipa::path(
+    tag="Coverage",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("CoverageInput" = CoverageInput, description = "SAM alignments, or a reference with reads or sequences, and the record to draw"),
+    )
+)]
+#[post("/coverage/svg")]
+async fn coverage_svg(form: Json<CoverageInput>) -> HttpResponse {
+    let source = match form.to_source().await {
+        Ok(source) => source,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    let record = form.record.to_owned();
+    let svg = web::block(move || coverage::gen_coverage_svg(&source, record))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|svg| svg);
+    image_response("image/svg+xml", svg)
+}
+
+#[utoipa::path(
+    tag="Coverage",
+    responses(
+        (status = 200, description = "HttpResponse"),
+    ),
+    params(
+        ("CoverageInput" = CoverageInput, description = "SAM alignments, or a reference with reads or sequences, and the record to draw"),
+    )
+)]
+#[post("/coverage/png")]
+async fn coverage_png(form: Json<CoverageInput>) -> HttpResponse {
+    let source = match form.to_source().await {
+        Ok(source) => source,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    let record = form.record.to_owned();
+    let png = web::block(move || coverage::gen_coverage_png(&source, record))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|png| png);
+    image_response("image/png", png)
+}
@@ -1,24 +1,49 @@
-use actix_web::{post, web::Json, Either, HttpResponse};
+use actix_web::{post, web::Bytes, web::Json, Either, HttpResponse};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use plasmid::prelude::Strand;
+
 use crate::core::dna::algos::DNAAlignment;
 
 use crate::core::dna;
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
 use crate::core::schema::*;
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct DnaString {
+    /// DNA String or RegionReference
+    #[schema()]
+    dna: SequenceInput,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DnaWindow {
+    /// DNA String or RegionReference
     #[schema()]
-    dna: String,
+    dna: SequenceInput,
+    #[schema()]
+    window: Option<usize>,
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct DnaAlign {
+    /// DNA String or RegionReference
     #[schema()]
-    dna_a: String,
+    dna_a: SequenceInput,
+    /// DNA String or RegionReference
     #[schema()]
-    dna_b: String,
+    dna_b: SequenceInput,
+}
+
+impl DnaAlign {
+    /// Both DNA strings, read from their FASTA files for region references
+    async fn resolve(&self) -> Result<(String, String), Error> {
+        Ok((
+            faidx::sequence(self.dna_a.to_owned()).await?,
+            faidx::sequence(self.dna_b.to_owned()).await?,
+        ))
+    }
 }
 
 #[derive(Serialize, ToSchema)]
@@ -67,10 +92,14 @@ pub struct AminoAcids {
     )
 )]
 #[post("/dna/to_protein")]
-async fn dna_to_protein(form: Json<DnaString>) -> Json<ProteinString> {
-    Json(ProteinString {
-        protein: dna::utils::dna_to_protein(form.dna.to_owned()),
-    })
+async fn dna_to_protein(form: Json<DnaString>) -> Either<Json<ProteinString>, Json<Error>> {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(Json(ProteinString {
+        protein: dna::utils::dna_to_protein(dna),
+    }))
 }
 
 #[utoipa::path(
@@ -84,9 +113,11 @@ async fn dna_to_protein(form: Json<DnaString>) -> Json<ProteinString> {
 )]
 #[post("/dna/circular_svg")]
 async fn dna_to_circular_svg(form: Json<DnaString>) -> HttpResponse {
-    HttpResponse::Ok()
-        .content_type("image/svg+xml")
-        .body(dna::utils::gen_dna_circular_svg(form.dna.to_owned()))
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response("image/svg+xml", dna::utils::gen_dna_circular_svg(dna))
 }
 
 #[utoipa::path(
@@ -100,9 +131,11 @@ async fn dna_to_circular_svg(form: Json<DnaString>) -> HttpResponse {
 )]
 #[post("/dna/circular_png")]
 async fn dna_to_circular_png(form: Json<DnaString>) -> HttpResponse {
-    HttpResponse::Ok()
-        .content_type("image/png")
-        .body(dna::utils::gen_dna_circular_png(form.dna.to_owned()))
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response("image/png", dna::utils::gen_dna_circular_png(dna))
 }
 
 #[utoipa::path(
@@ -116,9 +149,304 @@ async fn dna_to_circular_png(form: Json<DnaString>) -> HttpResponse {
 )]
 #[post("/dna/circular_png_bw")]
 async fn dna_to_circular_png_bw(form: Json<DnaString>) -> HttpResponse {
-    HttpResponse::Ok()
-        .content_type("image/png")
-        .body(dna::utils::gen_dna_circular_png_bw(form.dna.to_owned()))
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response("image/png", dna::utils::gen_dna_circular_png_bw(dna))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DnaFeatures {
+    /// DNA String or RegionReference
+    #[schema()]
+    dna: SequenceInput,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    topology: Option<String>,
+    /// Minimum fraction of matching bases or residues, above 0 and at most 1, defaults to 0.9
+    #[schema()]
+    min_identity: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DnaFeature {
+    #[schema()]
+    name: String,
+    /// Feature key, e.g. `CDS`, `promoter` or `primer_bind`
+    #[schema()]
+    feature_type: String,
+    /// `forward` or `reverse`
+    #[schema()]
+    strand: String,
+    #[schema()]
+    start: usize,
+    /// Last position of the feature (inclusive), smaller than `start` if it spans the origin
+    #[schema()]
+    end: usize,
+    /// Identity of near-exact matches
+    #[schema()]
+    note: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DnaFeatureList {
+    #[schema()]
+    features: Vec<DnaFeature>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DnaSearch {
+    /// DNA String or RegionReference
+    #[schema()]
+    dna: SequenceInput,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    topology: Option<String>,
+    /// Motif to search, e.g. `GGTCTC`, `ATG.{3,6}TAA` or `N-{P}-[ST]-{P}`
+    #[schema()]
+    pattern: String,
+    /// `iupac` (default), `regex` or `prosite` for protein motifs
+    #[schema()]
+    syntax: Option<String>,
+    /// Maximum number of mismatches, insertions and deletions, defaults to 0
+    #[schema()]
+    max_errors: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DnaSearchHit {
+    #[schema()]
+    start: usize,
+    /// Last position of the match (inclusive), smaller than `start` if it spans the origin
+    #[schema()]
+    end: usize,
+    /// `forward` or `reverse`
+    #[schema()]
+    strand: String,
+    /// Number of mismatches, insertions and deletions
+    #[schema()]
+    distance: usize,
+    /// Matched bases, or residues of protein motifs, read along the strand
+    #[schema()]
+    matched: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DnaSearchReport {
+    #[schema()]
+    hits: Vec<DnaSearchHit>,
+}
+
+#[utoipa::path(
+    tag="DNA Algorithms",
+    responses(
+        (status = 200, description = "DnaSearchReport", body = DnaSearchReport),
+    ),
+    params(
+        ("DnaSearch" = DnaSearch, description = "DNA String, topology, motif and maximum errors"),
+    )
+)]
+#[post("/dna/search")]
+async fn dna_search_motif(form: Json<DnaSearch>) -> Either<Json<DnaSearchReport>, Json<Error>> {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    match dna::utils::search_motif(
+        dna,
+        form.topology.to_owned(),
+        form.pattern.to_owned(),
+        form.syntax.to_owned(),
+        form.max_errors,
+    ) {
+        Ok(hits) => Either::Left(Json(DnaSearchReport {
+            hits: hits
+                .into_iter()
+                .map(|hit| DnaSearchHit {
+                    start: hit.start,
+                    end: hit.end,
+                    strand: match hit.strand {
+                        Strand::Reverse => "reverse",
+                        _ => "forward",
+                    }
+                    .to_string(),
+                    distance: hit.distance,
+                    matched: hit.matched,
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="DNA Algorithms",
+    responses(
+        (status = 200, description = "DnaFeatureList", body = DnaFeatureList),
+    ),
+    params(
+        ("DnaFeatures" = DnaFeatures, description = "DNA String, topology and minimum identity"),
+    )
+)]
+#[post("/dna/features")]
+async fn dna_common_features(form: Json<DnaFeatures>) -> Either<Json<DnaFeatureList>, Json<Error>> {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    match dna::utils::annotate_common_features(dna, form.topology.to_owned(), form.min_identity) {
+        Ok(features) => Either::Left(Json(DnaFeatureList {
+            features: features
+                .into_iter()
+                .map(|ann| DnaFeature {
+                    feature_type: ann.kind.to_string(),
+                    strand: match ann.strand {
+                        Strand::Forward => "forward",
+                        Strand::Reverse => "reverse",
+                        Strand::Unknown => "unknown",
+                    }
+                    .to_string(),
+                    note: ann.qualifier("note").map(|note| note.to_string()),
+                    name: ann.text,
+                    start: ann.start,
+                    end: ann.end,
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="DNA Algorithms",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("DnaFeatures" = DnaFeatures, description = "DNA String, topology and minimum identity"),
+    )
+)]
+#[post("/dna/linear_svg")]
+async fn dna_to_linear_svg(form: Json<DnaFeatures>) -> HttpResponse {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/svg+xml",
+        dna::utils::gen_dna_linear_svg(dna, form.topology.to_owned(), form.min_identity),
+    )
+}
+
+#[utoipa::path(
+    tag="DNA Algorithms",
+    responses(
+        (status = 200, description = "HttpResponse"),
+    ),
+    params(
+        ("DnaFeatures" = DnaFeatures, description = "DNA String, topology and minimum identity"),
+    )
+)]
+#[post("/dna/linear_png")]
+async fn dna_to_linear_png(form: Json<DnaFeatures>) -> HttpResponse {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/png",
+        dna::utils::gen_dna_linear_png(dna, form.topology.to_owned(), form.min_identity),
+    )
+}
+
+/// Respond with an image, or a JSON error if it could not be rendered
+pub(crate) fn image_response(content_type: &str, image: Result<Bytes, String>) -> HttpResponse {
+    match image {
+        Ok(image) => HttpResponse::Ok().content_type(content_type).body(image),
+        Err(error) => HttpResponse::BadRequest().json(Error { error }),
+    }
+}
+
+#[utoipa::path(
+    tag="DNA Algorithms",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("DnaWindow" = DnaWindow, description = "DNA String and GC window size"),
+    )
+)]
+#[post("/dna/circular_gc_svg")]
+async fn dna_to_circular_gc_svg(form: Json<DnaWindow>) -> HttpResponse {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/svg+xml",
+        dna::utils::gen_dna_circular_gc_svg(dna, form.window),
+    )
+}
+
+#[utoipa::path(
+    tag="DNA Algorithms",
+    responses(
+        (status = 200, description = "HttpResponse"),
+    ),
+    params(
+        ("DnaWindow" = DnaWindow, description = "DNA String and GC window size"),
+    )
+)]
+#[post("/dna/circular_gc_png")]
+async fn dna_to_circular_gc_png(form: Json<DnaWindow>) -> HttpResponse {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/png",
+        dna::utils::gen_dna_circular_gc_png(dna, form.window),
+    )
+}
+
+#[utoipa::path(
+    tag="DNA Algorithms",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("DnaWindow" = DnaWindow, description = "DNA String and GC window size"),
+    )
+)]
+#[post("/dna/gc_plot_svg")]
+async fn dna_to_gc_plot_svg(form: Json<DnaWindow>) -> HttpResponse {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/svg+xml",
+        dna::utils::gen_gc_plot_svg(dna, form.window),
+    )
+}
+
+#[utoipa::path(
+    tag="DNA Algorithms",
+    responses(
+        (status = 200, description = "HttpResponse"),
+    ),
+    params(
+        ("DnaWindow" = DnaWindow, description = "DNA String and GC window size"),
+    )
+)]
+#[post("/dna/gc_plot_png")]
+async fn dna_to_gc_plot_png(form: Json<DnaWindow>) -> HttpResponse {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response("image/png", dna::utils::gen_gc_plot_png(dna, form.window))
 }
 
 #[utoipa::path(
@@ -131,10 +459,14 @@ async fn dna_to_circular_png_bw(form: Json<DnaString>) -> HttpResponse {
     )
 )]
 #[post("/dna/to_amino_acids")]
-async fn dna_to_amino_acids(form: Json<DnaString>) -> Json<AminoAcids> {
-    Json(AminoAcids {
-        amino_acids: dna::utils::amino_acids_from_dna(form.dna.to_owned()),
-    })
+async fn dna_to_amino_acids(form: Json<DnaString>) -> Either<Json<AminoAcids>, Json<Error>> {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(Json(AminoAcids {
+        amino_acids: dna::utils::amino_acids_from_dna(dna),
+    }))
 }
 
 #[utoipa::path(
@@ -147,10 +479,14 @@ async fn dna_to_amino_acids(form: Json<DnaString>) -> Json<AminoAcids> {
     )
 )]
 #[post("/dna/kmer_substring")]
-async fn kmer_substring_from(form: Json<DnaString>) -> Json<DnaString> {
-    Json(DnaString {
-        dna: dna::utils::derive_kmer_substring_from_dna(form.dna.to_owned()),
-    })
+async fn kmer_substring_from(form: Json<DnaString>) -> Either<Json<DnaString>, Json<Error>> {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(Json(DnaString {
+        dna: dna::utils::derive_kmer_substring_from_dna(dna).into(),
+    }))
 }
 
 #[utoipa::path(
@@ -164,8 +500,11 @@ async fn kmer_substring_from(form: Json<DnaString>) -> Json<DnaString> {
 )]
 #[post("/dna/ndiffs")]
 async fn compute_dna_ndiffs(form: Json<DnaAlign>) -> Either<Json<DnaNdiffs>, Json<Error>> {
-    let ndiff_compute =
-        dna::utils::compute_dna_ndiffs(form.dna_a.to_owned(), form.dna_b.to_owned());
+    let (dna_a, dna_b) = match form.resolve().await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let ndiff_compute = dna::utils::compute_dna_ndiffs(dna_a, dna_b);
 
     let response = match ndiff_compute {
         Some(ndiff) => Either::Left(Json(DnaNdiffs { ndiff })),
@@ -187,13 +526,16 @@ async fn compute_dna_ndiffs(form: Json<DnaAlign>) -> Either<Json<DnaNdiffs>, Jso
     )
 )]
 #[post("/dna/hamming_distance")]
-async fn compute_dna_hamming_distance(form: Json<DnaAlign>) -> Json<HammingDistance> {
-    Json(HammingDistance {
-        distance: dna::utils::compute_dna_hamming_distance(
-            form.dna_a.to_owned(),
-            form.dna_b.to_owned(),
-        ),
-    })
+async fn compute_dna_hamming_distance(
+    form: Json<DnaAlign>,
+) -> Either<Json<HammingDistance>, Json<Error>> {
+    let (dna_a, dna_b) = match form.resolve().await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(Json(HammingDistance {
+        distance: dna::utils::compute_dna_hamming_distance(dna_a, dna_b),
+    }))
 }
 
 #[utoipa::path(
@@ -206,13 +548,16 @@ async fn compute_dna_hamming_distance(form: Json<DnaAlign>) -> Json<HammingDista
     )
 )]
 #[post("/dna/levenshtein_distance")]
-async fn compute_dna_levenshtein_distance(form: Json<DnaAlign>) -> Json<LevenshteinDistance> {
-    Json(LevenshteinDistance {
-        distance: dna::utils::compute_dna_levenshtein_distance(
-            form.dna_a.to_owned(),
-            form.dna_b.to_owned(),
-        ),
-    })
+async fn compute_dna_levenshtein_distance(
+    form: Json<DnaAlign>,
+) -> Either<Json<LevenshteinDistance>, Json<Error>> {
+    let (dna_a, dna_b) = match form.resolve().await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(Json(LevenshteinDistance {
+        distance: dna::utils::compute_dna_levenshtein_distance(dna_a, dna_b),
+    }))
 }
 
 #[utoipa::path(
@@ -225,11 +570,16 @@ async fn compute_dna_levenshtein_distance(form: Json<DnaAlign>) -> Json<Levensht
     )
 )]
 #[post("/dna/sparse_alignment")]
-async fn calculate_sparse_alignments(form: Json<DnaAlign>) -> Json<SparseAlignment> {
-    let (score, match_path) =
-        dna::utils::calculate_sparse_alignments(form.dna_a.to_owned(), form.dna_b.to_owned());
+async fn calculate_sparse_alignments(
+    form: Json<DnaAlign>,
+) -> Either<Json<SparseAlignment>, Json<Error>> {
+    let (dna_a, dna_b) = match form.resolve().await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let (score, match_path) = dna::utils::calculate_sparse_alignments(dna_a, dna_b);
 
-    Json(SparseAlignment { score, match_path })
+    Either::Left(Json(SparseAlignment { score, match_path }))
 }
 
 #[utoipa::path(
@@ -242,11 +592,12 @@ async fn calculate_sparse_alignments(form: Json<DnaAlign>) -> Json<SparseAlignme
     )
 )]
 #[post("/dna/needleman_wunsch")]
-async fn align_needleman_wunsch(form: Json<DnaAlign>) -> Json<DNAAlignment> {
-    Json(dna::algos::align_needleman_wunsch(
-        form.dna_a.to_owned(),
-        form.dna_b.to_owned(),
-    ))
+async fn align_needleman_wunsch(form: Json<DnaAlign>) -> Either<Json<DNAAlignment>, Json<Error>> {
+    let (dna_a, dna_b) = match form.resolve().await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(Json(dna::algos::align_needleman_wunsch(dna_a, dna_b)))
 }
 
 #[utoipa::path(
@@ -259,9 +610,10 @@ async fn align_needleman_wunsch(form: Json<DnaAlign>) -> Json<DNAAlignment> {
     )
 )]
 #[post("/dna/smith_waterman")]
-async fn align_smith_waterman(form: Json<DnaAlign>) -> Json<DNAAlignment> {
-    Json(dna::algos::align_smith_waterman(
-        form.dna_a.to_owned(),
-        form.dna_b.to_owned(),
-    ))
+async fn align_smith_waterman(form: Json<DnaAlign>) -> Either<Json<DNAAlignment>, Json<Error>> {
+    let (dna_a, dna_b) = match form.resolve().await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(Json(dna::algos::align_smith_waterman(dna_a, dna_b)))
 }
@@ -0,0 +1,289 @@
+use actix_web::{
+    post,
+    web::{Bytes, Either, Json},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::prelude::{Strand, Topology};
+
+use crate::core::editor::utils::{self as editor, EditorSnapshot};
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
+use crate::core::schema::Error;
+
+#[derive(Deserialize, ToSchema)]
+pub struct EditorOpen {
+    /// DNA String or RegionReference
+    #[schema()]
+    dna: SequenceInput,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    topology: Option<String>,
+    /// Annotate common plasmid features, defaults to true
+    #[schema()]
+    annotate: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct EditorSession {
+    #[schema()]
+    id: u64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EditorEdit {
+    #[schema()]
+    id: u64,
+    /// `insert`, `delete` or `replace`
+    #[schema()]
+    operation: String,
+    /// Position to insert before, or first position to delete or replace
+    #[schema()]
+    start: usize,
+    /// Position after the last deleted or replaced base (exclusive)
+    #[schema()]
+    end: Option<usize>,
+    /// Bases to insert or to replace the region with, inline or as a RegionReference, not allowed for `delete`
+    #[schema()]
+    bases: Option<SequenceInput>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct EditorExtract {
+    #[schema()]
+    id: u64,
+    #[schema()]
+    start: usize,
+    /// Position after the last extracted base (exclusive), smaller than `start` to extract across the origin
+    #[schema()]
+    end: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EditorSegment {
+    #[schema()]
+    start: usize,
+    /// Last position of the segment (inclusive)
+    #[schema()]
+    end: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EditorFeature {
+    #[schema()]
+    name: String,
+    /// Feature key, e.g. `CDS`, `promoter` or `primer_bind`
+    #[schema()]
+    feature_type: String,
+    /// `forward`, `reverse` or `unknown`
+    #[schema()]
+    strand: String,
+    #[schema()]
+    start: usize,
+    /// Last position of the feature (inclusive), smaller than `start` if it spans the origin
+    #[schema()]
+    end: usize,
+    /// Segments of joined features, e.g. exons
+    #[schema()]
+    segments: Vec<EditorSegment>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EditorState {
+    /// Session id to pass to further editor actions, sessions unused for an hour are closed
+    #[schema()]
+    id: u64,
+    #[schema()]
+    sequence: String,
+    #[schema()]
+    length: usize,
+    /// `linear` or `circular`
+    #[schema()]
+    topology: String,
+    #[schema()]
+    features: Vec<EditorFeature>,
+    /// Number of edits that can be undone, at most 100
+    #[schema()]
+    undo: usize,
+    /// Number of undone edits that can be redone
+    #[schema()]
+    redo: usize,
+}
+
+impl EditorState {
+    fn new(snapshot: EditorSnapshot) -> Self {
+        EditorState {
+            id: snapshot.id,
+            length: snapshot.sequence.len(),
+            sequence: snapshot.sequence,
+            topology: match snapshot.topology {
+                Topology::Linear => "linear",
+                Topology::Circular => "circular",
+            }
+            .to_string(),
+            features: snapshot
+                .annotations
+                .into_iter()
+                .map(|ann| EditorFeature {
+                    feature_type: ann.kind.to_string(),
+                    strand: match ann.strand {
+                        Strand::Forward => "forward",
+                        Strand::Reverse => "reverse",
+                        Strand::Unknown => "unknown",
+                    }
+                    .to_string(),
+                    segments: ann
+                        .segments
+                        .iter()
+                        .map(|&(start, end)| EditorSegment { start, end })
+                        .collect(),
+                    name: ann.text,
+                    start: ann.start,
+                    end: ann.end,
+                })
+                .collect(),
+            undo: snapshot.undo,
+            redo: snapshot.redo,
+        }
+    }
+}
+
+fn editor_response(
+    snapshot: Result<EditorSnapshot, String>,
+) -> Either<Json<EditorState>, Json<Error>> {
+    match snapshot {
+        Ok(snapshot) => Either::Left(Json(EditorState::new(snapshot))),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Sequence Editor",
+    responses(
+        (status = 200, description = "EditorState", body = EditorState),
+    ),
+    params(
+        ("EditorOpen" = EditorOpen, description = "DNA String, topology and whether to annotate common features"),
+    )
+)]
+#[post("/editor/open")]
+async fn editor_open(form: Json<EditorOpen>) -> Either<Json<EditorState>, Json<Error>> {
+    let dna = match faidx::sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    editor_response(editor::open_session(
+        dna,
+        form.topology.to_owned(),
+        form.annotate,
+    ))
+}
+
+#[utoipa::path(
+    tag="Sequence Editor",
+    request_body(content = String, description = "Contents of a SnapGene .dna file, optionally gzip compressed", content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "EditorState", body = EditorState),
+    )
+)]
+#[post("/editor/upload")]
+async fn editor_upload(body: Bytes) -> Either<Json<EditorState>, Json<Error>> {
+    editor_response(editor::open_snapgene(&body))
+}
+
+#[utoipa::path(
+    tag="Sequence Editor",
+    responses(
+        (status = 200, description = "EditorState", body = EditorState),
+    ),
+    params(
+        ("EditorSession" = EditorSession, description = "Session id"),
+    )
+)]
+#[post("/editor/state")]
+async fn editor_state(form: Json<EditorSession>) -> Either<Json<EditorState>, Json<Error>> {
+    editor_response(editor::session(form.id))
+}
+
+#[utoipa::path(
+    tag="Sequence Editor",
+    responses(
+        (status = 200, description = "EditorState", body = EditorState),
+    ),
+    params(
+        ("EditorEdit" = EditorEdit, description = "Session id, operation, region and bases"),
+    )
+)]
+#[post("/editor/edit")]
+async fn editor_edit(form: Json<EditorEdit>) -> Either<Json<EditorState>, Json<Error>> {
+    let bases = match faidx::optional_sequence(form.bases.to_owned()).await {
+        Ok(bases) => bases,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    editor_response(editor::edit_session(
+        form.id,
+        &form.operation,
+        form.start,
+        form.end,
+        bases,
+    ))
+}
+
+#[utoipa::path(
+    tag="Sequence Editor",
+    responses(
+        (status = 200, description = "EditorState", body = EditorState),
+    ),
+    params(
+        ("EditorSession" = EditorSession, description = "Session id"),
+    )
+)]
+#[post("/editor/undo")]
+async fn editor_undo(form: Json<EditorSession>) -> Either<Json<EditorState>, Json<Error>> {
+    editor_response(editor::undo_session(form.id))
+}
+
+#[utoipa::path(
+    tag="Sequence Editor",
+    responses(
+        (status = 200, description = "EditorState", body = EditorState),
+    ),
+    params(
+        ("EditorSession" = EditorSession, description = "Session id"),
+    )
+)]
+#[post("/editor/redo")]
+async fn editor_redo(form: Json<EditorSession>) -> Either<Json<EditorState>, Json<Error>> {
+    editor_response(editor::redo_session(form.id))
+}
+
+#[utoipa::path(
+    tag="Sequence Editor",
+    responses(
+        (status = 200, description = "EditorState of the new session", body = EditorState),
+    ),
+    params(
+        ("EditorExtract" = EditorExtract, description = "Session id and region"),
+    )
+)]
+#[post("/editor/extract")]
+async fn editor_extract(form: Json<EditorExtract>) -> Either<Json<EditorState>, Json<Error>> {
+    editor_response(editor::extract_session(form.id, form.start, form.end))
+}
+
+#[utoipa::path(
+    tag="Sequence Editor",
+    responses(
+        (status = 200, description = "Closed session", body = EditorSession),
+    ),
+    params(
+        ("EditorSession" = EditorSession, description = "Session id"),
+    )
+)]
+#[post("/editor/close")]
+async fn editor_close(form: Json<EditorSession>) -> Either<Json<EditorSession>, Json<Error>> {
+    match editor::close_session(form.id) {
+        Ok(()) => Either::Left(Json(EditorSession { id: form.id })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
@@ -0,0 +1,167 @@
+use actix_web::{
+    get, post,
+    web::{Either, Json},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::traits::ToLetter;
+use plasmid::uni::{supplier_name, CutMode, RestrictionEnzyme};
+
+use crate::core::enzymes;
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
+use crate::core::schema::Error;
+
+#[derive(Deserialize, ToSchema)]
+pub struct EnzymeSearch {
+    /// Recognized sequence on either strand, may contain IUPAC codes, e.g. `GAATTC`
+    #[schema()]
+    site: Option<String>,
+    /// `blunt`, `5'` or `3'`
+    #[schema()]
+    overhang_type: Option<String>,
+    /// REBASE supplier code (e.g. `N`) or part of the supplier name
+    #[schema()]
+    supplier: Option<String>,
+    /// DNA sequence or RegionReference to count the cuts of every enzyme in
+    #[schema()]
+    dna: Option<SequenceInput>,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    topology: Option<String>,
+    /// Exact number of cuts in `dna`, 1 for unique and 2 for double cutters
+    #[schema()]
+    cuts: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EnzymeSupplier {
+    #[schema()]
+    code: char,
+    #[schema()]
+    name: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EnzymeInfo {
+    #[schema()]
+    name: String,
+    /// Recognition site, 5' to 3'
+    #[schema()]
+    site: String,
+    /// `within` the site, `outside` of it (Type IIS) or `nick`
+    #[schema()]
+    cut_mode: String,
+    /// Top strand cut relative to the start of the site
+    #[schema()]
+    top_cut: Option<isize>,
+    /// Bottom strand cut relative to the start of the site
+    #[schema()]
+    bottom_cut: Option<isize>,
+    /// `blunt`, `5'` or `3'`, none for nicking enzymes
+    #[schema()]
+    overhang_type: Option<String>,
+    #[schema()]
+    overhang_length: Option<usize>,
+    /// Methylated bases blocking cleavage in REBASE notation, e.g. `3(6)`
+    #[schema()]
+    methylation: Vec<String>,
+    #[schema()]
+    suppliers: Vec<EnzymeSupplier>,
+    /// Number of cuts in the searched sequence
+    #[schema()]
+    cuts: Option<usize>,
+}
+
+impl EnzymeInfo {
+    fn new(enzyme: &RestrictionEnzyme, cuts: Option<usize>) -> Self {
+        let (top_cut, bottom_cut) = match enzyme.cut_offsets() {
+            Some((top, bottom)) => (Some(top), Some(bottom)),
+            None => (None, None),
+        };
+        EnzymeInfo {
+            name: enzyme.name.to_owned(),
+            site: enzyme.site().iter().map(|n| n.to_letter()).collect(),
+            cut_mode: match enzyme.mode() {
+                CutMode::M => "within",
+                CutMode::A => "outside",
+                CutMode::N => "nick",
+            }
+            .to_string(),
+            top_cut,
+            bottom_cut,
+            overhang_type: enzymes::utils::overhang_type(enzyme).map(|t| t.to_string()),
+            overhang_length: enzyme.overhang().map(|o| o.unsigned_abs()),
+            methylation: enzyme
+                .methylation
+                .iter()
+                .map(|m| format!("{}({})", m.position, m.methylation.code()))
+                .collect(),
+            suppliers: enzyme
+                .suppliers
+                .iter()
+                .map(|code| EnzymeSupplier {
+                    code: *code,
+                    name: supplier_name(*code).map(|name| name.to_string()),
+                })
+                .collect(),
+            cuts,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct EnzymeList {
+    #[schema()]
+    enzymes: Vec<EnzymeInfo>,
+}
+
+#[utoipa::path(
+    tag="Restriction Enzymes",
+    responses(
+        (status = 200, description = "EnzymeList", body = EnzymeList),
+    )
+)]
+#[get("/enzymes/list")]
+async fn enzymes_list() -> Json<EnzymeList> {
+    Json(EnzymeList {
+        enzymes: enzymes::utils::enzymes()
+            .iter()
+            .map(|enzyme| EnzymeInfo::new(enzyme, None))
+            .collect(),
+    })
+}
+
+#[utoipa::path(
+    tag="Restriction Enzymes",
+    responses(
+        (status = 200, description = "EnzymeList", body = EnzymeList),
+    ),
+    params(
+        ("EnzymeSearch" = EnzymeSearch, description = "Recognition site, overhang type, supplier and cutter frequency in a DNA String"),
+    )
+)]
+#[post("/enzymes/search")]
+async fn enzymes_search(form: Json<EnzymeSearch>) -> Either<Json<EnzymeList>, Json<Error>> {
+    let dna = match faidx::optional_sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let filter = enzymes::utils::EnzymeFilter {
+        site: form.site.to_owned(),
+        overhang_type: form.overhang_type.to_owned(),
+        supplier: form.supplier.to_owned(),
+        sequence: dna,
+        topology: form.topology.to_owned(),
+        cuts: form.cuts,
+    };
+    match enzymes::utils::search(&filter) {
+        Ok(found) => Either::Left(Json(EnzymeList {
+            enzymes: found
+                .into_iter()
+                .map(|(enzyme, cuts)| EnzymeInfo::new(enzyme, cuts))
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
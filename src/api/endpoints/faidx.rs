@@ -0,0 +1,116 @@
+use actix_web::{
+    post,
+    web::{Either, Json},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::prelude::Compression;
+
+use crate::core::faidx::utils as faidx;
+use crate::core::schema::Error;
+
+#[derive(Deserialize, ToSchema)]
+pub struct FaidxBuild {
+    /// Path to an uncompressed or BGZF compressed FASTA file on the server
+    #[schema(example = "genomes/hg38.fa.gz")]
+    path: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FaidxSequence {
+    #[schema()]
+    name: String,
+    #[schema()]
+    length: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FaidxInfo {
+    #[schema()]
+    path: String,
+    /// Whether the file is BGZF compressed, its `.gzi` index is then written as well
+    #[schema()]
+    bgzf: bool,
+    #[schema()]
+    sequences: Vec<FaidxSequence>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct FaidxFetch {
+    /// Path to an uncompressed or BGZF compressed FASTA file on the server, indexed on first use
+    #[schema(example = "genomes/hg38.fa.gz")]
+    path: String,
+    /// Regions as written by samtools, e.g. `chr1`, `chr1:1000` or `chr1:1,000-2,000`
+    #[schema()]
+    regions: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FaidxRegion {
+    #[schema()]
+    region: String,
+    #[schema()]
+    sequence: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FaidxFetchReport {
+    #[schema()]
+    regions: Vec<FaidxRegion>,
+}
+
+#[utoipa::path(
+    tag="Indexed FASTA",
+    responses(
+        (status = 200, description = "FaidxInfo", body = FaidxInfo),
+    ),
+    params(
+        ("FaidxBuild" = FaidxBuild, description = "Path to the FASTA file to write a .fai index for"),
+    )
+)]
+#[post("/faidx/index")]
+async fn faidx_index(form: Json<FaidxBuild>) -> Either<Json<FaidxInfo>, Json<Error>> {
+    match faidx::index_fasta(&form.path) {
+        Ok((index, compression)) => Either::Left(Json(FaidxInfo {
+            path: form.path.to_owned(),
+            bgzf: compression == Compression::Bgzf,
+            sequences: index
+                .records
+                .into_iter()
+                .map(|record| FaidxSequence {
+                    name: record.name,
+                    length: record.length,
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Indexed FASTA",
+    responses(
+        (status = 200, description = "FaidxFetchReport", body = FaidxFetchReport),
+    ),
+    params(
+        ("FaidxFetch" = FaidxFetch, description = "Path to an indexed FASTA file and the regions to read from it"),
+    )
+)]
+#[post("/faidx/fetch")]
+async fn faidx_fetch(form: Json<FaidxFetch>) -> Either<Json<FaidxFetchReport>, Json<Error>> {
+    let regions = form
+        .regions
+        .iter()
+        .map(|region| {
+            faidx::fetch_region(&form.path, region).map(|sequence| FaidxRegion {
+                region: region.to_owned(),
+                sequence,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>();
+    match regions {
+        Ok(regions) => Either::Left(Json(FaidxFetchReport { regions })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
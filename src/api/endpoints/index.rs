@@ -0,0 +1,196 @@
+use actix_web::{
+    get, post,
+    web::{self, Either, Json},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::prelude::{ReferenceIndex, Strand};
+
+use crate::core::index::utils as index;
+use crate::core::schema::Error;
+
+/// Occurrences located by default
+const DEFAULT_HIT_LIMIT: usize = 1000;
+
+#[derive(Deserialize, ToSchema)]
+pub struct IndexBuild {
+    /// Name to look the index up by, e.g. `hg38` or `pUC19`
+    #[schema()]
+    name: String,
+    /// FASTA contents of the reference
+    #[schema()]
+    fasta: Option<String>,
+    /// Path to a FASTA file on the server, if no contents are given
+    #[schema()]
+    path: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IndexSequence {
+    #[schema()]
+    name: String,
+    #[schema()]
+    length: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IndexInfo {
+    #[schema()]
+    name: String,
+    /// Total number of indexed bases
+    #[schema()]
+    length: usize,
+    #[schema()]
+    sequences: Vec<IndexSequence>,
+}
+
+impl IndexInfo {
+    fn new(index: &ReferenceIndex) -> Self {
+        IndexInfo {
+            name: index.name.to_owned(),
+            length: index.total_len(),
+            sequences: index
+                .sequences
+                .iter()
+                .map(|sequence| IndexSequence {
+                    name: sequence.name.to_owned(),
+                    length: sequence.len,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IndexList {
+    #[schema()]
+    indexes: Vec<IndexInfo>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct IndexQuery {
+    /// Name of the reference index
+    #[schema()]
+    name: String,
+    /// Pattern to look up, may contain IUPAC codes
+    #[schema()]
+    pattern: String,
+    /// Maximum number of substituted bases, defaults to 0
+    #[schema()]
+    max_mismatches: Option<usize>,
+    /// Maximum number of occurrences to locate, defaults to 1000, 0 to only count them
+    #[schema()]
+    limit: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IndexHit {
+    /// Name of the record the occurrence is in
+    #[schema()]
+    sequence: String,
+    /// First position of the occurrence on the top strand
+    #[schema()]
+    position: usize,
+    /// `forward` or `reverse`
+    #[schema()]
+    strand: String,
+    #[schema()]
+    mismatches: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IndexSearchReport {
+    /// Occurrences on both strands
+    #[schema()]
+    count: usize,
+    #[schema()]
+    forward_count: usize,
+    #[schema()]
+    reverse_count: usize,
+    /// Located occurrences, fewest mismatches first
+    #[schema()]
+    hits: Vec<IndexHit>,
+}
+
+#[utoipa::path(
+    tag="Reference Index",
+    responses(
+        (status = 200, description = "IndexInfo", body = IndexInfo),
+    ),
+    params(
+        ("IndexBuild" = IndexBuild, description = "Index name and FASTA contents or path"),
+    )
+)]
+#[post("/index/build")]
+async fn index_build(form: Json<IndexBuild>) -> Either<Json<IndexInfo>, Json<Error>> {
+    let form = form.into_inner();
+    let index = web::block(move || index::build_index(&form.name, form.fasta, form.path))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|index| index);
+    match index {
+        Ok(index) => Either::Left(Json(IndexInfo::new(&index))),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Reference Index",
+    responses(
+        (status = 200, description = "IndexList", body = IndexList),
+    )
+)]
+#[get("/index/list")]
+async fn index_list() -> Json<IndexList> {
+    Json(IndexList {
+        indexes: index::indexes()
+            .iter()
+            .map(|index| IndexInfo::new(index))
+            .collect(),
+    })
+}
+
+#[utoipa::path(
+    tag="Reference Index",
+    responses(
+        (status = 200, description = "IndexSearchReport", body = IndexSearchReport),
+    ),
+    params(
+        ("IndexQuery" = IndexQuery, description = "Index name, pattern, maximum mismatches and number of occurrences to locate"),
+    )
+)]
+#[post("/index/search")]
+async fn index_search(form: Json<IndexQuery>) -> Either<Json<IndexSearchReport>, Json<Error>> {
+    let search = index::find_index(&form.name).and_then(|index| {
+        index
+            .search(
+                &form.pattern,
+                form.max_mismatches.unwrap_or(0),
+                form.limit.unwrap_or(DEFAULT_HIT_LIMIT),
+            )
+            .map_err(|e| e.to_string())
+    });
+    match search {
+        Ok(search) => Either::Left(Json(IndexSearchReport {
+            count: search.forward_count + search.reverse_count,
+            forward_count: search.forward_count,
+            reverse_count: search.reverse_count,
+            hits: search
+                .hits
+                .into_iter()
+                .map(|hit| IndexHit {
+                    sequence: hit.sequence,
+                    position: hit.position,
+                    strand: match hit.strand {
+                        Strand::Reverse => "reverse",
+                        _ => "forward",
+                    }
+                    .to_string(),
+                    mismatches: hit.mismatches,
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
@@ -0,0 +1,106 @@
+use actix_web::{
+    post,
+    web::{self, Either, Json},
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::core::mapping::utils as mapping;
+use crate::core::schema::Error;
+
+#[derive(Deserialize, ToSchema)]
+pub struct ReadMapping {
+    /// FASTQ contents of the reads
+    #[schema()]
+    reads: Option<String>,
+    /// Path to a FASTQ file on the server, if no contents are given
+    #[schema(example = "samples/SRR0000001.fastq")]
+    path: Option<String>,
+    /// FASTA contents of the reference
+    #[schema()]
+    reference: Option<String>,
+    /// Name of a reference index to map to, if no reference is given
+    #[schema()]
+    index: Option<String>,
+    /// Length of the exact seeds, defaults to 19
+    #[schema()]
+    seed_len: Option<usize>,
+    /// Smallest alignment score of mapped reads, defaults to 30
+    #[schema()]
+    min_score: Option<i32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadMappingStats {
+    #[schema()]
+    reads: usize,
+    #[schema()]
+    mapped: usize,
+    #[schema()]
+    mapped_percent: f64,
+    /// Mean depth over all reference bases
+    #[schema()]
+    mean_coverage: f64,
+    /// Percentage of reference bases covered by at least one read
+    #[schema()]
+    covered_percent: f64,
+    /// Mismatched, inserted and deleted bases per aligned read base
+    #[schema()]
+    error_rate: f64,
+    #[schema()]
+    mean_mapq: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadMappingReport {
+    /// Alignments in SAM format
+    #[schema()]
+    sam: String,
+    #[schema()]
+    stats: ReadMappingStats,
+}
+
+#[utoipa::path(
+    tag="Read Mapping",
+    responses(
+        (status = 200, description = "ReadMappingReport", body = ReadMappingReport),
+    ),
+    params(
+        ("ReadMapping" = ReadMapping, description = "FASTQ reads, FASTA reference or index name and seeding parameters"),
+    )
+)]
+#[post("/mapping/map")]
+async fn mapping_map(form: Json<ReadMapping>) -> Either<Json<ReadMappingReport>, Json<Error>> {
+    let form = form.into_inner();
+    let mapping = web::block(move || {
+        mapping::map_reads(
+            form.reads,
+            form.path,
+            form.reference,
+            form.index,
+            form.seed_len,
+            form.min_score,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|mapping| mapping);
+    match mapping {
+        Ok(result) => {
+            let stats = result.stats();
+            Either::Left(Json(ReadMappingReport {
+                sam: mapping::sam(&result),
+                stats: ReadMappingStats {
+                    reads: stats.reads,
+                    mapped: stats.mapped,
+                    mapped_percent: stats.mapped_percent,
+                    mean_coverage: stats.mean_coverage,
+                    covered_percent: stats.covered_percent,
+                    error_rate: stats.error_rate,
+                    mean_mapq: stats.mean_mapq,
+                },
+            }))
+        }
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
@@ -1,3 +1,14 @@
 pub mod fasta;
 pub mod sequence;
-pub mod dna;
\ No newline at end of file
+pub mod dna;
+pub mod primer;
+pub mod cloning;
+pub mod enzymes;
+pub mod editor;
+pub mod index;
+pub mod mapping;
+pub mod coverage;
+pub mod variants;
+pub mod traces;
+pub mod annotations;
+pub mod faidx;
\ No newline at end of file
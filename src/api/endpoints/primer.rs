@@ -0,0 +1,499 @@
+use actix_web::{
+    post,
+    web::{Either, Json},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::prelude::{
+    PcrParameters, PcrPrimer, PrimerCandidate, PrimerConstraints, PrimerProperties, PrimerSite,
+    TmConditions,
+};
+use plasmid::traits::ToLetter;
+
+use super::dna::image_response;
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
+use crate::core::primer;
+use crate::core::schema::Error;
+
+#[derive(Deserialize, ToSchema)]
+pub struct ReactionConditions {
+    /// Monovalent cation (Na+/K+) concentration in mM
+    #[schema()]
+    monovalent: Option<f64>,
+    /// Mg2+ concentration in mM
+    #[schema()]
+    magnesium: Option<f64>,
+    /// dNTP concentration in mM
+    #[schema()]
+    dntp: Option<f64>,
+    /// Oligonucleotide concentration in nM
+    #[schema()]
+    oligo: Option<f64>,
+}
+
+impl ReactionConditions {
+    fn to_tm_conditions(conditions: &Option<ReactionConditions>) -> TmConditions {
+        let default = TmConditions::default();
+        match conditions {
+            Some(c) => TmConditions {
+                monovalent: c.monovalent.unwrap_or(default.monovalent),
+                magnesium: c.magnesium.unwrap_or(default.magnesium),
+                dntp: c.dntp.unwrap_or(default.dntp),
+                oligo: c.oligo.unwrap_or(default.oligo),
+            },
+            None => default,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct Primer {
+    #[schema()]
+    primer: String,
+    /// One of `nearest_neighbor` (default), `wallace` or `basic`
+    #[schema()]
+    method: Option<String>,
+    #[schema()]
+    conditions: Option<ReactionConditions>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PrimerPair {
+    #[schema()]
+    forward: String,
+    #[schema()]
+    reverse: String,
+    #[schema()]
+    conditions: Option<ReactionConditions>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PrimerReport {
+    #[schema()]
+    length: usize,
+    #[schema()]
+    gc_content: f32,
+    #[schema()]
+    tm: f64,
+    #[schema()]
+    tm_nearest_neighbor: f64,
+    #[schema()]
+    tm_basic: f64,
+    #[schema()]
+    gc_clamp: usize,
+    #[schema()]
+    self_dimer_dg: f64,
+    #[schema()]
+    self_three_prime_dg: f64,
+    #[schema()]
+    hairpin_dg: f64,
+}
+
+impl PrimerReport {
+    fn new(tm: f64, props: PrimerProperties) -> Self {
+        PrimerReport {
+            length: props.length,
+            gc_content: props.gc_content,
+            tm,
+            tm_nearest_neighbor: props.tm,
+            tm_basic: props.tm_basic,
+            gc_clamp: props.gc_clamp,
+            self_dimer_dg: props.self_dimer_dg,
+            self_three_prime_dg: props.self_three_prime_dg,
+            hairpin_dg: props.hairpin_dg,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PrimerPairReport {
+    #[schema()]
+    forward: PrimerReport,
+    #[schema()]
+    reverse: PrimerReport,
+    #[schema()]
+    tm_difference: f64,
+    #[schema()]
+    dimer_dg: f64,
+    #[schema()]
+    three_prime_dg: f64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PrimerDesign {
+    /// DNA String or RegionReference
+    #[schema()]
+    template: SequenceInput,
+    /// First base of the region to amplify (0-based)
+    #[schema()]
+    target_start: usize,
+    /// Base after the last base of the region to amplify (0-based)
+    #[schema()]
+    target_end: usize,
+    #[schema()]
+    product_size_min: Option<usize>,
+    #[schema()]
+    product_size_max: Option<usize>,
+    #[schema()]
+    length_min: Option<usize>,
+    #[schema()]
+    length_max: Option<usize>,
+    #[schema()]
+    tm_min: Option<f64>,
+    #[schema()]
+    tm_max: Option<f64>,
+    #[schema()]
+    tm_optimal: Option<f64>,
+    #[schema()]
+    gc_min: Option<f32>,
+    #[schema()]
+    gc_max: Option<f32>,
+    #[schema()]
+    max_tm_difference: Option<f64>,
+    #[schema()]
+    max_pairs: Option<usize>,
+    #[schema()]
+    conditions: Option<ReactionConditions>,
+}
+
+impl PrimerDesign {
+    fn to_constraints(&self) -> PrimerConstraints {
+        let default = PrimerConstraints::default();
+        PrimerConstraints {
+            product_size: self.product_size_min.unwrap_or(*default.product_size.start())
+                ..=self.product_size_max.unwrap_or(*default.product_size.end()),
+            length: self.length_min.unwrap_or(*default.length.start())
+                ..=self.length_max.unwrap_or(*default.length.end()),
+            tm: self.tm_min.unwrap_or(*default.tm.start())..=self.tm_max.unwrap_or(*default.tm.end()),
+            optimal_tm: self.tm_optimal.unwrap_or(default.optimal_tm),
+            gc_content: self.gc_min.unwrap_or(*default.gc_content.start())
+                ..=self.gc_max.unwrap_or(*default.gc_content.end()),
+            max_tm_difference: self.max_tm_difference.unwrap_or(default.max_tm_difference),
+            max_pairs: self.max_pairs.unwrap_or(default.max_pairs),
+            conditions: ReactionConditions::to_tm_conditions(&self.conditions),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DesignedPrimer {
+    #[schema()]
+    sequence: String,
+    #[schema()]
+    start: usize,
+    #[schema()]
+    end: usize,
+    #[schema()]
+    off_target: usize,
+    #[schema()]
+    penalty: f64,
+    #[schema()]
+    properties: PrimerReport,
+}
+
+impl DesignedPrimer {
+    fn new(candidate: PrimerCandidate) -> Self {
+        DesignedPrimer {
+            sequence: candidate.sequence.iter().map(|n| n.to_letter()).collect(),
+            start: candidate.start,
+            end: candidate.end,
+            off_target: candidate.off_target,
+            penalty: candidate.penalty,
+            properties: PrimerReport::new(candidate.properties.tm, candidate.properties),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DesignedPair {
+    #[schema()]
+    rank: usize,
+    #[schema()]
+    forward: DesignedPrimer,
+    #[schema()]
+    reverse: DesignedPrimer,
+    #[schema()]
+    product_size: usize,
+    #[schema()]
+    tm_difference: f64,
+    #[schema()]
+    dimer_dg: f64,
+    #[schema()]
+    three_prime_dg: f64,
+    #[schema()]
+    penalty: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PrimerDesignReport {
+    #[schema()]
+    pairs: Vec<DesignedPair>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct Pcr {
+    /// Forward primer (5' to 3'), may contain IUPAC codes
+    #[schema()]
+    forward: String,
+    /// Reverse primer (5' to 3'), may contain IUPAC codes
+    #[schema()]
+    reverse: String,
+    /// Template sequence or RegionReference, mutually exclusive with `fasta`
+    #[schema()]
+    template: Option<SequenceInput>,
+    /// Multi-FASTA reference, mutually exclusive with `template`
+    #[schema()]
+    fasta: Option<String>,
+    /// Maximum weighted number of mismatches per primer
+    #[schema()]
+    max_mismatches: Option<usize>,
+    /// Number of 3' terminal bases whose mismatches are weighted more strictly
+    #[schema()]
+    three_prime_length: Option<usize>,
+    /// Weight of a mismatch within the 3' terminal bases
+    #[schema()]
+    three_prime_weight: Option<usize>,
+    #[schema()]
+    max_product_size: Option<usize>,
+}
+
+impl Pcr {
+    fn to_parameters(&self) -> PcrParameters {
+        let default = PcrParameters::default();
+        PcrParameters {
+            max_mismatches: self.max_mismatches.unwrap_or(default.max_mismatches),
+            three_prime_length: self.three_prime_length.unwrap_or(default.three_prime_length),
+            three_prime_weight: self.three_prime_weight.unwrap_or(default.three_prime_weight),
+            max_product_size: self.max_product_size.unwrap_or(default.max_product_size),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PcrPrimerSite {
+    /// `forward` or `reverse`
+    #[schema()]
+    primer: String,
+    #[schema()]
+    start: usize,
+    #[schema()]
+    end: usize,
+    #[schema()]
+    mismatches: usize,
+}
+
+impl PcrPrimerSite {
+    fn new(site: PrimerSite) -> Self {
+        PcrPrimerSite {
+            primer: match site.primer {
+                PcrPrimer::Forward => "forward".to_string(),
+                PcrPrimer::Reverse => "reverse".to_string(),
+            },
+            start: site.start,
+            end: site.end,
+            mismatches: site.mismatches,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PcrAmplicon {
+    /// Identifier of the FASTA record the product was amplified from
+    #[schema()]
+    record: Option<String>,
+    #[schema()]
+    start: usize,
+    #[schema()]
+    end: usize,
+    #[schema()]
+    length: usize,
+    #[schema()]
+    left: PcrPrimerSite,
+    #[schema()]
+    right: PcrPrimerSite,
+    #[schema()]
+    sequence: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PcrReport {
+    #[schema()]
+    amplicons: Vec<PcrAmplicon>,
+}
+
+#[utoipa::path(
+    tag="Primer Design",
+    responses(
+        (status = 200, description = "PrimerReport", body = PrimerReport),
+    ),
+    params(
+        ("Primer" = Primer, description = "Primer (5' to 3'), Tm method and reaction conditions"),
+    )
+)]
+#[post("/primer/properties")]
+async fn primer_properties(form: Json<Primer>) -> Either<Json<PrimerReport>, Json<Error>> {
+    let conditions = ReactionConditions::to_tm_conditions(&form.conditions);
+    match primer::utils::primer_properties(form.primer.to_owned(), form.method.to_owned(), conditions) {
+        Ok((tm, props)) => Either::Left(Json(PrimerReport::new(tm, props))),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Primer Design",
+    responses(
+        (status = 200, description = "PrimerPairReport", body = PrimerPairReport),
+    ),
+    params(
+        ("PrimerPair" = PrimerPair, description = "Forward and reverse primers (5' to 3') and reaction conditions"),
+    )
+)]
+#[post("/primer/pair")]
+async fn primer_pair(form: Json<PrimerPair>) -> Either<Json<PrimerPairReport>, Json<Error>> {
+    let conditions = ReactionConditions::to_tm_conditions(&form.conditions);
+    match primer::utils::primer_pair_properties(form.forward.to_owned(), form.reverse.to_owned(), conditions) {
+        Ok(pair) => Either::Left(Json(PrimerPairReport {
+            forward: PrimerReport::new(pair.forward.tm, pair.forward),
+            reverse: PrimerReport::new(pair.reverse.tm, pair.reverse),
+            tm_difference: pair.tm_difference,
+            dimer_dg: pair.dimer_dg,
+            three_prime_dg: pair.three_prime_dg,
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Primer Design",
+    responses(
+        (status = 200, description = "PrimerDesignReport", body = PrimerDesignReport),
+    ),
+    params(
+        ("PrimerDesign" = PrimerDesign, description = "Template, target region and primer constraints"),
+    )
+)]
+#[post("/primer/design")]
+async fn primer_design(form: Json<PrimerDesign>) -> Either<Json<PrimerDesignReport>, Json<Error>> {
+    let template = match faidx::sequence(form.template.to_owned()).await {
+        Ok(template) => template,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    match primer::utils::design_primers(
+        template,
+        form.target_start,
+        form.target_end,
+        form.to_constraints(),
+    ) {
+        Ok(pairs) => Either::Left(Json(PrimerDesignReport {
+            pairs: pairs
+                .into_iter()
+                .enumerate()
+                .map(|(i, pair)| DesignedPair {
+                    rank: i + 1,
+                    forward: DesignedPrimer::new(pair.forward),
+                    reverse: DesignedPrimer::new(pair.reverse),
+                    product_size: pair.product_size,
+                    tm_difference: pair.tm_difference,
+                    dimer_dg: pair.dimer_dg,
+                    three_prime_dg: pair.three_prime_dg,
+                    penalty: pair.penalty,
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Primer Design",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("PrimerDesign" = PrimerDesign, description = "Template, target region and primer constraints"),
+    )
+)]
+#[post("/primer/design_svg")]
+async fn primer_design_svg(form: Json<PrimerDesign>) -> HttpResponse {
+    let template = match faidx::sequence(form.template.to_owned()).await {
+        Ok(template) => template,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/svg+xml",
+        primer::utils::design_primers_svg(
+            template,
+            form.target_start,
+            form.target_end,
+            form.to_constraints(),
+        ),
+    )
+}
+
+#[utoipa::path(
+    tag="Primer Design",
+    responses(
+        (status = 200, description = "HttpResponse"),
+    ),
+    params(
+        ("PrimerDesign" = PrimerDesign, description = "Template, target region and primer constraints"),
+    )
+)]
+#[post("/primer/design_png")]
+async fn primer_design_png(form: Json<PrimerDesign>) -> HttpResponse {
+    let template = match faidx::sequence(form.template.to_owned()).await {
+        Ok(template) => template,
+        Err(error) => return HttpResponse::BadRequest().json(error),
+    };
+    image_response(
+        "image/png",
+        primer::utils::design_primers_png(
+            template,
+            form.target_start,
+            form.target_end,
+            form.to_constraints(),
+        ),
+    )
+}
+
+#[utoipa::path(
+    tag="Primer Design",
+    responses(
+        (status = 200, description = "PcrReport", body = PcrReport),
+    ),
+    params(
+        ("Pcr" = Pcr, description = "Primer pair, template or multi-FASTA reference and mismatch limits"),
+    )
+)]
+#[post("/primer/pcr")]
+async fn primer_pcr(form: Json<Pcr>) -> Either<Json<PcrReport>, Json<Error>> {
+    let template = match faidx::optional_sequence(form.template.to_owned()).await {
+        Ok(template) => template,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    match primer::utils::pcr(
+        form.forward.to_owned(),
+        form.reverse.to_owned(),
+        template,
+        form.fasta.to_owned(),
+        form.to_parameters(),
+    ) {
+        Ok(amplicons) => Either::Left(Json(PcrReport {
+            amplicons: amplicons
+                .into_iter()
+                .map(|(record, amplicon)| PcrAmplicon {
+                    record,
+                    start: amplicon.start,
+                    end: amplicon.end,
+                    length: amplicon.len(),
+                    sequence: amplicon.sequence.iter().map(|n| n.to_letter()).collect(),
+                    left: PcrPrimerSite::new(amplicon.left),
+                    right: PcrPrimerSite::new(amplicon.right),
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
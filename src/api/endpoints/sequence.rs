@@ -4,20 +4,27 @@ use actix_web::{
     Responder,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use utoipa::ToSchema;
 
+use plasmid::traits::ToLetter;
+
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
+use crate::core::schema::Error;
 use crate::core::sequence;
 
 #[derive(Deserialize, ToSchema)]
 pub struct Sequence {
+    /// Sequence or RegionReference
     #[schema()]
-    sequence: String,
+    sequence: SequenceInput,
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct NucleotideIndex {
+    /// Sequence or RegionReference
     #[schema()]
-    sequence: String,
+    sequence: SequenceInput,
     #[schema()]
     index: usize,
 }
@@ -44,6 +51,57 @@ pub struct GenomicSequence {
     length: i64,
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct SequenceWindow {
+    /// Sequence or RegionReference
+    #[schema()]
+    sequence: SequenceInput,
+    #[schema()]
+    window: Option<usize>,
+    #[schema()]
+    step: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct GcWindowStatistics {
+    #[schema()]
+    window: usize,
+    #[schema()]
+    step: usize,
+    #[schema()]
+    positions: Vec<usize>,
+    #[schema()]
+    gc_content: Vec<f32>,
+    #[schema()]
+    gc_skew: Vec<f32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SequenceStatistics {
+    #[schema()]
+    length: usize,
+    #[schema()]
+    counts: BTreeMap<String, usize>,
+    #[schema()]
+    gc_content: f32,
+    #[schema()]
+    at_content: f32,
+    #[schema()]
+    gc_skew: f32,
+    #[schema()]
+    cpg_observed_expected: f32,
+    #[schema()]
+    molecular_weight_ss: f64,
+    #[schema()]
+    molecular_weight_ds: f64,
+    #[schema()]
+    extinction_coefficient_ss: f64,
+    #[schema()]
+    extinction_coefficient_ds: f64,
+    #[schema()]
+    windows: Option<GcWindowStatistics>,
+}
+
 #[utoipa::path(
     tag="DNA Sequencing",
     responses(
@@ -54,8 +112,12 @@ pub struct GenomicSequence {
     )
 )]
 #[post("/sequence/nucleotide_at_index")]
-async fn nucleotide_at_index(form: Json<NucleotideIndex>) -> impl Responder {
-    sequence::utils::nucleotide_index(form.sequence.to_owned(), form.index)
+async fn nucleotide_at_index(form: Json<NucleotideIndex>) -> Either<String, Json<Error>> {
+    let sequence = match faidx::sequence(form.sequence.to_owned()).await {
+        Ok(sequence) => sequence,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(sequence::utils::nucleotide_index(sequence, form.index))
 }
 
 #[utoipa::path(
@@ -68,8 +130,12 @@ async fn nucleotide_at_index(form: Json<NucleotideIndex>) -> impl Responder {
     )
 )]
 #[post("/sequence/codon_frames")]
-async fn codon_frames(form: Json<Sequence>) -> impl Responder {
-    sequence::utils::codon_frames(form.sequence.to_owned())
+async fn codon_frames(form: Json<Sequence>) -> Either<String, Json<Error>> {
+    let sequence = match faidx::sequence(form.sequence.to_owned()).await {
+        Ok(sequence) => sequence,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    Either::Left(sequence::utils::codon_frames(sequence))
 }
 
 #[utoipa::path(
@@ -82,10 +148,16 @@ async fn codon_frames(form: Json<Sequence>) -> impl Responder {
     )
 )]
 #[post("/sequence/lorf")]
-async fn seq_lorf(form: Json<Sequence>) -> Either<Json<SingleLorf>, Json<MultiLorf>> {
-    let lorf = sequence::utils::seq_lorf(form.sequence.to_owned());
+async fn seq_lorf(
+    form: Json<Sequence>,
+) -> Either<Either<Json<SingleLorf>, Json<MultiLorf>>, Json<Error>> {
+    let sequence = match faidx::sequence(form.sequence.to_owned()).await {
+        Ok(sequence) => sequence,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let lorf = sequence::utils::seq_lorf(sequence);
 
-    match lorf {
+    Either::Left(match lorf {
         Either::Left(lorf) => {
             let length = lorf.len();
             Either::Left(Json(SingleLorf { lorf, length }))
@@ -94,7 +166,7 @@ async fn seq_lorf(form: Json<Sequence>) -> Either<Json<SingleLorf>, Json<MultiLo
             let length = lorfs.len();
             Either::Right(Json(MultiLorf { lorfs, length }))
         }
-    }
+    })
 }
 
 #[utoipa::path(
@@ -110,3 +182,46 @@ async fn seq_lorf(form: Json<Sequence>) -> Either<Json<SingleLorf>, Json<MultiLo
 async fn seq_random(form: Json<GenomicSequence>) -> impl Responder {
     sequence::utils::seq_random(form.length)
 }
+
+#[utoipa::path(
+    tag="DNA Sequencing",
+    responses(
+        (status = 200, description = "SequenceStatistics", body = SequenceStatistics),
+    ),
+    params(
+        ("SequenceWindow" = SequenceWindow, description = "Sequence and optional window size"),
+    )
+)]
+#[post("/sequence/stats")]
+async fn seq_stats(form: Json<SequenceWindow>) -> Either<Json<SequenceStatistics>, Json<Error>> {
+    let sequence = match faidx::sequence(form.sequence.to_owned()).await {
+        Ok(sequence) => sequence,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    match sequence::utils::seq_stats(sequence, form.window, form.step) {
+        Ok((stats, windows)) => Either::Left(Json(SequenceStatistics {
+            length: stats.length,
+            counts: stats
+                .counts
+                .iter()
+                .map(|(n, count)| (n.to_letter().to_string(), *count))
+                .collect(),
+            gc_content: stats.gc_content,
+            at_content: stats.at_content,
+            gc_skew: stats.gc_skew,
+            cpg_observed_expected: stats.cpg_observed_expected,
+            molecular_weight_ss: stats.molecular_weight_ss,
+            molecular_weight_ds: stats.molecular_weight_ds,
+            extinction_coefficient_ss: stats.extinction_coefficient_ss,
+            extinction_coefficient_ds: stats.extinction_coefficient_ds,
+            windows: windows.map(|w| GcWindowStatistics {
+                window: w.window,
+                step: w.step,
+                positions: w.positions,
+                gc_content: w.gc_content,
+                gc_skew: w.gc_skew,
+            }),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
@@ -0,0 +1,316 @@
+use actix_web::{
+    post,
+    web::{Bytes, Either, Json},
+    HttpResponse,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::imp::TraceFile;
+
+use crate::api::endpoints::dna::image_response;
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
+use crate::core::schema::Error;
+use crate::core::traces::utils::{self as traces, DEFAULT_TRIM_QUALITY};
+
+#[derive(Deserialize, ToSchema)]
+pub struct TraceSource {
+    /// Path to an ABIF (.ab1) or SCF file on the server
+    #[schema(example = "samples/clone1.ab1")]
+    path: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TraceInput {
+    #[schema(example = "samples/clone1.ab1")]
+    path: String,
+    /// Quality below which the ends are trimmed, defaults to 20
+    #[schema()]
+    min_quality: Option<u8>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TraceView {
+    #[schema(example = "samples/clone1.ab1")]
+    path: String,
+    /// First base call to draw, 0-based
+    #[schema()]
+    start: Option<usize>,
+    /// Base call after the last one to draw
+    #[schema()]
+    end: Option<usize>,
+    /// Draw the reverse complement of the trace
+    #[schema()]
+    reverse: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TraceAlign {
+    #[schema()]
+    traces: Vec<TraceSource>,
+    /// DNA String or RegionReference
+    #[schema()]
+    reference: SequenceInput,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    topology: Option<String>,
+    /// Quality below which the ends of the reads are trimmed, defaults to 20
+    #[schema()]
+    min_quality: Option<u8>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TraceInfo {
+    #[schema()]
+    name: String,
+    #[schema()]
+    bases: String,
+    #[schema()]
+    qualities: Vec<u8>,
+    /// Sample index of the peak of every base call
+    #[schema()]
+    peaks: Vec<usize>,
+    #[schema()]
+    a: Vec<u16>,
+    #[schema()]
+    c: Vec<u16>,
+    #[schema()]
+    g: Vec<u16>,
+    #[schema()]
+    t: Vec<u16>,
+    /// Calls left after quality trimming, `trim_end` exclusive
+    #[schema()]
+    trim_start: usize,
+    #[schema()]
+    trim_end: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TraceDiscrepancyInfo {
+    /// 0-based reference position
+    #[schema()]
+    position: usize,
+    /// Reference base, `-` for an insertion in the read
+    #[schema()]
+    reference: String,
+    /// Called base, `-` for a deletion in the read
+    #[schema()]
+    call: String,
+    /// Index of the base call in the aligned orientation of the trace
+    #[schema()]
+    index: Option<usize>,
+    #[schema()]
+    quality: Option<u8>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TraceAlignmentInfo {
+    #[schema()]
+    name: String,
+    /// Whether the reverse complement of the trace aligned
+    #[schema()]
+    reverse: bool,
+    #[schema()]
+    score: i32,
+    /// 0-based reference bounds, `end` exclusive and smaller than `start` across the origin
+    #[schema()]
+    start: usize,
+    #[schema()]
+    end: usize,
+    #[schema()]
+    read_start: usize,
+    #[schema()]
+    read_end: usize,
+    #[schema()]
+    identity: f64,
+    #[schema()]
+    cigar: String,
+    #[schema()]
+    discrepancies: Vec<TraceDiscrepancyInfo>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TraceAlignmentReport {
+    #[schema()]
+    alignments: Vec<TraceAlignmentInfo>,
+}
+
+/// Calls and channels of a trace, with the calls left after trimming its ends below `min_quality`
+fn trace_response(
+    trace: Result<TraceFile, String>,
+    min_quality: Option<u8>,
+) -> Either<Json<TraceInfo>, Json<Error>> {
+    match trace {
+        Ok(trace) => {
+            let trimmed = trace.quality_trim(min_quality.unwrap_or(DEFAULT_TRIM_QUALITY));
+            let [a, c, g, t] = trace.channels;
+            Either::Left(Json(TraceInfo {
+                name: trace.name,
+                bases: trace.bases,
+                qualities: trace.qualities,
+                peaks: trace.peaks,
+                a,
+                c,
+                g,
+                t,
+                trim_start: trimmed.start,
+                trim_end: trimmed.end,
+            }))
+        }
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Sanger Traces",
+    responses(
+        (status = 200, description = "TraceInfo", body = TraceInfo),
+    ),
+    params(
+        ("TraceInput" = TraceInput, description = "ABIF or SCF trace and trimming quality"),
+    )
+)]
+#[post("/traces/read")]
+async fn traces_read(form: Json<TraceInput>) -> Either<Json<TraceInfo>, Json<Error>> {
+    trace_response(traces::load_trace(&form.path), form.min_quality)
+}
+
+#[utoipa::path(
+    tag="Sanger Traces",
+    request_body(content = String, description = "Contents of an ABIF (.ab1) or SCF file, trimmed at quality 20", content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "TraceInfo", body = TraceInfo),
+    )
+)]
+#[post("/traces/upload")]
+async fn traces_upload(body: Bytes) -> Either<Json<TraceInfo>, Json<Error>> {
+    trace_response(traces::parse_trace(&body), None)
+}
+
+#[utoipa::path(
+    tag="Sanger Traces",
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    ),
+    params(
+        ("TraceView" = TraceView, description = "ABIF or SCF trace and the base calls to draw"),
+    )
+)]
+#[post("/traces/svg")]
+async fn traces_svg(form: Json<TraceView>) -> HttpResponse {
+    image_response(
+        "image/svg+xml",
+        traces::load_trace(&form.path)
+            .and_then(|trace| traces::gen_trace_svg(&trace, form.start, form.end, form.reverse)),
+    )
+}
+
+#[utoipa::path(
+    tag="Sanger Traces",
+    responses(
+        (status = 200, description = "HttpResponse"),
+    ),
+    params(
+        ("TraceView" = TraceView, description = "ABIF or SCF trace and the base calls to draw"),
+    )
+)]
+#[post("/traces/png")]
+async fn traces_png(form: Json<TraceView>) -> HttpResponse {
+    image_response(
+        "image/png",
+        traces::load_trace(&form.path)
+            .and_then(|trace| traces::gen_trace_png(&trace, form.start, form.end, form.reverse)),
+    )
+}
+
+#[utoipa::path(
+    tag="Sanger Traces",
+    request_body(content = String, description = "Contents of an ABIF (.ab1) or SCF file, drawn whole", content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "HttpResponse", body = HttpResponse),
+    )
+)]
+#[post("/traces/upload/svg")]
+async fn traces_upload_svg(body: Bytes) -> HttpResponse {
+    image_response(
+        "image/svg+xml",
+        traces::parse_trace(&body)
+            .and_then(|trace| traces::gen_trace_svg(&trace, None, None, None)),
+    )
+}
+
+#[utoipa::path(
+    tag="Sanger Traces",
+    request_body(content = String, description = "Contents of an ABIF (.ab1) or SCF file, drawn whole", content_type = "application/octet-stream"),
+    responses(
+        (status = 200, description = "HttpResponse"),
+    )
+)]
+#[post("/traces/upload/png")]
+async fn traces_upload_png(body: Bytes) -> HttpResponse {
+    image_response(
+        "image/png",
+        traces::parse_trace(&body)
+            .and_then(|trace| traces::gen_trace_png(&trace, None, None, None)),
+    )
+}
+
+#[utoipa::path(
+    tag="Sanger Traces",
+    responses(
+        (status = 200, description = "TraceAlignmentReport", body = TraceAlignmentReport),
+    ),
+    params(
+        ("TraceAlign" = TraceAlign, description = "ABIF or SCF traces and the reference DNA String they are aligned to"),
+    )
+)]
+#[post("/traces/align")]
+async fn traces_align(form: Json<TraceAlign>) -> Either<Json<TraceAlignmentReport>, Json<Error>> {
+    let reference = match faidx::sequence(form.reference.to_owned()).await {
+        Ok(reference) => reference,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let alignments = form
+        .traces
+        .iter()
+        .map(|source| traces::load_trace(&source.path))
+        .collect::<Result<Vec<_>, String>>()
+        .and_then(|loaded| {
+            traces::align_traces(
+                &loaded,
+                reference,
+                form.topology.to_owned(),
+                form.min_quality,
+            )
+        });
+    match alignments {
+        Ok(alignments) => Either::Left(Json(TraceAlignmentReport {
+            alignments: alignments
+                .into_iter()
+                .map(|a| TraceAlignmentInfo {
+                    name: a.name,
+                    reverse: a.reverse,
+                    score: a.score,
+                    start: a.start,
+                    end: a.end,
+                    read_start: a.read_start,
+                    read_end: a.read_end,
+                    identity: a.identity,
+                    cigar: a.cigar,
+                    discrepancies: a
+                        .discrepancies
+                        .into_iter()
+                        .map(|d| TraceDiscrepancyInfo {
+                            position: d.position,
+                            reference: d.reference.to_string(),
+                            call: d.call.to_string(),
+                            index: d.index,
+                            quality: d.quality,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
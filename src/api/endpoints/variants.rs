@@ -0,0 +1,296 @@
+use actix_web::{
+    post,
+    web::{self, Either, Json},
+};
+use plasmid::prelude::SequenceVariant;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::core::coverage::utils::CoverageSource;
+use crate::core::faidx::utils::{self as faidx, SequenceInput};
+use crate::core::schema::Error;
+use crate::core::variants::utils::{self as variants, VariantCalls};
+
+#[derive(Deserialize, ToSchema)]
+pub struct VariantDiff {
+    /// DNA String or RegionReference
+    #[schema()]
+    reference: SequenceInput,
+    /// DNA String or RegionReference
+    #[schema()]
+    sample: SequenceInput,
+    /// Name of the reference in the VCF output, defaults to `reference`
+    #[schema()]
+    name: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct VariantCall {
+    /// FASTA reference the variants are called against
+    #[schema()]
+    reference: String,
+    /// Alignments in SAM format, with an `@SQ` header line for each reference
+    #[schema()]
+    sam: Option<String>,
+    /// Path to a SAM file on the server, if no alignments are given
+    #[schema(example = "samples/SRR0000001.sam")]
+    sam_path: Option<String>,
+    /// FASTQ reads, mapped to the reference with the read mapper
+    #[schema()]
+    reads: Option<String>,
+    /// Path to a FASTQ file on the server, if no reads are given
+    #[schema(example = "samples/SRR0000001.fastq")]
+    reads_path: Option<String>,
+    /// Sequences or RegionReferences aligned to the first reference record with Needleman–Wunsch
+    #[schema()]
+    sequences: Option<Vec<SequenceInput>>,
+    /// Fewest reads spanning a position to call variants at it, defaults to 5
+    #[schema()]
+    min_depth: Option<u32>,
+    /// Smallest fraction of reads supporting an allele, defaults to 0.2
+    #[schema()]
+    min_frequency: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VariantInfo {
+    #[schema()]
+    chrom: String,
+    /// 1-based position of the reference allele, as in VCF
+    #[schema()]
+    position: usize,
+    #[schema()]
+    reference: String,
+    #[schema()]
+    alternate: String,
+    /// `snv`, `ins` or `del`
+    #[schema()]
+    kind: String,
+    /// Reads spanning the variant
+    #[schema()]
+    depth: u32,
+    /// Reads supporting the alternate allele
+    #[schema()]
+    count: u32,
+    #[schema()]
+    frequency: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VariantReport {
+    #[schema()]
+    variants: Vec<VariantInfo>,
+    /// Variants in VCF 4.3 format
+    #[schema()]
+    vcf: String,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct VariantAlleles {
+    /// 1-based position of the reference allele, as in VCF
+    #[schema()]
+    position: usize,
+    #[schema()]
+    reference: String,
+    #[schema()]
+    alternate: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct VariantEffects {
+    /// DNA sequence or RegionReference, annotated with common features to find its CDS
+    #[schema()]
+    dna: Option<SequenceInput>,
+    /// `linear` (default) or `circular`
+    #[schema()]
+    topology: Option<String>,
+    /// Editor session whose sequence and CDS annotations are used instead of `dna`
+    #[schema()]
+    session: Option<u64>,
+    #[schema()]
+    variants: Option<Vec<VariantAlleles>>,
+    /// Sample sequence or RegionReference whose differences to the sequence are annotated as well
+    #[schema()]
+    sample: Option<SequenceInput>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CodingEffectInfo {
+    /// Name of the CDS
+    #[schema()]
+    feature: String,
+    /// `synonymous`, `missense`, `nonsense`, `frameshift`, `inframe_insertion`,
+    /// `inframe_deletion`, `start_lost`, `stop_lost` or `complex`
+    #[schema()]
+    effect: String,
+    /// HGVS coding DNA notation, e.g. `c.35G>A`
+    #[schema()]
+    hgvs_c: String,
+    /// HGVS protein notation, e.g. `p.Gly12Asp`
+    #[schema()]
+    hgvs_p: String,
+    #[schema()]
+    reference_codons: String,
+    #[schema()]
+    alternate_codons: String,
+    #[schema()]
+    reference_amino_acids: String,
+    #[schema()]
+    alternate_amino_acids: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VariantEffectInfo {
+    #[schema()]
+    variant: VariantAlleles,
+    /// Effects on every CDS the variant changes, empty if it is non-coding
+    #[schema()]
+    effects: Vec<CodingEffectInfo>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VariantEffectReport {
+    #[schema()]
+    variants: Vec<VariantEffectInfo>,
+}
+
+fn variant_response(
+    calls: Result<VariantCalls, String>,
+) -> Either<Json<VariantReport>, Json<Error>> {
+    match calls {
+        Ok(calls) => Either::Left(Json(VariantReport {
+            variants: calls
+                .variants
+                .into_iter()
+                .map(|v| VariantInfo {
+                    kind: v.kind.to_string(),
+                    chrom: v.chrom,
+                    position: v.position + 1,
+                    reference: v.reference,
+                    alternate: v.alternate,
+                    depth: v.depth,
+                    count: v.count,
+                    frequency: v.frequency,
+                })
+                .collect(),
+            vcf: calls.vcf,
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
+
+#[utoipa::path(
+    tag="Variant Calling",
+    responses(
+        (status = 200, description = "VariantReport", body = VariantReport),
+    ),
+    params(
+        ("VariantDiff" = VariantDiff, description = "Reference and sample DNA Strings"),
+    )
+)]
+#[post("/variants/diff")]
+async fn variants_diff(form: Json<VariantDiff>) -> Either<Json<VariantReport>, Json<Error>> {
+    let reference = match faidx::sequence(form.reference.to_owned()).await {
+        Ok(reference) => reference,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let sample = match faidx::sequence(form.sample.to_owned()).await {
+        Ok(sample) => sample,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    variant_response(variants::diff_sequences(
+        form.name.as_deref().unwrap_or("reference"),
+        &reference,
+        &sample,
+    ))
+}
+
+#[utoipa::path(
+    tag="Variant Calling",
+    responses(
+        (status = 200, description = "VariantReport", body = VariantReport),
+    ),
+    params(
+        ("VariantCall" = VariantCall, description = "FASTA reference, SAM alignments, reads or sequences and calling thresholds"),
+    )
+)]
+#[post("/variants/call")]
+async fn variants_call(form: Json<VariantCall>) -> Either<Json<VariantReport>, Json<Error>> {
+    let sequences = match faidx::optional_sequences(form.sequences.to_owned()).await {
+        Ok(sequences) => sequences,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let source = CoverageSource {
+        sam: form.sam.to_owned(),
+        sam_path: form.sam_path.to_owned(),
+        reference: Some(form.reference.to_owned()),
+        reads: form.reads.to_owned(),
+        reads_path: form.reads_path.to_owned(),
+        sequences,
+    };
+    let (min_depth, min_frequency) = (form.min_depth, form.min_frequency);
+    let calls = web::block(move || variants::call_variants(&source, min_depth, min_frequency))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|calls| calls);
+    variant_response(calls)
+}
+
+#[utoipa::path(
+    tag="Variant Calling",
+    responses(
+        (status = 200, description = "VariantEffectReport", body = VariantEffectReport),
+    ),
+    params(
+        ("VariantEffects" = VariantEffects, description = "DNA String or editor session, and variants or a sample sequence"),
+    )
+)]
+#[post("/variants/effects")]
+async fn variants_effects(
+    form: Json<VariantEffects>,
+) -> Either<Json<VariantEffectReport>, Json<Error>> {
+    let dna = match faidx::optional_sequence(form.dna.to_owned()).await {
+        Ok(dna) => dna,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let sample = match faidx::optional_sequence(form.sample.to_owned()).await {
+        Ok(sample) => sample,
+        Err(error) => return Either::Right(Json(error)),
+    };
+    let alleles = form
+        .variants
+        .iter()
+        .flatten()
+        .map(|v| SequenceVariant::new(v.position.max(1) - 1, &v.reference, &v.alternate))
+        .collect();
+    match variants::annotate_variants(dna, form.topology.to_owned(), form.session, alleles, sample)
+    {
+        Ok(annotated) => Either::Left(Json(VariantEffectReport {
+            variants: annotated
+                .into_iter()
+                .map(|annotated| VariantEffectInfo {
+                    variant: VariantAlleles {
+                        position: annotated.variant.position + 1,
+                        reference: annotated.variant.reference,
+                        alternate: annotated.variant.alternate,
+                    },
+                    effects: annotated
+                        .effects
+                        .into_iter()
+                        .map(|e| CodingEffectInfo {
+                            feature: e.feature,
+                            effect: e.effect.to_string(),
+                            hgvs_c: e.hgvs_c,
+                            hgvs_p: e.hgvs_p,
+                            reference_codons: e.reference_codons,
+                            alternate_codons: e.alternate_codons,
+                            reference_amino_acids: e.reference_amino_acids,
+                            alternate_amino_acids: e.alternate_amino_acids,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        })),
+        Err(error) => Either::Right(Json(Error { error })),
+    }
+}
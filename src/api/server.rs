@@ -1,23 +1,51 @@
-use actix_web::{get, App, HttpServer, Responder};
+use actix_web::{get, web, App, HttpServer, Responder};
 use actix_cors::Cors;
 use actix_files as fs;
 
 const PORT: u16 = 1337;
 
+/// Largest file accepted by upload endpoints, and largest JSON request body
+const UPLOAD_LIMIT: usize = 64 * 1024 * 1024;
+
 use utoipa_swagger_ui::SwaggerUi;
 
 use utoipa::OpenApi;
 
 /// All endpoints
 use super::endpoints::{
+    annotations::{annotations_export, annotations_features},
+    cloning::{
+        cloning_digest, cloning_digest_gel_png, cloning_digest_gel_svg, cloning_gibson,
+        cloning_golden_gate, cloning_ligate,
+    },
+    coverage::{coverage_bedgraph, coverage_pileup, coverage_png, coverage_svg},
     dna::{
         align_needleman_wunsch, align_smith_waterman, calculate_sparse_alignments,
         compute_dna_hamming_distance, compute_dna_levenshtein_distance, compute_dna_ndiffs,
-        dna_to_amino_acids, dna_to_circular_png, dna_to_circular_png_bw, dna_to_circular_svg,
+        dna_common_features, dna_search_motif, dna_to_amino_acids, dna_to_circular_gc_png,
+        dna_to_circular_gc_svg, dna_to_circular_png, dna_to_circular_png_bw, dna_to_circular_svg,
+        dna_to_gc_plot_png, dna_to_gc_plot_svg, dna_to_linear_png, dna_to_linear_svg,
         dna_to_protein, kmer_substring_from,
     },
+    editor::{
+        editor_close, editor_edit, editor_extract, editor_open, editor_redo, editor_state,
+        editor_undo, editor_upload,
+    },
+    enzymes::{enzymes_list, enzymes_search},
+    faidx::{faidx_fetch, faidx_index},
     fasta::lorf_from_fasta,
-    sequence::{codon_frames, nucleotide_at_index, seq_lorf, seq_random},
+    index::{index_build, index_list, index_search},
+    mapping::mapping_map,
+    primer::{
+        primer_design, primer_design_png, primer_design_svg, primer_pair, primer_pcr,
+        primer_properties,
+    },
+    sequence::{codon_frames, nucleotide_at_index, seq_lorf, seq_random, seq_stats},
+    traces::{
+        traces_align, traces_png, traces_read, traces_svg, traces_upload, traces_upload_png,
+        traces_upload_svg,
+    },
+    variants::{variants_call, variants_diff, variants_effects},
 };
 
 #[get("/")]
@@ -30,6 +58,20 @@ pub async fn spin() -> Result<(), std::io::Error> {
     info!("DNArchery API server listening on port {}", PORT);
     info!("Browse to http://127.0.0.1:1337/ui for the UI");
 
+    // Optionally replace the built-in restriction enzymes with a REBASE file
+    match crate::core::enzymes::utils::load_configured_enzymes() {
+        Ok(Some(count)) => info!("Loaded {} restriction enzymes", count),
+        Ok(None) => (),
+        Err(error) => error!("Using built-in restriction enzymes, {}", error),
+    }
+
+    // Reload the reference indexes built before
+    match crate::core::index::utils::load_indexes() {
+        Ok(0) => (),
+        Ok(count) => info!("Loaded {} reference indexes", count),
+        Err(error) => error!("Cannot load reference indexes, {}", error),
+    }
+
     #[derive(OpenApi)]
     #[openapi(info(
         description = "A free and open-source DNA Sequencing/Visualization software for bioinformatics research. "
@@ -41,6 +83,14 @@ super::endpoints::dna::dna_to_protein,
 super::endpoints::dna::dna_to_circular_svg,
 super::endpoints::dna::dna_to_circular_png,
 super::endpoints::dna::dna_to_circular_png_bw,
+super::endpoints::dna::dna_to_circular_gc_svg,
+super::endpoints::dna::dna_to_circular_gc_png,
+super::endpoints::dna::dna_to_gc_plot_svg,
+super::endpoints::dna::dna_to_gc_plot_png,
+super::endpoints::dna::dna_to_linear_svg,
+super::endpoints::dna::dna_to_linear_png,
+super::endpoints::dna::dna_common_features,
+super::endpoints::dna::dna_search_motif,
 super::endpoints::dna::dna_to_amino_acids,
 super::endpoints::dna::kmer_substring_from,
 super::endpoints::dna::compute_dna_ndiffs,
@@ -56,10 +106,73 @@ super::endpoints::sequence::nucleotide_at_index,
 super::endpoints::sequence::codon_frames,
 super::endpoints::sequence::seq_lorf,
 super::endpoints::sequence::seq_random,
+super::endpoints::sequence::seq_stats,
+// Primer Endpoints
+super::endpoints::primer::primer_properties,
+super::endpoints::primer::primer_pair,
+super::endpoints::primer::primer_design,
+super::endpoints::primer::primer_design_svg,
+super::endpoints::primer::primer_design_png,
+super::endpoints::primer::primer_pcr,
+// Cloning Endpoints
+super::endpoints::cloning::cloning_digest,
+super::endpoints::cloning::cloning_digest_gel_svg,
+super::endpoints::cloning::cloning_digest_gel_png,
+super::endpoints::cloning::cloning_golden_gate,
+super::endpoints::cloning::cloning_gibson,
+super::endpoints::cloning::cloning_ligate,
+// Restriction Enzyme Endpoints
+super::endpoints::enzymes::enzymes_list,
+super::endpoints::enzymes::enzymes_search,
+// Sequence Editor Endpoints
+super::endpoints::editor::editor_open,
+super::endpoints::editor::editor_upload,
+super::endpoints::editor::editor_state,
+super::endpoints::editor::editor_edit,
+super::endpoints::editor::editor_undo,
+super::endpoints::editor::editor_redo,
+super::endpoints::editor::editor_extract,
+super::endpoints::editor::editor_close,
+// Reference Index Endpoints
+super::endpoints::index::index_build,
+super::endpoints::index::index_list,
+super::endpoints::index::index_search,
+// Read Mapping Endpoints
+super::endpoints::mapping::mapping_map,
+// Coverage Endpoints
+super::endpoints::coverage::coverage_pileup,
+super::endpoints::coverage::coverage_bedgraph,
+super::endpoints::coverage::coverage_svg,
+super::endpoints::coverage::coverage_png,
+// Variant Calling Endpoints
+super::endpoints::variants::variants_diff,
+super::endpoints::variants::variants_call,
+super::endpoints::variants::variants_effects,
+// Sanger Trace Endpoints
+super::endpoints::traces::traces_read,
+super::endpoints::traces::traces_upload,
+super::endpoints::traces::traces_svg,
+super::endpoints::traces::traces_png,
+super::endpoints::traces::traces_upload_svg,
+super::endpoints::traces::traces_upload_png,
+super::endpoints::traces::traces_align,
+// Annotation Endpoints
+super::endpoints::annotations::annotations_features,
+super::endpoints::annotations::annotations_export,
+// Indexed FASTA Endpoints
+super::endpoints::faidx::faidx_index,
+super::endpoints::faidx::faidx_fetch,
         ),
         components(schemas(
 // DNA Endpoints
 super::endpoints::dna::DnaString,
+super::endpoints::dna::DnaWindow,
+super::endpoints::dna::DnaFeatures,
+super::endpoints::dna::DnaFeature,
+super::endpoints::dna::DnaFeatureList,
+super::endpoints::dna::DnaSearch,
+super::endpoints::dna::DnaSearchHit,
+super::endpoints::dna::DnaSearchReport,
 super::endpoints::dna::DnaAlign,
 super::endpoints::dna::DnaNdiffs,
 super::endpoints::dna::HammingDistance,
@@ -75,6 +188,104 @@ super::endpoints::sequence::NucleotideIndex,
 super::endpoints::sequence::SingleLorf,
 super::endpoints::sequence::MultiLorf,
 super::endpoints::sequence::GenomicSequence,
+super::endpoints::sequence::SequenceWindow,
+super::endpoints::sequence::SequenceStatistics,
+super::endpoints::sequence::GcWindowStatistics,
+// Primer Endpoints
+super::endpoints::primer::ReactionConditions,
+super::endpoints::primer::Primer,
+super::endpoints::primer::PrimerPair,
+super::endpoints::primer::PrimerReport,
+super::endpoints::primer::PrimerPairReport,
+super::endpoints::primer::PrimerDesign,
+super::endpoints::primer::DesignedPrimer,
+super::endpoints::primer::DesignedPair,
+super::endpoints::primer::PrimerDesignReport,
+super::endpoints::primer::Pcr,
+super::endpoints::primer::PcrPrimerSite,
+super::endpoints::primer::PcrAmplicon,
+super::endpoints::primer::PcrReport,
+// Cloning Endpoints
+super::endpoints::cloning::Digest,
+super::endpoints::cloning::DigestEnd,
+super::endpoints::cloning::DigestFragment,
+super::endpoints::cloning::DigestReport,
+super::endpoints::cloning::AssemblyPart,
+super::endpoints::cloning::GoldenGate,
+super::endpoints::cloning::Gibson,
+super::endpoints::cloning::AssembledPart,
+super::endpoints::cloning::AssemblyJunction,
+super::endpoints::cloning::AssemblyReport,
+super::endpoints::cloning::Ligate,
+super::endpoints::cloning::LigationFeature,
+super::endpoints::cloning::LigationConstruct,
+super::endpoints::cloning::LigationReport,
+// Restriction Enzyme Endpoints
+super::endpoints::enzymes::EnzymeSearch,
+super::endpoints::enzymes::EnzymeSupplier,
+super::endpoints::enzymes::EnzymeInfo,
+super::endpoints::enzymes::EnzymeList,
+// Sequence Editor Endpoints
+super::endpoints::editor::EditorOpen,
+super::endpoints::editor::EditorSession,
+super::endpoints::editor::EditorEdit,
+super::endpoints::editor::EditorExtract,
+super::endpoints::editor::EditorSegment,
+super::endpoints::editor::EditorFeature,
+super::endpoints::editor::EditorState,
+// Reference Index Endpoints
+super::endpoints::index::IndexBuild,
+super::endpoints::index::IndexSequence,
+super::endpoints::index::IndexInfo,
+super::endpoints::index::IndexList,
+super::endpoints::index::IndexQuery,
+super::endpoints::index::IndexHit,
+super::endpoints::index::IndexSearchReport,
+// Read Mapping Endpoints
+super::endpoints::mapping::ReadMapping,
+super::endpoints::mapping::ReadMappingStats,
+super::endpoints::mapping::ReadMappingReport,
+// Coverage Endpoints
+super::endpoints::coverage::CoverageInput,
+super::endpoints::coverage::PileupPosition,
+super::endpoints::coverage::PileupReference,
+super::endpoints::coverage::PileupReport,
+// Variant Calling Endpoints
+super::endpoints::variants::VariantDiff,
+super::endpoints::variants::VariantCall,
+super::endpoints::variants::VariantInfo,
+super::endpoints::variants::VariantReport,
+super::endpoints::variants::VariantAlleles,
+super::endpoints::variants::VariantEffects,
+super::endpoints::variants::CodingEffectInfo,
+super::endpoints::variants::VariantEffectInfo,
+super::endpoints::variants::VariantEffectReport,
+// Sanger Trace Endpoints
+super::endpoints::traces::TraceSource,
+super::endpoints::traces::TraceInput,
+super::endpoints::traces::TraceView,
+super::endpoints::traces::TraceAlign,
+super::endpoints::traces::TraceInfo,
+super::endpoints::traces::TraceDiscrepancyInfo,
+super::endpoints::traces::TraceAlignmentInfo,
+super::endpoints::traces::TraceAlignmentReport,
+// Annotation Endpoints
+super::endpoints::annotations::AnnotationQuery,
+super::endpoints::annotations::AnnotationExport,
+super::endpoints::annotations::FeatureSegment,
+super::endpoints::annotations::FeatureQualifier,
+super::endpoints::annotations::FeatureInfo,
+super::endpoints::annotations::AnnotatedSequenceInfo,
+super::endpoints::annotations::AnnotationReport,
+// Indexed FASTA Endpoints
+super::endpoints::faidx::FaidxBuild,
+super::endpoints::faidx::FaidxSequence,
+super::endpoints::faidx::FaidxInfo,
+super::endpoints::faidx::FaidxFetch,
+super::endpoints::faidx::FaidxRegion,
+super::endpoints::faidx::FaidxFetchReport,
+crate::core::faidx::utils::RegionReference,
+crate::core::faidx::utils::SequenceInput,
         ))
     )]
     struct ApiDoc;
@@ -86,15 +297,70 @@ super::endpoints::sequence::GenomicSequence,
             .wrap(
                 Cors::permissive()
             )
+            .app_data(web::PayloadConfig::new(UPLOAD_LIMIT))
+            .app_data(web::JsonConfig::default().limit(UPLOAD_LIMIT))
             .service(index)
             .service(lorf_from_fasta)
             .service(nucleotide_at_index)
             .service(codon_frames)
             .service(seq_lorf)
             .service(seq_random)
+            .service(seq_stats)
+            .service(primer_properties)
+            .service(primer_pair)
+            .service(primer_design)
+            .service(primer_design_svg)
+            .service(primer_design_png)
+            .service(primer_pcr)
+            .service(cloning_digest)
+            .service(cloning_digest_gel_svg)
+            .service(cloning_digest_gel_png)
+            .service(cloning_golden_gate)
+            .service(cloning_gibson)
+            .service(cloning_ligate)
+            .service(enzymes_list)
+            .service(enzymes_search)
+            .service(editor_open)
+            .service(editor_upload)
+            .service(editor_state)
+            .service(editor_edit)
+            .service(editor_undo)
+            .service(editor_redo)
+            .service(editor_extract)
+            .service(editor_close)
+            .service(index_build)
+            .service(index_list)
+            .service(index_search)
+            .service(mapping_map)
+            .service(coverage_pileup)
+            .service(coverage_bedgraph)
+            .service(coverage_svg)
+            .service(coverage_png)
+            .service(variants_diff)
+            .service(variants_call)
+            .service(variants_effects)
+            .service(traces_read)
+            .service(traces_upload)
+            .service(traces_svg)
+            .service(traces_png)
+            .service(traces_upload_svg)
+            .service(traces_upload_png)
+            .service(traces_align)
+            .service(annotations_features)
+            .service(annotations_export)
+            .service(faidx_index)
+            .service(faidx_fetch)
             .service(dna_to_circular_svg)
             .service(dna_to_circular_png)
             .service(dna_to_circular_png_bw)
+            .service(dna_to_circular_gc_svg)
+            .service(dna_to_circular_gc_png)
+            .service(dna_to_gc_plot_svg)
+            .service(dna_to_gc_plot_png)
+            .service(dna_to_linear_svg)
+            .service(dna_to_linear_png)
+            .service(dna_common_features)
+            .service(dna_search_motif)
             .service(dna_to_amino_acids)
             .service(dna_to_protein)
             .service(kmer_substring_from)
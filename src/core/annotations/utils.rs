@@ -0,0 +1,215 @@
+use plasmid::prelude::{
+    half_open_to_one_based, inclusive_to_half_open, one_based_to_half_open, read_text_file,
+    Annotation, BedFile, Export, FastaRecord, Gff3File, Import, MultiFastaFile, SequenceFeatures,
+};
+
+use crate::core::cloning::utils::parse_topology;
+
+/// Formats of feature files
+#[derive(Clone, Copy, PartialEq)]
+pub enum FeatureFormat {
+    /// GFF3, 1-based closed coordinates
+    Gff3,
+    /// BED, 0-based half-open coordinates
+    Bed,
+}
+
+/// A sequence with the features attached to it
+pub struct AnnotatedSequence {
+    pub seqid: String,
+    pub length: usize,
+    pub annotations: Vec<Annotation>,
+}
+
+/// Sequences of a FASTA file with the features of a GFF3 or BED file
+pub struct AnnotationSet {
+    pub sequences: Vec<AnnotatedSequence>,
+    /// Seqids with features but no sequence
+    pub unmatched: Vec<String>,
+}
+
+/// Where the sequences and features of an annotation set come from
+pub struct AnnotationSource {
+    /// FASTA contents, optional if the GFF3 file has a `##FASTA` section
+    pub fasta: Option<String>,
+    pub fasta_path: Option<String>,
+    /// GFF3 or BED contents
+    pub features: Option<String>,
+    pub features_path: Option<String>,
+    /// `gff3` or `bed`, guessed from the extension of `features_path` by default
+    pub format: Option<String>,
+    /// `linear` (default) or `circular`, for all sequences
+    pub topology: Option<String>,
+}
+
+/// Parse a feature file format, by default `bed` for `.bed` or `.bed.gz` files and `gff3` otherwise
+pub fn parse_format(format: Option<&str>, path: Option<&str>) -> Result<FeatureFormat, String> {
+    match format.map(|f| f.to_ascii_lowercase()).as_deref() {
+        Some("gff3") | Some("gff") => Ok(FeatureFormat::Gff3),
+        Some("bed") => Ok(FeatureFormat::Bed),
+        Some(other) => Err(format!(
+            "unknown feature format '{other}', expected gff3 or bed"
+        )),
+        None => match path.map(|p| p.to_ascii_lowercase()) {
+            Some(path) if path.trim_end_matches(".gz").ends_with(".bed") => Ok(FeatureFormat::Bed),
+            _ => Ok(FeatureFormat::Gff3),
+        },
+    }
+}
+
+/// Contents given in a request, or read from a file on the server
+fn read_contents(
+    contents: Option<String>,
+    path: Option<String>,
+    what: &str,
+) -> Result<Option<String>, String> {
+    match (contents, path) {
+        (Some(contents), _) => Ok(Some(contents)),
+        (None, Some(path)) => read_text_file(&path)
+            .map(Some)
+            .map_err(|e| format!("cannot read {what} {path}: {e}")),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Attach features to the FASTA records with their seqid
+fn attach_features<F>(
+    features: &F,
+    records: &[FastaRecord],
+    circular: bool,
+) -> Result<AnnotationSet, String>
+where
+    F: SequenceFeatures,
+{
+    let sequences = records
+        .iter()
+        .map(|record| {
+            let length = record.sequence.len();
+            let annotations = features
+                .annotations(record.id(), length, circular)
+                .map_err(|e| e.to_string())?;
+            Ok(AnnotatedSequence {
+                seqid: record.id().to_string(),
+                length,
+                annotations,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let unmatched = features
+        .seqids()
+        .into_iter()
+        .filter(|seqid| !records.iter().any(|record| record.id() == *seqid))
+        .map(|seqid| seqid.to_string())
+        .collect();
+    Ok(AnnotationSet {
+        sequences,
+        unmatched,
+    })
+}
+
+impl AnnotationSource {
+    /// Load the sequences and attach their features by seqid
+    pub fn load(self) -> Result<AnnotationSet, String> {
+        let format = parse_format(self.format.as_deref(), self.features_path.as_deref())?;
+        let circular = parse_topology(self.topology.as_deref())?.is_circular();
+        let features = read_contents(self.features, self.features_path, "features")?
+            .ok_or_else(|| "either features or features_path is required".to_string())?;
+        let fasta = read_contents(self.fasta, self.fasta_path, "sequences")?
+            .map(|fasta| MultiFastaFile::import(fasta).map_err(|e| e.to_string()))
+            .transpose()?;
+
+        match format {
+            FeatureFormat::Gff3 => {
+                let mut gff = Gff3File::import(features).map_err(|e| e.to_string())?;
+                let records = match fasta {
+                    Some(fasta) => fasta.records,
+                    None => std::mem::take(&mut gff.sequences),
+                };
+                if records.is_empty() {
+                    return Err(
+                        "no sequences found, either fasta or a ##FASTA section is required"
+                            .to_string(),
+                    );
+                }
+                attach_features(&gff, &records, circular)
+            }
+            FeatureFormat::Bed => {
+                let bed = BedFile::import(features).map_err(|e| e.to_string())?;
+                let records = fasta
+                    .map(|fasta| fasta.records)
+                    .filter(|records| !records.is_empty())
+                    .ok_or_else(|| "no sequences found, fasta is required".to_string())?;
+                attach_features(&bed, &records, circular)
+            }
+        }
+    }
+}
+
+/// Bounds of inclusive annotation positions, 1-based closed or 0-based half-open.
+/// Across the origin the end is past the length of the sequence.
+pub fn feature_bounds(start: usize, end: usize, length: usize, zero_based: bool) -> (usize, usize) {
+    let range = inclusive_to_half_open(start, end, length);
+    match zero_based {
+        true => (range.start, range.end),
+        false => half_open_to_one_based(range),
+    }
+}
+
+/// Features overlapping a region of a sequence, or of all sequences if no seqid is given.
+///
+/// The region is 1-based closed, or 0-based half-open if `zero_based`, and defaults to the
+/// whole sequence.
+pub fn query_features<'a>(
+    set: &'a AnnotationSet,
+    seqid: Option<&str>,
+    start: Option<usize>,
+    end: Option<usize>,
+    zero_based: bool,
+) -> Result<Vec<(&'a AnnotatedSequence, Vec<&'a Annotation>)>, String> {
+    if let Some(seqid) = seqid {
+        if !set.sequences.iter().any(|sequence| sequence.seqid == seqid) {
+            return Err(format!("sequence {seqid} not found"));
+        }
+    }
+    set.sequences
+        .iter()
+        .filter(|sequence| seqid.is_none() || seqid == Some(sequence.seqid.as_str()))
+        .map(|sequence| {
+            let region = match zero_based {
+                true => start.unwrap_or(0)..end.unwrap_or(sequence.length),
+                false => one_based_to_half_open(start.unwrap_or(1), end.unwrap_or(sequence.length))
+                    .map_err(|e| e.to_string())?,
+            };
+            let region = region.start..region.end.min(sequence.length);
+            if region.is_empty() {
+                return Ok((sequence, Vec::new()));
+            }
+            let features = sequence
+                .annotations
+                .iter()
+                .filter(|annotation| annotation.overlaps(region.start, region.end - 1))
+                .collect();
+            Ok((sequence, features))
+        })
+        .collect()
+}
+
+/// Write the features of all sequences as GFF3 or BED
+pub fn export_features(set: &AnnotationSet, format: FeatureFormat) -> String {
+    match format {
+        FeatureFormat::Gff3 => {
+            let mut gff = Gff3File::default();
+            for sequence in set.sequences.iter() {
+                gff.add_annotations(&sequence.seqid, sequence.length, &sequence.annotations);
+            }
+            gff.export()
+        }
+        FeatureFormat::Bed => {
+            let mut bed = BedFile::default();
+            for sequence in set.sequences.iter() {
+                bed.add_annotations(&sequence.seqid, sequence.length, &sequence.annotations);
+            }
+            bed.export()
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use plasmid::prelude::{
+    digest as digest_sequence, gibson as gibson_assembly, golden_gate as golden_gate_assembly,
+    restriction_ligation, Assembly, Export, Fragment, GelExport, GelLadder, GelLane, Ligation,
+    Topology,
+};
+use plasmid::seq::DnaSequence;
+
+use actix_web::web::Bytes; // for SVG byte object
+
+use crate::core::dna::utils::render_svg_to_png;
+use crate::core::enzymes::utils::find_enzyme;
+
+/// Parse a topology name, defaulting to linear
+pub fn parse_topology(topology: Option<&str>) -> Result<Topology, String> {
+    match topology.map(|t| t.to_lowercase()).as_deref() {
+        None | Some("linear") => Ok(Topology::Linear),
+        Some("circular") => Ok(Topology::Circular),
+        Some(other) => Err(format!(
+            "unknown topology '{other}', expected one of linear, circular"
+        )),
+    }
+}
+
+/// Parse a gel ladder name, defaulting to the 1 kb ladder
+pub fn parse_ladder(ladder: Option<&str>) -> Result<GelLadder, String> {
+    match ladder.map(|l| l.to_lowercase().replace([' ', '_'], "")).as_deref() {
+        None | Some("1kb") => Ok(GelLadder::OneKb),
+        Some("1kbplus") => Ok(GelLadder::OneKbPlus),
+        Some("100bp") => Ok(GelLadder::HundredBp),
+        Some(other) => Err(format!(
+            "unknown ladder '{other}', expected one of 1kb, 1kb_plus, 100bp"
+        )),
+    }
+}
+
+/// Digest a DNA sequence with restriction enzymes given by name,
+/// looked up in the configured enzyme database
+pub fn digest(
+    seq: String,
+    topology: Option<String>,
+    enzymes: &[String],
+) -> Result<Vec<Fragment>, String> {
+    let topology = parse_topology(topology.as_deref())?;
+    let seq = DnaSequence::from_str(seq.trim()).map_err(|e| e.to_string())?;
+    let enzymes = enzymes
+        .iter()
+        .map(|name| find_enzyme(name))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(digest_sequence(seq.as_nucleotides(), topology, &enzymes))
+}
+
+/// Simulate an agarose gel of a restriction digest next to a size ladder, in SVG format
+pub fn digest_gel_svg(
+    seq: String,
+    topology: Option<String>,
+    enzymes: &[String],
+    ladder: Option<String>,
+) -> Result<Bytes, String> {
+    let ladder = parse_ladder(ladder.as_deref())?;
+    let fragments = digest(seq, topology, enzymes)?;
+
+    let sizes = fragments.iter().map(|f| f.len()).collect::<Vec<_>>();
+    let label = if enzymes.is_empty() {
+        "Uncut".to_string()
+    } else {
+        enzymes.join(" + ")
+    };
+    let gel = GelExport::new(ladder, vec![GelLane::new(label, &sizes)]);
+
+    Ok(Bytes::from(gel.export()))
+}
+
+/// Simulate an agarose gel of a restriction digest next to a size ladder, in PNG format
+pub fn digest_gel_png(
+    seq: String,
+    topology: Option<String>,
+    enzymes: &[String],
+    ladder: Option<String>,
+) -> Result<Bytes, String> {
+    digest_gel_svg(seq, topology, enzymes, ladder).map(|svg| render_svg_to_png(&svg))
+}
+
+/// Default minimum overlap of Gibson assembly parts in bp
+pub const DEFAULT_GIBSON_OVERLAP: usize = 20;
+
+/// Parse assembly parts given as DNA strings with an optional topology each
+fn parse_parts(parts: &[(String, Option<String>)]) -> Result<Vec<DnaSequence>, String> {
+    parts
+        .iter()
+        .map(|(dna, topology)| {
+            let mut seq = DnaSequence::from_str(dna.trim()).map_err(|e| e.to_string())?;
+            seq.set_topology(parse_topology(topology.as_deref())?);
+            Ok(seq)
+        })
+        .collect()
+}
+
+/// Simulate a Golden Gate assembly of parts with a Type IIS enzyme given by name
+pub fn golden_gate(parts: &[(String, Option<String>)], enzyme: String) -> Result<Assembly, String> {
+    let parts = parse_parts(parts)?;
+    let enzyme = find_enzyme(&enzyme)?;
+
+    golden_gate_assembly(&parts, enzyme).map_err(|e| e.to_string())
+}
+
+/// Simulate a Gibson/HiFi assembly of linear parts by their terminal overlaps
+pub fn gibson(parts: &[String], min_overlap: Option<usize>) -> Result<Assembly, String> {
+    let parts = parts
+        .iter()
+        .map(|dna| (dna.to_owned(), None))
+        .collect::<Vec<_>>();
+    let parts = parse_parts(&parts)?;
+
+    gibson_assembly(&parts, min_overlap.unwrap_or(DEFAULT_GIBSON_OVERLAP))
+        .map_err(|e| e.to_string())
+}
+
+/// Digest a vector and an insert with restriction enzymes given by name and ligate
+/// the insert into the vector backbone in every orientation with compatible ends.
+/// The vector defaults to circular and the insert to linear, the insert is cut with
+/// the vector enzymes unless its own are given
+pub fn ligate(
+    vector: String,
+    vector_topology: Option<String>,
+    vector_enzymes: &[String],
+    insert: String,
+    insert_topology: Option<String>,
+    insert_enzymes: Option<&[String]>,
+) -> Result<Ligation, String> {
+    let mut vector = DnaSequence::from_str(vector.trim()).map_err(|e| e.to_string())?;
+    vector.set_topology(match vector_topology {
+        Some(topology) => parse_topology(Some(&topology))?,
+        None => Topology::Circular,
+    });
+    let mut insert = DnaSequence::from_str(insert.trim()).map_err(|e| e.to_string())?;
+    insert.set_topology(parse_topology(insert_topology.as_deref())?);
+
+    let find_all = |names: &[String]| {
+        names
+            .iter()
+            .map(|name| find_enzyme(name))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let vector_enzymes = find_all(vector_enzymes)?;
+    let insert_enzymes = match insert_enzymes {
+        Some(names) => find_all(names)?,
+        None => vector_enzymes.clone(),
+    };
+
+    restriction_ligation(&vector, &vector_enzymes, &insert, &insert_enzymes)
+        .map_err(|e| e.to_string())
+}
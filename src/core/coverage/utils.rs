@@ -0,0 +1,111 @@
+use actix_web::web::Bytes;
+
+use plasmid::ana::Pileups;
+use plasmid::prelude::{read_text_file, CoverageTrackExport, Export, Import, MultiFastaFile};
+
+use crate::core::dna::algos::align_needleman_wunsch;
+use crate::core::dna::utils::render_svg_to_png;
+use crate::core::mapping::utils::map_reads;
+
+/// Where the aligned reads of a pileup come from
+pub struct CoverageSource {
+    /// Alignments in SAM format, with an `@SQ` header line for each reference
+    pub sam: Option<String>,
+    /// Path to a SAM file on the server, if no alignments are given
+    pub sam_path: Option<String>,
+    /// FASTA reference for reads or sequences
+    pub reference: Option<String>,
+    /// FASTQ reads mapped to the reference
+    pub reads: Option<String>,
+    /// Path to a FASTQ file on the server, if no reads are given
+    pub reads_path: Option<String>,
+    /// Sequences aligned to the first reference record with Needleman–Wunsch
+    pub sequences: Option<Vec<String>>,
+}
+
+/// Names and upper case sequences of the records of a FASTA reference
+pub fn parse_reference(fasta: &str) -> Result<Vec<(String, String)>, String> {
+    let fasta = MultiFastaFile::import(fasta).map_err(|e| e.to_string())?;
+    Ok(fasta
+        .records
+        .iter()
+        .map(|record| {
+            let sequence = record
+                .sequence
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>();
+            (record.id().to_string(), sequence.to_ascii_uppercase())
+        })
+        .collect())
+}
+
+impl CoverageSource {
+    /// Pile up the alignments of the source, SAM input taking precedence over reads and sequences
+    pub fn pileup(&self) -> Result<Pileups, String> {
+        if let Some(sam) = &self.sam {
+            return Pileups::from_sam(sam).map_err(|e| e.to_string());
+        }
+        if let Some(path) = &self.sam_path {
+            let sam = read_text_file(path).map_err(|e| format!("cannot read SAM {path}: {e}"))?;
+            return Pileups::from_sam(&sam).map_err(|e| e.to_string());
+        }
+        let reference = self
+            .reference
+            .to_owned()
+            .ok_or("either sam, sam_path or a reference is required")?;
+        if self.reads.is_some() || self.reads_path.is_some() {
+            let mapping = map_reads(
+                self.reads.to_owned(),
+                self.reads_path.to_owned(),
+                Some(reference),
+                None,
+                None,
+                None,
+            )?;
+            return Pileups::from_mapping(&mapping).map_err(|e| e.to_string());
+        }
+        let Some(sequences) = &self.sequences else {
+            return Err(
+                "either reads, reads_path or sequences to align to the reference are required"
+                    .to_string(),
+            );
+        };
+
+        let (name, target) = parse_reference(&reference)?
+            .into_iter()
+            .next()
+            .ok_or("no sequences found in the reference")?;
+        let mut pileups = Pileups::new([(name, target.len())]);
+        for sequence in sequences.iter() {
+            let read = sequence.trim().to_ascii_uppercase();
+            let alignment = align_needleman_wunsch(target.to_owned(), read.to_owned());
+            pileups.add_alignment(0, &alignment.steps(), read.as_bytes());
+        }
+        Ok(pileups)
+    }
+}
+
+/// Generate a coverage track of a reference record, by default the first one, in SVG format
+pub fn gen_coverage_svg(source: &CoverageSource, record: Option<String>) -> Result<Bytes, String> {
+    let pileups = source.pileup()?;
+    let pileup = match record {
+        Some(name) => pileups
+            .references
+            .iter()
+            .find(|pileup| pileup.name == name)
+            .ok_or_else(|| format!("unknown reference record '{name}'"))?,
+        None => pileups
+            .references
+            .first()
+            .ok_or("no reference records to draw")?,
+    };
+    let track = CoverageTrackExport::new(&pileup.name, &pileup.depth());
+
+    Ok(Bytes::from(track.export()))
+}
+
+/// Generate a coverage track of a reference record in PNG format
+pub fn gen_coverage_png(source: &CoverageSource, record: Option<String>) -> Result<Bytes, String> {
+    gen_coverage_svg(source, record).map(|svg| render_svg_to_png(&svg))
+}
@@ -3,6 +3,7 @@ use utoipa::ToSchema;
 
 extern crate seal;
 
+use plasmid::ana::AlignmentStep;
 use seal::pair::{
     AlignmentSet, InMemoryAlignmentMatrix, NeedlemanWunsch, SmithWaterman, Step, Strategy,
 };
@@ -19,6 +20,33 @@ pub struct DNAAlignment {
     alignment_b: String,
 }
 
+impl DNAAlignment {
+    /// Columns of the alignment, `x` indexing the first and `y` the second sequence
+    pub fn steps(&self) -> Vec<AlignmentStep> {
+        let (mut x, mut y) = (0, 0);
+        self.alignment_a
+            .chars()
+            .zip(self.alignment_b.chars())
+            .filter_map(|columns| match columns {
+                ('-', '-') => None,
+                ('-', _) => {
+                    y += 1;
+                    Some(AlignmentStep::Insert { y: y - 1 })
+                }
+                (_, '-') => {
+                    x += 1;
+                    Some(AlignmentStep::Delete { x: x - 1 })
+                }
+                _ => {
+                    x += 1;
+                    y += 1;
+                    Some(AlignmentStep::Align { x: x - 1, y: y - 1 })
+                }
+            })
+            .collect()
+    }
+}
+
 fn align_dna<S: Strategy>(dna_a: String, dna_b: String, strategy: S) -> DNAAlignment
 where
     S: Debug,
@@ -2,6 +2,8 @@
 ///
 /// Index Tables and conversions (protein) from: https://github.com/dweb0/protein-translate/blob/master/src/lib.rs
 ///
+use plasmid::ana::DEFAULT_FEATURE_IDENTITY;
+use plasmid::exp::default_gc_window;
 use plasmid::prelude::*;
 use plasmid::seq::DnaSequence;
 
@@ -17,8 +19,12 @@ use nsvg;
 use resvg::usvg_text_layout::{fontdb, TreeTextToPath};
 
 use actix_web::web::Bytes; // for SVG byte object
+use std::collections::HashMap;
 use std::io::Cursor; // in-memory buffer for PNG
 
+use crate::core::cloning::utils::parse_topology;
+use crate::core::enzymes;
+
 // https://en.wikipedia.org/wiki/DNA_and_RNA_codon_tables
 static ASCII_TO_INDEX: [usize; 128] = [
     4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, // 0-15
@@ -150,23 +156,43 @@ pub fn amino_acids_from_dna(seq: String) -> Vec<String> {
 }
 
 /// Generate a circular structure of DNA sequence in SVG format.
-pub fn gen_dna_circular_svg(seq: String) -> Bytes {
-    let mut seq: DnaSequence = DnaSequence::from_str(seq).unwrap();
-
-    // Annotate restriction enzyme cut sites
-    seq.annotate_restriction_enzymes();
+pub fn gen_dna_circular_svg(seq: String) -> Result<Bytes, String> {
+    let mut seq: DnaSequence = DnaSequence::from_str(seq).map_err(|e| e.to_string())?;
+    seq.set_topology(Topology::Circular);
+
+    // Annotate common plasmid features, drawn as the feature track
+    seq.annotate_common_features(DEFAULT_FEATURE_IDENTITY);
+    let features = seq.as_annotations().to_vec();
+
+    // Annotate cut sites of the configured enzymes, including those spanning the origin,
+    // and draw the sites of enzymes cutting once
+    seq.annotate_restriction_enzymes_with(enzymes::utils::enzymes());
+    let sites = &seq.as_annotations()[features.len()..];
+    let mut cuts = HashMap::<&str, usize>::new();
+    for site in sites {
+        *cuts.entry(site.text.as_str()).or_default() += 1;
+    }
+    let unique_sites = sites
+        .iter()
+        .filter(|site| cuts[site.text.as_str()] == 1)
+        .map(|site| site.clone().with_color("hsla(0, 0%, 35%, 1)"));
+    let annotations = features
+        .iter()
+        .cloned()
+        .chain(unique_sites)
+        .collect::<Vec<_>>();
 
     // Generate SVG of circular DNA
     let conf = SvgExportConfig::circular();
-    let svg = SvgExport::new(conf, seq.as_nucleotides());
+    let svg = SvgExport::new(conf, seq.as_nucleotides()).with_annotations(&annotations);
 
-    Bytes::from(svg.export())
+    Ok(Bytes::from(svg.export()))
 }
 
 /// Generate a circular structure of DNA sequence in B/W PNG format.
 /// This essentially converts the above SVG generation to raw PNG file.
-pub fn gen_dna_circular_png_bw(seq: String) -> Bytes {
-    let svg = gen_dna_circular_svg(seq);
+pub fn gen_dna_circular_png_bw(seq: String) -> Result<Bytes, String> {
+    let svg = gen_dna_circular_svg(seq)?;
 
     // parse RAW svg as UTF8 (handle replacement chars if any)
     let svg = String::from_utf8_lossy(&svg);
@@ -190,20 +216,23 @@ pub fn gen_dna_circular_png_bw(seq: String) -> Bytes {
     )
     .expect("Failed to render png.");
 
-    Bytes::from(raw_bytes.into_inner())
+    Ok(Bytes::from(raw_bytes.into_inner()))
 }
 
 /// Generate a circular structure of DNA sequence in PNG format.
 /// This essentially converts the above SVG generation to raw PNG file.
-pub fn gen_dna_circular_png(seq: String) -> Bytes {
-    let svg = gen_dna_circular_svg(seq);
+pub fn gen_dna_circular_png(seq: String) -> Result<Bytes, String> {
+    gen_dna_circular_svg(seq).map(|svg| render_svg_to_png(&svg))
+}
 
+/// Rasterize an SVG document to a PNG file with resvg.
+pub fn render_svg_to_png(svg: &[u8]) -> Bytes {
     let opt = usvg::Options::default();
 
     let mut fontdb = fontdb::Database::new();
     fontdb.load_system_fonts();
 
-    let mut tree = usvg::Tree::from_str(&String::from_utf8_lossy(&svg), &opt).unwrap();
+    let mut tree = usvg::Tree::from_str(&String::from_utf8_lossy(svg), &opt).unwrap();
     tree.convert_text(&fontdb);
 
     let pixmap_size = tree.size.to_screen_size();
@@ -220,6 +249,113 @@ pub fn gen_dna_circular_png(seq: String) -> Bytes {
     Bytes::from(pixmap.encode_png().unwrap())
 }
 
+/// Generate a circular structure of DNA sequence in SVG format with
+/// sliding-window GC content and cumulative GC skew as inner rings.
+pub fn gen_dna_circular_gc_svg(seq: String, window: Option<usize>) -> Result<Bytes, String> {
+    let seq: DnaSequence = DnaSequence::from_str(seq).map_err(|e| e.to_string())?;
+
+    let window = window.unwrap_or_else(|| default_gc_window(seq.as_nucleotides().len()));
+    let conf = SvgExportConfig::circular().with_gc_ring(window);
+    let svg = SvgExport::new(conf, seq.as_nucleotides());
+
+    Ok(Bytes::from(svg.export()))
+}
+
+/// Detect common plasmid features, e.g. promoters, resistance genes and tags,
+/// with at least `min_identity` (above 0, at most 1) of their sequence matching.
+pub fn annotate_common_features(
+    seq: String,
+    topology: Option<String>,
+    min_identity: Option<f64>,
+) -> Result<Vec<Annotation>, String> {
+    let min_identity = min_identity.unwrap_or(DEFAULT_FEATURE_IDENTITY);
+    if !(min_identity > 0_f64 && min_identity <= 1_f64) {
+        return Err(format!(
+            "min_identity must be above 0 and at most 1, got {min_identity}"
+        ));
+    }
+    let mut seq = DnaSequence::from_str(seq.trim()).map_err(|e| e.to_string())?;
+    seq.set_topology(parse_topology(topology.as_deref())?);
+    seq.annotate_common_features(min_identity);
+
+    Ok(seq.as_annotations().to_vec())
+}
+
+/// Parse a motif in `iupac` (default), `regex` or `prosite` syntax
+pub fn parse_motif(pattern: &str, syntax: Option<&str>) -> Result<Motif, String> {
+    match syntax.map(|s| s.to_lowercase()).as_deref() {
+        None | Some("iupac") => Motif::iupac(pattern),
+        Some("regex") => Motif::regex(pattern),
+        Some("prosite") => Motif::prosite(pattern),
+        Some(other) => {
+            return Err(format!(
+                "unknown motif syntax '{other}', expected one of iupac, regex, prosite"
+            ))
+        }
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Search a motif on both strands of a DNA sequence, allowing up to `max_errors` edits
+pub fn search_motif(
+    seq: String,
+    topology: Option<String>,
+    pattern: String,
+    syntax: Option<String>,
+    max_errors: Option<usize>,
+) -> Result<Vec<MotifHit>, String> {
+    let motif = parse_motif(&pattern, syntax.as_deref())?;
+    let mut seq = DnaSequence::from_str(seq.trim()).map_err(|e| e.to_string())?;
+    seq.set_topology(parse_topology(topology.as_deref())?);
+    seq.search_motif(&motif, max_errors.unwrap_or(0))
+        .map_err(|e| e.to_string())
+}
+
+/// Generate a linear map of DNA sequence with its common features in SVG format.
+pub fn gen_dna_linear_svg(
+    seq: String,
+    topology: Option<String>,
+    min_identity: Option<f64>,
+) -> Result<Bytes, String> {
+    let features = annotate_common_features(seq.to_owned(), topology, min_identity)?;
+    let seq = DnaSequence::from_str(seq.trim()).map_err(|e| e.to_string())?;
+
+    let conf = SvgExportConfig::linear();
+    let svg = SvgExport::new(conf, seq.as_nucleotides()).with_annotations(&features);
+
+    Ok(Bytes::from(svg.export()))
+}
+
+/// Generate a linear map of DNA sequence with its common features in PNG format.
+pub fn gen_dna_linear_png(
+    seq: String,
+    topology: Option<String>,
+    min_identity: Option<f64>,
+) -> Result<Bytes, String> {
+    gen_dna_linear_svg(seq, topology, min_identity).map(|svg| render_svg_to_png(&svg))
+}
+
+/// Generate a circular structure of DNA sequence with GC rings in PNG format.
+pub fn gen_dna_circular_gc_png(seq: String, window: Option<usize>) -> Result<Bytes, String> {
+    gen_dna_circular_gc_svg(seq, window).map(|svg| render_svg_to_png(&svg))
+}
+
+/// Generate a linear chart of sliding-window GC content and cumulative GC skew in SVG format.
+/// The minimum of the cumulative skew hints at the origin of replication.
+pub fn gen_gc_plot_svg(seq: String, window: Option<usize>) -> Result<Bytes, String> {
+    let seq = crate::core::sequence::utils::parse_iupac(&seq)?;
+
+    let window = window.unwrap_or_else(|| default_gc_window(seq.len()));
+    let plot = GcPlotExport::new(window, &seq);
+
+    Ok(Bytes::from(plot.export()))
+}
+
+/// Generate a linear chart of GC content and cumulative GC skew in PNG format.
+pub fn gen_gc_plot_png(seq: String, window: Option<usize>) -> Result<Bytes, String> {
+    gen_gc_plot_svg(seq, window).map(|svg| render_svg_to_png(&svg))
+}
+
 /// Derive K-mer substring from DNA Sequence
 pub fn derive_kmer_substring_from_dna(seq: String) -> String {
     let dna_string = DnaString::from_dna_string(&seq);
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
+use plasmid::ana::DEFAULT_FEATURE_IDENTITY;
+use plasmid::prelude::{Annotation, DnaNucleotide, Import, SnapGeneFile, Topology};
+use plasmid::seq::DnaSequence;
+
+use crate::core::cloning::utils::parse_topology;
+
+/// Maximum number of open sessions, the least recently used one is closed to open another
+const MAX_SESSIONS: usize = 256;
+
+/// Sessions unused for this long are closed
+const SESSION_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Maximum number of edits that can be undone in a session
+const HISTORY_LIMIT: usize = 100;
+
+struct Session {
+    seq: DnaSequence,
+    last_used: Instant,
+}
+
+/// Sequences opened in the editor, by session id
+static SESSIONS: OnceLock<Mutex<HashMap<u64, Session>>> = OnceLock::new();
+
+static NEXT_SESSION: AtomicU64 = AtomicU64::new(1);
+
+/// Snapshot of an editor session after an action
+pub struct EditorSnapshot {
+    pub id: u64,
+    pub sequence: String,
+    pub topology: Topology,
+    pub annotations: Vec<Annotation>,
+    pub undo: usize,
+    pub redo: usize,
+}
+
+impl EditorSnapshot {
+    fn new(id: u64, seq: &DnaSequence) -> Self {
+        EditorSnapshot {
+            id,
+            sequence: seq.to_string(),
+            topology: seq.topology(),
+            annotations: seq.as_annotations().to_vec(),
+            undo: seq.history().len(),
+            redo: seq.undone().len(),
+        }
+    }
+}
+
+/// Open sessions, with the expired ones closed
+fn sessions() -> Result<MutexGuard<'static, HashMap<u64, Session>>, String> {
+    let mut sessions = SESSIONS
+        .get_or_init(Default::default)
+        .lock()
+        .map_err(|_| "editor sessions are unavailable".to_string())?;
+    sessions.retain(|_, session| session.last_used.elapsed() < SESSION_TTL);
+    Ok(sessions)
+}
+
+/// Run `f` on the sequence of an open session
+fn with_session<T, F>(id: u64, f: F) -> Result<T, String>
+where
+    F: FnOnce(&mut DnaSequence) -> Result<T, String>,
+{
+    let mut sessions = sessions()?;
+    let session = sessions
+        .get_mut(&id)
+        .ok_or_else(|| format!("unknown editor session {id}"))?;
+    session.last_used = Instant::now();
+    f(&mut session.seq)
+}
+
+fn insert_session(mut seq: DnaSequence) -> Result<EditorSnapshot, String> {
+    seq.set_history_limit(Some(HISTORY_LIMIT));
+    let mut sessions = sessions()?;
+    if sessions.len() >= MAX_SESSIONS {
+        let oldest = sessions
+            .iter()
+            .min_by_key(|(_, session)| session.last_used)
+            .map(|(id, _)| *id);
+        if let Some(oldest) = oldest {
+            sessions.remove(&oldest);
+        }
+    }
+    let id = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
+    let snapshot = EditorSnapshot::new(id, &seq);
+    let last_used = Instant::now();
+    sessions.insert(id, Session { seq, last_used });
+    Ok(snapshot)
+}
+
+fn parse_bases(bases: Option<&str>) -> Result<Vec<DnaNucleotide>, String> {
+    let seq = DnaSequence::from_str(bases.unwrap_or_default().trim()).map_err(|e| e.to_string())?;
+    Ok(seq.as_nucleotides().to_vec())
+}
+
+/// Open a sequence in a new editor session, optionally annotated with common features
+pub fn open_session(
+    seq: String,
+    topology: Option<String>,
+    annotate: Option<bool>,
+) -> Result<EditorSnapshot, String> {
+    let mut seq = DnaSequence::from_str(seq.trim()).map_err(|e| e.to_string())?;
+    seq.set_topology(parse_topology(topology.as_deref())?);
+    if annotate.unwrap_or(true) {
+        seq.annotate_common_features(DEFAULT_FEATURE_IDENTITY);
+    }
+    insert_session(seq)
+}
+
+/// Open an uploaded SnapGene `.dna` file in a new editor session, with its features and primers
+pub fn open_snapgene(contents: &[u8]) -> Result<EditorSnapshot, String> {
+    let seq =
+        SnapGeneFile::import_bytes(contents).map_err(|e| format!("invalid SnapGene file: {e}"))?;
+    insert_session(seq)
+}
+
+/// Apply an `insert`, `delete` or `replace` operation to the sequence of a session.
+///
+/// Insertions go before `start`, deletions and replacements cover `start` up to the exclusive `end`.
+/// Deletions take no bases.
+pub fn edit_session(
+    id: u64,
+    operation: &str,
+    start: usize,
+    end: Option<usize>,
+    bases: Option<String>,
+) -> Result<EditorSnapshot, String> {
+    let bases = parse_bases(bases.as_deref())?;
+    let end = || end.ok_or_else(|| format!("operation '{operation}' requires an end position"));
+    let range = match operation.to_lowercase().as_str() {
+        "insert" if bases.is_empty() => return Err("nothing to insert".to_string()),
+        "insert" => start..start,
+        "delete" if !bases.is_empty() => {
+            return Err("bases cannot be given for a delete, use replace".to_string())
+        }
+        "delete" | "replace" => start..end()?,
+        other => {
+            return Err(format!(
+                "unknown operation '{other}', expected one of insert, delete, replace"
+            ))
+        }
+    };
+    with_session(id, |seq| {
+        seq.replace(range, &bases).map_err(|e| e.to_string())?;
+        Ok(EditorSnapshot::new(id, seq))
+    })
+}
+
+/// Undo the latest edit of a session
+pub fn undo_session(id: u64) -> Result<EditorSnapshot, String> {
+    with_session(id, |seq| match seq.undo() {
+        true => Ok(EditorSnapshot::new(id, seq)),
+        false => Err("nothing to undo".to_string()),
+    })
+}
+
+/// Redo the latest undone edit of a session
+pub fn redo_session(id: u64) -> Result<EditorSnapshot, String> {
+    with_session(id, |seq| match seq.redo() {
+        true => Ok(EditorSnapshot::new(id, seq)),
+        false => Err("nothing to redo".to_string()),
+    })
+}
+
+/// Current state of a session
+pub fn session(id: u64) -> Result<EditorSnapshot, String> {
+    with_session(id, |seq| Ok(EditorSnapshot::new(id, seq)))
+}
+
+/// Extract `start` up to the exclusive `end` of a session into a new session.
+/// On circular sequences, a `start` after `end` spans the origin.
+pub fn extract_session(id: u64, start: usize, end: usize) -> Result<EditorSnapshot, String> {
+    let sub = with_session(id, |seq| {
+        seq.subsequence(start, end).map_err(|e| e.to_string())
+    })?;
+    insert_session(sub)
+}
+
+/// Close a session, discarding its sequence and history
+pub fn close_session(id: u64) -> Result<(), String> {
+    sessions()?
+        .remove(&id)
+        .map(|_| ())
+        .ok_or_else(|| format!("unknown editor session {id}"))
+}
@@ -0,0 +1,139 @@
+use std::sync::OnceLock;
+
+use plasmid::prelude::{
+    read_text_file, reverse_complement, Import, IupacNucleotide, RebaseFile, RestrictionEnzymes,
+};
+use plasmid::uni::{find_restriction_enzyme_in, supplier_name, RestrictionEnzyme};
+
+use crate::core::cloning::utils::parse_topology;
+use crate::core::sequence::utils::parse_iupac;
+
+/// Environment variable pointing to a REBASE `withrefm` or `emboss_e` file
+pub const ENZYME_FILE_VAR: &str = "DNARCHERY_ENZYMES";
+
+/// Environment variable pointing to the `emboss_r` file matching an `emboss_e` file
+pub const ENZYME_REFERENCES_VAR: &str = "DNARCHERY_ENZYME_REFERENCES";
+
+static ENZYMES: OnceLock<Vec<RestrictionEnzyme>> = OnceLock::new();
+
+/// Load the configured enzyme database, replacing the built-in enzymes.
+/// Returns the number of loaded enzymes, or `None` if no file is configured.
+pub fn load_configured_enzymes() -> Result<Option<usize>, String> {
+    let Ok(path) = std::env::var(ENZYME_FILE_VAR) else {
+        return Ok(None);
+    };
+    let read = |path: &str| {
+        read_text_file(path).map_err(|e| format!("cannot read enzyme file {path}: {e}"))
+    };
+    let contents = read(&path)?;
+    let rebase = match std::env::var(ENZYME_REFERENCES_VAR) {
+        Ok(references) => RebaseFile::import_emboss(&contents, Some(&read(&references)?)),
+        Err(_) => RebaseFile::import(contents),
+    }
+    .map_err(|e| format!("invalid enzyme file {path}: {e}"))?;
+
+    let count = rebase.enzymes.len();
+    ENZYMES
+        .set(rebase.enzymes)
+        .map_err(|_| "enzyme database is already loaded".to_string())?;
+    Ok(Some(count))
+}
+
+/// All available restriction enzymes, either from the configured file or built-in
+pub fn enzymes() -> &'static [RestrictionEnzyme] {
+    ENZYMES.get().unwrap_or(&*RestrictionEnzymes)
+}
+
+/// Look up an available restriction enzyme by name
+pub fn find_enzyme(name: &str) -> Result<&'static RestrictionEnzyme, String> {
+    find_restriction_enzyme_in(enzymes(), name.trim())
+        .ok_or_else(|| format!("unknown restriction enzyme '{}'", name.trim()))
+}
+
+/// `blunt`, `5'` or `3'` for enzymes cutting both strands
+pub fn overhang_type(enzyme: &RestrictionEnzyme) -> Option<&'static str> {
+    enzyme.overhang().map(|overhang| match overhang {
+        0 => "blunt",
+        o if o > 0 => "5'",
+        _ => "3'",
+    })
+}
+
+/// Parse an overhang type filter
+fn parse_overhang_type(overhang: &str) -> Result<&'static str, String> {
+    match overhang.to_lowercase().replace(['_', ' ', '-'], "").as_str() {
+        "blunt" => Ok("blunt"),
+        "5'" | "5" | "fiveprime" => Ok("5'"),
+        "3'" | "3" | "threeprime" => Ok("3'"),
+        other => Err(format!(
+            "unknown overhang type '{other}', expected one of blunt, 5', 3'"
+        )),
+    }
+}
+
+/// Criteria to search enzymes by, all of them optional
+pub struct EnzymeFilter {
+    /// Sequence the enzyme has to recognize, on either strand
+    pub site: Option<String>,
+    /// `blunt`, `5'` or `3'`
+    pub overhang_type: Option<String>,
+    /// REBASE supplier code or part of the supplier name
+    pub supplier: Option<String>,
+    /// Sequence to count the cuts in
+    pub sequence: Option<String>,
+    pub topology: Option<String>,
+    /// Exact number of cuts in `sequence`, e.g. 1 for unique cutters
+    pub cuts: Option<usize>,
+}
+
+/// Search the available enzymes. If a sequence is given, the number of
+/// sites of every matching enzyme in it is returned as well.
+pub fn search(
+    filter: &EnzymeFilter,
+) -> Result<Vec<(&'static RestrictionEnzyme, Option<usize>)>, String> {
+    let site = filter.site.as_deref().map(parse_iupac).transpose()?;
+    let overhang = filter
+        .overhang_type
+        .as_deref()
+        .map(parse_overhang_type)
+        .transpose()?;
+    let supplier = filter.supplier.as_deref().map(|s| s.trim().to_lowercase());
+    let topology = parse_topology(filter.topology.as_deref())?;
+    let sequence = filter.sequence.as_deref().map(parse_iupac).transpose()?;
+    if filter.cuts.is_some() && sequence.is_none() {
+        return Err("counting cuts requires a sequence".to_string());
+    }
+
+    let recognizes = |enzyme: &RestrictionEnzyme, site: &[IupacNucleotide]| {
+        let enzyme_site = enzyme.site();
+        let matches = |site: &[IupacNucleotide]| {
+            site.len() == enzyme_site.len()
+                && site.iter().zip(&enzyme_site).all(|(s, e)| s.matches(e))
+        };
+        matches(site) || matches(&reverse_complement(site))
+    };
+    let supplied_by = |enzyme: &RestrictionEnzyme, supplier: &str| {
+        enzyme.suppliers.iter().any(|code| {
+            code.to_lowercase().to_string() == supplier
+                || supplier_name(*code).is_some_and(|name| name.to_lowercase().contains(supplier))
+        })
+    };
+
+    let mut results = Vec::new();
+    for enzyme in enzymes() {
+        if site.as_ref().is_some_and(|site| !recognizes(enzyme, site))
+            || overhang.is_some_and(|overhang| overhang_type(enzyme) != Some(overhang))
+            || supplier.as_ref().is_some_and(|s| !supplied_by(enzyme, s))
+        {
+            continue;
+        }
+        let cuts = sequence
+            .as_ref()
+            .map(|seq| enzyme.find_sites(seq, topology.is_circular()).len());
+        if filter.cuts.is_some_and(|n| cuts != Some(n)) {
+            continue;
+        }
+        results.push((enzyme, cuts));
+    }
+    Ok(results)
+}
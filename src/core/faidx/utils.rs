@@ -0,0 +1,176 @@
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::Path;
+
+use actix_web::web;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use plasmid::prelude::{Compression, Export, FastaIndex, GziIndex, IndexedFasta, Region};
+
+use crate::core::schema::Error;
+
+/// Region of a sequence in an indexed FASTA file on the server, sent instead of an inline sequence.
+///
+/// The region is given either as a string, e.g. `chr1:1000-2000`, or as a seqid with optional
+/// 1-based closed bounds. The file is indexed on first use.
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct RegionReference {
+    /// Path to an uncompressed or BGZF compressed FASTA file on the server
+    #[schema(example = "genomes/hg38.fa.gz")]
+    pub path: String,
+    /// Region as written by samtools, e.g. `chr1`, `chr1:1000` or `chr1:1,000-2,000`
+    #[schema(example = "chr1:1000-2000")]
+    pub region: Option<String>,
+    /// Sequence of the region, if no region string is given
+    #[schema(example = "chr1")]
+    pub seqid: Option<String>,
+    /// First position, 1-based, defaults to the start of the sequence
+    #[schema(example = 1000)]
+    pub start: Option<u64>,
+    /// Last position, 1-based and included, defaults to the end of the sequence
+    #[schema(example = 2000)]
+    pub end: Option<u64>,
+}
+
+impl RegionReference {
+    /// Region string of the reference
+    pub fn region(&self) -> Result<String, String> {
+        match (&self.region, &self.seqid) {
+            (Some(region), _) => Ok(region.to_owned()),
+            (None, Some(seqid)) => Ok(Region {
+                seqid: seqid.to_owned(),
+                start: self.start,
+                end: self.end,
+            }
+            .to_string()),
+            (None, None) => Err(format!(
+                "either region or seqid is required for {}",
+                self.path
+            )),
+        }
+    }
+
+    /// Bases of the referenced region
+    pub fn fetch(&self) -> Result<String, String> {
+        fetch_region(&self.path, &self.region()?)
+    }
+}
+
+/// A sequence given inline, e.g. `ATGC`, or as a [`RegionReference`] to an indexed FASTA file
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum SequenceInput {
+    /// Sequence given inline
+    #[schema(example = "ATGCATGC")]
+    Inline(String),
+    /// Region of an indexed FASTA file on the server
+    Region(RegionReference),
+}
+
+impl SequenceInput {
+    fn is_inline(&self) -> bool {
+        matches!(self, SequenceInput::Inline(_))
+    }
+
+    /// Bases of the sequence, read from disk for a [`RegionReference`]
+    pub fn resolve(self) -> Result<String, String> {
+        match self {
+            SequenceInput::Inline(sequence) => Ok(sequence),
+            SequenceInput::Region(reference) => reference.fetch(),
+        }
+    }
+}
+
+impl From<String> for SequenceInput {
+    fn from(sequence: String) -> Self {
+        SequenceInput::Inline(sequence)
+    }
+}
+
+/// Path of a file next to `path`, with an extension appended, e.g. `genome.fa.fai`
+fn sidecar_path(path: &str, extension: &str) -> OsString {
+    let mut sidecar = Path::new(path).as_os_str().to_owned();
+    sidecar.push(extension);
+    sidecar
+}
+
+/// Index a FASTA file, writing `<path>.fai` and, for BGZF files, `<path>.gzi` next to it.
+/// Existing indexes are rebuilt.
+pub fn index_fasta(path: &str) -> Result<(FastaIndex, Compression), String> {
+    let read_error = |e: std::io::Error| format!("cannot read {path}: {e}");
+    let mut head = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|file| file.take(28).read_to_end(&mut head))
+        .map_err(read_error)?;
+    let compression = Compression::detect(&head);
+    if compression == Compression::Gzip {
+        return Err(format!(
+            "{path} is gzip compressed and cannot be read at random, compress it with bgzip"
+        ));
+    }
+    let index =
+        FastaIndex::build_from_path(path).map_err(|e| format!("cannot index {path}: {e}"))?;
+    let write_error = |e: std::io::Error| format!("cannot write the index of {path}: {e}");
+    std::fs::write(sidecar_path(path, ".fai"), index.export()).map_err(write_error)?;
+    if compression == Compression::Bgzf {
+        let mut file = std::fs::File::open(path).map_err(read_error)?;
+        let gzi = GziIndex::build(&mut file).map_err(|e| format!("cannot index {path}: {e}"))?;
+        std::fs::write(sidecar_path(path, ".gzi"), gzi.to_bytes()).map_err(write_error)?;
+    }
+    Ok((index, compression))
+}
+
+/// Bases of a region of a FASTA file, e.g. `chr1:1000-2000`, read through its `.fai` index.
+/// The index is written first if the file has none.
+pub fn fetch_region(path: &str, region: &str) -> Result<String, String> {
+    if !Path::new(&sidecar_path(path, ".fai")).exists() {
+        index_fasta(path)?;
+    }
+    IndexedFasta::open(path)
+        .and_then(|mut fasta| fasta.fetch_region(region))
+        .map_err(|e| format!("cannot read {region} from {path}: {e}"))
+}
+
+/// Resolve sequences given inline or as [`RegionReference`]s. Regions are read on the
+/// blocking thread pool, so that FASTA files are not read on the async workers.
+pub async fn sequences(inputs: Vec<SequenceInput>) -> Result<Vec<String>, Error> {
+    let inline = inputs.iter().all(SequenceInput::is_inline);
+    let resolve_all = move || {
+        inputs
+            .into_iter()
+            .map(SequenceInput::resolve)
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let resolved = match inline {
+        true => resolve_all(),
+        false => web::block(resolve_all)
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|sequences| sequences),
+    };
+    resolved.map_err(|error| Error { error })
+}
+
+/// Resolve a sequence given inline or as a [`RegionReference`]
+pub async fn sequence(input: SequenceInput) -> Result<String, Error> {
+    Ok(sequences(vec![input]).await?.remove(0))
+}
+
+/// Resolve an optional sequence given inline or as a [`RegionReference`]
+pub async fn optional_sequence(input: Option<SequenceInput>) -> Result<Option<String>, Error> {
+    match input {
+        Some(input) => sequence(input).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Resolve optional sequences given inline or as [`RegionReference`]s
+pub async fn optional_sequences(
+    inputs: Option<Vec<SequenceInput>>,
+) -> Result<Option<Vec<String>>, Error> {
+    match inputs {
+        Some(inputs) => sequences(inputs).await.map(Some),
+        None => Ok(None),
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use plasmid::prelude::{read_text_file, ReferenceIndex};
+
+/// Environment variable pointing to the directory reference indexes are stored in
+pub const INDEX_DIR_VAR: &str = "DNARCHERY_INDEX_DIR";
+
+const DEFAULT_INDEX_DIR: &str = "indexes";
+
+const INDEX_EXTENSION: &str = "fmi";
+
+static INDEXES: OnceLock<RwLock<HashMap<String, Arc<ReferenceIndex>>>> = OnceLock::new();
+
+/// Write an index to a file
+fn save_index(index: &ReferenceIndex, path: &Path) -> Result<(), String> {
+    std::fs::File::create(path)
+        .and_then(|mut file| file.write_all(&index.to_bytes()))
+        .map_err(|e| format!("cannot write index {}: {e}", path.display()))
+}
+
+/// Read an index written by `save_index`
+fn load_index(name: &str, path: &Path) -> Result<ReferenceIndex, String> {
+    let mut data = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut data))
+        .map_err(|e| format!("cannot read index {}: {e}", path.display()))?;
+    ReferenceIndex::from_bytes(name, &data)
+        .map_err(|e| format!("invalid index file {}: {e}", path.display()))
+}
+
+/// Directory indexes are stored in, `indexes` unless configured
+pub fn index_dir() -> PathBuf {
+    std::env::var(INDEX_DIR_VAR)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_INDEX_DIR))
+}
+
+fn registry() -> &'static RwLock<HashMap<String, Arc<ReferenceIndex>>> {
+    INDEXES.get_or_init(Default::default)
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    let valid = |c: char| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.');
+    match !name.is_empty() && !name.starts_with('.') && name.chars().all(valid) {
+        true => Ok(()),
+        false => Err(format!(
+            "invalid index name '{name}', use letters, digits, '-', '_' and '.'"
+        )),
+    }
+}
+
+/// Load all indexes of the index directory, returning how many were loaded
+pub fn load_indexes() -> Result<usize, String> {
+    let dir = index_dir();
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let entries = std::fs::read_dir(&dir)
+        .map_err(|e| format!("cannot read index directory {}: {e}", dir.display()))?;
+    let mut loaded = 0;
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.extension().and_then(|e| e.to_str()) != Some(INDEX_EXTENSION) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        match load_index(name, &path) {
+            Ok(index) => {
+                registry()
+                    .write()
+                    .map_err(|_| "reference indexes are unavailable".to_string())?
+                    .insert(name.to_string(), Arc::new(index));
+                loaded += 1;
+            }
+            Err(error) => warn!("Skipping reference index, {}", error),
+        }
+    }
+    Ok(loaded)
+}
+
+/// Build an index from FASTA contents or a FASTA file, save it to the index directory
+/// and make it available for lookups, replacing an index of the same name
+pub fn build_index(
+    name: &str,
+    fasta: Option<String>,
+    path: Option<String>,
+) -> Result<Arc<ReferenceIndex>, String> {
+    check_name(name)?;
+    let fasta = match (fasta, path) {
+        (Some(fasta), _) => fasta,
+        (None, Some(path)) => {
+            read_text_file(&path).map_err(|e| format!("cannot read reference {path}: {e}"))?
+        }
+        (None, None) => return Err("either fasta or path is required".to_string()),
+    };
+    let index = ReferenceIndex::build(name, &fasta).map_err(|e| e.to_string())?;
+
+    let dir = index_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("cannot create index directory {}: {e}", dir.display()))?;
+    save_index(&index, &dir.join(format!("{name}.{INDEX_EXTENSION}")))?;
+
+    let index = Arc::new(index);
+    registry()
+        .write()
+        .map_err(|_| "reference indexes are unavailable".to_string())?
+        .insert(name.to_string(), index.clone());
+    Ok(index)
+}
+
+/// All available indexes, by name
+pub fn indexes() -> Vec<Arc<ReferenceIndex>> {
+    let mut indexes = registry()
+        .read()
+        .map(|indexes| indexes.values().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    indexes.sort_by(|a, b| a.name.cmp(&b.name));
+    indexes
+}
+
+/// Look up an available index by name
+pub fn find_index(name: &str) -> Result<Arc<ReferenceIndex>, String> {
+    registry()
+        .read()
+        .map_err(|_| "reference indexes are unavailable".to_string())?
+        .get(name.trim())
+        .cloned()
+        .ok_or_else(|| format!("unknown reference index '{}'", name.trim()))
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use plasmid::ana::{map_fastq, Mapping, DEFAULT_MIN_SCORE, DEFAULT_SEED_LEN};
+use plasmid::prelude::{read_text_file, ReferenceIndex};
+
+use crate::core::index::utils as index;
+
+/// Map FASTQ reads, given as contents or a file path, to a FASTA reference or an available index.
+///
+/// Exact seeds are looked up in the FM-index of the reference and extended
+/// with a banded Smith–Waterman alignment, soft clipping unaligned read ends.
+pub fn map_reads(
+    reads: Option<String>,
+    path: Option<String>,
+    reference: Option<String>,
+    index_name: Option<String>,
+    seed_len: Option<usize>,
+    min_score: Option<i32>,
+) -> Result<Mapping, String> {
+    let reference = match (reference, index_name) {
+        (Some(fasta), _) => {
+            Arc::new(ReferenceIndex::build("reference", &fasta).map_err(|e| e.to_string())?)
+        }
+        (None, Some(name)) => index::find_index(&name)?,
+        (None, None) => return Err("either reference or index is required".to_string()),
+    };
+    let reads = match (reads, path) {
+        (Some(reads), _) => reads,
+        (None, Some(path)) => {
+            read_text_file(&path).map_err(|e| format!("cannot read reads {path}: {e}"))?
+        }
+        (None, None) => return Err("either reads or path is required".to_string()),
+    };
+    map_fastq(
+        reference,
+        &reads,
+        seed_len.unwrap_or(DEFAULT_SEED_LEN),
+        min_score.unwrap_or(DEFAULT_MIN_SCORE),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Alignments of a mapping in SAM format
+pub fn sam(mapping: &Mapping) -> String {
+    mapping.sam("dnarchery", env!("CARGO_PKG_VERSION"))
+}
@@ -1,4 +1,15 @@
 pub mod dna;
 pub mod sequence;
 pub mod fasta;
+pub mod primer;
+pub mod cloning;
+pub mod enzymes;
+pub mod editor;
+pub mod index;
+pub mod mapping;
+pub mod coverage;
+pub mod variants;
+pub mod traces;
+pub mod annotations;
+pub mod faidx;
 pub mod schema;
\ No newline at end of file
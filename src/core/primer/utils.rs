@@ -0,0 +1,156 @@
+use plasmid::prelude::{
+    in_silico_pcr, Amplicon, DesignedPrimerPair, Export, Import, MultiFastaFile, PcrParameters,
+    PrimerConstraints, PrimerPairProperties, PrimerProperties, SvgExport, SvgExportConfig,
+    TmConditions, TmMethod,
+};
+use plasmid::seq::DnaSequence;
+
+use actix_web::web::Bytes; // for SVG byte object
+
+use crate::core::dna::utils::render_svg_to_png;
+use crate::core::sequence::utils::parse_iupac;
+
+/// Parse a primer or template given 5' to 3'
+pub fn parse_primer(primer: &str) -> Result<DnaSequence, String> {
+    DnaSequence::from_str(primer.trim()).map_err(|e| e.to_string())
+}
+
+/// Parse a melting temperature method name, defaulting to nearest-neighbor
+pub fn parse_tm_method(method: Option<&str>) -> Result<TmMethod, String> {
+    match method.map(|m| m.to_lowercase()).as_deref() {
+        None | Some("nearest_neighbor") => Ok(TmMethod::NearestNeighbor),
+        Some("wallace") => Ok(TmMethod::Wallace),
+        Some("basic") => Ok(TmMethod::Basic),
+        Some(other) => Err(format!(
+            "unknown Tm method '{other}', expected one of nearest_neighbor, wallace, basic"
+        )),
+    }
+}
+
+/// Melting temperature and primer properties of a single oligo
+pub fn primer_properties(
+    primer: String,
+    method: Option<String>,
+    conditions: TmConditions,
+) -> Result<(f64, PrimerProperties), String> {
+    let seq = parse_primer(&primer)?;
+    let method = parse_tm_method(method.as_deref())?;
+
+    Ok((
+        seq.melting_temperature(method, &conditions),
+        seq.primer_properties(&conditions),
+    ))
+}
+
+/// Properties of a forward/reverse primer pair
+pub fn primer_pair_properties(
+    forward: String,
+    reverse: String,
+    conditions: TmConditions,
+) -> Result<PrimerPairProperties, String> {
+    let forward = parse_primer(&forward)?;
+    let reverse = parse_primer(&reverse)?;
+
+    Ok(forward.primer_pair_properties(&reverse, &conditions))
+}
+
+/// Reject primer lengths of zero and ranges whose minimum is above their maximum
+fn check_constraints(constraints: &PrimerConstraints) -> Result<(), String> {
+    if *constraints.length.start() == 0 {
+        return Err("length_min must be above 0".to_string());
+    }
+    if constraints.length.is_empty() {
+        return Err("length_min must not be above length_max".to_string());
+    }
+    if constraints.tm.is_empty() {
+        return Err("tm_min must not be above tm_max".to_string());
+    }
+    if constraints.product_size.is_empty() {
+        return Err("product_size_min must not be above product_size_max".to_string());
+    }
+    Ok(())
+}
+
+/// Design ranked primer pairs amplifying the `target_start..target_end` region of a template
+pub fn design_primers(
+    template: String,
+    target_start: usize,
+    target_end: usize,
+    constraints: PrimerConstraints,
+) -> Result<Vec<DesignedPrimerPair>, String> {
+    check_constraints(&constraints)?;
+    let seq = parse_primer(&template)?;
+
+    seq.design_primers(target_start..target_end, &constraints)
+        .map_err(|e| e.to_string())
+}
+
+/// Circular map of a template in SVG format with the designed primer pairs annotated
+pub fn design_primers_svg(
+    template: String,
+    target_start: usize,
+    target_end: usize,
+    constraints: PrimerConstraints,
+) -> Result<Bytes, String> {
+    check_constraints(&constraints)?;
+    let mut seq = parse_primer(&template)?;
+
+    seq.annotate_primer_pairs(target_start..target_end, &constraints)
+        .map_err(|e| e.to_string())?;
+    let svg = SvgExport::new(SvgExportConfig::circular(), seq.as_nucleotides())
+        .with_annotations(seq.as_annotations());
+
+    Ok(Bytes::from(svg.export()))
+}
+
+/// Circular map of a template in PNG format with the designed primer pairs annotated
+pub fn design_primers_png(
+    template: String,
+    target_start: usize,
+    target_end: usize,
+    constraints: PrimerConstraints,
+) -> Result<Bytes, String> {
+    design_primers_svg(template, target_start, target_end, constraints)
+        .map(|svg| render_svg_to_png(&svg))
+}
+
+/// Predict the products of a primer pair with IUPAC codes, either from a single
+/// template or from every record of a multi-FASTA file.
+/// Products are returned with the identifier of the record they were amplified from.
+pub fn pcr(
+    forward: String,
+    reverse: String,
+    template: Option<String>,
+    fasta: Option<String>,
+    params: PcrParameters,
+) -> Result<Vec<(Option<String>, Amplicon)>, String> {
+    let forward = parse_iupac(&forward)?;
+    let reverse = parse_iupac(&reverse)?;
+    if forward.is_empty() || reverse.is_empty() {
+        return Err("Both primers are required".to_string());
+    }
+
+    match (template, fasta) {
+        (Some(template), None) => {
+            let template = parse_iupac(&template)?;
+            Ok(in_silico_pcr(&template, &forward, &reverse, &params)
+                .into_iter()
+                .map(|amplicon| (None, amplicon))
+                .collect())
+        }
+        (None, Some(fasta)) => {
+            let fasta = MultiFastaFile::import(fasta).map_err(|e| e.to_string())?;
+            let mut amplicons = Vec::new();
+            for record in fasta.records.iter() {
+                let template = record.as_iupac_sequence().map_err(|e| e.to_string())?;
+                amplicons.extend(
+                    in_silico_pcr(&template, &forward, &reverse, &params)
+                        .into_iter()
+                        .map(|amplicon| (Some(record.id().to_string()), amplicon)),
+                );
+            }
+            Ok(amplicons)
+        }
+        _ => Err("Expected either a template or a FASTA file".to_string()),
+    }
+}
@@ -1,4 +1,6 @@
 use rust_genomics::{Sequence, LORF};
+use plasmid::prelude::{GcWindows, IupacNucleotide, SequenceStats};
+use plasmid::traits::TryFromLetter;
 
 use actix_web::Either; // either type for web response
 
@@ -51,4 +53,31 @@ pub fn seq_lorf(sequence: String) -> Either<String, Vec<String>> {
 pub fn seq_random(len: i64) -> String {
     let sequence = Sequence::gen_random_seq(len);
     sequence.seq
-}
\ No newline at end of file
+}
+
+/// Parse a nucleotide sequence that may contain IUPAC ambiguity codes
+pub fn parse_iupac(sequence: &str) -> Result<Vec<IupacNucleotide>, String> {
+    sequence
+        .trim()
+        .chars()
+        .map(|c| IupacNucleotide::try_from_letter(c).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Composition statistics of a sequence, with GC content and GC skew
+/// over a sliding window if a window size is given
+pub fn seq_stats(
+    sequence: String,
+    window: Option<usize>,
+    step: Option<usize>,
+) -> Result<(SequenceStats, Option<GcWindows>), String> {
+    if window == Some(0) || step == Some(0) {
+        return Err("window and step must be above 0".to_string());
+    }
+    let seq = parse_iupac(&sequence)?;
+
+    let stats = SequenceStats::compute(&seq);
+    let windows = window.map(|window| GcWindows::compute(&seq, window, step.unwrap_or(window)));
+
+    Ok((stats, windows))
+}
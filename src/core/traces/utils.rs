@@ -0,0 +1,235 @@
+use bio::alignment::pairwise::Aligner;
+use bio::alignment::AlignmentOperation;
+use plasmid::prelude::{Export, TraceExport, TraceFile};
+use plasmid::seq::DnaSequence;
+
+use actix_web::web::Bytes; // for SVG byte object
+
+use crate::core::cloning::utils::parse_topology;
+use crate::core::dna::utils::render_svg_to_png;
+
+/// Quality below which the ends of a read are trimmed before aligning it
+pub const DEFAULT_TRIM_QUALITY: u8 = 20;
+
+/// Fewest calls left after trimming for a read to be aligned
+const MIN_TRIMMED_LEN: usize = 20;
+
+const MATCH_SCORE: i32 = 2;
+const MISMATCH_SCORE: i32 = -3;
+const GAP_OPEN_SCORE: i32 = -5;
+const GAP_EXTEND_SCORE: i32 = -2;
+
+/// A base where a read differs from the reference
+pub struct TraceDiscrepancy {
+    /// 0-based reference position, of the base following an insertion
+    pub position: usize,
+    /// Reference base, `-` for an insertion in the read
+    pub reference: char,
+    /// Called base, `-` for a deletion in the read
+    pub call: char,
+    /// Index of the base call in the aligned orientation of the trace, and its quality
+    pub index: Option<usize>,
+    pub quality: Option<u8>,
+}
+
+/// Alignment of the quality-trimmed calls of a trace to a reference
+pub struct TraceAlignment {
+    pub name: String,
+    /// Whether the reverse complement of the trace was aligned
+    pub reverse: bool,
+    pub score: i32,
+    /// 0-based reference position of the first aligned call
+    pub start: usize,
+    /// Reference position after the last aligned call, smaller than `start` across the origin
+    pub end: usize,
+    /// Aligned calls of the trace in its aligned orientation, `read_end` exclusive
+    pub read_start: usize,
+    pub read_end: usize,
+    pub identity: f64,
+    pub cigar: String,
+    pub discrepancies: Vec<TraceDiscrepancy>,
+}
+
+/// Read a trace from a file on the server
+pub fn load_trace(path: &str) -> Result<TraceFile, String> {
+    TraceFile::import_from_path(path).map_err(|e| format!("cannot read trace {path}: {e}"))
+}
+
+/// Read a trace from the uploaded contents of an ABIF or SCF file
+pub fn parse_trace(contents: &[u8]) -> Result<TraceFile, String> {
+    TraceFile::from_bytes(contents).map_err(|e| format!("invalid trace file: {e}"))
+}
+
+/// Align the trimmed calls of a trace in the given orientation, `None` if too few are left
+fn align_orientation(
+    trace: &TraceFile,
+    reference: &[u8],
+    circular: bool,
+    min_quality: u8,
+    reverse: bool,
+) -> Option<TraceAlignment> {
+    let trimmed = trace.quality_trim(min_quality);
+    if trimmed.len() < MIN_TRIMMED_LEN.min(reference.len()) || trimmed.is_empty() {
+        return None;
+    }
+    let read = trace
+        .bases
+        .chars()
+        .skip(trimmed.start)
+        .take(trimmed.len())
+        .map(|c| c.to_ascii_uppercase() as u8)
+        .collect::<Vec<_>>();
+
+    // Reads may cross the origin of a circular reference
+    let len = reference.len();
+    let target = match circular {
+        true => [reference, &reference[..read.len().min(len)]].concat(),
+        false => reference.to_vec(),
+    };
+    let score = |a: u8, b: u8| match (a, b) {
+        (a, b) if a == b => MATCH_SCORE,
+        (b'N', _) | (_, b'N') => 0,
+        _ => MISMATCH_SCORE,
+    };
+    let mut aligner = Aligner::with_capacity(
+        read.len(),
+        target.len(),
+        GAP_OPEN_SCORE,
+        GAP_EXTEND_SCORE,
+        score,
+    );
+    let alignment = aligner.semiglobal(&read, &target);
+
+    let (mut x, mut y) = (alignment.xstart, alignment.ystart);
+    let (mut matches, mut columns) = (0, 0);
+    let mut cigar: Vec<(usize, char)> = Vec::new();
+    let mut discrepancies = Vec::new();
+    for operation in alignment.operations.iter() {
+        let (column, discrepancy) = match operation {
+            AlignmentOperation::Match => ('M', None),
+            AlignmentOperation::Subst => ('M', Some((target[y] as char, read[x] as char))),
+            AlignmentOperation::Del => ('D', Some((target[y] as char, '-'))),
+            AlignmentOperation::Ins => ('I', Some(('-', read[x] as char))),
+            AlignmentOperation::Xclip(_) | AlignmentOperation::Yclip(_) => continue,
+        };
+        columns += 1;
+        if let Some((reference, call)) = discrepancy {
+            let index = (call != '-').then_some(trimmed.start + x);
+            discrepancies.push(TraceDiscrepancy {
+                position: y % len,
+                reference,
+                call,
+                index,
+                quality: index.map(|i| trace.qualities[i]),
+            });
+        } else {
+            matches += 1;
+        }
+        match cigar.last_mut() {
+            Some((count, last)) if *last == column => *count += 1,
+            _ => cigar.push((1, column)),
+        }
+        if column != 'D' {
+            x += 1;
+        }
+        if column != 'I' {
+            y += 1;
+        }
+    }
+
+    Some(TraceAlignment {
+        name: trace.name.to_owned(),
+        reverse,
+        score: alignment.score,
+        start: alignment.ystart % len,
+        end: match alignment.yend % len {
+            0 => len,
+            end => end,
+        },
+        read_start: trimmed.start + alignment.xstart,
+        read_end: trimmed.start + alignment.xend,
+        identity: match columns {
+            0 => 0.0,
+            columns => matches as f64 / columns as f64,
+        },
+        cigar: cigar
+            .iter()
+            .map(|(count, op)| format!("{count}{op}"))
+            .collect(),
+        discrepancies,
+    })
+}
+
+/// Align a trace to a reference on both strands, keeping the better scoring orientation
+pub fn align_trace(
+    trace: &TraceFile,
+    reference: &DnaSequence,
+    min_quality: u8,
+) -> Result<TraceAlignment, String> {
+    let bases = reference.to_string().into_bytes();
+    if bases.is_empty() {
+        return Err("the reference is empty".to_string());
+    }
+    let circular = reference.is_circular();
+    let forward = align_orientation(trace, &bases, circular, min_quality, false);
+    let reverse = align_orientation(
+        &trace.reverse_complement(),
+        &bases,
+        circular,
+        min_quality,
+        true,
+    );
+    match (forward, reverse) {
+        (Some(forward), Some(reverse)) if reverse.score > forward.score => Ok(reverse),
+        (Some(forward), _) => Ok(forward),
+        (None, Some(reverse)) => Ok(reverse),
+        (None, None) => Err(format!(
+            "trace '{}' has too few calls of quality {min_quality} or more",
+            trace.name
+        )),
+    }
+}
+
+/// Align traces to a reference DNA sequence, see `align_trace`
+pub fn align_traces(
+    traces: &[TraceFile],
+    reference: String,
+    topology: Option<String>,
+    min_quality: Option<u8>,
+) -> Result<Vec<TraceAlignment>, String> {
+    let mut reference = DnaSequence::from_str(reference.trim()).map_err(|e| e.to_string())?;
+    reference.set_topology(parse_topology(topology.as_deref())?);
+    let min_quality = min_quality.unwrap_or(DEFAULT_TRIM_QUALITY);
+    traces
+        .iter()
+        .map(|trace| align_trace(trace, &reference, min_quality))
+        .collect()
+}
+
+/// Draw the chromatogram of a trace in SVG format, optionally only the calls in `start..end`
+/// of its reverse complement.
+pub fn gen_trace_svg(
+    trace: &TraceFile,
+    start: Option<usize>,
+    end: Option<usize>,
+    reverse: Option<bool>,
+) -> Result<Bytes, String> {
+    let trace = match reverse.unwrap_or(false) {
+        true => trace.reverse_complement(),
+        false => trace.clone(),
+    };
+    let export =
+        TraceExport::new(&trace).with_range(start.unwrap_or(0), end.unwrap_or(trace.len()));
+
+    Ok(Bytes::from(export.export()))
+}
+
+/// Draw the chromatogram of a trace in PNG format, see `gen_trace_svg`.
+pub fn gen_trace_png(
+    trace: &TraceFile,
+    start: Option<usize>,
+    end: Option<usize>,
+    reverse: Option<bool>,
+) -> Result<Bytes, String> {
+    gen_trace_svg(trace, start, end, reverse).map(|svg| render_svg_to_png(&svg))
+}
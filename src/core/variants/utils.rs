@@ -0,0 +1,134 @@
+use plasmid::ana::{
+    call_pileups, diff_alignment, to_vcf, Variant, DEFAULT_FEATURE_IDENTITY, DEFAULT_MIN_DEPTH,
+    DEFAULT_MIN_FREQUENCY,
+};
+use plasmid::prelude::{CodingEffect, SequenceVariant};
+use plasmid::seq::DnaSequence;
+
+use crate::core::cloning::utils::parse_topology;
+use crate::core::coverage::utils::{parse_reference, CoverageSource};
+use crate::core::dna::algos::align_needleman_wunsch;
+use crate::core::editor::utils as editor;
+
+/// Called variants, also rendered as VCF
+pub struct VariantCalls {
+    pub variants: Vec<Variant>,
+    pub vcf: String,
+}
+
+/// A variant with its effects on the CDS it changes, empty if it is non-coding
+pub struct AnnotatedVariant {
+    pub variant: SequenceVariant,
+    pub effects: Vec<CodingEffect>,
+}
+
+/// Source of VCF files written by the server
+fn vcf_source() -> String {
+    format!("dnarchery-{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// Differences of a sample sequence to a reference sequence, from their Needleman–Wunsch alignment
+pub fn diff_sequences(chrom: &str, reference: &str, sample: &str) -> Result<VariantCalls, String> {
+    let reference = reference.trim().to_ascii_uppercase();
+    let sample = sample.trim().to_ascii_uppercase();
+    if reference.is_empty() || sample.is_empty() {
+        return Err("both a reference and a sample sequence are required".to_string());
+    }
+    let alignment = align_needleman_wunsch(reference.to_owned(), sample.to_owned());
+    let variants = diff_alignment(
+        chrom,
+        reference.as_bytes(),
+        sample.as_bytes(),
+        &alignment.steps(),
+    );
+    Ok(VariantCalls {
+        vcf: to_vcf(
+            &variants,
+            &[(chrom.to_string(), reference.len())],
+            &vcf_source(),
+        ),
+        variants,
+    })
+}
+
+/// Call variants from SAM alignments, mapped reads or aligned sequences against a FASTA reference
+pub fn call_variants(
+    source: &CoverageSource,
+    min_depth: Option<u32>,
+    min_frequency: Option<f64>,
+) -> Result<VariantCalls, String> {
+    let fasta = source
+        .reference
+        .as_deref()
+        .ok_or("a reference is required to call variants")?;
+    let references = parse_reference(fasta)?;
+    let variants = call_pileups(
+        &source.pileup()?,
+        &references,
+        min_depth.unwrap_or(DEFAULT_MIN_DEPTH),
+        min_frequency.unwrap_or(DEFAULT_MIN_FREQUENCY),
+    )
+    .map_err(|e| e.to_string())?;
+    let contigs = references
+        .iter()
+        .map(|(name, sequence)| (name.to_owned(), sequence.len()))
+        .collect::<Vec<_>>();
+    Ok(VariantCalls {
+        vcf: to_vcf(&variants, &contigs, &vcf_source()),
+        variants,
+    })
+}
+
+/// Effects of variants on the CDS of a sequence, given as `variants` and/or as the
+/// differences of a `sample` to it.
+///
+/// The sequence and its annotations come from an editor `session`, otherwise
+/// `seq` is annotated with common features to find its CDS.
+pub fn annotate_variants(
+    seq: Option<String>,
+    topology: Option<String>,
+    session: Option<u64>,
+    mut variants: Vec<SequenceVariant>,
+    sample: Option<String>,
+) -> Result<Vec<AnnotatedVariant>, String> {
+    let seq = match (session, seq) {
+        (Some(id), _) => {
+            let snapshot = editor::session(id)?;
+            let mut seq = DnaSequence::from_str(&snapshot.sequence).map_err(|e| e.to_string())?;
+            seq.set_topology(snapshot.topology);
+            *seq.as_mut_annotations() = snapshot.annotations;
+            seq
+        }
+        (None, Some(dna)) => {
+            let mut seq = DnaSequence::from_str(dna.trim()).map_err(|e| e.to_string())?;
+            seq.set_topology(parse_topology(topology.as_deref())?);
+            seq.annotate_common_features(DEFAULT_FEATURE_IDENTITY);
+            seq
+        }
+        (None, None) => {
+            return Err("either a DNA sequence or an editor session is required".to_string())
+        }
+    };
+    if let Some(sample) = sample {
+        let calls = diff_sequences("reference", &seq.to_string(), &sample)?;
+        variants.extend(
+            calls
+                .variants
+                .into_iter()
+                .map(|v| SequenceVariant::new(v.position, v.reference, v.alternate)),
+        );
+    }
+    if variants.is_empty() {
+        return Err("no variants given".to_string());
+    }
+
+    variants
+        .into_iter()
+        .map(|variant| {
+            Ok(AnnotatedVariant {
+                effects: seq.variant_effects(&variant).map_err(|e| e.to_string())?,
+                variant,
+            })
+        })
+        .collect()
+}